@@ -1,4 +1,5 @@
 use gunk_engine::core::application;
+use gunk_engine::renderer::vulkan_renderer::gk_vk_camera::camera_controller::GkFlyCamera;
 
 fn main() {
     println!("Hello, world!");
@@ -10,11 +11,30 @@ fn main() {
         height: 800,
         b_fullscreen: false,
         b_resizable: true,
-        b_border: true
+        b_border: true,
+        validation: Default::default(),
+        cpu_profile: None,
+        b_vsync: true,
+        b_reverse_z: false,
+        max_fps: None,
+        tick_rate: 60.0,
+        action_bindings: None,
+        input_record: None,
+        input_replay: None,
     };
     
-    let (mut app, evloop) = application::Application::new(app_config);
+    let (mut app, evloop) = match application::Application::new(app_config)
+    {
+        Ok(app) => app,
+        Err(e) =>
+        {
+            eprintln!("Failed to start: {}", e);
+            std::process::exit(1);
+        }
+    };
     
+    let view = app.renderer.camera_mut().view;
+    app.camera_controller = Some(Box::new(GkFlyCamera::from_view(&view)));
     app.init();
     app.run(evloop);
 }
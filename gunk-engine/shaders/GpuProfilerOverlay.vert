@@ -0,0 +1,26 @@
+#version 460
+
+// One quad per draw, corners taken from the push constant rect in normalized device coordinates.
+layout(push_constant) uniform Bar
+{
+    vec4 rect;  // x0, y0, x1, y1
+    vec4 color;
+} bar;
+
+layout(location = 0) out vec4 fragColor;
+
+const vec2 corners[6] = vec2[](
+    vec2(0.0, 0.0),
+    vec2(1.0, 0.0),
+    vec2(1.0, 1.0),
+    vec2(1.0, 1.0),
+    vec2(0.0, 1.0),
+    vec2(0.0, 0.0)
+);
+
+void main()
+{
+    vec2 corner = corners[gl_VertexIndex];
+    gl_Position = vec4(mix(bar.rect.xy, bar.rect.zw, corner), 0.0, 1.0);
+    fragColor = bar.color;
+}
@@ -9,7 +9,8 @@ layout(binding = 0) uniform CameraUniform {
     mat4 proj;
 } camera_ubo;
 
-layout(binding = 1) uniform ModelSpace
+// World matrix of the scene node being drawn.
+layout(push_constant) uniform ModelSpace
 {
     mat4 data;
 } model;
@@ -23,4 +24,4 @@ void main()
 
     fragColor = inColor;
     outTexCoords = inTexCoords;
-}
\ No newline at end of file
+}
@@ -6,12 +6,27 @@ use winit::{
     dpi::PhysicalSize,
 };
 
-use std::{string::String, ffi::CString};
+use std::{string::String, ffi::CString, path::PathBuf};
 
+use crate::{ log_err, log_info, log_warn };
 use crate::platform::main_loop;
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::core::logger::{ gk_logger, gk_log_install_log_bridge, GkLogRing };
+use crate::core::log_console::GkLogConsole;
+use crate::core::profiler::{ gk_cpu_profiler_set_enabled, gk_cpu_write_chrome_trace };
+use crate::core::frame_scheduler::GkFrameScheduler;
+use crate::core::time::GkTime;
+use crate::core::ecs::{ GkWorld, GkSchedule, GkSceneSync, GkDeltaTime, GkPhysicsSettings, gk_previous_transform_system, gk_rigid_body_system };
+use crate::platform::input::GkInput;
+use crate::platform::event_bus::GkEventBus;
+use crate::renderer::vulkan_renderer::gk_vk_camera::camera_controller::{ GkCameraController, GkCameraInput };
+use crate::platform::action_map::GkActionMap;
+use crate::platform::gamepad::GkGamepads;
+use crate::platform::input_recording::{ GkInputRecorder, GkInputRecording, GkInputReplay };
 use crate::renderer::{
     renderer_utils::GfxRenderer,
     vulkan_renderer::vulkan_renderer::VulkanRenderer,
+    vulkan_renderer::gk_vulkan::gunk_vk_validation::GkVkValidationConfig,
 };
 
 /// ### AppConfig struct
@@ -24,6 +39,15 @@ use crate::renderer::{
 ///     b_fullscreen:       bool    <i>// is fullscreen?
 ///     b_resizeable:       bool    <i>// is resizable?
 ///     b_border:           bool    <i>// has border?
+///     validation:         GkVkValidationConfig    <i>// GUNK_VK_* environment variables override it.
+///     cpu_profile:        Option&lt;PathBuf&gt;         <i>// Profile the cpu and write a Chrome trace here on exit. GUNK_CPU_PROFILE overrides it.
+///     b_vsync:            bool    <i>// Wait for the display's vertical blank when presenting.
+///     b_reverse_z:        bool    <i>// Reverse-Z depth buffer, far maps to 0 for better depth precision.
+///     max_fps:            Option&lt;f32&gt;     <i>// Frame cap, None to only be limited by vsync.
+///     tick_rate:          f32     <i>// Fixed update ticks per second.
+///     action_bindings:    Option&lt;PathBuf&gt;     <i>// Action bindings file, loaded at startup if it exists and written by save_action_bindings().
+///     input_record:       Option&lt;PathBuf&gt;     <i>// Record all input and frame times and write them here on exit. GUNK_INPUT_RECORD overrides it.
+///     input_replay:       Option&lt;PathBuf&gt;     <i>// Drive the application from a recording instead of live input, exiting when it ends. GUNK_INPUT_REPLAY overrides it.
 /// </pre>
 pub struct AppConfig
 {
@@ -33,38 +57,91 @@ pub struct AppConfig
     pub b_fullscreen:   bool, // is fullscreen
     pub b_resizable:    bool, // is resizable
     pub b_border:       bool,  // has border
+    pub validation:     GkVkValidationConfig,
+    pub cpu_profile:    Option<PathBuf>,
+    pub b_vsync:        bool,
+    pub b_reverse_z:    bool,
+    pub max_fps:        Option<f32>,
+    pub tick_rate:      f32,
+    pub action_bindings: Option<PathBuf>,
+    pub input_record:   Option<PathBuf>,
+    pub input_replay:   Option<PathBuf>,
 }
 
+/// Log records kept for the editor console.
+const LOG_CONSOLE_CAPACITY: usize = 1024;
+
 /// ### Application struct
 /// *Contains members necessary for a functioning application*
 pub struct Application
 {
-    pub config:     AppConfig,
-    pub window:     Window,
-    pub renderer:   Box<dyn GfxRenderer>,
-    pub minimized:  bool
+    pub config:         AppConfig,
+    pub window:         Window,
+    pub renderer:       Box<dyn GfxRenderer>,
+    pub minimized:      bool,
+    pub log_console:    GkLogConsole,
+    pub scheduler:      GkFrameScheduler,
+    pub time:           GkTime,
+    pub input:          GkInput,
+    pub actions:        GkActionMap,
+    pub gamepads:       GkGamepads,
+    pub recorder:       Option<GkInputRecorder>,
+    pub replay:         Option<GkInputReplay>,
+    pub events:         GkEventBus,
+    pub camera_controller: Option<Box<dyn GkCameraController>>,
+    pub world:          GkWorld,
+    pub schedule:       GkSchedule,
+    pub scene_sync:     GkSceneSync,
 }
 
 impl Application
 {
-    /// ### Application::new( ... ) -> (Application, EventLoop\<()\>)
+    /// ### Application::new( ... ) -> GkResult\<(Application, EventLoop\<()\>)\>
     /// *Creates an instance of the application.<br> Also return an EventLoop.*
     /// <pre>
     /// - Params
     ///     config:     AppConfig
     /// - Return
-    ///     (Application, EventLoop&lt;()&gt;)
+    ///     GkResult&lt;(Application, EventLoop&lt;()&gt;)&gt;
     /// </pre>
-    pub fn new(config: AppConfig) -> (Self, EventLoop<()>)
+    pub fn new(mut config: AppConfig) -> GkResult<(Self, EventLoop<()>)>
     {
-        let evloop: EventLoop<()> = EventLoop::new().unwrap();
-        let window: Window = Window::new(&evloop).unwrap();
+        gk_log_install_log_bridge();
+        let log_ring = GkLogRing::new(LOG_CONSOLE_CAPACITY);
+        gk_logger().add_sink(Box::new(log_ring.clone()));
+        if let Some(path) = std::env::var_os("GUNK_CPU_PROFILE").filter(|path| !path.is_empty())
+        {
+            config.cpu_profile = Some(PathBuf::from(path));
+        }
+        gk_cpu_profiler_set_enabled(config.cpu_profile.is_some());
+        if let Some(path) = std::env::var_os("GUNK_INPUT_RECORD").filter(|path| !path.is_empty())
+        {
+            config.input_record = Some(PathBuf::from(path));
+        }
+        if let Some(path) = std::env::var_os("GUNK_INPUT_REPLAY").filter(|path| !path.is_empty())
+        {
+            config.input_replay = Some(PathBuf::from(path));
+        }
+        let replay = match &config.input_replay
+        {
+            Some(path) =>
+            {
+                let recording = GkInputRecording::load(path)?;
+                log_info!(format!("Replaying {} frames of input from {}", recording.frames.len(), path.display()));
+                Some(GkInputReplay::new(recording))
+            },
+            None => None,
+        };
+
+        let evloop: EventLoop<()> = EventLoop::new().map_err(|e| GkError::Unsupported(e.to_string()))?;
+        let window: Window = Window::new(&evloop).map_err(|e| GkError::Unsupported(e.to_string()))?;
         
         window.set_resizable(config.b_resizable);
 
         if config.b_fullscreen
         {
-            let video_mode = window.current_monitor().unwrap().video_modes().next().unwrap();
+            let video_mode = window.current_monitor().and_then(|monitor| monitor.video_modes().next())
+                .ok_or_else(|| GkError::Unsupported(String::from("No monitor video mode available for fullscreen")))?;
             let fullscreen = Fullscreen::Exclusive(video_mode);
             window.set_fullscreen(Some(fullscreen));
         } else {
@@ -74,19 +151,56 @@ impl Application
         let renderer = Box::new(
             VulkanRenderer::new(
                 &window, 
-                CString::new(config.title.clone()).unwrap(), 
-                ash::vk::make_api_version(0, 0, 1, 0)
-            )
+                CString::new(config.title.clone()).unwrap_or_default(), 
+                ash::vk::make_api_version(0, 0, 1, 0),
+                config.validation.clone().with_env(),
+                config.b_vsync,
+                config.b_reverse_z
+            )?
         );
-        let app = Self
+        let scheduler = GkFrameScheduler::new(config.tick_rate, config.max_fps);
+        let actions = match &config.action_bindings
+        {
+            Some(path) if path.exists() => GkActionMap::load(path).unwrap_or_else(|e|
+            {
+                log_warn!(format!("Ignoring action bindings, {}", e));
+                GkActionMap::new()
+            }),
+            _ => GkActionMap::new(),
+        };
+        let mut world = GkWorld::new();
+        world.insert_resource(GkDeltaTime(0.0));
+        world.insert_resource(GkPhysicsSettings::default());
+        let mut schedule = GkSchedule::new();
+        schedule.add_system(gk_previous_transform_system());
+        schedule.add_system(gk_rigid_body_system());
+        let mut app = Self
         {
             config,
             window,
             renderer,
-            minimized: false
+            minimized: false,
+            log_console: GkLogConsole::new(log_ring),
+            scheduler,
+            time: GkTime::new(),
+            input: GkInput::new(),
+            actions,
+            gamepads: GkGamepads::with_default_backends(),
+            recorder: None,
+            replay,
+            events: GkEventBus::new(),
+            camera_controller: None,
+            world,
+            schedule,
+            scene_sync: GkSceneSync::new(),
         };
+        if app.config.input_record.is_some()
+        {
+            if app.replay.is_some() { log_warn!("Not recording input while replaying a recording"); }
+            else { app.recorder = Some(GkInputRecorder::new(&app.input, &app.gamepads)); }
+        }
 
-        (app, evloop)
+        Ok((app, evloop))
     }
     
 
@@ -113,6 +227,42 @@ impl Application
         self.renderer.resized();
     }
 
+    /// ### fn Application::save_action_bindings( &self ) -> GkResult\<()\>
+    /// *Writes the current bindings to config.action_bindings, e.g. after the player rebinds. Does nothing if it is None.*
+    pub fn save_action_bindings(&self) -> GkResult<()>
+    {
+        match &self.config.action_bindings
+        {
+            Some(path) => self.actions.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// ### fn Application::update_camera( &mut self, ... )
+    /// *Moves the renderer's camera with camera_controller, if there is one. Called once per rendered frame.*
+    pub fn update_camera(&mut self, delta_time: f32)
+    {
+        let Some(controller) = self.camera_controller.as_mut() else { return; };
+        controller.update(&GkCameraInput::from_input(&self.input, false), delta_time);
+        self.renderer.camera_mut().view = controller.view();
+    }
+
+    /// ### fn Application::fixed_update( &mut self, ... )
+    /// *Runs one simulation tick of step seconds, every system in schedule over world. Called config.tick_rate times per second, before rendering.*
+    pub fn fixed_update(&mut self, step: f32)
+    {
+        self.world.insert_resource(GkDeltaTime(step));
+        self.schedule.run(&mut self.world);
+    }
+
+    /// ### fn Application::sync_scene( &mut self, alpha: f32 )
+    /// *Mirrors world's renderable entities into the renderer's scene, alpha of the way from their previous tick to the last one.*<br>
+    /// *Called once per rendered frame, before rendering, with GkFrameTiming::alpha.*
+    pub fn sync_scene(&mut self, alpha: f32)
+    {
+        self.scene_sync.sync(&self.world, self.renderer.scene_mut(), alpha);
+    }
+
 }
 
 
@@ -121,5 +271,27 @@ impl Drop for Application
     fn drop(&mut self)
     {
         self.renderer.destroy();
+
+        let frame_stats = self.scheduler.limiter.stats();
+        if !frame_stats.is_empty() { log_info!(format!("Frame times, {}", frame_stats.summary())); }
+
+        if let Some(path) = self.config.cpu_profile.as_ref()
+        {
+            match gk_cpu_write_chrome_trace(path)
+            {
+                Ok(()) => { log_info!(format!("Cpu profile written to {}", path.display())); },
+                Err(e) => { log_err!(e); }
+            }
+        }
+
+        if let (Some(recorder), Some(path)) = (self.recorder.take(), self.config.input_record.as_ref())
+        {
+            let recording = recorder.finish();
+            match recording.save(path)
+            {
+                Ok(()) => { log_info!(format!("Recorded {} frames of input to {}", recording.frames.len(), path.display())); },
+                Err(e) => { log_err!(e); }
+            }
+        }
     }
 }
\ No newline at end of file
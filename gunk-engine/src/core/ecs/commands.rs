@@ -0,0 +1,105 @@
+use super::entity::GkEntity;
+use super::world::GkWorld;
+
+/// A structural change to the world, waiting for GkSchedule to apply it.
+pub type GkCommand = Box<dyn FnOnce(&mut GkWorld)>;
+
+/// ### GkCommands struct
+/// *Changes a system can't make through a shared world, queued until the end of its batch.*<br>
+/// *spawn() hands out the new entity right away, its components arrive with the queued inserts.*
+pub struct GkCommands<'w>
+{
+    world:  &'w GkWorld,
+    queue:  Vec<GkCommand>,
+}
+
+impl<'w> GkCommands<'w>
+{
+    pub fn new(world: &'w GkWorld) -> Self
+    {
+        Self { world, queue: Vec::new() }
+    }
+
+    pub fn spawn(&mut self) -> GkEntity
+    {
+        self.world.reserve_entity()
+    }
+
+    pub fn insert<T: 'static>(&mut self, entity: GkEntity, component: T)
+    {
+        self.add(move |world| { world.insert(entity, component); });
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: GkEntity)
+    {
+        self.add(move |world| { world.remove::<T>(entity); });
+    }
+
+    pub fn despawn(&mut self, entity: GkEntity)
+    {
+        self.add(move |world| { world.despawn(entity); });
+    }
+
+    pub fn insert_resource<T: 'static>(&mut self, resource: T)
+    {
+        self.add(move |world| { world.insert_resource(resource); });
+    }
+
+    /// Queues any change to the world.
+    pub fn add(&mut self, command: impl FnOnce(&mut GkWorld) + 'static)
+    {
+        self.queue.push(Box::new(command));
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.queue.is_empty()
+    }
+
+    /// The queued commands, in the order they were queued.
+    pub fn into_queue(self) -> Vec<GkCommand>
+    {
+        self.queue
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Tag(u32);
+
+    #[test]
+    fn commands_wait_until_applied_and_keep_their_order()
+    {
+        let mut world = GkWorld::new();
+        let doomed = world.spawn();
+        let queue =
+        {
+            let mut commands = GkCommands::new(&world);
+            let spawned = commands.spawn();
+            commands.insert(spawned, Tag(1));
+            commands.insert(spawned, Tag(2));
+            commands.despawn(doomed);
+            commands.insert_resource(Tag(7));
+            assert!(world.is_alive(spawned) && !world.has::<Tag>(spawned));
+            assert!(world.is_alive(doomed));
+            assert_eq!(commands.len(), 4);
+            commands.into_queue()
+        };
+        for command in queue { command(&mut world); }
+
+        assert!(!world.is_alive(doomed));
+        assert_eq!(world.query::<&Tag>().count(), 1);
+        world.query::<&Tag>().for_each(|_, tag| assert_eq!(*tag, Tag(2)));
+        assert_eq!(*world.resource::<Tag>().unwrap(), Tag(7));
+    }
+}
@@ -0,0 +1,275 @@
+use std::path::PathBuf;
+
+use nalgebra_glm as glm;
+
+use crate::math::GkTransform;
+use super::system::GkFnSystem;
+
+// Built in components. GkTransform, from math, is the transform component.
+
+/// ### GkPreviousTransform struct
+/// *The entity's GkTransform before the current fixed tick, kept by gk_previous_transform_system().*<br>
+/// *GkSceneSync draws entities that have one between the two ticks, so movement stays smooth at any frame rate.*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkPreviousTransform(pub GkTransform);
+
+/// ### GkMeshRenderer struct
+/// *Draws a mesh at the entity's GkTransform, through the renderer's scene. See GkSceneSync.*
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkMeshRenderer
+{
+    pub mesh:       PathBuf,
+    pub b_visible:  bool,
+}
+
+impl GkMeshRenderer
+{
+    pub fn new(mesh: PathBuf) -> Self
+    {
+        Self { mesh, b_visible: true }
+    }
+}
+
+/// ### GkCameraComponent struct
+/// *Moves one of the renderer's cameras with the entity's GkTransform, looking down its local +Y with +Z up.*
+/// <pre>
+/// - Members
+///     slot:   usize   <i>// Index into GfxRenderer::cameras(), 0 is the main camera.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkCameraComponent
+{
+    pub slot:   usize,
+}
+
+/// ### GkRigidBody struct
+/// *Moves the entity's GkTransform by its velocities, integrated by gk_rigid_body_system() every fixed tick.*
+/// <pre>
+/// - Members
+///     velocity:           glm::Vec3   <i>// World units per second.</i>
+///     angular_velocity:   glm::Vec3   <i>// World space axis, radians per second along its length.</i>
+///     mass:               f32         <i>// 0 or less makes the body static, it never moves.</i>
+///     linear_damping:     f32         <i>// Fraction of velocity lost per second.</i>
+///     b_gravity:          bool
+///     force:              glm::Vec3   <i>// Summed by apply_force(), cleared after each tick.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkRigidBody
+{
+    pub velocity:           glm::Vec3,
+    pub angular_velocity:   glm::Vec3,
+    pub mass:               f32,
+    pub linear_damping:     f32,
+    pub b_gravity:          bool,
+    pub force:              glm::Vec3,
+}
+
+impl GkRigidBody
+{
+    pub fn new(mass: f32) -> Self
+    {
+        Self
+        {
+            velocity: glm::Vec3::zeros(),
+            angular_velocity: glm::Vec3::zeros(),
+            mass,
+            linear_damping: 0.0,
+            b_gravity: true,
+            force: glm::Vec3::zeros(),
+        }
+    }
+
+    pub fn with_velocity(mut self, velocity: glm::Vec3) -> Self
+    {
+        self.velocity = velocity;
+        self
+    }
+
+    pub fn with_angular_velocity(mut self, angular_velocity: glm::Vec3) -> Self
+    {
+        self.angular_velocity = angular_velocity;
+        self
+    }
+
+    pub fn with_damping(mut self, linear_damping: f32) -> Self
+    {
+        self.linear_damping = linear_damping;
+        self
+    }
+
+    pub fn with_gravity(mut self, b_gravity: bool) -> Self
+    {
+        self.b_gravity = b_gravity;
+        self
+    }
+
+    pub fn is_static(&self) -> bool
+    {
+        self.mass <= 0.0
+    }
+
+    /// Pushes the body during the next tick.
+    pub fn apply_force(&mut self, force: glm::Vec3)
+    {
+        self.force += force;
+    }
+
+    /// Changes the velocity at once, such as for a jump or a hit.
+    pub fn apply_impulse(&mut self, impulse: glm::Vec3)
+    {
+        if !self.is_static() { self.velocity += impulse / self.mass; }
+    }
+}
+
+/// ### GkDeltaTime struct
+/// *Resource holding the seconds simulated by the current schedule run, the fixed tick step.*
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GkDeltaTime(pub f32);
+
+/// ### GkPhysicsSettings struct
+/// *Resource read by gk_rigid_body_system().*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkPhysicsSettings
+{
+    pub gravity:    glm::Vec3,
+}
+
+impl Default for GkPhysicsSettings
+{
+    fn default() -> Self
+    {
+        // The engine is z up.
+        Self { gravity: glm::Vec3::new(0.0, 0.0, -9.81) }
+    }
+}
+
+/// ### fn gk_previous_transform_system() -> GkFnSystem
+/// *Copies every GkTransform into the entity's GkPreviousTransform. Add it to the schedule before anything that moves entities.*<br>
+/// *Entities without one get it through commands, before the next batch runs.*
+pub fn gk_previous_transform_system() -> GkFnSystem
+{
+    GkFnSystem::new("previous transforms", |world, commands|
+    {
+        world.query::<(&GkTransform, &mut GkPreviousTransform)>().for_each(|_, (transform, previous)| previous.0 = *transform);
+        world.query::<&GkTransform>().for_each(|entity, transform|
+        {
+            if !world.has::<GkPreviousTransform>(entity) { commands.insert(entity, GkPreviousTransform(*transform)); }
+        });
+    })
+    .with_query::<(&GkTransform, &mut GkPreviousTransform)>()
+}
+
+/// ### fn gk_rigid_body_system() -> GkFnSystem
+/// *Integrates forces, gravity and velocities of every GkRigidBody into its GkTransform (semi-implicit Euler). Collisions are not handled.*
+pub fn gk_rigid_body_system() -> GkFnSystem
+{
+    GkFnSystem::new("rigid bodies", |world, _|
+    {
+        let delta_time = world.resource::<GkDeltaTime>().map_or(0.0, |delta| delta.0);
+        let gravity = world.resource::<GkPhysicsSettings>().map_or(glm::Vec3::zeros(), |settings| settings.gravity);
+        if delta_time <= 0.0 { return; }
+
+        world.query::<(&mut GkTransform, &mut GkRigidBody)>().for_each(|_, (transform, body)|
+        {
+            if body.is_static()
+            {
+                body.force = glm::Vec3::zeros();
+                return;
+            }
+            let mut acceleration = body.force / body.mass;
+            if body.b_gravity { acceleration += gravity; }
+            body.velocity += acceleration * delta_time;
+            body.velocity *= (1.0 - body.linear_damping * delta_time).max(0.0);
+            body.force = glm::Vec3::zeros();
+            transform.translation += body.velocity * delta_time;
+
+            let angle = body.angular_velocity.norm() * delta_time;
+            if angle > 0.0
+            {
+                let spin = glm::quat_angle_axis(angle, &body.angular_velocity.normalize());
+                transform.rotation = glm::quat_normalize(&(spin * transform.rotation));
+            }
+        });
+    })
+    .reads::<GkDeltaTime>()
+    .reads::<GkPhysicsSettings>()
+    .with_query::<(&mut GkTransform, &mut GkRigidBody)>()
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::core::ecs::{ GkSchedule, GkWorld };
+
+    fn simulate(world: &mut GkWorld, ticks: u32, step: f32)
+    {
+        let mut schedule = GkSchedule::new();
+        schedule.add_system(gk_rigid_body_system());
+        world.insert_resource(GkDeltaTime(step));
+        for _ in 0..ticks { schedule.run(world); }
+    }
+
+    #[test]
+    fn falling_bodies_follow_gravity()
+    {
+        let mut world = GkWorld::new();
+        world.insert_resource(GkPhysicsSettings::default());
+        let ball = world.spawn();
+        world.insert(ball, GkTransform::from_translation(glm::Vec3::new(0.0, 0.0, 100.0)));
+        world.insert(ball, GkRigidBody::new(2.0).with_velocity(glm::Vec3::new(1.0, 0.0, 0.0)));
+        let floor = world.spawn();
+        world.insert(floor, GkTransform::identity());
+        world.insert(floor, GkRigidBody::new(0.0));
+
+        simulate(&mut world, 100, 0.01);
+        let position = world.get::<GkTransform>(ball).unwrap().translation;
+        // Semi-implicit Euler over-shoots the exact 4.905 by a step's worth of velocity.
+        assert!((position.x - 1.0).abs() < 1e-4);
+        assert!((100.0 - position.z - 4.905).abs() < 0.1, "fell to {}", position.z);
+        assert_eq!(world.get::<GkTransform>(floor).unwrap().translation, glm::Vec3::zeros());
+    }
+
+    #[test]
+    fn previous_transforms_lag_one_tick_behind()
+    {
+        let mut world = GkWorld::new();
+        world.insert_resource(GkPhysicsSettings { gravity: glm::Vec3::zeros() });
+        world.insert_resource(GkDeltaTime(1.0));
+        let body = world.spawn();
+        world.insert(body, GkTransform::identity());
+        world.insert(body, GkRigidBody::new(1.0).with_velocity(glm::Vec3::new(2.0, 0.0, 0.0)));
+
+        let mut schedule = GkSchedule::new();
+        schedule.add_system(gk_previous_transform_system());
+        schedule.add_system(gk_rigid_body_system());
+        assert_eq!(schedule.batches(), vec![vec!["previous transforms"], vec!["rigid bodies"]]);
+
+        schedule.run(&mut world);
+        assert_eq!(world.get::<GkPreviousTransform>(body).unwrap().0.translation.x, 0.0);
+        schedule.run(&mut world);
+        assert_eq!(world.get::<GkPreviousTransform>(body).unwrap().0.translation.x, 2.0);
+        assert_eq!(world.get::<GkTransform>(body).unwrap().translation.x, 4.0);
+    }
+
+    #[test]
+    fn forces_and_spin_apply_for_one_tick()
+    {
+        let mut world = GkWorld::new();
+        let body = world.spawn();
+        world.insert(body, GkTransform::identity());
+        let mut rigid_body = GkRigidBody::new(4.0).with_gravity(false)
+            .with_angular_velocity(glm::Vec3::new(0.0, 0.0, std::f32::consts::PI));
+        rigid_body.apply_force(glm::Vec3::new(40.0, 0.0, 0.0));
+        world.insert(body, rigid_body);
+
+        simulate(&mut world, 10, 0.1);
+        let body_state = *world.get::<GkRigidBody>(body).unwrap();
+        assert!((body_state.velocity.x - 1.0).abs() < 1e-5);
+        assert_eq!(body_state.force, glm::Vec3::zeros());
+        // Half a turn around z in a second.
+        let forward = glm::quat_rotate_vec3(&world.get::<GkTransform>(body).unwrap().rotation, &glm::Vec3::x());
+        assert!((forward - glm::Vec3::new(-1.0, 0.0, 0.0)).norm() < 1e-4);
+    }
+}
@@ -0,0 +1,91 @@
+/// ### GkEntity struct
+/// *A game object, only an id. Ids of despawned entities stay dead even after their index is reused.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GkEntity
+{
+    index:      u32,
+    generation: u32,
+}
+
+impl GkEntity
+{
+    /// Slot of the entity, unique among the living ones.
+    pub fn index(&self) -> u32
+    {
+        self.index
+    }
+
+    pub fn generation(&self) -> u32
+    {
+        self.generation
+    }
+}
+
+/// ### GkEntities struct
+/// *Hands out entity ids, reusing the indices of despawned entities with a new generation.*
+#[derive(Default)]
+pub struct GkEntities
+{
+    generations:    Vec<u32>,
+    alive:          Vec<bool>,
+    free:           Vec<u32>,
+}
+
+impl GkEntities
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self) -> GkEntity
+    {
+        match self.free.pop()
+        {
+            Some(index) =>
+            {
+                self.alive[index as usize] = true;
+                GkEntity { index, generation: self.generations[index as usize] }
+            },
+            None =>
+            {
+                self.generations.push(0);
+                self.alive.push(true);
+                GkEntity { index: self.generations.len() as u32 - 1, generation: 0 }
+            },
+        }
+    }
+
+    /// Returns false if entity was already despawned.
+    pub fn despawn(&mut self, entity: GkEntity) -> bool
+    {
+        if !self.is_alive(entity) { return false; }
+        let index = entity.index as usize;
+        self.alive[index] = false;
+        self.generations[index] = self.generations[index].wrapping_add(1);
+        self.free.push(entity.index);
+        true
+    }
+
+    pub fn is_alive(&self, entity: GkEntity) -> bool
+    {
+        let index = entity.index as usize;
+        index < self.alive.len() && self.alive[index] && self.generations[index] == entity.generation
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.generations.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = GkEntity> + '_
+    {
+        self.alive.iter().enumerate().filter(|(_, &b_alive)| b_alive)
+            .map(|(index, _)| GkEntity { index: index as u32, generation: self.generations[index] })
+    }
+}
@@ -0,0 +1,20 @@
+// Entity-component-system for game objects. Entities are ids, components live in one sparse set per type,
+// systems declare what they borrow and a GkSchedule runs them in conflict free batches.
+
+pub mod entity;
+pub mod storage;
+pub mod world;
+pub mod query;
+pub mod system;
+pub mod commands;
+pub mod components;
+pub mod scene_sync;
+
+pub use entity::GkEntity;
+pub use storage::GkSparseSet;
+pub use world::GkWorld;
+pub use query::{ GkQuery, IQueryParam };
+pub use system::{ GkAccess, GkSystem, GkFnSystem, GkSchedule };
+pub use commands::GkCommands;
+pub use components::{ GkMeshRenderer, GkCameraComponent, GkPreviousTransform, GkRigidBody, GkDeltaTime, GkPhysicsSettings, gk_previous_transform_system, gk_rigid_body_system };
+pub use scene_sync::GkSceneSync;
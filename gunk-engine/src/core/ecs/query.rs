@@ -0,0 +1,212 @@
+use std::cell::{ Ref, RefMut };
+
+use super::entity::GkEntity;
+use super::storage::GkSparseSet;
+use super::system::GkAccess;
+use super::world::GkWorld;
+
+/// ### IQueryParam trait
+/// *What a GkQuery borrows and hands out per entity. Implemented for &T, &mut T and tuples of up to four of them.*
+pub trait IQueryParam
+{
+    /// Storages held for the life of the query.
+    type Guard<'w>;
+    /// One entity's components.
+    type Item<'g>;
+
+    fn borrow(world: &GkWorld) -> Self::Guard<'_>;
+
+    /// Entities that may match. The shortest storage of a tuple, so queries walk the rarest component.
+    fn candidates<'g>(guard: &'g Self::Guard<'_>) -> &'g [GkEntity];
+
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, entity: GkEntity) -> Option<Self::Item<'g>>;
+
+    /// Adds what the query borrows to a system's access.
+    fn declare(access: &mut GkAccess);
+}
+
+impl<T: 'static> IQueryParam for &T
+{
+    type Guard<'w> = Option<Ref<'w, GkSparseSet<T>>>;
+    type Item<'g> = &'g T;
+
+    fn borrow(world: &GkWorld) -> Self::Guard<'_>
+    {
+        world.storage::<T>()
+    }
+
+    fn candidates<'g>(guard: &'g Self::Guard<'_>) -> &'g [GkEntity]
+    {
+        guard.as_ref().map_or(&[], |storage| storage.entities())
+    }
+
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, entity: GkEntity) -> Option<Self::Item<'g>>
+    {
+        guard.as_ref()?.get(entity)
+    }
+
+    fn declare(access: &mut GkAccess)
+    {
+        access.add_read::<T>();
+    }
+}
+
+impl<T: 'static> IQueryParam for &mut T
+{
+    type Guard<'w> = Option<RefMut<'w, GkSparseSet<T>>>;
+    type Item<'g> = &'g mut T;
+
+    fn borrow(world: &GkWorld) -> Self::Guard<'_>
+    {
+        world.storage_mut::<T>()
+    }
+
+    fn candidates<'g>(guard: &'g Self::Guard<'_>) -> &'g [GkEntity]
+    {
+        guard.as_ref().map_or(&[], |storage| storage.entities())
+    }
+
+    fn fetch<'g>(guard: &'g mut Self::Guard<'_>, entity: GkEntity) -> Option<Self::Item<'g>>
+    {
+        guard.as_mut()?.get_mut(entity)
+    }
+
+    fn declare(access: &mut GkAccess)
+    {
+        access.add_write::<T>();
+    }
+}
+
+macro_rules! impl_query_tuple
+{
+    ($($name:ident: $param:ident),+) =>
+    {
+        impl<$($param: IQueryParam),+> IQueryParam for ($($param,)+)
+        {
+            type Guard<'w> = ($($param::Guard<'w>,)+);
+            type Item<'g> = ($($param::Item<'g>,)+);
+
+            fn borrow(world: &GkWorld) -> Self::Guard<'_>
+            {
+                ($($param::borrow(world),)+)
+            }
+
+            fn candidates<'g>(guard: &'g Self::Guard<'_>) -> &'g [GkEntity]
+            {
+                let ($($name,)+) = guard;
+                let mut shortest: Option<&[GkEntity]> = None;
+                $(
+                    let entities = $param::candidates($name);
+                    if shortest.map_or(true, |shortest| entities.len() < shortest.len()) { shortest = Some(entities); }
+                )+
+                shortest.unwrap_or(&[])
+            }
+
+            fn fetch<'g>(guard: &'g mut Self::Guard<'_>, entity: GkEntity) -> Option<Self::Item<'g>>
+            {
+                let ($($name,)+) = guard;
+                Some(($($param::fetch($name, entity)?,)+))
+            }
+
+            fn declare(access: &mut GkAccess)
+            {
+                $($param::declare(access);)+
+            }
+        }
+    };
+}
+
+impl_query_tuple!(a: A);
+impl_query_tuple!(a: A, b: B);
+impl_query_tuple!(a: A, b: B, c: C);
+impl_query_tuple!(a: A, b: B, c: C, d: D);
+
+/// ### GkQuery struct
+/// *Entities having every component of P, made by GkWorld::query(). The components stay borrowed until the query is dropped.*
+pub struct GkQuery<'w, P: IQueryParam>
+{
+    guard:  P::Guard<'w>,
+}
+
+impl<'w, P: IQueryParam> GkQuery<'w, P>
+{
+    pub(crate) fn new(world: &'w GkWorld) -> Self
+    {
+        Self { guard: P::borrow(world) }
+    }
+
+    /// ### fn GkQuery::for_each( &mut self, ... )
+    /// *Calls f with every matching entity and its components.*
+    pub fn for_each(&mut self, mut f: impl for<'g> FnMut(GkEntity, P::Item<'g>))
+    {
+        for entity in P::candidates(&self.guard).to_vec()
+        {
+            if let Some(item) = P::fetch(&mut self.guard, entity) { f(entity, item); }
+        }
+    }
+
+    /// entity's components, None if it lacks one of them.
+    pub fn get(&mut self, entity: GkEntity) -> Option<P::Item<'_>>
+    {
+        P::fetch(&mut self.guard, entity)
+    }
+
+    pub fn entities(&mut self) -> Vec<GkEntity>
+    {
+        let mut entities = Vec::new();
+        self.for_each(|entity, _| entities.push(entity));
+        entities
+    }
+
+    pub fn count(&mut self) -> usize
+    {
+        let mut count = 0;
+        self.for_each(|_, _| count += 1);
+        count
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Position(f32);
+    #[derive(Debug, PartialEq)]
+    struct Velocity(f32);
+    struct Frozen;
+
+    #[test]
+    fn queries_match_entities_with_every_component()
+    {
+        let mut world = GkWorld::new();
+        let moving = world.spawn();
+        let parked = world.spawn();
+        let ghost = world.spawn();
+        world.insert(moving, Position(1.0));
+        world.insert(moving, Velocity(2.0));
+        world.insert(parked, Position(5.0));
+        world.insert(ghost, Velocity(9.0));
+
+        world.query::<(&mut Position, &Velocity)>().for_each(|_, (position, velocity)| position.0 += velocity.0);
+        assert_eq!(*world.get::<Position>(moving).unwrap(), Position(3.0));
+        assert_eq!(*world.get::<Position>(parked).unwrap(), Position(5.0));
+
+        assert_eq!(world.query::<&Position>().count(), 2);
+        assert_eq!(world.query::<(&Position, &Velocity)>().entities(), [moving]);
+        assert_eq!(world.query::<(&Position, &Frozen)>().count(), 0);
+        assert!(world.query::<(&Position, &Velocity)>().get(parked).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn a_query_cannot_name_a_component_twice()
+    {
+        let mut world = GkWorld::new();
+        let entity = world.spawn();
+        world.insert(entity, Position(0.0));
+        world.query::<(&mut Position, &Position)>().count();
+    }
+}
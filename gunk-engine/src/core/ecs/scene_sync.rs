@@ -0,0 +1,148 @@
+use std::collections::{ HashMap, HashSet };
+
+use crate::math::GkTransform;
+use crate::scene::{ GkCameraAttachment, GkMeshAttachment, GkNodeId, GkSceneGraph };
+use super::components::{ GkCameraComponent, GkMeshRenderer, GkPreviousTransform };
+use super::entity::GkEntity;
+use super::world::GkWorld;
+
+/// ### GkSceneSync struct
+/// *Mirrors entities with a GkTransform and a GkMeshRenderer or GkCameraComponent into the renderer's scene,
+/// one root node per entity. Nodes of entities that lose those components or are despawned are removed.*
+#[derive(Default)]
+pub struct GkSceneSync
+{
+    nodes:  HashMap<GkEntity, GkNodeId>,
+}
+
+impl GkSceneSync
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// Scene node mirroring entity, if it has one.
+    pub fn node(&self, entity: GkEntity) -> Option<GkNodeId>
+    {
+        self.nodes.get(&entity).copied()
+    }
+
+    /// ### fn GkSceneSync::sync( &mut self, ... )
+    /// *Brings scene up to date with world. Called once per rendered frame, unchanged transforms leave their node's world matrix cached.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     world:  &GkWorld
+    ///     scene:  &mut GkSceneGraph
+    ///     alpha:  f32     <i>// GkFrameTiming::alpha, how far into the next tick the frame is. Entities with a GkPreviousTransform are drawn that far from it to their GkTransform.</i>
+    /// </pre>
+    pub fn sync(&mut self, world: &GkWorld, scene: &mut GkSceneGraph, alpha: f32)
+    {
+        let mut seen = HashSet::new();
+        if let Some(transforms) = world.storage::<GkTransform>()
+        {
+            let meshes = world.storage::<GkMeshRenderer>();
+            let cameras = world.storage::<GkCameraComponent>();
+            let previous = world.storage::<GkPreviousTransform>();
+            for (entity, current) in transforms.iter()
+            {
+                let mesh = meshes.as_ref().and_then(|meshes| meshes.get(entity));
+                let camera = cameras.as_ref().and_then(|cameras| cameras.get(entity));
+                if mesh.is_none() && camera.is_none() { continue; }
+                let transform = match previous.as_ref().and_then(|previous| previous.get(entity))
+                {
+                    Some(previous) => &previous.0.lerp(current, alpha),
+                    None => current,
+                };
+
+                let id = match self.nodes.get(&entity)
+                {
+                    Some(&id) if scene.contains(id) => id,
+                    _ => scene.add_node(&format!("entity {}", entity.index()), *transform),
+                };
+                self.nodes.insert(entity, id);
+                seen.insert(entity);
+
+                if scene.node(id).is_some_and(|node| node.local() != transform) { scene.set_local(id, *transform); }
+                if let Some(node) = scene.node_mut(id)
+                {
+                    node.mesh = mesh.map(|mesh| GkMeshAttachment { mesh: mesh.mesh.clone(), b_visible: mesh.b_visible });
+                    node.camera = camera.map(|camera| GkCameraAttachment { slot: camera.slot });
+                }
+            }
+        }
+
+        self.nodes.retain(|entity, id|
+        {
+            let b_keep = seen.contains(entity);
+            if !b_keep { scene.remove_node(*id); }
+            b_keep
+        });
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use std::path::PathBuf;
+
+    use nalgebra_glm as glm;
+
+    use super::*;
+
+    #[test]
+    fn entities_are_mirrored_until_they_stop_rendering()
+    {
+        let mut world = GkWorld::new();
+        let mut scene = GkSceneGraph::new();
+        let mut sync = GkSceneSync::new();
+
+        let room = world.spawn();
+        world.insert(room, GkTransform::from_translation(glm::Vec3::new(1.0, 2.0, 3.0)));
+        world.insert(room, GkMeshRenderer::new(PathBuf::from("room.obj")));
+        let eye = world.spawn();
+        world.insert(eye, GkTransform::identity());
+        world.insert(eye, GkCameraComponent { slot: 1 });
+        let logic_only = world.spawn();
+        world.insert(logic_only, GkTransform::identity());
+
+        sync.sync(&world, &mut scene, 1.0);
+        scene.update_world_transforms();
+        assert_eq!(scene.len(), 2);
+        let room_node = sync.node(room).unwrap();
+        assert_eq!(scene.node(room_node).unwrap().world_position(), glm::Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(scene.cameras().next().unwrap().0.slot, 1);
+        assert!(sync.node(logic_only).is_none());
+
+        // Unchanged transforms keep their cached world matrices.
+        sync.sync(&world, &mut scene, 1.0);
+        assert_eq!(scene.update_world_transforms(), 0);
+        world.get_mut::<GkTransform>(room).unwrap().translation.z = 10.0;
+        sync.sync(&world, &mut scene, 1.0);
+        assert_eq!(scene.update_world_transforms(), 1);
+
+        world.remove::<GkMeshRenderer>(room);
+        world.despawn(eye);
+        sync.sync(&world, &mut scene, 1.0);
+        assert!(scene.is_empty());
+        assert!(sync.node(room).is_none() && !scene.contains(room_node));
+    }
+
+    #[test]
+    fn entities_are_drawn_between_their_last_two_ticks()
+    {
+        let mut world = GkWorld::new();
+        let mut scene = GkSceneGraph::new();
+        let mut sync = GkSceneSync::new();
+        let ball = world.spawn();
+        world.insert(ball, GkTransform::from_translation(glm::Vec3::new(4.0, 0.0, 0.0)));
+        world.insert(ball, GkPreviousTransform(GkTransform::identity()));
+        world.insert(ball, GkMeshRenderer::new(PathBuf::from("ball.obj")));
+
+        sync.sync(&world, &mut scene, 0.25);
+        scene.update_world_transforms();
+        assert_eq!(scene.node(sync.node(ball).unwrap()).unwrap().world_position(), glm::Vec3::new(1.0, 0.0, 0.0));
+    }
+}
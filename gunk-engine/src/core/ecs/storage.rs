@@ -0,0 +1,187 @@
+use std::any::Any;
+
+use super::entity::GkEntity;
+
+const EMPTY: u32 = u32::MAX;
+
+/// ### GkSparseSet struct
+/// *Components of one type, packed for iteration and indexed by entity for lookups.*<br>
+/// *Adding and removing are O(1). Removing moves the last component into the hole, so order is not kept.*
+pub struct GkSparseSet<T>
+{
+    sparse:     Vec<u32>,       // Entity index to position in dense, EMPTY when the entity has no component
+    entities:   Vec<GkEntity>,
+    components: Vec<T>,
+}
+
+impl<T> Default for GkSparseSet<T>
+{
+    fn default() -> Self
+    {
+        Self { sparse: Vec::new(), entities: Vec::new(), components: Vec::new() }
+    }
+}
+
+impl<T> GkSparseSet<T>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkSparseSet::insert( &mut self, ... ) -> Option\<T\>
+    /// *Adds or replaces entity's component, returning the replaced one.*
+    pub fn insert(&mut self, entity: GkEntity, component: T) -> Option<T>
+    {
+        let index = entity.index() as usize;
+        if index >= self.sparse.len() { self.sparse.resize(index + 1, EMPTY); }
+        match self.sparse[index]
+        {
+            EMPTY =>
+            {
+                self.sparse[index] = self.entities.len() as u32;
+                self.entities.push(entity);
+                self.components.push(component);
+                None
+            },
+            dense =>
+            {
+                // The slot may still hold a despawned entity with the same index.
+                let dense = dense as usize;
+                let previous = std::mem::replace(&mut self.components[dense], component);
+                let b_same = std::mem::replace(&mut self.entities[dense], entity) == entity;
+                b_same.then_some(previous)
+            },
+        }
+    }
+
+    pub fn remove(&mut self, entity: GkEntity) -> Option<T>
+    {
+        let dense = self.dense_index(entity)?;
+        self.sparse[entity.index() as usize] = EMPTY;
+        self.entities.swap_remove(dense);
+        let component = self.components.swap_remove(dense);
+        if let Some(moved) = self.entities.get(dense) { self.sparse[moved.index() as usize] = dense as u32; }
+        Some(component)
+    }
+
+    pub fn get(&self, entity: GkEntity) -> Option<&T>
+    {
+        self.dense_index(entity).map(|dense| &self.components[dense])
+    }
+
+    pub fn get_mut(&mut self, entity: GkEntity) -> Option<&mut T>
+    {
+        self.dense_index(entity).map(|dense| &mut self.components[dense])
+    }
+
+    pub fn contains(&self, entity: GkEntity) -> bool
+    {
+        self.dense_index(entity).is_some()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.entities.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.entities.is_empty()
+    }
+
+    /// Entities with a component, in storage order.
+    pub fn entities(&self) -> &[GkEntity]
+    {
+        &self.entities
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GkEntity, &T)>
+    {
+        self.entities.iter().copied().zip(self.components.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (GkEntity, &mut T)>
+    {
+        self.entities.iter().copied().zip(self.components.iter_mut())
+    }
+
+    fn dense_index(&self, entity: GkEntity) -> Option<usize>
+    {
+        let dense = *self.sparse.get(entity.index() as usize)?;
+        (dense != EMPTY && self.entities[dense as usize] == entity).then_some(dense as usize)
+    }
+}
+
+/// Type erased storage, so GkWorld can drop a despawned entity's components without knowing their types.
+pub(crate) trait IComponentStorage
+{
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn remove_entity(&mut self, entity: GkEntity) -> bool;
+}
+
+impl<T: 'static> IComponentStorage for GkSparseSet<T>
+{
+    fn as_any(&self) -> &dyn Any
+    {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+
+    fn remove_entity(&mut self, entity: GkEntity) -> bool
+    {
+        self.remove(entity).is_some()
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::core::ecs::entity::GkEntities;
+
+    #[test]
+    fn removing_keeps_the_other_components_reachable()
+    {
+        let mut entities = GkEntities::new();
+        let ids: Vec<GkEntity> = (0..6).map(|_| entities.spawn()).collect();
+        let mut set = GkSparseSet::new();
+        for (i, &entity) in ids.iter().enumerate() { set.insert(entity, i * 10); }
+
+        assert_eq!(set.remove(ids[1]), Some(10));
+        assert_eq!(set.remove(ids[1]), None);
+        assert_eq!(set.remove(ids[5]), Some(50));
+        assert_eq!(set.len(), 4);
+        for (i, &entity) in ids.iter().enumerate()
+        {
+            let expected = (i != 1 && i != 5).then_some(i * 10);
+            assert_eq!(set.get(entity).copied(), expected);
+        }
+        assert!(set.iter().all(|(entity, &value)| ids.iter().position(|&id| id == entity) == Some(value / 10)));
+    }
+
+    #[test]
+    fn stale_entities_do_not_see_the_new_occupant()
+    {
+        let mut entities = GkEntities::new();
+        let old = entities.spawn();
+        let mut set = GkSparseSet::new();
+        set.insert(old, "old");
+        entities.despawn(old);
+        let new = entities.spawn();
+        assert_eq!(new.index(), old.index());
+
+        assert_eq!(set.get(new), None);
+        assert_eq!(set.insert(new, "new"), None);
+        assert_eq!(set.get(old), None);
+        assert_eq!(set.get(new), Some(&"new"));
+        assert_eq!(set.insert(new, "newer"), Some("new"));
+        assert_eq!(set.len(), 1);
+    }
+}
@@ -0,0 +1,284 @@
+use std::any::TypeId;
+
+use super::commands::GkCommands;
+use super::query::IQueryParam;
+use super::world::GkWorld;
+
+/// ### GkAccess struct
+/// *The component and resource types a system reads and writes. Writing a type also allows reading it.*
+#[derive(Clone, Debug, Default)]
+pub struct GkAccess
+{
+    read:   Vec<(TypeId, &'static str)>,
+    write:  Vec<(TypeId, &'static str)>,
+}
+
+impl GkAccess
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn add_read<T: 'static>(&mut self)
+    {
+        let type_id = TypeId::of::<T>();
+        if !self.reads(type_id) { self.read.push((type_id, std::any::type_name::<T>())); }
+    }
+
+    pub fn add_write<T: 'static>(&mut self)
+    {
+        let type_id = TypeId::of::<T>();
+        self.read.retain(|&(read, _)| read != type_id);
+        if !self.writes(type_id) { self.write.push((type_id, std::any::type_name::<T>())); }
+    }
+
+    pub fn reads(&self, type_id: TypeId) -> bool
+    {
+        self.writes(type_id) || self.read.iter().any(|&(read, _)| read == type_id)
+    }
+
+    pub fn writes(&self, type_id: TypeId) -> bool
+    {
+        self.write.iter().any(|&(write, _)| write == type_id)
+    }
+
+    /// ### fn GkAccess::conflicts_with( &self, ... ) -> bool
+    /// *Whether the two systems could not run at the same time, one writing a type the other uses.*
+    pub fn conflicts_with(&self, other: &GkAccess) -> bool
+    {
+        self.write.iter().any(|&(type_id, _)| other.reads(type_id))
+            || other.write.iter().any(|&(type_id, _)| self.reads(type_id))
+    }
+
+    /// Type names, for logs.
+    pub fn describe(&self) -> String
+    {
+        let read: Vec<&str> = self.read.iter().map(|&(_, name)| name).collect();
+        let write: Vec<&str> = self.write.iter().map(|&(_, name)| name).collect();
+        format!("reads [{}] writes [{}]", read.join(", "), write.join(", "))
+    }
+}
+
+/// ### GkSystem trait
+/// *Logic run over the world by a GkSchedule. The world only lends a system what access() declares.*
+pub trait GkSystem
+{
+    fn name(&self) -> &str;
+
+    fn access(&self) -> GkAccess;
+
+    // Structural changes, like spawning or adding components, go through commands and land after the system's batch.
+    fn run(&mut self, world: &GkWorld, commands: &mut GkCommands);
+}
+
+type GkSystemFn = Box<dyn FnMut(&GkWorld, &mut GkCommands)>;
+
+/// ### GkFnSystem struct
+/// *A GkSystem made from a closure, declaring its access with the builder functions.*
+/// <pre>
+/// GkFnSystem::new("movement", |world, _| world.query::&lt;(&mut GkTransform, &GkRigidBody)&gt;().for_each(...))
+///     .with_query::&lt;(&mut GkTransform, &GkRigidBody)&gt;()
+/// </pre>
+pub struct GkFnSystem
+{
+    name:   String,
+    access: GkAccess,
+    run:    GkSystemFn,
+}
+
+impl GkFnSystem
+{
+    pub fn new(name: &str, run: impl FnMut(&GkWorld, &mut GkCommands) + 'static) -> Self
+    {
+        Self { name: String::from(name), access: GkAccess::new(), run: Box::new(run) }
+    }
+
+    /// Declares reading the T component or resource.
+    pub fn reads<T: 'static>(mut self) -> Self
+    {
+        self.access.add_read::<T>();
+        self
+    }
+
+    /// Declares writing the T component or resource.
+    pub fn writes<T: 'static>(mut self) -> Self
+    {
+        self.access.add_write::<T>();
+        self
+    }
+
+    /// Declares everything the P query borrows.
+    pub fn with_query<P: IQueryParam>(mut self) -> Self
+    {
+        P::declare(&mut self.access);
+        self
+    }
+}
+
+impl GkSystem for GkFnSystem
+{
+    fn name(&self) -> &str
+    {
+        &self.name
+    }
+
+    fn access(&self) -> GkAccess
+    {
+        self.access.clone()
+    }
+
+    fn run(&mut self, world: &GkWorld, commands: &mut GkCommands)
+    {
+        (self.run)(world, commands)
+    }
+}
+
+/// ### GkSchedule struct
+/// *Runs systems in the order they were added, grouped into batches of systems whose access does not conflict.*<br>
+/// *A system lands in the batch after the last one holding a system it conflicts with, so conflicting systems keep their order.
+/// Batches run one after another and their systems in order, the grouping is what a worker pool would run in parallel.
+/// Commands are applied at the end of each batch.*
+#[derive(Default)]
+pub struct GkSchedule
+{
+    systems:    Vec<Box<dyn GkSystem>>,
+    accesses:   Vec<GkAccess>,
+    batches:    Vec<Vec<usize>>,
+}
+
+impl GkSchedule
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn add_system(&mut self, system: impl GkSystem + 'static) -> &mut Self
+    {
+        let access = system.access();
+        let batch = self.batches.iter()
+            .rposition(|batch| batch.iter().any(|&index| self.accesses[index].conflicts_with(&access)))
+            .map_or(0, |last| last + 1);
+        if batch == self.batches.len() { self.batches.push(Vec::new()); }
+        self.batches[batch].push(self.systems.len());
+
+        log_info!(format!("Added system '{}' to batch {}, {}", system.name(), batch, access.describe()));
+        self.systems.push(Box::new(system));
+        self.accesses.push(access);
+        self
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.systems.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.systems.is_empty()
+    }
+
+    /// System names in each batch.
+    pub fn batches(&self) -> Vec<Vec<&str>>
+    {
+        self.batches.iter().map(|batch| batch.iter().map(|&index| self.systems[index].name()).collect()).collect()
+    }
+
+    /// ### fn GkSchedule::run( &mut self, ... )
+    /// *Runs every system once.*
+    pub fn run(&mut self, world: &mut GkWorld)
+    {
+        for batch in self.batches.iter()
+        {
+            let mut commands = GkCommands::new(world);
+            for &index in batch.iter()
+            {
+                let system = &mut self.systems[index];
+                world.begin_system(system.name(), self.accesses[index].clone());
+                system.run(world, &mut commands);
+                world.end_system();
+            }
+            let queue = commands.into_queue();
+            for command in queue
+            {
+                command(world);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    struct Position(f32);
+    struct Velocity(f32);
+    struct Gravity(f32);
+
+    #[test]
+    fn conflicting_systems_go_to_later_batches()
+    {
+        let mut schedule = GkSchedule::new();
+        schedule
+            .add_system(GkFnSystem::new("gravity", |_, _| {}).reads::<Gravity>().writes::<Velocity>())
+            .add_system(GkFnSystem::new("render", |_, _| {}).reads::<Position>())
+            .add_system(GkFnSystem::new("movement", |_, _| {}).with_query::<(&mut Position, &Velocity)>())
+            .add_system(GkFnSystem::new("audio", |_, _| {}).reads::<Velocity>())
+            .add_system(GkFnSystem::new("debug", |_, _| {}).reads::<Position>().reads::<Gravity>());
+
+        assert_eq!(schedule.batches(), vec![vec!["gravity", "render"], vec!["movement", "audio"], vec!["debug"]]);
+    }
+
+    #[test]
+    fn systems_run_in_order_and_see_earlier_batches_commands()
+    {
+        let mut world = GkWorld::new();
+        world.insert_resource(Gravity(-10.0));
+        let log = Rc::new(RefCell::new(Vec::new()));
+
+        let mut schedule = GkSchedule::new();
+        let spawn_log = log.clone();
+        schedule.add_system(GkFnSystem::new("spawner", move |world, commands|
+        {
+            spawn_log.borrow_mut().push(format!("spawner sees {}", world.query::<&Position>().count()));
+            let entity = commands.spawn();
+            commands.insert(entity, Position(0.0));
+            commands.insert(entity, Velocity(1.0));
+        }).reads::<Position>());
+        schedule.add_system(GkFnSystem::new("gravity", |world, _|
+        {
+            let gravity = world.resource::<Gravity>().unwrap().0;
+            world.query::<&mut Velocity>().for_each(|_, velocity| velocity.0 += gravity * 0.1);
+        }).reads::<Gravity>().writes::<Velocity>());
+        let move_log = log.clone();
+        schedule.add_system(GkFnSystem::new("movement", move |world, _|
+        {
+            world.query::<(&mut Position, &Velocity)>().for_each(|_, (position, velocity)| position.0 += velocity.0);
+            world.query::<&Position>().for_each(|_, position| move_log.borrow_mut().push(format!("at {}", position.0)));
+        }).with_query::<(&mut Position, &Velocity)>());
+
+        schedule.run(&mut world);
+        schedule.run(&mut world);
+        // The first spawn lands before movement runs, the second velocity is zeroed by gravity before moving.
+        assert_eq!(*log.borrow(), ["spawner sees 0", "at 1", "spawner sees 1", "at 1", "at 1"]);
+        assert_eq!(world.entity_count(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "system 'sneaky' writes")]
+    fn undeclared_access_panics()
+    {
+        let mut world = GkWorld::new();
+        let entity = world.spawn();
+        world.insert(entity, Position(0.0));
+        let mut schedule = GkSchedule::new();
+        schedule.add_system(GkFnSystem::new("sneaky", |world, _| { world.query::<&mut Position>().count(); }).reads::<Position>());
+        schedule.run(&mut world);
+    }
+}
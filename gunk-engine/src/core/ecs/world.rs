@@ -0,0 +1,262 @@
+use std::any::{ Any, TypeId };
+use std::cell::{ Ref, RefCell, RefMut };
+use std::collections::HashMap;
+
+use super::entity::{ GkEntities, GkEntity };
+use super::query::{ GkQuery, IQueryParam };
+use super::storage::{ GkSparseSet, IComponentStorage };
+use super::system::GkAccess;
+
+/// ### GkWorld struct
+/// *Entities, their components and the world's resources. Any 'static type can be a component or a resource.*<br>
+/// *Components and resources are borrowed at runtime, so systems share a &GkWorld and borrow only what they use.
+/// Borrowing a type mutably twice at once panics, as a RefCell would.*
+#[derive(Default)]
+pub struct GkWorld
+{
+    entities:       RefCell<GkEntities>,
+    storages:       HashMap<TypeId, RefCell<Box<dyn IComponentStorage>>>,
+    resources:      HashMap<TypeId, RefCell<Box<dyn Any>>>,
+    running:        RefCell<Option<(String, GkAccess)>>,    // The system being run and what it declared
+}
+
+impl GkWorld
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self) -> GkEntity
+    {
+        self.entities.get_mut().spawn()
+    }
+
+    /// ### fn GkWorld::reserve_entity( &self ) -> GkEntity
+    /// *Spawns an entity without components through a shared world, for GkCommands::spawn().*
+    pub fn reserve_entity(&self) -> GkEntity
+    {
+        self.entities.borrow_mut().spawn()
+    }
+
+    /// ### fn GkWorld::despawn( &mut self, ... ) -> bool
+    /// *Drops entity's components and frees its id. Returns false if it was already despawned.*
+    pub fn despawn(&mut self, entity: GkEntity) -> bool
+    {
+        if !self.entities.get_mut().despawn(entity) { return false; }
+        for storage in self.storages.values_mut()
+        {
+            storage.get_mut().remove_entity(entity);
+        }
+        true
+    }
+
+    pub fn is_alive(&self, entity: GkEntity) -> bool
+    {
+        self.entities.borrow().is_alive(entity)
+    }
+
+    pub fn entity_count(&self) -> usize
+    {
+        self.entities.borrow().len()
+    }
+
+    pub fn entities(&self) -> Vec<GkEntity>
+    {
+        self.entities.borrow().iter().collect()
+    }
+
+    /// ### fn GkWorld::insert\<T\>( &mut self, ... ) -> bool
+    /// *Adds or replaces entity's T. Returns false, dropping component, if entity was despawned.*
+    pub fn insert<T: 'static>(&mut self, entity: GkEntity, component: T) -> bool
+    {
+        if !self.entities.get_mut().is_alive(entity) { return false; }
+        let storage = self.storages.entry(TypeId::of::<T>()).or_insert_with(|| RefCell::new(Box::new(GkSparseSet::<T>::new())));
+        downcast_mut::<T>(storage.get_mut().as_mut()).insert(entity, component);
+        true
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: GkEntity) -> Option<T>
+    {
+        let storage = self.storages.get_mut(&TypeId::of::<T>())?;
+        downcast_mut::<T>(storage.get_mut().as_mut()).remove(entity)
+    }
+
+    pub fn has<T: 'static>(&self, entity: GkEntity) -> bool
+    {
+        self.storage::<T>().is_some_and(|storage| storage.contains(entity))
+    }
+
+    pub fn get<T: 'static>(&self, entity: GkEntity) -> Option<Ref<'_, T>>
+    {
+        Ref::filter_map(self.storage::<T>()?, |storage| storage.get(entity)).ok()
+    }
+
+    pub fn get_mut<T: 'static>(&self, entity: GkEntity) -> Option<RefMut<'_, T>>
+    {
+        RefMut::filter_map(self.storage_mut::<T>()?, |storage| storage.get_mut(entity)).ok()
+    }
+
+    /// ### fn GkWorld::storage\<T\>( &self ) -> Option\<Ref\<GkSparseSet\<T\>\>\>
+    /// *Every T, None if no entity ever had one. Panics if T is borrowed mutably, or undeclared by the running system.*
+    pub fn storage<T: 'static>(&self) -> Option<Ref<'_, GkSparseSet<T>>>
+    {
+        self.check_access::<T>(false);
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        let storage = storage.try_borrow().unwrap_or_else(|_| panic!("{} is already borrowed mutably", std::any::type_name::<T>()));
+        Some(Ref::map(storage, |storage| downcast_ref::<T>(storage.as_ref())))
+    }
+
+    /// ### fn GkWorld::storage_mut\<T\>( &self ) -> Option\<RefMut\<GkSparseSet\<T\>\>\>
+    /// *Every T, None if no entity ever had one. Panics if T is already borrowed, or not declared written by the running system.*
+    pub fn storage_mut<T: 'static>(&self) -> Option<RefMut<'_, GkSparseSet<T>>>
+    {
+        self.check_access::<T>(true);
+        let storage = self.storages.get(&TypeId::of::<T>())?;
+        let storage = storage.try_borrow_mut().unwrap_or_else(|_| panic!("{} is already borrowed", std::any::type_name::<T>()));
+        Some(RefMut::map(storage, |storage| downcast_mut::<T>(storage.as_mut())))
+    }
+
+    /// ### fn GkWorld::query\<P\>( &self ) -> GkQuery\<P\>
+    /// *Borrows the components named by P, such as (&GkTransform, &mut GkRigidBody), for the entities that have all of them.*
+    pub fn query<P: IQueryParam>(&self) -> GkQuery<'_, P>
+    {
+        GkQuery::new(self)
+    }
+
+    /// Adds or replaces the T resource, returning the replaced one.
+    pub fn insert_resource<T: 'static>(&mut self, resource: T) -> Option<T>
+    {
+        self.resources.insert(TypeId::of::<T>(), RefCell::new(Box::new(resource)))
+            .and_then(|previous| previous.into_inner().downcast::<T>().ok())
+            .map(|previous| *previous)
+    }
+
+    pub fn remove_resource<T: 'static>(&mut self) -> Option<T>
+    {
+        self.resources.remove(&TypeId::of::<T>())
+            .and_then(|resource| resource.into_inner().downcast::<T>().ok())
+            .map(|resource| *resource)
+    }
+
+    pub fn has_resource<T: 'static>(&self) -> bool
+    {
+        self.resources.contains_key(&TypeId::of::<T>())
+    }
+
+    pub fn resource<T: 'static>(&self) -> Option<Ref<'_, T>>
+    {
+        self.check_access::<T>(false);
+        let resource = self.resources.get(&TypeId::of::<T>())?;
+        let resource = resource.try_borrow().unwrap_or_else(|_| panic!("{} is already borrowed mutably", std::any::type_name::<T>()));
+        Ref::filter_map(resource, |resource| resource.downcast_ref::<T>()).ok()
+    }
+
+    pub fn resource_mut<T: 'static>(&self) -> Option<RefMut<'_, T>>
+    {
+        self.check_access::<T>(true);
+        let resource = self.resources.get(&TypeId::of::<T>())?;
+        let resource = resource.try_borrow_mut().unwrap_or_else(|_| panic!("{} is already borrowed", std::any::type_name::<T>()));
+        RefMut::filter_map(resource, |resource| resource.downcast_mut::<T>()).ok()
+    }
+
+    /// Limits borrows to what a system declared, until end_system().
+    pub(crate) fn begin_system(&self, name: &str, access: GkAccess)
+    {
+        *self.running.borrow_mut() = Some((String::from(name), access));
+    }
+
+    pub(crate) fn end_system(&self)
+    {
+        *self.running.borrow_mut() = None;
+    }
+
+    fn check_access<T: 'static>(&self, b_write: bool)
+    {
+        let running = self.running.borrow();
+        let Some((name, access)) = running.as_ref() else { return; };
+        let type_id = TypeId::of::<T>();
+        let b_allowed = if b_write { access.writes(type_id) } else { access.reads(type_id) };
+        if !b_allowed
+        {
+            panic!("system '{}' {} {} without declaring it", name, if b_write { "writes" } else { "reads" }, std::any::type_name::<T>());
+        }
+    }
+}
+
+fn downcast_ref<T: 'static>(storage: &dyn IComponentStorage) -> &GkSparseSet<T>
+{
+    storage.as_any().downcast_ref::<GkSparseSet<T>>().expect("component storage holds another type")
+}
+
+fn downcast_mut<T: 'static>(storage: &mut dyn IComponentStorage) -> &mut GkSparseSet<T>
+{
+    storage.as_any_mut().downcast_mut::<GkSparseSet<T>>().expect("component storage holds another type")
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Health(i32);
+    #[derive(Debug, PartialEq)]
+    struct Name(&'static str);
+
+    #[test]
+    fn despawning_drops_every_component()
+    {
+        let mut world = GkWorld::new();
+        let hero = world.spawn();
+        let villain = world.spawn();
+        world.insert(hero, Health(10));
+        world.insert(hero, Name("hero"));
+        world.insert(villain, Health(5));
+
+        assert!(world.despawn(hero));
+        assert!(!world.despawn(hero));
+        assert!(!world.insert(hero, Health(1)));
+        assert!(!world.has::<Health>(hero) && !world.has::<Name>(hero));
+        assert_eq!(world.storage::<Health>().unwrap().len(), 1);
+        assert_eq!(world.entities(), [villain]);
+
+        let reborn = world.spawn();
+        assert_eq!(reborn.index(), hero.index());
+        assert!(world.get::<Health>(reborn).is_none());
+    }
+
+    #[test]
+    fn components_and_resources_borrow_independently()
+    {
+        let mut world = GkWorld::new();
+        let entity = world.spawn();
+        world.insert(entity, Health(3));
+        world.insert(entity, Name("crate"));
+        assert_eq!(world.insert_resource(7u32), None);
+        assert_eq!(world.insert_resource(8u32), Some(7));
+
+        {
+            let mut health = world.get_mut::<Health>(entity).unwrap();
+            let name = world.get::<Name>(entity).unwrap();
+            *world.resource_mut::<u32>().unwrap() += 1;
+            health.0 -= name.0.len() as i32;
+        }
+        assert_eq!(*world.get::<Health>(entity).unwrap(), Health(-2));
+        assert_eq!(world.remove_resource::<u32>(), Some(9));
+        assert!(world.resource::<u32>().is_none());
+        assert_eq!(world.remove::<Name>(entity), Some(Name("crate")));
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn borrowing_a_component_mutably_twice_panics()
+    {
+        let mut world = GkWorld::new();
+        let entity = world.spawn();
+        world.insert(entity, Health(1));
+        let _first = world.storage_mut::<Health>();
+        let _second = world.storage_mut::<Health>();
+    }
+}
@@ -1,67 +1,74 @@
-use std::thread;
 use std::time::Duration;
 use std::time::Instant;
 
-const SAMPLE_COUNT: usize = 5;
-const SAMPLE_COUNT_FLOAT: f32 = SAMPLE_COUNT as f32;
+use crate::core::frame_scheduler::{ gk_sleep_until, GkFrameStats };
+
+/// Frames kept for fps() and stats().
+const SAMPLE_COUNT: usize = 240;
 
 pub struct FPSLimiter {
-    counter: Instant,
-    frame_time_prefer: u32, // unit microseconds
-    samples: [u32; SAMPLE_COUNT],
-    current_frame: usize,
-    delta_frame: u32,
+    counter: Instant, // start of the current frame
+    frame_time_prefer: Option<Duration>, // None runs uncapped
+    delta_frame: Duration,
+    stats: GkFrameStats,
 }
 
 impl FPSLimiter {
     pub fn new() -> FPSLimiter {
         const DEFAULT_PREFER_FPS: f32 = 60.0;
 
-        FPSLimiter {
+        let mut limiter = FPSLimiter {
             counter: Instant::now(),
-            frame_time_prefer: (1000_000.0_f32 / DEFAULT_PREFER_FPS) as u32,
-            samples: [0; SAMPLE_COUNT],
-            current_frame: 0,
-            delta_frame: 0,
-        }
+            frame_time_prefer: None,
+            delta_frame: Duration::ZERO,
+            stats: GkFrameStats::new(SAMPLE_COUNT),
+        };
+        limiter.set_prefer_fps(DEFAULT_PREFER_FPS);
+        limiter
     }
 
+    /// Caps the frame rate, 0 or less removes the cap.
     pub fn set_prefer_fps(&mut self, prefer_fps: f32) {
-        self.frame_time_prefer = (1000_000.0_f32 / prefer_fps) as u32;
+        self.frame_time_prefer = if prefer_fps > 0.0 {
+            Some(Duration::from_secs_f64(1.0 / prefer_fps as f64))
+        } else {
+            None
+        };
     }
 
-    /// Call this function in game loop to update its inner status.
+    /// Call this function at the start of every frame to update its inner status.
     pub fn tick_frame(&mut self) {
-        let time_elapsed = self.counter.elapsed();
-        self.counter = Instant::now();
-
-        self.delta_frame = time_elapsed.subsec_micros();
-        self.samples[self.current_frame] = self.delta_frame;
-        self.current_frame = (self.current_frame + 1) % SAMPLE_COUNT;
+        let now = Instant::now();
+        self.delta_frame = now - self.counter;
+        self.counter = now;
+        self.stats.push(self.delta_frame);
     }
 
-    // TODO: this function seems not work.
-    pub fn keep_fps(&self) {
-        if self.frame_time_prefer > self.delta_frame {
-            let delay = Duration::from_micros((self.frame_time_prefer - self.delta_frame) as u64);
-
-            thread::sleep(delay);
+    /// Call this function at the end of a frame. Waits until the preferred frame time has passed
+    /// since tick_frame(), sleeping then spinning for the last bit unless b_spin is false.
+    pub fn keep_fps(&self, b_spin: bool) {
+        if let Some(frame_time) = self.frame_time_prefer {
+            gk_sleep_until(self.counter + frame_time, b_spin);
         }
     }
 
-    /// Calculate the current FPS.
+    /// Calculate the current FPS from the average of the recent frames.
     pub fn fps(&self) -> f32 {
-        let mut sum = 0_u32;
-        self.samples.iter().for_each(|val| {
-            sum += val;
-        });
-
-        1000_000.0_f32 / (sum as f32 / SAMPLE_COUNT_FLOAT)
+        let avg_ms = self.stats.avg_ms();
+        if avg_ms > 0.0 { (1000.0 / avg_ms) as f32 } else { 0.0 }
     }
 
-    /// Return current delta time in seconds
-    /// this function ignore its second part, since the second is mostly zero.
+    /// Return the last frame's duration in seconds.
     pub fn delta_time(&self) -> f32 {
-        self.delta_frame as f32 / 1000_000.0_f32 // time in second
+        self.delta_frame.as_secs_f32()
+    }
+
+    pub fn delta(&self) -> Duration {
+        self.delta_frame
+    }
+
+    /// Frame times of the recent frames.
+    pub fn stats(&self) -> &GkFrameStats {
+        &self.stats
     }
 }
@@ -0,0 +1,363 @@
+use std::collections::VecDeque;
+use std::time::{ Duration, Instant };
+
+use crate::core::fps_limiter::FPSLimiter;
+use crate::core::time::GkTime;
+
+/// Time before a deadline spent spinning instead of sleeping, covering the os scheduler's wake up latency.
+const GK_SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+/// ### fn gk_sleep_until( ... )
+/// *Sleeps until shortly before deadline, then spins until it passes.*
+/// <pre>
+/// - Params
+///     deadline:   Instant
+///     b_spin:     bool    <i>// False sleeps the whole way, for when a little lateness is fine.</i>
+/// </pre>
+pub fn gk_sleep_until(deadline: Instant, b_spin: bool)
+{
+    let now = Instant::now();
+    if now >= deadline { return; }
+
+    let remaining = deadline - now;
+    if !b_spin
+    {
+        std::thread::sleep(remaining);
+        return;
+    }
+    if remaining > GK_SPIN_MARGIN
+    {
+        std::thread::sleep(remaining - GK_SPIN_MARGIN);
+    }
+    while Instant::now() < deadline
+    {
+        std::hint::spin_loop();
+    }
+}
+
+/// ### GkFrameStatsSummary struct
+/// *Frame times in milliseconds over the stats window.*
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GkFrameStatsSummary
+{
+    pub frames: usize,
+    pub min:    f64,
+    pub avg:    f64,
+    pub max:    f64,
+    pub p50:    f64,
+    pub p95:    f64,
+    pub p99:    f64,
+}
+
+impl std::fmt::Display for GkFrameStatsSummary
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{} frames: min {:.2} ms, avg {:.2} ms, max {:.2} ms, p50 {:.2} ms, p95 {:.2} ms, p99 {:.2} ms",
+            self.frames, self.min, self.avg, self.max, self.p50, self.p95, self.p99)
+    }
+}
+
+/// ### GkFrameStats struct
+/// *Rolling window of the latest frame times.*
+/// <pre>
+/// - Members
+///     samples:    VecDeque&lt;f64&gt;    <i>// Milliseconds, oldest first.</i>
+///     capacity:   usize
+/// </pre>
+#[derive(Clone, Debug)]
+pub struct GkFrameStats
+{
+    samples:    VecDeque<f64>,
+    capacity:   usize,
+}
+
+impl GkFrameStats
+{
+    pub fn new(capacity: usize) -> Self
+    {
+        let capacity = capacity.max(1);
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    pub fn push(&mut self, frame_time: Duration)
+    {
+        if self.samples.len() == self.capacity { self.samples.pop_front(); }
+        self.samples.push_back(frame_time.as_secs_f64() * 1000.0);
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.samples.is_empty()
+    }
+
+    /// ### fn GkFrameStats::avg_ms( &self ) -> f64
+    /// *Average frame time, 0 if no frame was recorded yet.*
+    pub fn avg_ms(&self) -> f64
+    {
+        if self.samples.is_empty() { return 0.0; }
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    /// ### fn GkFrameStats::percentile_ms( &self, ... ) -> f64
+    /// *Frame time below which percent of the window's frames fall, nearest rank.*
+    pub fn percentile_ms(&self, percent: f64) -> f64
+    {
+        let sorted = self.sorted();
+        percentile_of(&sorted, percent)
+    }
+
+    /// ### fn GkFrameStats::summary( &self ) -> GkFrameStatsSummary
+    /// *Min, average, max and percentiles of the window.*
+    pub fn summary(&self) -> GkFrameStatsSummary
+    {
+        let sorted = self.sorted();
+        if sorted.is_empty() { return GkFrameStatsSummary::default(); }
+        GkFrameStatsSummary
+        {
+            frames: sorted.len(),
+            min: sorted[0],
+            avg: self.avg_ms(),
+            max: sorted[sorted.len() - 1],
+            p50: percentile_of(&sorted, 50.0),
+            p95: percentile_of(&sorted, 95.0),
+            p99: percentile_of(&sorted, 99.0),
+        }
+    }
+
+    fn sorted(&self) -> Vec<f64>
+    {
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        sorted
+    }
+}
+
+fn percentile_of(sorted: &[f64], percent: f64) -> f64
+{
+    if sorted.is_empty() { return 0.0; }
+    let rank = (percent.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// ### GkFixedTimestep struct
+/// *Accumulates frame time and hands it out in fixed size update ticks.*
+/// <pre>
+/// - Members
+///     step:           Duration
+///     max_ticks:      u32         <i>// Ticks per frame at most. Time beyond that is dropped so a slow frame can't snowball.</i>
+///     accumulator:    Duration    <i>// Time not yet simulated, less than step after advance().</i>
+/// </pre>
+#[derive(Clone, Debug)]
+pub struct GkFixedTimestep
+{
+    step:           Duration,
+    max_ticks:      u32,
+    accumulator:    Duration,
+}
+
+impl GkFixedTimestep
+{
+    /// ### fn GkFixedTimestep::new( ... ) -> GkFixedTimestep
+    /// <pre>
+    /// - Params
+    ///     tick_rate:  f32     <i>// Ticks per second.</i>
+    ///     max_ticks:  u32
+    /// - Return
+    ///     GkFixedTimestep
+    /// </pre>
+    pub fn new(tick_rate: f32, max_ticks: u32) -> Self
+    {
+        Self
+        {
+            step: Duration::from_secs_f64(1.0 / tick_rate.max(1.0) as f64),
+            max_ticks: max_ticks.max(1),
+            accumulator: Duration::ZERO,
+        }
+    }
+
+    pub fn step(&self) -> Duration
+    {
+        self.step
+    }
+
+    /// ### fn GkFixedTimestep::advance( &mut self, ... ) -> u32
+    /// *Adds a frame's time and returns how many ticks to run for it.*
+    pub fn advance(&mut self, frame_time: Duration) -> u32
+    {
+        self.accumulator += frame_time;
+        let mut ticks = 0;
+        while self.accumulator >= self.step && ticks < self.max_ticks
+        {
+            self.accumulator -= self.step;
+            ticks += 1;
+        }
+        if self.accumulator >= self.step
+        {
+            self.accumulator = Duration::ZERO;
+        }
+        ticks
+    }
+
+    /// ### fn GkFixedTimestep::alpha( &self ) -> f32
+    /// *How far between the last tick and the next one the current frame is, in [0, 1).<br>
+    /// Used to interpolate between the previous and current simulation state when rendering.*
+    pub fn alpha(&self) -> f32
+    {
+        (self.accumulator.as_secs_f64() / self.step.as_secs_f64()) as f32
+    }
+}
+
+/// ### GkFrameTiming struct
+/// *What a frame should do, from GkFrameScheduler::begin_frame().*
+/// <pre>
+/// - Members
+///     delta:  f32     <i>// Game seconds since the previous frame, for variable rate rendering.</i>
+///     ticks:  u32     <i>// Fixed update ticks to run before rendering.</i>
+///     step:   f32     <i>// Seconds per fixed tick.</i>
+///     alpha:  f32     <i>// How far past the last tick the frame is, in ticks. Application::sync_scene() draws entities this far between their last two ticks.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkFrameTiming
+{
+    pub delta:  f32,
+    pub ticks:  u32,
+    pub step:   f32,
+    pub alpha:  f32,
+}
+
+/// ### GkFrameScheduler struct
+/// *Runs the loop as fixed update ticks followed by one variable rate render,
+/// capped by an FPSLimiter.*
+/// <pre>
+/// - Members
+///     limiter:    FPSLimiter
+///     fixed:      GkFixedTimestep
+/// </pre>
+pub struct GkFrameScheduler
+{
+    pub limiter:    FPSLimiter,
+    pub fixed:      GkFixedTimestep,
+}
+
+impl GkFrameScheduler
+{
+    /// ### fn GkFrameScheduler::new( ... ) -> GkFrameScheduler
+    /// <pre>
+    /// - Params
+    ///     tick_rate:  f32             <i>// Fixed update ticks per second.</i>
+    ///     max_fps:    Option&lt;f32&gt;     <i>// Frame cap, None to run uncapped.</i>
+    /// - Return
+    ///     GkFrameScheduler
+    /// </pre>
+    pub fn new(tick_rate: f32, max_fps: Option<f32>) -> Self
+    {
+        let mut limiter = FPSLimiter::new();
+        limiter.set_prefer_fps(max_fps.unwrap_or(0.0));
+        Self { limiter, fixed: GkFixedTimestep::new(tick_rate, 8) }
+    }
+
+    /// ### fn GkFrameScheduler::begin_frame( &mut self, ... ) -> GkFrameTiming
+    /// *Ends the previous frame's timing, advances time by it and works out this frame's ticks.*<br>
+    /// *Ticks follow game time, so none run while time is paused.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     time:       &mut GkTime
+    /// - Return
+    ///     GkFrameTiming
+    /// </pre>
+    pub fn begin_frame(&mut self, time: &mut GkTime) -> GkFrameTiming
+    {
+        self.limiter.tick_frame();
+        let real_delta = self.limiter.delta();
+        self.advance(time, real_delta)
+    }
+
+    /// ### fn GkFrameScheduler::begin_replayed_frame( &mut self, ... ) -> GkFrameTiming
+    /// *Like begin_frame(), but advances time by a recorded frame time instead of the wall clock, so a replay
+    /// runs the same ticks as the recording did. The limiter still measures and paces the real frame.*
+    pub fn begin_replayed_frame(&mut self, time: &mut GkTime, real_delta: Duration) -> GkFrameTiming
+    {
+        self.limiter.tick_frame();
+        self.advance(time, real_delta)
+    }
+
+    fn advance(&mut self, time: &mut GkTime, real_delta: Duration) -> GkFrameTiming
+    {
+        let game_delta = time.advance(real_delta);
+        let ticks = self.fixed.advance(game_delta);
+        GkFrameTiming
+        {
+            delta: time.delta(),
+            ticks,
+            step: self.fixed.step().as_secs_f32(),
+            alpha: self.fixed.alpha(),
+        }
+    }
+
+    /// ### fn GkFrameScheduler::end_frame( &self, ... )
+    /// *Waits out the rest of the frame if a cap is set.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     b_present_paced:    bool    <i>// Presenting already waits for vsync, so sleeping is accurate enough.</i>
+    /// </pre>
+    pub fn end_frame(&self, b_present_paced: bool)
+    {
+        self.limiter.keep_fps(!b_present_paced);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{ Duration, Instant };
+
+    use super::{ gk_sleep_until, GkFixedTimestep, GkFrameStats };
+
+    #[test]
+    fn fixed_timestep_carries_the_remainder_as_alpha()
+    {
+        let mut fixed = GkFixedTimestep::new(50.0, 4);
+
+        assert_eq!(fixed.advance(Duration::from_millis(30)), 1);
+        assert!((fixed.alpha() - 0.5).abs() < 1e-4);
+        assert_eq!(fixed.advance(Duration::from_millis(10)), 1);
+        assert!(fixed.alpha().abs() < 1e-4);
+
+        // A frame longer than max_ticks steps drops the excess instead of catching up later.
+        assert_eq!(fixed.advance(Duration::from_secs(2)), 4);
+        assert_eq!(fixed.alpha(), 0.0);
+    }
+
+    #[test]
+    fn stats_report_min_max_and_percentiles()
+    {
+        let mut stats = GkFrameStats::new(100);
+        stats.push(Duration::from_secs(5));
+        for ms in 1..=100 { stats.push(Duration::from_millis(ms)); }
+
+        let summary = stats.summary();
+        assert_eq!(summary.frames, 100);
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 100.0);
+        assert!((summary.avg - 50.5).abs() < 1e-9);
+        assert_eq!(summary.p50, 50.0);
+        assert_eq!(summary.p95, 95.0);
+        assert_eq!(summary.p99, 99.0);
+    }
+
+    #[test]
+    fn sleep_until_does_not_return_early()
+    {
+        let deadline = Instant::now() + Duration::from_millis(3);
+        gk_sleep_until(deadline, true);
+        assert!(Instant::now() >= deadline);
+    }
+}
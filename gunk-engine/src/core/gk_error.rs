@@ -0,0 +1,119 @@
+use ash::vk;
+
+/// ### GkError enum
+/// *Errors returned by the engine's fallible constructors and loaders.*
+/// <pre>
+/// - Variants
+///     Vulkan(vk::Result)      <i>// A Vulkan call failed.</i>
+///     Allocation(String)      <i>// gpu_allocator could not allocate or free memory.</i>
+///     Io(String)              <i>// A file could not be opened or read.</i>
+///     ImageDecode(String)     <i>// An image file could not be decoded.</i>
+///     ShaderCompile(String)   <i>// A glsl shader failed to compile to spirv.</i>
+///     Asset(String)           <i>// An asset is malformed or missing data.</i>
+///     Unsupported(String)     <i>// The hardware or platform lacks something required.</i>
+///     Config(String)          <i>// A config file is malformed.</i>
+/// </pre>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GkError
+{
+    Vulkan(vk::Result),
+    Allocation(String),
+    Io(String),
+    ImageDecode(String),
+    ShaderCompile(String),
+    Asset(String),
+    Unsupported(String),
+    Config(String),
+}
+
+/// Result type used across the engine.
+pub type GkResult<T> = Result<T, GkError>;
+
+impl GkError
+{
+    /// ### fn GkError::io( ... ) -> GkError
+    /// *Wraps an io error together with the path it happened on.*
+    /// <pre>
+    /// - Params
+    ///     path:   &std::path::Path
+    ///     err:    std::io::Error
+    /// - Return
+    ///     GkError
+    /// </pre>
+    pub fn io(path: &std::path::Path, err: std::io::Error) -> Self
+    {
+        GkError::Io(format!("{}: {}", path.display(), err))
+    }
+}
+
+impl std::fmt::Display for GkError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            GkError::Vulkan(result) => write!(f, "vulkan error: {}", result),
+            GkError::Allocation(msg) => write!(f, "allocation error: {}", msg),
+            GkError::Io(msg) => write!(f, "io error: {}", msg),
+            GkError::ImageDecode(msg) => write!(f, "image decode error: {}", msg),
+            GkError::ShaderCompile(msg) => write!(f, "shader compile error: {}", msg),
+            GkError::Asset(msg) => write!(f, "asset error: {}", msg),
+            GkError::Unsupported(msg) => write!(f, "unsupported: {}", msg),
+            GkError::Config(msg) => write!(f, "config error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for GkError {}
+
+impl From<vk::Result> for GkError
+{
+    fn from(result: vk::Result) -> Self
+    {
+        GkError::Vulkan(result)
+    }
+}
+
+impl From<gpu_allocator::AllocationError> for GkError
+{
+    fn from(err: gpu_allocator::AllocationError) -> Self
+    {
+        GkError::Allocation(err.to_string())
+    }
+}
+
+impl From<image::ImageError> for GkError
+{
+    fn from(err: image::ImageError) -> Self
+    {
+        match err
+        {
+            image::ImageError::IoError(e) => GkError::Io(e.to_string()),
+            e => GkError::ImageDecode(e.to_string())
+        }
+    }
+}
+
+impl From<shaderc::Error> for GkError
+{
+    fn from(err: shaderc::Error) -> Self
+    {
+        GkError::ShaderCompile(err.to_string())
+    }
+}
+
+impl From<tobj::LoadError> for GkError
+{
+    fn from(err: tobj::LoadError) -> Self
+    {
+        GkError::Asset(err.to_string())
+    }
+}
+
+impl From<ash::LoadingError> for GkError
+{
+    fn from(err: ash::LoadingError) -> Self
+    {
+        GkError::Unsupported(err.to_string())
+    }
+}
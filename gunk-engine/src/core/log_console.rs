@@ -0,0 +1,175 @@
+use crate::core::logger::{ ELogLevel, GkLogRecord, GkLogRing };
+
+/// ### GkLogConsole struct
+/// *State of a scrollable log console panel reading from a GkLogRing.*<br>
+/// *Holds which levels are shown, a text filter and the scroll position. Drawing it is up to the UI.*
+/// <pre>
+/// - Members
+///     ring:           GkLogRing
+///     shown_levels:   [bool; 5]   <i>// Indexed by ELogLevel, Error first.</i>
+///     text_filter:    String      <i>// Case insensitive, matched against message and target. Empty shows all.</i>
+///     scroll:         usize       <i>// Rows scrolled up from the newest record. 0 follows new records.</i>
+/// </pre>
+pub struct GkLogConsole
+{
+    ring:               GkLogRing,
+    shown_levels:       [bool; 5],
+    pub text_filter:    String,
+    scroll:             usize,
+}
+
+impl GkLogConsole
+{
+    /// ### fn GkLogConsole::new( ... ) -> GkLogConsole
+    /// *A console showing every level of ring's records, following the newest.*
+    pub fn new(ring: GkLogRing) -> Self
+    {
+        Self { ring, shown_levels: [true; 5], text_filter: String::new(), scroll: 0 }
+    }
+
+    pub fn ring(&self) -> &GkLogRing
+    {
+        &self.ring
+    }
+
+    pub fn is_level_shown(&self, level: ELogLevel) -> bool
+    {
+        self.shown_levels[level as usize - 1]
+    }
+
+    pub fn set_level_shown(&mut self, level: ELogLevel, b_shown: bool)
+    {
+        self.shown_levels[level as usize - 1] = b_shown;
+    }
+
+    /// ### fn GkLogConsole::show_up_to( &mut self, ... )
+    /// *Shows level and every more severe level, hiding the rest.*
+    pub fn show_up_to(&mut self, level: ELogLevel)
+    {
+        for (index, shown) in self.shown_levels.iter_mut().enumerate()
+        {
+            *shown = index < level as usize;
+        }
+    }
+
+    /// ### fn GkLogConsole::filtered( &self ) -> Vec\<GkLogRecord\>
+    /// *Records passing the level and text filters, oldest first.*
+    pub fn filtered(&self) -> Vec<GkLogRecord>
+    {
+        let text = self.text_filter.to_lowercase();
+        self.ring.records().into_iter()
+            .filter(|record| self.is_level_shown(record.level))
+            .filter(|record|
+            {
+                text.is_empty()
+                    || record.message.to_lowercase().contains(&text)
+                    || record.target.to_lowercase().contains(&text)
+            })
+            .collect()
+    }
+
+    /// ### fn GkLogConsole::rows( &self, ... ) -> Vec\<GkLogRecord\>
+    /// *The filtered records visible in a panel height rows tall at the current scroll, oldest first.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     height:     usize
+    /// - Return
+    ///     Vec&lt;GkLogRecord&gt;
+    /// </pre>
+    pub fn rows(&self, height: usize) -> Vec<GkLogRecord>
+    {
+        let records = self.filtered();
+        let scroll = self.scroll.min(records.len().saturating_sub(height));
+        let end = records.len() - scroll;
+        records[end.saturating_sub(height)..end].to_vec()
+    }
+
+    /// ### fn GkLogConsole::scroll_by( &mut self, ... )
+    /// *Scrolls towards older records for positive rows, newer for negative.*
+    pub fn scroll_by(&mut self, rows: isize)
+    {
+        let max_scroll = self.filtered().len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(rows).min(max_scroll);
+    }
+
+    pub fn scroll_to_bottom(&mut self)
+    {
+        self.scroll = 0;
+    }
+
+    /// ### fn GkLogConsole::is_following( &self ) -> bool
+    /// *Whether the console is at the bottom and shows new records as they arrive.*
+    pub fn is_following(&self) -> bool
+    {
+        self.scroll == 0
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.ring.clear();
+        self.scroll = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::core::logger::{ ELogLevel, GkLogRecord, GkLogRing, GkLogSink };
+
+    use super::GkLogConsole;
+
+    fn console_with(records: &[(ELogLevel, &str)]) -> GkLogConsole
+    {
+        let mut ring = GkLogRing::new(16);
+        for (index, &(level, message)) in records.iter().enumerate()
+        {
+            ring.write(&GkLogRecord::new(level, "gunk_engine::test", String::from(message), "a.rs", index as u32, 1));
+        }
+        GkLogConsole::new(ring)
+    }
+
+    fn messages(records: &[GkLogRecord]) -> Vec<&str>
+    {
+        records.iter().map(|record| record.message.as_str()).collect()
+    }
+
+    #[test]
+    fn console_filters_by_level_and_text()
+    {
+        let mut console = console_with(&[
+            (ELogLevel::Info, "Creating swapchain"),
+            (ELogLevel::Warn, "Swapchain out of date"),
+            (ELogLevel::Debug, "Picked queue family 0"),
+            (ELogLevel::Error, "Cubemap file_names count must be either 1 or 6"),
+        ]);
+
+        console.show_up_to(ELogLevel::Warn);
+        assert_eq!(messages(&console.filtered()), vec!["Swapchain out of date", "Cubemap file_names count must be either 1 or 6"]);
+
+        console.set_level_shown(ELogLevel::Info, true);
+        console.text_filter = String::from("SWAPCHAIN");
+        assert_eq!(messages(&console.filtered()), vec!["Creating swapchain", "Swapchain out of date"]);
+    }
+
+    #[test]
+    fn console_scrolls_within_the_filtered_records()
+    {
+        let mut console = console_with(&[
+            (ELogLevel::Info, "a"), (ELogLevel::Info, "b"), (ELogLevel::Info, "c"), (ELogLevel::Info, "d"), (ELogLevel::Info, "e"),
+        ]);
+
+        assert!(console.is_following());
+        assert_eq!(messages(&console.rows(2)), vec!["d", "e"]);
+
+        console.scroll_by(2);
+        assert_eq!(messages(&console.rows(2)), vec!["b", "c"]);
+
+        console.scroll_by(10);
+        assert_eq!(messages(&console.rows(2)), vec!["a", "b"]);
+        assert_eq!(messages(&console.rows(10)), vec!["a", "b", "c", "d", "e"]);
+
+        console.scroll_by(-100);
+        assert!(console.is_following());
+    }
+}
@@ -1,17 +1,36 @@
-
 pub extern crate colored;
 
-// enum LogLevel
-// {
-//     Info,
-//     Warn,
-//     Error,
-// }
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fs::{ File, OpenOptions };
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{ Path, PathBuf };
+use std::sync::{ Arc, Mutex, OnceLock, RwLock };
+use std::sync::atomic::{ AtomicU8, Ordering };
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+use colored::Colorize;
+
+use crate::core::gk_error::{ GkError, GkResult };
+
+static LOGGER: OnceLock<GkLogger> = OnceLock::new();
+static LOG_BRIDGE: GkLogBridge = GkLogBridge;
 
+/// Records captured on a thread by each GkLogCapture alive on it.
+const GK_LOG_CAPTURE_CAPACITY: usize = 4096;
+
+thread_local!
+{
+    static CAPTURES: RefCell<Vec<GkLogRing>> = const { RefCell::new(Vec::new()) };
+}
+
+/// ### logger!( ... )
+/// *Prints its arguments straight to stdout, bypassing the log level and sinks.*
 #[macro_export]
 macro_rules! logger {
     () => {};
-    ( $x:expr ) => 
+    ( $x:expr ) =>
     {
         println!( "{} ", format!("{}", $x).as_str() );
     };
@@ -24,138 +43,82 @@ macro_rules! logger {
     };
 }
 
-/// ### log_info!( ... )
-/// *Logs messages as information.<br> Will print blue text in the terminal.*
+/// ### gk_log!( ... )
+/// *Sends a message to the logger's sinks if level is enabled for target.*<br>
+/// *Takes a format string and its arguments, a single Display value,
+/// or several Display values which are joined with spaces.*
+#[doc(hidden)]
 #[macro_export]
-macro_rules! log_info {
-    () => {};
-    ( $x:expr ) => 
-    {
-        let label: &str = "Info: ";
-        let data = format!("{} ", $x );
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-        
-        $crate::logger!(
-            // label.blue().bold(),
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::blue(label)),
-            // data.blue(),
-            $crate::core::logger::colored::Colorize::blue(data.as_str()),
-            // meta_data.blue()
-            $crate::core::logger::colored::Colorize::blue(meta_data.as_str())
-        );
+macro_rules! gk_log {
+    ( @write $level:expr, $target:expr, $message:expr ) =>
+    {
+        if $crate::core::logger::gk_logger().enabled($level, $target)
+        {
+            $crate::core::logger::gk_logger().log(&$crate::core::logger::GkLogRecord::new(
+                $level, $target, $message, file!(), line!(), column!()
+            ));
+        }
     };
-    ( $( $x:expr ),* ) =>
+    ( $level:expr, $target:expr $(,)? ) => {};
+    ( $level:expr, $target:expr, $fmt:literal $(, $arg:expr )+ $(,)? ) =>
     {
-        // use colored::Colorize;
+        $crate::gk_log!(@write $level, $target, format!($fmt $(, $arg )+))
+    };
+    ( $level:expr, $target:expr, $x:expr ) =>
+    {
+        $crate::gk_log!(@write $level, $target, format!("{}", $x))
+    };
+    ( $level:expr, $target:expr, $( $x:expr ),+ ) =>
+    {
+        $crate::gk_log!(@write $level, $target, {
+            let mut t = std::string::String::new();
+            $( t.push_str(format!("{} ", $x ).as_str()); )+
+            t.truncate(t.trim_end().len());
+            t
+        })
+    };
+}
 
-        let mut t = std::string::String::from("");
+/// ### log_trace!( ... )
+/// *Logs messages as tracing detail. Hidden unless the level allows Trace.*
+#[macro_export]
+macro_rules! log_trace {
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Trace, module_path!(), $( $x )*) };
+}
 
-        let label: &str = "Info: ";
-        $( t.push_str(format!("{} ", $x ).as_str()); )*
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-        
-        $crate::logger!(
-            // label.blue().bold(),
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::blue(label)),
-            // t.blue(), 
-            $crate::core::logger::colored::Colorize::blue(t.as_str()),
-            // meta_data.blue()
-            $crate::core::logger::colored::Colorize::blue(meta_data.as_str())
-        );
-    };
+/// ### log_debug!( ... )
+/// *Logs messages as debug output. Hidden unless the level allows Debug.*
+#[macro_export]
+macro_rules! log_debug {
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Debug, module_path!(), $( $x )*) };
+}
+
+/// ### log_info!( ... )
+/// *Logs messages as information.<br> Will print blue text in the terminal.*
+#[macro_export]
+macro_rules! log_info {
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Info, module_path!(), $( $x )*) };
 }
 
 /// ### log_warn!( ... )
 /// *Logs messages as warnings.<br> Will print yellow text in the terminal.*
 #[macro_export]
 macro_rules! log_warn {
-    () => {};
-    ( $x:expr ) => 
-    {
-        // use colored::Colorize;
-
-        let label: &str = "Warning: ";
-        let data = format!("{} ", $x );
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-        
-        $crate::logger!(
-            // label.yellow().bold(),
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::yellow(label)),
-            // data.yellow(), 
-            $crate::core::logger::colored::Colorize::yellow(data.as_str()),
-            // meta_data.yellow()
-            $crate::core::logger::colored::Colorize::yellow(meta_data.as_str())
-        );
-    };
-    ( $( $x:expr ),* ) =>
-    {
-        // use colored::Colorize;
-
-        let mut t = std::string::String::from("");
-
-        let label: &str = "Warning: ";
-        $( t.push_str(format!("{} ", $x ).as_str()); )*
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-        
-        $crate::logger!(
-            // label.yellow().bold(), 
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::yellow(label)),
-            // t.yellow(), 
-            $crate::core::logger::colored::Colorize::yellow(t.as_str()),
-            // meta_data.yellow()
-            $crate::core::logger::colored::Colorize::yellow(meta_data.as_str())
-        );
-    };
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Warn, module_path!(), $( $x )*) };
 }
 
 /// ### log_err!( ... )
 /// *Logs messages as errors.<br> Will print red text in the terminal.*
 #[macro_export]
 macro_rules! log_err {
-    () => {};
-    ( $x:expr ) => 
-    {
-        // use colored::Colorize;
-        
-        let label = "Error: ";
-        let data = format!("{} ", $x );
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-
-        $crate::logger!(
-            // label.red().bold(), 
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::red(label)),
-            // data.red(), 
-            $crate::core::logger::colored::Colorize::red(data.as_str()),
-            // meta_data.red()
-            $crate::core::logger::colored::Colorize::red(meta_data.as_str())
-        );
-    };
-    ( $( $x:expr ),* ) =>
-    {
-        // use colored::Colorize;
-
-        let mut t = std::string::String::from("");
-
-        let label: &str = "Error: ";
-        $( t.push_str(format!("{} ", $x ).as_str()); )*
-        let meta_data = format!("\n\tfile: {}({}) line: {}", file!(), line!(), line!() );
-
-        $crate::logger!(
-            // label.red().bold(), 
-            $crate::core::logger::colored::Colorize::bold($crate::core::logger::colored::Colorize::red(label)),
-            // t.red(), 
-            $crate::core::logger::colored::Colorize::red(t.as_str()),
-            // meta_data.red()
-            $crate::core::logger::colored::Colorize::red(meta_data.as_str())
-        );
-    };
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Error, module_path!(), $( $x )*) };
 }
 
 /// ### check_err!( ... )
 /// *Logs an error if present in Result\<()\>*
 #[macro_export]
 macro_rules! check_err {
-    ( $result:expr ) => 
+    ( $result:expr ) =>
     {
         match $result
         {
@@ -163,4 +126,739 @@ macro_rules! check_err {
             Err(e) => { $crate::log_err!(e); None }
         }
     };
-}
\ No newline at end of file
+}
+
+/// ### ELogLevel enum
+/// *Severity of a log record, most severe first.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ELogLevel
+{
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl ELogLevel
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self
+        {
+            ELogLevel::Error => "Error",
+            ELogLevel::Warn => "Warning",
+            ELogLevel::Info => "Info",
+            ELogLevel::Debug => "Debug",
+            ELogLevel::Trace => "Trace",
+        }
+    }
+
+    /// ### fn ELogLevel::parse( ... ) -> Option\<ELogLevel\>
+    /// *Reads error, warn, info, debug or trace, in any case.*
+    pub fn parse(name: &str) -> Option<Self>
+    {
+        match name.trim().to_ascii_lowercase().as_str()
+        {
+            "error" | "err" => Some(ELogLevel::Error),
+            "warn" | "warning" => Some(ELogLevel::Warn),
+            "info" => Some(ELogLevel::Info),
+            "debug" => Some(ELogLevel::Debug),
+            "trace" => Some(ELogLevel::Trace),
+            _ => None
+        }
+    }
+
+    fn from_u8(value: u8) -> Self
+    {
+        match value
+        {
+            1 => ELogLevel::Error,
+            2 => ELogLevel::Warn,
+            3 => ELogLevel::Info,
+            4 => ELogLevel::Debug,
+            _ => ELogLevel::Trace,
+        }
+    }
+
+    fn from_log(level: log::Level) -> Self
+    {
+        match level
+        {
+            log::Level::Error => ELogLevel::Error,
+            log::Level::Warn => ELogLevel::Warn,
+            log::Level::Info => ELogLevel::Info,
+            log::Level::Debug => ELogLevel::Debug,
+            log::Level::Trace => ELogLevel::Trace,
+        }
+    }
+
+    fn to_log_filter(self) -> log::LevelFilter
+    {
+        match self
+        {
+            ELogLevel::Error => log::LevelFilter::Error,
+            ELogLevel::Warn => log::LevelFilter::Warn,
+            ELogLevel::Info => log::LevelFilter::Info,
+            ELogLevel::Debug => log::LevelFilter::Debug,
+            ELogLevel::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+/// ### GkLogFilter struct
+/// *Most verbose level logged, overridable per target (module path prefix).*
+/// <pre>
+/// - Members
+///     default_level:  ELogLevel
+///     targets:        Vec&lt;(String, ELogLevel)&gt;     <i>// The longest matching target wins.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GkLogFilter
+{
+    pub default_level:  ELogLevel,
+    pub targets:        Vec<(String, ELogLevel)>,
+}
+
+impl Default for GkLogFilter
+{
+    fn default() -> Self
+    {
+        Self { default_level: ELogLevel::Info, targets: Vec::new() }
+    }
+}
+
+impl GkLogFilter
+{
+    /// ### fn GkLogFilter::parse( ... ) -> GkLogFilter
+    /// *Reads a comma separated spec like "warn,gunk_engine::renderer=debug,winit=error".<br>
+    /// A bare level sets default_level. Unreadable entries are skipped.*
+    /// <pre>
+    /// - Params
+    ///     spec:       &str
+    /// - Return
+    ///     GkLogFilter
+    /// </pre>
+    pub fn parse(spec: &str) -> Self
+    {
+        let mut filter = Self::default();
+        for entry in spec.split(',').map(|entry| entry.trim()).filter(|entry| !entry.is_empty())
+        {
+            match entry.split_once('=')
+            {
+                Some((target, level)) =>
+                {
+                    if let Some(level) = ELogLevel::parse(level)
+                    {
+                        filter.targets.push((String::from(target.trim()), level));
+                    }
+                },
+                None =>
+                {
+                    if let Some(level) = ELogLevel::parse(entry) { filter.default_level = level; }
+                }
+            }
+        }
+        filter
+    }
+
+    /// ### fn GkLogFilter::from_env() -> GkLogFilter
+    /// *Parses the GUNK_LOG environment variable, or the default filter if unset.*
+    pub fn from_env() -> Self
+    {
+        std::env::var("GUNK_LOG").map(|spec| Self::parse(&spec)).unwrap_or_default()
+    }
+
+    /// ### fn GkLogFilter::level_for( &self, ... ) -> ELogLevel
+    /// *Most verbose level logged for target.*
+    pub fn level_for(&self, target: &str) -> ELogLevel
+    {
+        self.targets.iter()
+            .filter(|(prefix, _)|
+            {
+                target.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default_level, |&(_, level)| level)
+    }
+
+    /// ### fn GkLogFilter::max_level( &self ) -> ELogLevel
+    /// *Most verbose level logged for any target.*
+    pub fn max_level(&self) -> ELogLevel
+    {
+        self.targets.iter().map(|&(_, level)| level).fold(self.default_level, ELogLevel::max)
+    }
+}
+
+/// ### GkLogRecord struct
+/// *A single log message with where and when it was logged.*
+/// <pre>
+/// - Members
+///     level:      ELogLevel
+///     target:     String      <i>// Module path of the caller, or the log crate target.</i>
+///     message:    String
+///     file:       String
+///     line:       u32
+///     column:     u32         <i>// 0 for records from the log crate.</i>
+///     timestamp:  SystemTime
+///     thread:     String      <i>// Thread name, or its id if unnamed.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GkLogRecord
+{
+    pub level:      ELogLevel,
+    pub target:     String,
+    pub message:    String,
+    pub file:       String,
+    pub line:       u32,
+    pub column:     u32,
+    pub timestamp:  SystemTime,
+    pub thread:     String,
+}
+
+impl GkLogRecord
+{
+    /// ### fn GkLogRecord::new( ... ) -> GkLogRecord
+    /// *Stamps a record with the current time and thread.*
+    /// <pre>
+    /// - Params
+    ///     level:      ELogLevel
+    ///     target:     &str
+    ///     message:    String
+    ///     file:       &str
+    ///     line:       u32
+    ///     column:     u32
+    /// - Return
+    ///     GkLogRecord
+    /// </pre>
+    pub fn new(level: ELogLevel, target: &str, message: String, file: &str, line: u32, column: u32) -> Self
+    {
+        let current = std::thread::current();
+        let thread = current.name().map_or_else(|| format!("{:?}", current.id()), String::from);
+        Self
+        {
+            level,
+            target: String::from(target),
+            message,
+            file: String::from(file),
+            line,
+            column,
+            timestamp: SystemTime::now(),
+            thread,
+        }
+    }
+
+    /// ### fn GkLogRecord::timestamp_string( &self ) -> String
+    /// *UTC timestamp formatted as YYYY-MM-DD hh:mm:ss.mmm*
+    pub fn timestamp_string(&self) -> String
+    {
+        let since_epoch = self.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let seconds = since_epoch.as_secs();
+        let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+        let time = seconds % 86400;
+        format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+            year, month, day, time / 3600, time / 60 % 60, time % 60, since_epoch.subsec_millis())
+    }
+}
+
+impl std::fmt::Display for GkLogRecord
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{} {:<7} [{}] {}: {} ({}:{}:{})",
+            self.timestamp_string(), self.level.label(), self.thread, self.target, self.message, self.file, self.line, self.column)
+    }
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32)
+{
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// ### GkLogSink trait
+/// *Destination for log records that passed the filter.*<br>
+/// *Sinks must not log themselves, the logger holds its sink lock while writing.*
+pub trait GkLogSink: Send
+{
+    fn write(&mut self, record: &GkLogRecord);
+    fn flush(&mut self) {}
+}
+
+/// ### GkTerminalSink struct
+/// *Prints records to stdout, colored by level.*
+#[derive(Default)]
+pub struct GkTerminalSink;
+
+impl GkLogSink for GkTerminalSink
+{
+    fn write(&mut self, record: &GkLogRecord)
+    {
+        let label = format!("{}: ", record.level.label());
+        let meta_data = format!("\n\t{} [{}] {} file: {} line: {} column: {}",
+            record.timestamp_string(), record.thread, record.target, record.file, record.line, record.column);
+        let (label, message, meta_data) = match record.level
+        {
+            ELogLevel::Error => (label.red().bold(), record.message.red(), meta_data.red()),
+            ELogLevel::Warn => (label.yellow().bold(), record.message.yellow(), meta_data.yellow()),
+            ELogLevel::Info => (label.blue().bold(), record.message.blue(), meta_data.blue()),
+            ELogLevel::Debug | ELogLevel::Trace => (label.dimmed().bold(), record.message.dimmed(), meta_data.dimmed()),
+        };
+        println!("{}{}{}", label, message, meta_data);
+    }
+
+    fn flush(&mut self)
+    {
+        let _ = std::io::stdout().flush();
+    }
+}
+
+/// ### GkFileSink struct
+/// *Appends records to a file, rotating it once it grows past max_bytes.*<br>
+/// *Rotated files are named path.1 (newest) to path.max_files (oldest).*
+/// <pre>
+/// - Members
+///     path:       PathBuf
+///     max_bytes:  u64
+///     max_files:  usize   <i>// Rotated files kept besides path. 0 truncates path instead.</i>
+/// </pre>
+pub struct GkFileSink
+{
+    path:       PathBuf,
+    max_bytes:  u64,
+    max_files:  usize,
+    file:       File,
+    written:    u64,
+}
+
+impl GkFileSink
+{
+    /// ### fn GkFileSink::new( ... ) -> GkResult\<GkFileSink\>
+    /// *Opens path for appending, creating it if needed.*
+    /// <pre>
+    /// - Params
+    ///     path:       &Path
+    ///     max_bytes:  u64
+    ///     max_files:  usize
+    /// - Return
+    ///     GkResult&lt;GkFileSink&gt;
+    /// </pre>
+    pub fn new(path: &Path, max_bytes: u64, max_files: usize) -> GkResult<Self>
+    {
+        let file = OpenOptions::new().create(true).append(true).open(path).map_err(|e| GkError::io(path, e))?;
+        let written = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        Ok(Self { path: path.to_path_buf(), max_bytes, max_files, file, written })
+    }
+
+    fn rotated_path(&self, index: usize) -> PathBuf
+    {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()>
+    {
+        self.file.flush()?;
+        if self.max_files > 0
+        {
+            let _ = std::fs::remove_file(self.rotated_path(self.max_files));
+            for index in (1..self.max_files).rev()
+            {
+                let from = self.rotated_path(index);
+                if from.exists() { std::fs::rename(&from, self.rotated_path(index + 1))?; }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+        }
+        self.file = OpenOptions::new().create(true).write(true).truncate(true).open(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl GkLogSink for GkFileSink
+{
+    fn write(&mut self, record: &GkLogRecord)
+    {
+        let line = format!("{}\n", record);
+        if self.written > 0 && self.written + line.len() as u64 > self.max_bytes
+        {
+            if let Err(e) = self.rotate()
+            {
+                eprintln!("Failed to rotate log file {}: {}", self.path.display(), e);
+            }
+        }
+        if self.file.write_all(line.as_bytes()).is_ok()
+        {
+            self.written += line.len() as u64;
+        }
+    }
+
+    fn flush(&mut self)
+    {
+        let _ = self.file.flush();
+    }
+}
+
+/// ### GkLogRing struct
+/// *Keeps the latest records in memory, e.g. for an editor console.*<br>
+/// *Clones share the same buffer, so one clone can be given to the logger and another kept for reading.*
+#[derive(Clone)]
+pub struct GkLogRing
+{
+    records:    Arc<Mutex<VecDeque<GkLogRecord>>>,
+    capacity:   usize,
+}
+
+impl GkLogRing
+{
+    pub fn new(capacity: usize) -> Self
+    {
+        Self { records: Arc::new(Mutex::new(VecDeque::new())), capacity: capacity.max(1) }
+    }
+
+    /// ### fn GkLogRing::records( &self ) -> Vec\<GkLogRecord\>
+    /// *Records currently held, oldest first.*
+    pub fn records(&self) -> Vec<GkLogRecord>
+    {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter().cloned().collect()
+    }
+
+    pub fn clear(&self)
+    {
+        self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+
+    fn push(&self, record: &GkLogRecord)
+    {
+        let mut records = self.records.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if records.len() == self.capacity { records.pop_front(); }
+        records.push_back(record.clone());
+    }
+}
+
+/// ### GkLogCapture struct
+/// *Collects every record logged on the creating thread while it is alive, whatever the filter and sinks.*<br>
+/// *Meant for tests asserting on log output. Records logged by other threads, e.g. asset workers, are not captured.*
+pub struct GkLogCapture
+{
+    ring:       GkLogRing,
+    _thread:    PhantomData<*const ()>,  // Stays on the thread it captures.
+}
+
+impl GkLogCapture
+{
+    /// ### fn GkLogCapture::start() -> GkLogCapture
+    /// *Starts capturing on the current thread until the capture is dropped.*
+    pub fn start() -> Self
+    {
+        let ring = GkLogRing::new(GK_LOG_CAPTURE_CAPACITY);
+        CAPTURES.with(|captures| captures.borrow_mut().push(ring.clone()));
+        Self { ring, _thread: PhantomData }
+    }
+
+    /// ### fn GkLogCapture::records( &self ) -> Vec\<GkLogRecord\>
+    /// *Captured records, oldest first.*
+    pub fn records(&self) -> Vec<GkLogRecord>
+    {
+        self.ring.records()
+    }
+
+    /// ### fn GkLogCapture::find( &self, ... ) -> Option\<GkLogRecord\>
+    /// *First captured record of level whose message contains text.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     level:      ELogLevel
+    ///     text:       &str
+    /// - Return
+    ///     Option&lt;GkLogRecord&gt;
+    /// </pre>
+    pub fn find(&self, level: ELogLevel, text: &str) -> Option<GkLogRecord>
+    {
+        self.records().into_iter().find(|record| record.level == level && record.message.contains(text))
+    }
+
+    /// ### fn GkLogCapture::assert_logged( &self, ... ) -> GkLogRecord
+    /// *Like find(), but panics listing the captured records if there is no match.*
+    pub fn assert_logged(&self, level: ELogLevel, text: &str) -> GkLogRecord
+    {
+        self.find(level, text).unwrap_or_else(||
+        {
+            let captured: Vec<String> = self.records().iter().map(|record| record.to_string()).collect();
+            panic!("no {} record containing {:?} was logged, captured:\n{}", level.label(), text, captured.join("\n"))
+        })
+    }
+
+    pub fn clear(&self)
+    {
+        self.ring.clear();
+    }
+}
+
+impl Drop for GkLogCapture
+{
+    fn drop(&mut self)
+    {
+        CAPTURES.with(|captures| captures.borrow_mut().retain(|ring| !Arc::ptr_eq(&ring.records, &self.ring.records)));
+    }
+}
+
+fn is_capturing() -> bool
+{
+    CAPTURES.with(|captures| !captures.borrow().is_empty())
+}
+
+impl GkLogSink for GkLogRing
+{
+    fn write(&mut self, record: &GkLogRecord)
+    {
+        self.push(record);
+    }
+}
+
+/// ### GkLogger struct
+/// *Filters records and hands them to every sink.*
+/// <pre>
+/// - Members
+///     max_level:  AtomicU8    <i>// Cached GkLogFilter::max_level(), checked before taking any lock.</i>
+///     filter:     RwLock&lt;GkLogFilter&gt;
+///     sinks:      Mutex&lt;Vec&lt;Box&lt;dyn GkLogSink&gt;&gt;&gt;
+/// </pre>
+pub struct GkLogger
+{
+    max_level:  AtomicU8,
+    filter:     RwLock<GkLogFilter>,
+    sinks:      Mutex<Vec<Box<dyn GkLogSink>>>,
+}
+
+impl GkLogger
+{
+    /// ### fn GkLogger::new( ... ) -> GkLogger
+    /// *A logger without sinks.*
+    pub fn new(filter: GkLogFilter) -> Self
+    {
+        Self
+        {
+            max_level: AtomicU8::new(filter.max_level() as u8),
+            filter: RwLock::new(filter),
+            sinks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn filter(&self) -> GkLogFilter
+    {
+        self.filter.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// ### fn GkLogger::set_filter( &self, ... )
+    /// *Replaces the filter. Also applies to records coming through the log crate bridge.*
+    pub fn set_filter(&self, filter: GkLogFilter)
+    {
+        let max_level = filter.max_level();
+        *self.filter.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = filter;
+        self.max_level.store(max_level as u8, Ordering::Relaxed);
+        if std::ptr::eq(self, gk_logger()) { log::set_max_level(max_level.to_log_filter()); }
+    }
+
+    pub fn max_level(&self) -> ELogLevel
+    {
+        ELogLevel::from_u8(self.max_level.load(Ordering::Relaxed))
+    }
+
+    /// ### fn GkLogger::enabled( &self, ... ) -> bool
+    /// *Whether a record of level for target would be logged, or captured by a GkLogCapture on this thread.*
+    pub fn enabled(&self, level: ELogLevel, target: &str) -> bool
+    {
+        self.passes_filter(level, target) || is_capturing()
+    }
+
+    fn passes_filter(&self, level: ELogLevel, target: &str) -> bool
+    {
+        level <= self.max_level()
+            && level <= self.filter.read().unwrap_or_else(|poisoned| poisoned.into_inner()).level_for(target)
+    }
+
+    pub fn add_sink(&self, sink: Box<dyn GkLogSink>)
+    {
+        self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(sink);
+    }
+
+    /// ### fn GkLogger::clear_sinks( &self )
+    /// *Flushes and removes every sink, including the default terminal sink.*
+    pub fn clear_sinks(&self)
+    {
+        let mut sinks = self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for sink in sinks.iter_mut() { sink.flush(); }
+        sinks.clear();
+    }
+
+    /// ### fn GkLogger::log( &self, ... )
+    /// *Hands record to the thread's captures, then to every sink if the filter allows it.*
+    pub fn log(&self, record: &GkLogRecord)
+    {
+        CAPTURES.with(|captures| for ring in captures.borrow().iter() { ring.push(record); });
+        if !self.passes_filter(record.level, &record.target) { return; }
+        let mut sinks = self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        for sink in sinks.iter_mut()
+        {
+            sink.write(record);
+            if record.level == ELogLevel::Error { sink.flush(); }
+        }
+    }
+
+    pub fn flush(&self)
+    {
+        for sink in self.sinks.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).iter_mut() { sink.flush(); }
+    }
+}
+
+/// ### fn gk_logger() -> &'static GkLogger
+/// *The engine wide logger used by the log_* macros.*<br>
+/// *Created on first use with a GkTerminalSink and the GUNK_LOG filter.*
+pub fn gk_logger() -> &'static GkLogger
+{
+    LOGGER.get_or_init(||
+    {
+        let logger = GkLogger::new(GkLogFilter::from_env());
+        logger.add_sink(Box::new(GkTerminalSink));
+        logger
+    })
+}
+
+/// ### fn gk_log_install_log_bridge()
+/// *Routes records from the log crate, and tracing through its log feature, into gk_logger().*<br>
+/// *Does nothing if another log crate logger is already installed.*
+pub fn gk_log_install_log_bridge()
+{
+    if log::set_logger(&LOG_BRIDGE).is_ok()
+    {
+        log::set_max_level(gk_logger().max_level().to_log_filter());
+    }
+}
+
+/// Forwards log crate records to gk_logger().
+struct GkLogBridge;
+
+impl log::Log for GkLogBridge
+{
+    fn enabled(&self, metadata: &log::Metadata) -> bool
+    {
+        gk_logger().enabled(ELogLevel::from_log(metadata.level()), metadata.target())
+    }
+
+    fn log(&self, record: &log::Record)
+    {
+        if !self.enabled(record.metadata()) { return; }
+        gk_logger().log(&GkLogRecord::new(
+            ELogLevel::from_log(record.level()),
+            record.target(),
+            record.args().to_string(),
+            record.file().unwrap_or("<unknown>"),
+            record.line().unwrap_or(0),
+            0
+        ));
+    }
+
+    fn flush(&self)
+    {
+        gk_logger().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::{ Duration, UNIX_EPOCH };
+
+    use super::*;
+
+    #[test]
+    fn filter_picks_the_longest_matching_target()
+    {
+        let filter = GkLogFilter::parse("warn, gunk_engine::renderer=debug, gunk_engine::renderer::vulkan_renderer=error, bogus=loud");
+
+        assert_eq!(filter.default_level, ELogLevel::Warn);
+        assert_eq!(filter.targets.len(), 2);
+        assert_eq!(filter.level_for("gunk_engine::core"), ELogLevel::Warn);
+        assert_eq!(filter.level_for("gunk_engine::renderer"), ELogLevel::Debug);
+        assert_eq!(filter.level_for("gunk_engine::renderer::gk_asset_loader"), ELogLevel::Debug);
+        assert_eq!(filter.level_for("gunk_engine::renderer::vulkan_renderer::gk_vulkan"), ELogLevel::Error);
+        assert_eq!(filter.level_for("gunk_engine::renderer_utils"), ELogLevel::Warn);
+        assert_eq!(filter.max_level(), ELogLevel::Debug);
+    }
+
+    #[test]
+    fn logger_sends_enabled_records_to_sinks()
+    {
+        let ring = GkLogRing::new(2);
+        let logger = GkLogger::new(GkLogFilter::parse("info,noisy=error"));
+        logger.add_sink(Box::new(ring.clone()));
+
+        logger.log(&GkLogRecord::new(ELogLevel::Debug, "app", String::from("hidden"), "a.rs", 1, 1));
+        logger.log(&GkLogRecord::new(ELogLevel::Warn, "noisy::dep", String::from("hidden"), "a.rs", 2, 1));
+        logger.log(&GkLogRecord::new(ELogLevel::Info, "app", String::from("first"), "a.rs", 3, 1));
+        logger.log(&GkLogRecord::new(ELogLevel::Error, "noisy", String::from("second"), "a.rs", 4, 1));
+        logger.log(&GkLogRecord::new(ELogLevel::Warn, "app", String::from("third"), "a.rs", 5, 9));
+
+        let records = ring.records();
+        assert_eq!(records.iter().map(|record| record.message.as_str()).collect::<Vec<_>>(), vec!["second", "third"]);
+        assert_eq!((records[1].line, records[1].column), (5, 9));
+    }
+
+    #[test]
+    fn capture_sees_records_the_filter_hides()
+    {
+        let logger = GkLogger::new(GkLogFilter::parse("error"));
+        let outer = GkLogCapture::start();
+        {
+            let inner = GkLogCapture::start();
+            logger.log(&GkLogRecord::new(ELogLevel::Debug, "app", String::from("inner detail"), "a.rs", 1, 1));
+            assert!(logger.enabled(ELogLevel::Trace, "app"));
+            assert_eq!(inner.assert_logged(ELogLevel::Debug, "detail").line, 1);
+        }
+        logger.log(&GkLogRecord::new(ELogLevel::Warn, "app", String::from("after"), "a.rs", 2, 1));
+
+        assert_eq!(outer.records().len(), 2);
+        assert!(outer.find(ELogLevel::Error, "after").is_none());
+        drop(outer);
+        assert!(!logger.enabled(ELogLevel::Warn, "app"));
+    }
+
+    #[test]
+    fn file_sink_rotates_past_max_bytes()
+    {
+        let dir = std::env::temp_dir().join(format!("gunk_log_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("gunk.log");
+        let record = GkLogRecord::new(ELogLevel::Info, "app", "x".repeat(40), "a.rs", 1, 1);
+
+        let mut sink = GkFileSink::new(&path, 100, 2).unwrap();
+        for _ in 0..5 { sink.write(&record); }
+        sink.flush();
+
+        assert!(path.exists());
+        assert!(dir.join("gunk.log.1").exists());
+        assert!(dir.join("gunk.log.2").exists());
+        assert!(!dir.join("gunk.log.3").exists());
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn timestamps_format_as_utc_dates()
+    {
+        let mut record = GkLogRecord::new(ELogLevel::Info, "app", String::new(), "a.rs", 1, 1);
+        record.timestamp = UNIX_EPOCH + Duration::from_millis(951_827_696_789);
+        assert_eq!(record.timestamp_string(), "2000-02-29 12:34:56.789");
+    }
+}
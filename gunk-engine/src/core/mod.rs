@@ -1,4 +1,11 @@
 pub mod application;
 #[macro_use]
 pub mod logger;
-pub mod fps_limiter;
\ No newline at end of file
+pub mod log_console;
+#[macro_use]
+pub mod profiler;
+pub mod fps_limiter;
+pub mod frame_scheduler;
+pub mod time;
+pub mod gk_error;
+pub mod ecs;
\ No newline at end of file
@@ -0,0 +1,377 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::sync::{ Arc, Mutex, OnceLock };
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
+use std::time::Instant;
+
+use crate::core::gk_error::{ GkError, GkResult };
+
+/// Events kept per thread. Once full the oldest events are dropped.
+const GK_CPU_RING_CAPACITY: usize = 1 << 16;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_THREAD_ID: AtomicU64 = AtomicU64::new(1);
+static THREADS: Mutex<Vec<Arc<GkCpuThread>>> = Mutex::new(Vec::new());
+static EPOCH: OnceLock<Instant> = OnceLock::new();
+
+thread_local!
+{
+    static LOCAL_THREAD: Arc<GkCpuThread> = GkCpuThread::register();
+    static LOCAL_DEPTH: Cell<u32> = const { Cell::new(0) };
+}
+
+/// ### profile_scope!( ... )
+/// *Times the rest of the enclosing block under the given name.<br> Does nothing while the profiler is disabled.*
+#[macro_export]
+macro_rules! profile_scope {
+    ( $name:expr ) =>
+    {
+        let _gk_profile_scope = $crate::core::profiler::GkCpuScope::new($name);
+    };
+}
+
+/// ### profile_fn!()
+/// *Times the rest of the enclosing function under its path, e.g. gunk_engine::core::application::Application::new.*
+#[macro_export]
+macro_rules! profile_fn {
+    () =>
+    {
+        let _gk_profile_scope = $crate::core::profiler::GkCpuScope::new({
+            fn f() {}
+            let name = $crate::core::profiler::type_name_of(f);
+            &name[..name.len() - 3]
+        });
+    };
+}
+
+/// ### profile_frame!()
+/// *Marks the end of a frame on the trace timeline.*
+#[macro_export]
+macro_rules! profile_frame {
+    () =>
+    {
+        $crate::core::profiler::gk_cpu_frame_mark();
+    };
+}
+
+/// ### ECpuEventKind enum
+/// <pre>
+/// - Variants
+///     Scope       <i>// A timed scope.</i>
+///     FrameMark   <i>// End of a frame, duration_ns is 0.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ECpuEventKind
+{
+    Scope,
+    FrameMark,
+}
+
+/// ### GkCpuEvent struct
+/// *One recorded event.*
+/// <pre>
+/// - Members
+///     kind:           ECpuEventKind
+///     name:           &'static str
+///     start_ns:       u64     <i>// Since the profiler's first use.</i>
+///     duration_ns:    u64
+///     depth:          u32     <i>// Scopes open on the thread when this one started.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkCpuEvent
+{
+    pub kind:           ECpuEventKind,
+    pub name:           &'static str,
+    pub start_ns:       u64,
+    pub duration_ns:    u64,
+    pub depth:          u32,
+}
+
+/// ### GkCpuRing struct
+/// *Fixed capacity event buffer. Pushing to a full ring drops the oldest event.*
+pub struct GkCpuRing
+{
+    events:     VecDeque<GkCpuEvent>,
+    capacity:   usize,
+    dropped:    u64,
+}
+
+impl GkCpuRing
+{
+    pub fn new(capacity: usize) -> Self
+    {
+        Self { events: VecDeque::new(), capacity: capacity.max(1), dropped: 0 }
+    }
+
+    pub fn push(&mut self, event: GkCpuEvent)
+    {
+        if self.events.len() == self.capacity
+        {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+
+    /// ### fn GkCpuRing::drain( &mut self ) -> (Vec\<GkCpuEvent\>, u64)
+    /// *Takes the buffered events, oldest first, and the number dropped since the last drain.*
+    pub fn drain(&mut self) -> (Vec<GkCpuEvent>, u64)
+    {
+        let dropped = std::mem::take(&mut self.dropped);
+        (self.events.drain(..).collect(), dropped)
+    }
+}
+
+/// A thread that has recorded events. Kept alive by the registry after the thread exits.
+struct GkCpuThread
+{
+    id:     u64,
+    name:   String,
+    ring:   Mutex<GkCpuRing>,
+}
+
+impl GkCpuThread
+{
+    fn register() -> Arc<Self>
+    {
+        let id = NEXT_THREAD_ID.fetch_add(1, Ordering::Relaxed);
+        let name = std::thread::current().name().map_or_else(|| format!("thread {}", id), String::from);
+        let thread = Arc::new(Self { id, name, ring: Mutex::new(GkCpuRing::new(GK_CPU_RING_CAPACITY)) });
+        THREADS.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(Arc::clone(&thread));
+        thread
+    }
+
+    fn record(&self, event: GkCpuEvent)
+    {
+        self.ring.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).push(event);
+    }
+}
+
+/// ### GkCpuThreadEvents struct
+/// *Events collected from one thread.*
+/// <pre>
+/// - Members
+///     thread_id:      u64
+///     thread_name:    String
+///     events:         Vec&lt;GkCpuEvent&gt;    <i>// Ordered by end time.</i>
+///     dropped:        u64                 <i>// Events lost to a full ring buffer.</i>
+/// </pre>
+#[derive(Clone, Debug)]
+pub struct GkCpuThreadEvents
+{
+    pub thread_id:      u64,
+    pub thread_name:    String,
+    pub events:         Vec<GkCpuEvent>,
+    pub dropped:        u64,
+}
+
+/// ### GkCpuScope struct
+/// *Records a scope event when dropped. Created by profile_scope!() and profile_fn!().*
+pub struct GkCpuScope
+{
+    name:       &'static str,
+    start_ns:   u64,
+    b_active:   bool,
+}
+
+impl GkCpuScope
+{
+    pub fn new(name: &'static str) -> Self
+    {
+        if !gk_cpu_profiler_enabled()
+        {
+            return Self { name, start_ns: 0, b_active: false };
+        }
+        LOCAL_DEPTH.with(|depth| depth.set(depth.get() + 1));
+        Self { name, start_ns: now_ns(), b_active: true }
+    }
+}
+
+impl Drop for GkCpuScope
+{
+    fn drop(&mut self)
+    {
+        if !self.b_active { return; }
+        let end_ns = now_ns();
+        let depth = LOCAL_DEPTH.with(|depth| { depth.set(depth.get() - 1); depth.get() });
+        LOCAL_THREAD.with(|thread| thread.record(GkCpuEvent
+        {
+            kind: ECpuEventKind::Scope,
+            name: self.name,
+            start_ns: self.start_ns,
+            duration_ns: end_ns - self.start_ns,
+            depth,
+        }));
+    }
+}
+
+fn now_ns() -> u64
+{
+    EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// Used by profile_fn!() to name the enclosing function.
+pub fn type_name_of<T>(_: T) -> &'static str
+{
+    std::any::type_name::<T>()
+}
+
+/// ### fn gk_cpu_profiler_set_enabled( ... )
+/// *Starts or stops recording. Scopes opened while disabled are never recorded.*
+pub fn gk_cpu_profiler_set_enabled(b_enabled: bool)
+{
+    if b_enabled { EPOCH.get_or_init(Instant::now); }
+    ENABLED.store(b_enabled, Ordering::Relaxed);
+}
+
+pub fn gk_cpu_profiler_enabled() -> bool
+{
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// ### fn gk_cpu_frame_mark()
+/// *Records the end of a frame on the calling thread.*
+pub fn gk_cpu_frame_mark()
+{
+    if !gk_cpu_profiler_enabled() { return; }
+    let start_ns = now_ns();
+    LOCAL_THREAD.with(|thread| thread.record(GkCpuEvent
+    {
+        kind: ECpuEventKind::FrameMark,
+        name: "Frame",
+        start_ns,
+        duration_ns: 0,
+        depth: 0,
+    }));
+}
+
+/// ### fn gk_cpu_profiler_collect() -> Vec\<GkCpuThreadEvents\>
+/// *Takes the events recorded so far by every thread. Threads without events are left out.*
+pub fn gk_cpu_profiler_collect() -> Vec<GkCpuThreadEvents>
+{
+    let threads = THREADS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    threads.iter()
+        .filter_map(|thread|
+        {
+            let (events, dropped) = thread.ring.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).drain();
+            if events.is_empty() && dropped == 0 { return None; }
+            Some(GkCpuThreadEvents { thread_id: thread.id, thread_name: thread.name.clone(), events, dropped })
+        })
+        .collect()
+}
+
+/// ### fn gk_cpu_chrome_trace( ... ) -> String
+/// *Chrome trace event json (chrome://tracing, Perfetto) of collected events.*<br>
+/// *Tracy loads it through its import-chrome tool. Threads use their id as tid, tid 0 is left for the gpu.*
+/// <pre>
+/// - Params
+///     threads:    &[GkCpuThreadEvents]
+/// - Return
+///     String
+/// </pre>
+pub fn gk_cpu_chrome_trace(threads: &[GkCpuThreadEvents]) -> String
+{
+    let mut events: Vec<String> = Vec::new();
+    for thread in threads.iter()
+    {
+        events.push(format!(
+            "{{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":0,\"tid\":{},\"args\":{{\"name\":\"{}\"}}}}",
+            thread.thread_id, json_escape(&thread.thread_name)));
+        for event in thread.events.iter()
+        {
+            let ts_us = event.start_ns as f64 / 1000.0;
+            events.push(match event.kind
+            {
+                ECpuEventKind::Scope => format!(
+                    "{{\"name\":\"{}\",\"cat\":\"cpu\",\"ph\":\"X\",\"pid\":0,\"tid\":{},\"ts\":{:.3},\"dur\":{:.3}}}",
+                    json_escape(event.name), thread.thread_id, ts_us, event.duration_ns as f64 / 1000.0),
+                ECpuEventKind::FrameMark => format!(
+                    "{{\"name\":\"{}\",\"cat\":\"cpu\",\"ph\":\"i\",\"s\":\"g\",\"pid\":0,\"tid\":{},\"ts\":{:.3}}}",
+                    json_escape(event.name), thread.thread_id, ts_us),
+            });
+        }
+    }
+    format!("{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[{}]}}", events.join(","))
+}
+
+/// ### fn gk_cpu_write_chrome_trace( ... ) -> GkResult\<()\>
+/// *Collects every thread's events and writes them as a Chrome trace.*
+/// <pre>
+/// - Params
+///     file_path:  &std::path::Path
+/// - Return
+///     GkResult&lt;()&gt;
+/// </pre>
+pub fn gk_cpu_write_chrome_trace(file_path: &std::path::Path) -> GkResult<()>
+{
+    std::fs::write(file_path, gk_cpu_chrome_trace(&gk_cpu_profiler_collect())).map_err(|e| GkError::io(file_path, e))
+}
+
+fn json_escape(text: &str) -> String
+{
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn scope(name: &'static str, start_ns: u64) -> GkCpuEvent
+    {
+        GkCpuEvent { kind: ECpuEventKind::Scope, name, start_ns, duration_ns: 10, depth: 0 }
+    }
+
+    #[test]
+    fn ring_drops_oldest_events_when_full()
+    {
+        let mut ring = GkCpuRing::new(2);
+        ring.push(scope("a", 0));
+        ring.push(scope("b", 1));
+        ring.push(scope("c", 2));
+
+        let (events, dropped) = ring.drain();
+        assert_eq!(events.iter().map(|event| event.name).collect::<Vec<_>>(), vec!["b", "c"]);
+        assert_eq!(dropped, 1);
+        assert_eq!(ring.drain(), (Vec::new(), 0));
+    }
+
+    #[test]
+    fn nested_scopes_record_their_depth()
+    {
+        let profiled = std::thread::Builder::new().name(String::from("profiled")).spawn(||
+        {
+            gk_cpu_profiler_set_enabled(true);
+            {
+                profile_scope!("outer");
+                profile_fn!();
+            }
+            profile_frame!();
+        }).unwrap();
+        profiled.join().unwrap();
+
+        let thread = gk_cpu_profiler_collect().into_iter().find(|thread| thread.thread_name == "profiled").unwrap();
+        let recorded: Vec<_> = thread.events.iter().map(|event| (event.kind, event.name, event.depth)).collect();
+        assert_eq!(recorded, vec![
+            (ECpuEventKind::Scope, "gunk_engine::core::profiler::tests::nested_scopes_record_their_depth::{{closure}}", 1),
+            (ECpuEventKind::Scope, "outer", 0),
+            (ECpuEventKind::FrameMark, "Frame", 0),
+        ]);
+    }
+
+    #[test]
+    fn chrome_trace_names_threads_and_events()
+    {
+        let threads = vec![GkCpuThreadEvents
+        {
+            thread_id: 3,
+            thread_name: String::from("main"),
+            events: vec![scope("Render \"3d\"", 2000)],
+            dropped: 0,
+        }];
+        let trace = gk_cpu_chrome_trace(&threads);
+
+        assert!(trace.contains("\"ph\":\"M\",\"pid\":0,\"tid\":3,\"args\":{\"name\":\"main\"}"));
+        assert!(trace.contains("{\"name\":\"Render \\\"3d\\\"\",\"cat\":\"cpu\",\"ph\":\"X\",\"pid\":0,\"tid\":3,\"ts\":2.000,\"dur\":0.010}"));
+    }
+}
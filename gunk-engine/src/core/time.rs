@@ -0,0 +1,257 @@
+use std::time::Duration;
+
+/// Longest game delta a single frame can produce outside deterministic mode,
+/// so a hitch or a breakpoint doesn't launch the simulation forward.
+const GK_MAX_FRAME_DELTA: Duration = Duration::from_millis(250);
+
+/// Delta of a single step while paused, unless deterministic mode sets one.
+const GK_DEFAULT_STEP: Duration = Duration::from_nanos(16_666_667);
+
+/// ### GkTime struct
+/// *Real and game clocks of the application.*<br>
+/// *Game time follows real time scaled by time_scale, stops while paused and can be stepped a frame at a time.
+/// In deterministic mode every frame advances game time by the same delta whatever the wall clock did,
+/// so tests and replays reproduce exactly.*
+/// <pre>
+/// - Members
+///     real_delta:     Duration
+///     real_elapsed:   Duration
+///     game_delta:     Duration
+///     game_elapsed:   Duration
+///     time_scale:     f64                 <i>// Time dilation, 1.0 is real time.</i>
+///     b_paused:       bool
+///     pending_steps:  u32                 <i>// Frames to advance while paused.</i>
+///     fixed_delta:    Option&lt;Duration&gt;    <i>// Some in deterministic mode.</i>
+///     frame_count:    u64
+/// </pre>
+#[derive(Clone, Debug)]
+pub struct GkTime
+{
+    real_delta:     Duration,
+    real_elapsed:   Duration,
+    game_delta:     Duration,
+    game_elapsed:   Duration,
+    time_scale:     f64,
+    b_paused:       bool,
+    pending_steps:  u32,
+    fixed_delta:    Option<Duration>,
+    frame_count:    u64,
+}
+
+impl Default for GkTime
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl GkTime
+{
+    pub fn new() -> Self
+    {
+        Self
+        {
+            real_delta: Duration::ZERO,
+            real_elapsed: Duration::ZERO,
+            game_delta: Duration::ZERO,
+            game_elapsed: Duration::ZERO,
+            time_scale: 1.0,
+            b_paused: false,
+            pending_steps: 0,
+            fixed_delta: None,
+            frame_count: 0,
+        }
+    }
+
+    /// ### fn GkTime::deterministic( ... ) -> GkTime
+    /// *A clock already in deterministic mode, advancing delta seconds every frame.*
+    pub fn deterministic(delta: f32) -> Self
+    {
+        let mut time = Self::new();
+        time.set_deterministic(Some(delta));
+        time
+    }
+
+    /// ### fn GkTime::advance( &mut self, ... ) -> Duration
+    /// *Moves both clocks forward by a frame and returns the frame's game delta.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     real_delta:     Duration    <i>// Wall clock time of the frame, e.g. from FPSLimiter.</i>
+    /// - Return
+    ///     Duration
+    /// </pre>
+    pub fn advance(&mut self, real_delta: Duration) -> Duration
+    {
+        self.real_delta = real_delta;
+        self.real_elapsed += real_delta;
+        self.frame_count += 1;
+
+        let frame_delta = match self.fixed_delta
+        {
+            Some(fixed_delta) => fixed_delta,
+            None => real_delta.min(GK_MAX_FRAME_DELTA),
+        };
+        self.game_delta = if !self.b_paused
+        {
+            Duration::from_nanos((frame_delta.as_nanos() as f64 * self.time_scale).round() as u64)
+        }
+        else if self.pending_steps > 0
+        {
+            // A step advances one unscaled frame, so stepping reads the same at any dilation.
+            self.pending_steps -= 1;
+            self.fixed_delta.unwrap_or(GK_DEFAULT_STEP)
+        }
+        else
+        {
+            Duration::ZERO
+        };
+        self.game_elapsed += self.game_delta;
+        self.game_delta
+    }
+
+    /// ### fn GkTime::delta( &self ) -> f32
+    /// *Game seconds of the current frame. 0 while paused.*
+    pub fn delta(&self) -> f32
+    {
+        self.game_delta.as_secs_f32()
+    }
+
+    pub fn game_delta(&self) -> Duration
+    {
+        self.game_delta
+    }
+
+    pub fn real_delta(&self) -> Duration
+    {
+        self.real_delta
+    }
+
+    /// ### fn GkTime::elapsed( &self ) -> Duration
+    /// *Game time since the clock started.*
+    pub fn elapsed(&self) -> Duration
+    {
+        self.game_elapsed
+    }
+
+    pub fn real_elapsed(&self) -> Duration
+    {
+        self.real_elapsed
+    }
+
+    pub fn frame_count(&self) -> u64
+    {
+        self.frame_count
+    }
+
+    pub fn time_scale(&self) -> f64
+    {
+        self.time_scale
+    }
+
+    /// ### fn GkTime::set_time_scale( &mut self, ... )
+    /// *Sets time dilation. 0.5 runs the game at half speed, 2.0 at double. Negative scales are clamped to 0.*
+    pub fn set_time_scale(&mut self, time_scale: f64)
+    {
+        self.time_scale = time_scale.max(0.0);
+    }
+
+    pub fn is_paused(&self) -> bool
+    {
+        self.b_paused
+    }
+
+    pub fn pause(&mut self)
+    {
+        self.b_paused = true;
+    }
+
+    /// ### fn GkTime::resume( &mut self )
+    /// *Unpauses, dropping steps that were not taken yet.*
+    pub fn resume(&mut self)
+    {
+        self.b_paused = false;
+        self.pending_steps = 0;
+    }
+
+    pub fn toggle_pause(&mut self)
+    {
+        if self.b_paused { self.resume(); } else { self.pause(); }
+    }
+
+    /// ### fn GkTime::step( &mut self, ... )
+    /// *Pauses and queues frames to advance, one per following advance() call.*
+    pub fn step(&mut self, frames: u32)
+    {
+        self.b_paused = true;
+        self.pending_steps += frames;
+    }
+
+    pub fn is_deterministic(&self) -> bool
+    {
+        self.fixed_delta.is_some()
+    }
+
+    /// ### fn GkTime::set_deterministic( &mut self, ... )
+    /// *Some(delta) feeds every frame delta seconds regardless of the wall clock, None follows real time again.*
+    pub fn set_deterministic(&mut self, delta: Option<f32>)
+    {
+        self.fixed_delta = delta.map(|delta| Duration::from_nanos((delta.max(0.0) as f64 * 1e9).round() as u64));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use super::GkTime;
+
+    #[test]
+    fn deterministic_mode_ignores_the_wall_clock()
+    {
+        let mut first = GkTime::deterministic(0.02);
+        let mut second = GkTime::deterministic(0.02);
+        for ms in [1, 50, 400, 16]
+        {
+            first.advance(Duration::from_millis(ms));
+            second.advance(Duration::from_millis(ms * 3));
+        }
+
+        assert_eq!(first.elapsed(), Duration::from_millis(80));
+        assert_eq!(first.elapsed(), second.elapsed());
+        assert_eq!(first.real_elapsed(), Duration::from_millis(467));
+        assert_eq!(first.frame_count(), 4);
+    }
+
+    #[test]
+    fn pause_stops_game_time_and_step_advances_one_frame()
+    {
+        let mut time = GkTime::deterministic(0.01);
+        time.set_time_scale(4.0);
+        time.pause();
+        assert_eq!(time.advance(Duration::from_millis(10)), Duration::ZERO);
+
+        time.step(2);
+        assert_eq!(time.advance(Duration::from_millis(10)), Duration::from_millis(10));
+        assert_eq!(time.advance(Duration::from_millis(10)), Duration::from_millis(10));
+        assert_eq!(time.advance(Duration::from_millis(10)), Duration::ZERO);
+        assert!(time.is_paused());
+
+        time.resume();
+        assert_eq!(time.advance(Duration::from_millis(10)), Duration::from_millis(40));
+        assert_eq!(time.elapsed(), Duration::from_millis(60));
+        assert_eq!(time.real_elapsed(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn real_time_is_scaled_and_clamped()
+    {
+        let mut time = GkTime::new();
+        time.set_time_scale(0.5);
+        assert_eq!(time.advance(Duration::from_millis(20)), Duration::from_millis(10));
+        assert_eq!(time.advance(Duration::from_secs(3)), Duration::from_millis(125));
+        assert_eq!(time.real_delta(), Duration::from_secs(3));
+    }
+}
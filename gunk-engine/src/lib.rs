@@ -1,8 +1,10 @@
 extern crate shaderc;
 
 pub mod core;
+pub mod math;
 pub mod platform;
 pub mod renderer;
+pub mod scene;
 
 pub fn add(left: usize, right: usize) -> usize {
     left + right
@@ -0,0 +1,283 @@
+use nalgebra_glm as glm;
+
+/// ### GkAabb struct
+/// *Axis aligned box. An empty box has min above max on every axis, so union() with it is a no-op.*
+/// <pre>
+/// - Members
+///     min:    glm::Vec3
+///     max:    glm::Vec3
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkAabb
+{
+    pub min:    glm::Vec3,
+    pub max:    glm::Vec3,
+}
+
+impl GkAabb
+{
+    pub fn new(min: glm::Vec3, max: glm::Vec3) -> Self
+    {
+        Self { min, max }
+    }
+
+    pub fn empty() -> Self
+    {
+        Self { min: glm::Vec3::repeat(f32::INFINITY), max: glm::Vec3::repeat(f32::NEG_INFINITY) }
+    }
+
+    pub fn from_center_extents(center: &glm::Vec3, extents: &glm::Vec3) -> Self
+    {
+        Self { min: center - extents, max: center + extents }
+    }
+
+    /// ### fn GkAabb::from_points( ... ) -> GkAabb
+    /// *The smallest box holding every point. Empty if there are none.*
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a glm::Vec3>) -> Self
+    {
+        points.into_iter().fold(Self::empty(), |aabb, point| aabb.expanded(point))
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn center(&self) -> glm::Vec3
+    {
+        (self.min + self.max) * 0.5
+    }
+
+    /// Half the size on each axis.
+    pub fn extents(&self) -> glm::Vec3
+    {
+        (self.max - self.min) * 0.5
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool
+    {
+        (0..3).all(|axis| point[axis] >= self.min[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Boxes that only touch count as intersecting.
+    pub fn intersects(&self, other: &GkAabb) -> bool
+    {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    pub fn expanded(&self, point: &glm::Vec3) -> Self
+    {
+        Self { min: glm::min2(&self.min, point), max: glm::max2(&self.max, point) }
+    }
+
+    pub fn union(&self, other: &GkAabb) -> Self
+    {
+        Self { min: glm::min2(&self.min, &other.min), max: glm::max2(&self.max, &other.max) }
+    }
+
+    /// ### fn GkAabb::closest_point( &self, ... ) -> glm::Vec3
+    /// *The point of the box nearest to point, point itself when inside.*
+    pub fn closest_point(&self, point: &glm::Vec3) -> glm::Vec3
+    {
+        glm::clamp_vec(point, &self.min, &self.max)
+    }
+
+    /// ### fn GkAabb::transformed( &self, ... ) -> GkAabb
+    /// *The box around this box once moved by an affine matrix, such as a model matrix.<br>
+    /// Built from the matrix columns instead of the eight corners (Arvo's method), the result is the same.*
+    pub fn transformed(&self, matrix: &glm::Mat4) -> Self
+    {
+        if self.is_empty() { return *self; }
+        let translation = glm::Vec3::new(matrix[(0, 3)], matrix[(1, 3)], matrix[(2, 3)]);
+        let (mut min, mut max) = (translation, translation);
+        for row in 0..3
+        {
+            for column in 0..3
+            {
+                let a = matrix[(row, column)] * self.min[column];
+                let b = matrix[(row, column)] * self.max[column];
+                min[row] += a.min(b);
+                max[row] += a.max(b);
+            }
+        }
+        Self { min, max }
+    }
+}
+
+/// ### GkSphere struct
+/// *Bounding sphere. Cheaper than GkAabb to cull, and unaffected by rotation.*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkSphere
+{
+    pub center: glm::Vec3,
+    pub radius: f32,
+}
+
+impl GkSphere
+{
+    pub fn new(center: glm::Vec3, radius: f32) -> Self
+    {
+        Self { center, radius }
+    }
+
+    /// The sphere through the corners of aabb.
+    pub fn from_aabb(aabb: &GkAabb) -> Self
+    {
+        Self { center: aabb.center(), radius: aabb.extents().norm() }
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool
+    {
+        (point - self.center).norm_squared() <= self.radius * self.radius
+    }
+
+    pub fn intersects(&self, other: &GkSphere) -> bool
+    {
+        let reach = self.radius + other.radius;
+        (other.center - self.center).norm_squared() <= reach * reach
+    }
+
+    pub fn intersects_aabb(&self, aabb: &GkAabb) -> bool
+    {
+        self.contains_point(&aabb.closest_point(&self.center))
+    }
+
+    /// ### fn GkSphere::transformed( &self, ... ) -> GkSphere
+    /// *The sphere around this sphere once moved by an affine matrix. Grows by the largest axis scale, so it stays a bound under non uniform scale.*
+    pub fn transformed(&self, matrix: &glm::Mat4) -> Self
+    {
+        let center = matrix * glm::Vec4::new(self.center.x, self.center.y, self.center.z, 1.0);
+        let scale = (0..3)
+            .map(|column| glm::Vec3::new(matrix[(0, column)], matrix[(1, column)], matrix[(2, column)]).norm())
+            .fold(0.0, f32::max);
+        Self { center: glm::Vec3::new(center.x, center.y, center.z), radius: self.radius * scale }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::math::prop_test::{ gk_check, GkTestRng };
+    use crate::math::GkTransform;
+
+    fn random_aabb(rng: &mut GkTestRng) -> GkAabb
+    {
+        GkAabb::from_center_extents(&rng.vec3(-20.0, 20.0), &rng.vec3(0.0, 5.0))
+    }
+
+    fn corners(aabb: &GkAabb) -> Vec<glm::Vec3>
+    {
+        (0..8).map(|i| glm::Vec3::new(
+            if i & 1 == 0 { aabb.min.x } else { aabb.max.x },
+            if i & 2 == 0 { aabb.min.y } else { aabb.max.y },
+            if i & 4 == 0 { aabb.min.z } else { aabb.max.z },
+        )).collect()
+    }
+
+    #[test]
+    fn from_points_holds_every_point_and_is_tight()
+    {
+        gk_check(256, |rng|
+        {
+            let points: Vec<glm::Vec3> = (0..1 + rng.next_u64() % 16).map(|_| rng.vec3(-50.0, 50.0)).collect();
+            let aabb = GkAabb::from_points(&points);
+            assert!(points.iter().all(|point| aabb.contains_point(point)));
+            for axis in 0..3
+            {
+                assert!(points.iter().any(|point| point[axis] == aabb.min[axis]));
+                assert!(points.iter().any(|point| point[axis] == aabb.max[axis]));
+            }
+        });
+        assert!(GkAabb::from_points(&[]).is_empty());
+    }
+
+    #[test]
+    fn union_holds_both_boxes_and_intersects_them()
+    {
+        gk_check(256, |rng|
+        {
+            let (a, b) = (random_aabb(rng), random_aabb(rng));
+            let union = a.union(&b);
+            assert!(corners(&a).iter().chain(corners(&b).iter()).all(|corner| union.contains_point(corner)));
+            assert!(union.intersects(&a) && union.intersects(&b));
+            assert_eq!(a.union(&GkAabb::empty()), a);
+            assert_eq!(a.intersects(&b), b.intersects(&a));
+        });
+    }
+
+    #[test]
+    fn intersecting_boxes_share_a_point()
+    {
+        gk_check(512, |rng|
+        {
+            let (a, b) = (random_aabb(rng), random_aabb(rng));
+            let overlap = GkAabb::new(glm::max2(&a.min, &b.min), glm::min2(&a.max, &b.max));
+            assert_eq!(a.intersects(&b), !overlap.is_empty());
+            if a.intersects(&b)
+            {
+                let shared = overlap.center();
+                assert!(a.contains_point(&shared) && b.contains_point(&shared));
+            }
+        });
+    }
+
+    #[test]
+    fn transformed_box_is_the_box_around_the_moved_corners()
+    {
+        gk_check(256, |rng|
+        {
+            let aabb = random_aabb(rng);
+            let matrix = GkTransform::from_translation(rng.vec3(-10.0, 10.0))
+                .with_rotation(rng.rotation())
+                .with_scale(rng.vec3(0.1, 3.0))
+                .to_matrix();
+            let moved: Vec<glm::Vec3> = corners(&aabb).iter()
+                .map(|c| { let p = matrix * glm::Vec4::new(c.x, c.y, c.z, 1.0); glm::Vec3::new(p.x, p.y, p.z) })
+                .collect();
+            let expected = GkAabb::from_points(&moved);
+            let actual = aabb.transformed(&matrix);
+            assert!((actual.min - expected.min).norm() < 1e-3 && (actual.max - expected.max).norm() < 1e-3,
+                "expected {:?}, got {:?}", expected, actual);
+        });
+    }
+
+    #[test]
+    fn spheres_bound_their_boxes_and_stay_bounds_when_moved()
+    {
+        gk_check(256, |rng|
+        {
+            let aabb = random_aabb(rng);
+            let sphere = GkSphere::from_aabb(&aabb);
+            assert!(corners(&aabb).iter().all(|corner| (corner - sphere.center).norm() <= sphere.radius + 1e-4));
+            assert!(sphere.intersects_aabb(&aabb));
+
+            let matrix = GkTransform::from_translation(rng.vec3(-10.0, 10.0))
+                .with_rotation(rng.rotation())
+                .with_scale(rng.vec3(0.1, 3.0))
+                .to_matrix();
+            let moved = sphere.transformed(&matrix);
+            for _ in 0..8
+            {
+                let point = sphere.center + rng.direction() * sphere.radius * rng.unit();
+                let p = matrix * glm::Vec4::new(point.x, point.y, point.z, 1.0);
+                let p = glm::Vec3::new(p.x, p.y, p.z);
+                assert!((p - moved.center).norm() <= moved.radius * (1.0 + 1e-4) + 1e-4);
+            }
+        });
+    }
+
+    #[test]
+    fn sphere_tests_agree_with_distances()
+    {
+        gk_check(256, |rng|
+        {
+            let a = GkSphere::new(rng.vec3(-10.0, 10.0), rng.range(0.0, 5.0));
+            let b = GkSphere::new(rng.vec3(-10.0, 10.0), rng.range(0.0, 5.0));
+            assert_eq!(a.intersects(&b), (a.center - b.center).norm() <= a.radius + b.radius);
+            let aabb = random_aabb(rng);
+            assert_eq!(a.intersects_aabb(&aabb), (aabb.closest_point(&a.center) - a.center).norm() <= a.radius);
+        });
+    }
+}
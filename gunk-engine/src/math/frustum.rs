@@ -0,0 +1,182 @@
+use nalgebra_glm as glm;
+
+use crate::math::{ GkAabb, GkPlane, GkSphere };
+use crate::renderer::vulkan_renderer::gk_vk_camera::GkCamera;
+
+/// ### GkFrustum struct
+/// *The volume a camera sees, as six planes facing inwards.*
+/// <pre>
+/// - Members
+///     planes:     [GkPlane; 6]    <i>// Left, right, top, bottom, then the depth 0 and depth 1 planes.</i>
+/// </pre>
+/// *With reverse-Z the depth 0 plane is the far one. An infinite far plane has no normal and a positive d, so every point is inside it.*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkFrustum
+{
+    pub planes: [GkPlane; 6],
+}
+
+impl GkFrustum
+{
+    /// ### fn GkFrustum::from_matrix( ... ) -> GkFrustum
+    /// *Extracts the planes of a projection times view matrix (Gribb and Hartmann), for Vulkan clip space with depth in [0, 1].*
+    /// <pre>
+    /// - Params
+    ///     view_proj:  &glm::Mat4      <i>// World space planes from proj * view, view space planes from proj alone.</i>
+    /// - Return
+    ///     GkFrustum
+    /// </pre>
+    pub fn from_matrix(view_proj: &glm::Mat4) -> Self
+    {
+        let row = |index: usize| view_proj.row(index).transpose();
+        let plane = |v: glm::Vec4| GkPlane::new(glm::Vec3::new(v.x, v.y, v.z), v.w).normalized();
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        Self
+        {
+            planes:
+            [
+                plane(w + x),
+                plane(w - x),
+                plane(w + y),
+                plane(w - y),
+                plane(z),
+                plane(w - z),
+            ],
+        }
+    }
+
+    pub fn from_camera(camera: &GkCamera) -> Self
+    {
+        Self::from_matrix(&(camera.projection.get_matrix() * camera.view.get_matrix()))
+    }
+
+    pub fn contains_point(&self, point: &glm::Vec3) -> bool
+    {
+        self.planes.iter().all(|plane| plane.signed_distance(point) >= 0.0)
+    }
+
+    /// ### fn GkFrustum::intersects_sphere( &self, ... ) -> bool
+    /// *False only if sphere is entirely outside. Spheres near a corner, outside but touching two planes' half spaces, still pass.*
+    pub fn intersects_sphere(&self, sphere: &GkSphere) -> bool
+    {
+        self.planes.iter().all(|plane| plane.signed_distance(&sphere.center) >= -sphere.radius)
+    }
+
+    /// ### fn GkFrustum::intersects_aabb( &self, ... ) -> bool
+    /// *False only if aabb is entirely outside, tested with the corner furthest along each plane's normal.
+    /// Like intersects_sphere() it can pass a box just outside a corner, which culling can afford.*
+    pub fn intersects_aabb(&self, aabb: &GkAabb) -> bool
+    {
+        self.planes.iter().all(|plane|
+        {
+            let furthest = glm::Vec3::new(
+                if plane.normal.x >= 0.0 { aabb.max.x } else { aabb.min.x },
+                if plane.normal.y >= 0.0 { aabb.max.y } else { aabb.min.y },
+                if plane.normal.z >= 0.0 { aabb.max.z } else { aabb.min.z },
+            );
+            plane.signed_distance(&furthest) >= 0.0
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::math::prop_test::{ gk_check, random_camera, GkTestRng };
+    use crate::renderer::vulkan_renderer::gk_vk_camera::{ CamProjection, CamView };
+    use crate::renderer::vulkan_renderer::gk_vk_camera::camera_controller::CAMERA_UP;
+
+    /// Whether point is inside the clip volume, or None when too close to an edge to tell after rounding.
+    fn clip_inside(camera: &GkCamera, point: &glm::Vec3) -> Option<bool>
+    {
+        let view_proj = camera.projection.get_matrix() * camera.view.get_matrix();
+        let clip = view_proj * glm::Vec4::new(point.x, point.y, point.z, 1.0);
+        let margins = [clip.w + clip.x, clip.w - clip.x, clip.w + clip.y, clip.w - clip.y, clip.z, clip.w - clip.z];
+        let scale = clip.w.abs().max(1.0);
+        if margins.iter().any(|margin| margin.abs() < 1e-3 * scale) { return None; }
+        Some(margins.iter().all(|&margin| margin > 0.0))
+    }
+
+    /// A random point, half of them in front of the camera where they are likely visible.
+    fn random_point(rng: &mut GkTestRng, camera: &GkCamera) -> glm::Vec3
+    {
+        if rng.unit() < 0.5 { return rng.vec3(-150.0, 150.0); }
+        camera.view.pos + camera.view.front * rng.range(0.0, 60.0) + rng.vec3(-20.0, 20.0)
+    }
+
+    #[test]
+    fn contains_point_agrees_with_clip_space()
+    {
+        gk_check(128, |rng|
+        {
+            let camera = random_camera(rng);
+            let frustum = GkFrustum::from_camera(&camera);
+            for _ in 0..32
+            {
+                let point = random_point(rng, &camera);
+                let Some(b_inside) = clip_inside(&camera, &point) else { continue; };
+                assert_eq!(frustum.contains_point(&point), b_inside, "{:?} with {:?}", point, camera);
+            }
+        });
+    }
+
+    #[test]
+    fn culled_spheres_have_no_visible_points()
+    {
+        gk_check(128, |rng|
+        {
+            let camera = random_camera(rng);
+            let frustum = GkFrustum::from_camera(&camera);
+            for _ in 0..16
+            {
+                let sphere = GkSphere::new(random_point(rng, &camera), rng.range(0.0, 10.0));
+                if frustum.contains_point(&sphere.center) { assert!(frustum.intersects_sphere(&sphere)); }
+                if frustum.intersects_sphere(&sphere) { continue; }
+                for _ in 0..16
+                {
+                    let point = sphere.center + rng.direction() * sphere.radius * rng.unit();
+                    assert_ne!(clip_inside(&camera, &point), Some(true), "culled {:?} has visible {:?}", sphere, point);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn culled_boxes_have_no_visible_points()
+    {
+        gk_check(128, |rng|
+        {
+            let camera = random_camera(rng);
+            let frustum = GkFrustum::from_camera(&camera);
+            for _ in 0..16
+            {
+                let aabb = GkAabb::from_center_extents(&random_point(rng, &camera), &rng.vec3(0.0, 8.0));
+                if frustum.contains_point(&aabb.center()) { assert!(frustum.intersects_aabb(&aabb)); }
+                if frustum.intersects_aabb(&aabb) { continue; }
+                for _ in 0..16
+                {
+                    let point = aabb.min + (aabb.max - aabb.min).component_mul(&rng.vec3(0.0, 1.0));
+                    assert_ne!(clip_inside(&camera, &point), Some(true), "culled {:?} has visible {:?}", aabb, point);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn infinite_far_keeps_distant_objects()
+    {
+        let view = CamView { pos: glm::Vec3::zeros(), front: glm::Vec3::y(), up: CAMERA_UP };
+        for b_reverse_z in [false, true]
+        {
+            let projection = CamProjection::perspective(1.0, 1.0, 0.1, 10.0).with_reverse_z(b_reverse_z);
+            let finite = GkFrustum::from_camera(&GkCamera::new(view, projection));
+            let infinite = GkFrustum::from_camera(&GkCamera::new(view, projection.with_infinite_far(true)));
+            let distant = GkSphere::new(glm::Vec3::new(0.0, 1.0e5, 0.0), 1.0);
+            assert!(!finite.intersects_sphere(&distant));
+            assert!(infinite.intersects_sphere(&distant));
+            assert!(!infinite.contains_point(&glm::Vec3::new(0.0, -1.0, 0.0)));
+        }
+    }
+}
@@ -0,0 +1,16 @@
+// Geometry for culling, picking and gizmos, on top of the nalgebra-glm types.
+
+pub mod bounds;
+pub mod plane;
+pub mod frustum;
+pub mod ray;
+pub mod transform;
+
+#[cfg(test)]
+pub(crate) mod prop_test;
+
+pub use bounds::{ GkAabb, GkSphere };
+pub use plane::GkPlane;
+pub use frustum::GkFrustum;
+pub use ray::GkRay;
+pub use transform::GkTransform;
@@ -0,0 +1,108 @@
+use nalgebra_glm as glm;
+
+/// ### GkPlane struct
+/// *The points p where dot(normal, p) + d == 0. Points the normal faces have a positive distance.*
+/// <pre>
+/// - Members
+///     normal:     glm::Vec3   <i>// Unit length, unless built by hand.</i>
+///     d:          f32
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkPlane
+{
+    pub normal: glm::Vec3,
+    pub d:      f32,
+}
+
+impl GkPlane
+{
+    pub fn new(normal: glm::Vec3, d: f32) -> Self
+    {
+        Self { normal, d }
+    }
+
+    pub fn from_point_normal(point: &glm::Vec3, normal: &glm::Vec3) -> Self
+    {
+        let normal = normal.normalize();
+        Self { normal, d: -normal.dot(point) }
+    }
+
+    /// ### fn GkPlane::from_points( ... ) -> Option\<GkPlane\>
+    /// *Plane through three points, facing the side they wind counter clockwise on. None if they are in a line.*
+    pub fn from_points(a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> Option<Self>
+    {
+        let normal = (b - a).cross(&(c - a));
+        if normal.norm_squared() <= f32::EPSILON * f32::EPSILON { return None; }
+        Some(Self::from_point_normal(a, &normal))
+    }
+
+    /// ### fn GkPlane::normalized( &self ) -> GkPlane
+    /// *Scales the plane so the normal is unit length. A plane with no normal is returned as is.*
+    pub fn normalized(&self) -> Self
+    {
+        let length = self.normal.norm();
+        if length <= f32::EPSILON { return *self; }
+        Self { normal: self.normal / length, d: self.d / length }
+    }
+
+    pub fn signed_distance(&self, point: &glm::Vec3) -> f32
+    {
+        self.normal.dot(point) + self.d
+    }
+
+    pub fn project_point(&self, point: &glm::Vec3) -> glm::Vec3
+    {
+        point - self.normal * self.signed_distance(point)
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::math::prop_test::{ gk_check, assert_vec_near };
+
+    #[test]
+    fn points_on_the_plane_have_zero_distance()
+    {
+        gk_check(256, |rng|
+        {
+            let (a, b, c) = (rng.vec3(-10.0, 10.0), rng.vec3(-10.0, 10.0), rng.vec3(-10.0, 10.0));
+            let Some(plane) = GkPlane::from_points(&a, &b, &c) else { return; };
+            for point in [a, b, c, (a + b + c) / 3.0]
+            {
+                assert!(plane.signed_distance(&point).abs() < 1e-3, "{:?} is off {:?}", point, plane);
+            }
+            assert!(plane.signed_distance(&(a + plane.normal)) > 0.0);
+        });
+    }
+
+    #[test]
+    fn distance_is_along_the_normal()
+    {
+        gk_check(256, |rng|
+        {
+            let (point, normal) = (rng.vec3(-10.0, 10.0), rng.direction());
+            let plane = GkPlane::from_point_normal(&point, &normal);
+            let offset = rng.range(-5.0, 5.0);
+            let moved = point + normal * offset + normal.cross(&rng.direction()) * 3.0;
+            assert!((plane.signed_distance(&moved) - offset).abs() < 1e-3);
+            assert_vec_near(&plane.project_point(&(point + normal * offset)), &point, 1e-4);
+        });
+    }
+
+    #[test]
+    fn normalizing_keeps_the_plane_and_scales_the_distance()
+    {
+        gk_check(128, |rng|
+        {
+            let scale = rng.range(0.1, 10.0);
+            let plane = GkPlane::from_point_normal(&rng.vec3(-10.0, 10.0), &rng.direction());
+            let scaled = GkPlane::new(plane.normal * scale, plane.d * scale).normalized();
+            assert_vec_near(&scaled.normal, &plane.normal, 1e-5);
+            assert!((scaled.d - plane.d).abs() < 1e-3);
+        });
+        assert!(GkPlane::from_points(&glm::Vec3::zeros(), &glm::Vec3::x(), &(glm::Vec3::x() * 2.0)).is_none());
+    }
+}
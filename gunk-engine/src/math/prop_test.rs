@@ -0,0 +1,108 @@
+// Seeded random cases for the math property tests.
+
+use nalgebra_glm as glm;
+
+use crate::renderer::vulkan_renderer::gk_vk_camera::{ CamProjection, CamView, GkCamera };
+use crate::renderer::vulkan_renderer::gk_vk_camera::camera_controller::{ camera_forward, CAMERA_UP };
+
+/// ### GkTestRng struct
+/// *xorshift64* generator. Small, fast and the same on every platform, so a failing seed can be replayed.*
+pub struct GkTestRng
+{
+    state: u64,
+}
+
+impl GkTestRng
+{
+    pub fn new(seed: u64) -> Self
+    {
+        // A zero state would only ever produce zeros.
+        Self { state: seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1 }
+    }
+
+    pub fn next_u64(&mut self) -> u64
+    {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// Uniform in [0, 1).
+    pub fn unit(&mut self) -> f32
+    {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    pub fn range(&mut self, min: f32, max: f32) -> f32
+    {
+        min + (max - min) * self.unit()
+    }
+
+    pub fn vec3(&mut self, min: f32, max: f32) -> glm::Vec3
+    {
+        glm::Vec3::new(self.range(min, max), self.range(min, max), self.range(min, max))
+    }
+
+    /// Uniform on the unit sphere.
+    pub fn direction(&mut self) -> glm::Vec3
+    {
+        loop
+        {
+            let v = self.vec3(-1.0, 1.0);
+            let length = v.norm();
+            if length > 1.0e-3 && length <= 1.0 { return v / length; }
+        }
+    }
+
+    pub fn rotation(&mut self) -> glm::Quat
+    {
+        let axis = self.direction();
+        glm::quat_angle_axis(self.range(-std::f32::consts::PI, std::f32::consts::PI), &axis)
+    }
+}
+
+/// ### fn gk_check( ... )
+/// *Runs property on cases random cases. A failure names the case seed, which check_seed() reruns alone.*
+pub fn gk_check(cases: u64, property: impl Fn(&mut GkTestRng))
+{
+    for case in 0..cases
+    {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| property(&mut GkTestRng::new(case))));
+        if let Err(panic) = result
+        {
+            eprintln!("property failed for case seed {}", case);
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+/// ### fn check_seed( ... )
+/// *Reruns one case reported by gk_check().*
+#[allow(dead_code)]
+pub fn check_seed(seed: u64, property: impl Fn(&mut GkTestRng))
+{
+    property(&mut GkTestRng::new(seed));
+}
+
+/// A perspective or orthographic camera anywhere, with random depth settings.
+pub fn random_camera(rng: &mut GkTestRng) -> GkCamera
+{
+    let view = CamView
+    {
+        pos: rng.vec3(-20.0, 20.0),
+        front: camera_forward(rng.range(-3.0, 3.0), rng.range(-1.4, 1.4)),
+        up: CAMERA_UP,
+    };
+    let (aspect, near) = (rng.range(0.5, 2.5), rng.range(0.05, 2.0));
+    let far = near + rng.range(5.0, 100.0);
+    let projection = if rng.unit() < 0.7 { CamProjection::perspective(rng.range(0.3, 2.5), aspect, near, far) }
+        else { CamProjection::orthographic(rng.range(1.0, 40.0), aspect, near, far) };
+    GkCamera::new(view, projection.with_reverse_z(rng.unit() < 0.5).with_infinite_far(rng.unit() < 0.3))
+}
+
+pub fn assert_vec_near(actual: &glm::Vec3, expected: &glm::Vec3, tolerance: f32)
+{
+    let scale = expected.norm().max(1.0);
+    assert!((actual - expected).norm() <= tolerance * scale, "expected {:?}, got {:?}", expected, actual);
+}
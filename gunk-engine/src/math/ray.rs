@@ -0,0 +1,277 @@
+use nalgebra_glm as glm;
+
+use crate::math::{ GkAabb, GkPlane, GkSphere };
+use crate::renderer::vulkan_renderer::gk_vk_camera::GkCamera;
+
+/// ### GkRay struct
+/// *A half line, for picking and gizmos. The intersect_* functions return the distance along it to the first hit.*
+/// <pre>
+/// - Members
+///     origin:     glm::Vec3
+///     dir:        glm::Vec3   <i>// Unit length, so distances are in world units.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkRay
+{
+    pub origin: glm::Vec3,
+    pub dir:    glm::Vec3,
+}
+
+impl GkRay
+{
+    pub fn new(origin: glm::Vec3, dir: glm::Vec3) -> Self
+    {
+        Self { origin, dir: dir.normalize() }
+    }
+
+    pub fn at(&self, distance: f32) -> glm::Vec3
+    {
+        self.origin + self.dir * distance
+    }
+
+    /// ### fn GkRay::from_screen( ... ) -> Option\<GkRay\>
+    /// *The ray under a cursor, starting on the camera's near plane.*
+    /// <pre>
+    /// - Params
+    ///     camera:         &GkCamera
+    ///     cursor:         glm::Vec2       <i>// Pixels from the window's top left, as GkInput reports it.</i>
+    ///     window_size:    (u32, u32)
+    /// - Return
+    ///     Option&lt;GkRay&gt;                <i>// None when the cursor is outside the camera's viewport.</i>
+    /// </pre>
+    pub fn from_screen(camera: &GkCamera, cursor: glm::Vec2, window_size: (u32, u32)) -> Option<Self>
+    {
+        let (x, y, width, height) = camera.viewport.to_pixels(window_size.0, window_size.1);
+        if width == 0 || height == 0 { return None; }
+        let ndc_x = (cursor.x - x as f32) / width as f32 * 2.0 - 1.0;
+        let ndc_y = (cursor.y - y as f32) / height as f32 * 2.0 - 1.0;
+        if !(-1.0..=1.0).contains(&ndc_x) || !(-1.0..=1.0).contains(&ndc_y) { return None; }
+
+        let inverse = (camera.projection.get_matrix() * camera.view.get_matrix()).try_inverse()?;
+        let unproject = |depth: f32|
+        {
+            let world = inverse * glm::Vec4::new(ndc_x, ndc_y, depth, 1.0);
+            glm::Vec3::new(world.x, world.y, world.z) / world.w
+        };
+        // Depth 0.5 is in front of the near plane for every projection, even with an infinite far plane.
+        let near = unproject(if camera.projection.b_reverse_z { 1.0 } else { 0.0 });
+        let toward = unproject(0.5);
+        Some(Self::new(near, toward - near))
+    }
+
+    /// ### fn GkRay::intersect_aabb( &self, ... ) -> Option\<f32\>
+    /// *Slab test. 0 when the origin is inside the box.*
+    pub fn intersect_aabb(&self, aabb: &GkAabb) -> Option<f32>
+    {
+        let (mut enter, mut exit) = (0.0f32, f32::INFINITY);
+        for axis in 0..3
+        {
+            if self.dir[axis] == 0.0
+            {
+                if self.origin[axis] < aabb.min[axis] || self.origin[axis] > aabb.max[axis] { return None; }
+                continue;
+            }
+            let inverse = 1.0 / self.dir[axis];
+            let a = (aabb.min[axis] - self.origin[axis]) * inverse;
+            let b = (aabb.max[axis] - self.origin[axis]) * inverse;
+            enter = enter.max(a.min(b));
+            exit = exit.min(a.max(b));
+        }
+        if enter <= exit { Some(enter) } else { None }
+    }
+
+    /// ### fn GkRay::intersect_triangle( &self, ... ) -> Option\<f32\>
+    /// *Möller-Trumbore test, hitting either side of the triangle. None for degenerate triangles.*
+    pub fn intersect_triangle(&self, a: &glm::Vec3, b: &glm::Vec3, c: &glm::Vec3) -> Option<f32>
+    {
+        let (edge1, edge2) = (b - a, c - a);
+        let p = self.dir.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() <= f32::EPSILON * edge1.norm() * edge2.norm() { return None; }
+
+        let inverse = 1.0 / determinant;
+        let offset = self.origin - a;
+        let u = offset.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) { return None; }
+        let q = offset.cross(&edge1);
+        let v = self.dir.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 { return None; }
+        let distance = edge2.dot(&q) * inverse;
+        (distance >= 0.0).then_some(distance)
+    }
+
+    /// ### fn GkRay::intersect_sphere( &self, ... ) -> Option\<f32\>
+    /// *0 when the origin is inside the sphere.*
+    pub fn intersect_sphere(&self, sphere: &GkSphere) -> Option<f32>
+    {
+        let offset = self.origin - sphere.center;
+        let c = offset.norm_squared() - sphere.radius * sphere.radius;
+        if c <= 0.0 { return Some(0.0); }
+        let b = offset.dot(&self.dir);
+        let discriminant = b * b - c;
+        if b > 0.0 || discriminant < 0.0 { return None; }
+        Some(-b - discriminant.sqrt())
+    }
+
+    /// ### fn GkRay::intersect_plane( &self, ... ) -> Option\<f32\>
+    /// *Hits the plane from either side. None if the ray runs parallel to it or points away.*
+    pub fn intersect_plane(&self, plane: &GkPlane) -> Option<f32>
+    {
+        let speed = plane.normal.dot(&self.dir);
+        if speed.abs() <= f32::EPSILON { return None; }
+        let distance = -plane.signed_distance(&self.origin) / speed;
+        (distance >= 0.0).then_some(distance)
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::math::prop_test::{ gk_check, random_camera, GkTestRng };
+    use crate::renderer::vulkan_renderer::gk_vk_camera::GkViewport;
+
+    fn random_aabb(rng: &mut GkTestRng) -> GkAabb
+    {
+        GkAabb::from_center_extents(&rng.vec3(-10.0, 10.0), &rng.vec3(0.1, 5.0))
+    }
+
+    fn distance_to_line(ray: &GkRay, point: &glm::Vec3) -> f32
+    {
+        let offset = point - ray.origin;
+        (offset - ray.dir * offset.dot(&ray.dir)).norm()
+    }
+
+    #[test]
+    fn screen_rays_pass_through_the_points_drawn_under_the_cursor()
+    {
+        gk_check(256, |rng|
+        {
+            let camera = random_camera(rng).with_viewport(GkViewport::grid(2, 2, (rng.next_u64() % 4) as u32));
+            let window = (1280, 720);
+            let (x, y, width, height) = camera.viewport.to_pixels(window.0, window.1);
+            let view_proj = camera.projection.get_matrix() * camera.view.get_matrix();
+
+            let ndc = glm::Vec2::new(rng.range(-0.95, 0.95), rng.range(-0.95, 0.95));
+            let depth = rng.range(0.05, 0.95);
+            let world = view_proj.try_inverse().unwrap() * glm::Vec4::new(ndc.x, ndc.y, depth, 1.0);
+            let point = glm::Vec3::new(world.x, world.y, world.z) / world.w;
+            if (point - camera.view.pos).norm() > 1.0e3 { return; }
+
+            let cursor = glm::Vec2::new(
+                x as f32 + (ndc.x + 1.0) * 0.5 * width as f32,
+                y as f32 + (ndc.y + 1.0) * 0.5 * height as f32,
+            );
+            let ray = GkRay::from_screen(&camera, cursor, window).unwrap();
+            let range = (point - ray.origin).norm().max(1.0);
+            assert!(distance_to_line(&ray, &point) < 1e-3 * range, "{:?} misses {:?}", ray, point);
+            assert!((point - ray.origin).dot(&ray.dir) > 0.0);
+        });
+    }
+
+    #[test]
+    fn screen_rays_outside_the_viewport_are_none()
+    {
+        let mut rng = GkTestRng::new(7);
+        let camera = random_camera(&mut rng).with_viewport(GkViewport::grid(2, 1, 1));
+        assert!(GkRay::from_screen(&camera, glm::Vec2::new(100.0, 300.0), (800, 600)).is_none());
+        assert!(GkRay::from_screen(&camera, glm::Vec2::new(600.0, 300.0), (800, 600)).is_some());
+        assert!(GkRay::from_screen(&camera, glm::Vec2::new(600.0, 300.0), (0, 0)).is_none());
+    }
+
+    #[test]
+    fn aabb_hits_land_on_the_box_and_misses_never_enter_it()
+    {
+        gk_check(512, |rng|
+        {
+            let aabb = random_aabb(rng);
+            let ray = GkRay::new(rng.vec3(-20.0, 20.0), rng.direction());
+            let grown = GkAabb::new(aabb.min.add_scalar(-1e-3), aabb.max.add_scalar(1e-3));
+            match ray.intersect_aabb(&aabb)
+            {
+                Some(distance) =>
+                {
+                    assert!(grown.contains_point(&ray.at(distance)));
+                    if distance > 0.0 { assert!(!aabb.contains_point(&ray.at(distance * 0.99 - 1e-3))); }
+                },
+                None => for step in 0..64
+                {
+                    assert!(!aabb.contains_point(&ray.at(step as f32 * 0.75)));
+                },
+            }
+
+            let target = aabb.min + (aabb.max - aabb.min).component_mul(&rng.vec3(0.0, 1.0));
+            let aimed = GkRay::new(ray.origin, target - ray.origin);
+            let distance = aimed.intersect_aabb(&aabb).expect("ray aimed into the box missed");
+            assert!(distance <= (target - ray.origin).norm() + 1e-3);
+        });
+    }
+
+    #[test]
+    fn triangle_hits_match_barycentric_points()
+    {
+        gk_check(512, |rng|
+        {
+            let (a, b, c) = (rng.vec3(-10.0, 10.0), rng.vec3(-10.0, 10.0), rng.vec3(-10.0, 10.0));
+            let Some(plane) = GkPlane::from_points(&a, &b, &c) else { return; };
+            let origin = rng.vec3(-20.0, 20.0);
+            if plane.signed_distance(&origin).abs() < 0.1 { return; }
+
+            let (u, v) = (rng.range(-0.5, 1.5), rng.range(-0.5, 1.5));
+            let w = 1.0 - u - v;
+            let target = a * w + b * u + c * v;
+            let to_target = target - origin;
+            // Skip grazing rays and targets within rounding of an edge.
+            if to_target.normalize().dot(&plane.normal).abs() < 0.05 || [u, v, w].iter().any(|t| t.abs() < 1e-3) { return; }
+
+            let hit = GkRay::new(origin, to_target).intersect_triangle(&a, &b, &c);
+            if u > 0.0 && v > 0.0 && w > 0.0
+            {
+                let distance = hit.expect("ray aimed inside the triangle missed");
+                assert!((distance - to_target.norm()).abs() < 1e-3 * to_target.norm().max(1.0));
+            }
+            else
+            {
+                assert!(hit.is_none(), "ray aimed outside the triangle hit at {:?}", hit);
+            }
+            assert!(GkRay::new(origin, -to_target).intersect_triangle(&a, &b, &c).is_none());
+        });
+    }
+
+    #[test]
+    fn sphere_hits_are_on_the_surface()
+    {
+        gk_check(512, |rng|
+        {
+            let sphere = GkSphere::new(rng.vec3(-10.0, 10.0), rng.range(0.1, 5.0));
+            let ray = GkRay::new(rng.vec3(-20.0, 20.0), rng.direction());
+            let hit = ray.intersect_sphere(&sphere);
+            if sphere.contains_point(&ray.origin) { assert_eq!(hit, Some(0.0)); return; }
+
+            let closest = ray.at((sphere.center - ray.origin).dot(&ray.dir).max(0.0));
+            assert_eq!(hit.is_some(), sphere.contains_point(&closest));
+            if let Some(distance) = hit
+            {
+                assert!(((ray.at(distance) - sphere.center).norm() - sphere.radius).abs() < 1e-3);
+            }
+        });
+    }
+
+    #[test]
+    fn plane_hits_are_on_the_plane()
+    {
+        gk_check(512, |rng|
+        {
+            let plane = GkPlane::from_point_normal(&rng.vec3(-10.0, 10.0), &rng.direction());
+            let ray = GkRay::new(rng.vec3(-20.0, 20.0), rng.direction());
+            let start = plane.signed_distance(&ray.origin);
+            let b_toward = start * plane.normal.dot(&ray.dir) < 0.0;
+            match ray.intersect_plane(&plane)
+            {
+                Some(distance) => assert!(plane.signed_distance(&ray.at(distance)).abs() < 1e-3 * distance.max(1.0)),
+                None => assert!(!b_toward),
+            }
+        });
+    }
+}
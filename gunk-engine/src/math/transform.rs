@@ -0,0 +1,188 @@
+use nalgebra_glm as glm;
+
+/// ### GkTransform struct
+/// *Scale, then rotation, then translation.*
+/// <pre>
+/// - Members
+///     translation:    glm::Vec3
+///     rotation:       glm::Quat   <i>// Kept unit length.</i>
+///     scale:          glm::Vec3
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkTransform
+{
+    pub translation:    glm::Vec3,
+    pub rotation:       glm::Quat,
+    pub scale:          glm::Vec3,
+}
+
+impl Default for GkTransform
+{
+    fn default() -> Self
+    {
+        Self::identity()
+    }
+}
+
+impl GkTransform
+{
+    pub fn identity() -> Self
+    {
+        Self { translation: glm::Vec3::zeros(), rotation: glm::Quat::identity(), scale: glm::Vec3::new(1.0, 1.0, 1.0) }
+    }
+
+    pub fn from_translation(translation: glm::Vec3) -> Self
+    {
+        Self { translation, ..Self::identity() }
+    }
+
+    pub fn with_rotation(mut self, rotation: glm::Quat) -> Self
+    {
+        self.rotation = glm::quat_normalize(&rotation);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: glm::Vec3) -> Self
+    {
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_uniform_scale(self, scale: f32) -> Self
+    {
+        self.with_scale(glm::Vec3::new(scale, scale, scale))
+    }
+
+    pub fn to_matrix(&self) -> glm::Mat4
+    {
+        glm::translation(&self.translation) * glm::quat_to_mat4(&self.rotation) * glm::scaling(&self.scale)
+    }
+
+    pub fn transform_point(&self, point: &glm::Vec3) -> glm::Vec3
+    {
+        glm::quat_rotate_vec3(&self.rotation, &point.component_mul(&self.scale)) + self.translation
+    }
+
+    /// ### fn GkTransform::transform_vector( &self, ... ) -> glm::Vec3
+    /// *Scales and rotates a direction or offset, ignoring the translation.*
+    pub fn transform_vector(&self, vector: &glm::Vec3) -> glm::Vec3
+    {
+        glm::quat_rotate_vec3(&self.rotation, &vector.component_mul(&self.scale))
+    }
+
+    /// ### fn GkTransform::then( &self, ... ) -> GkTransform
+    /// *Applies self and then parent, the transform of a child placed under parent.<br>
+    /// Exact when parent's scale is uniform. Otherwise the result keeps a scale per axis
+    /// and drops the shear a matrix product would have, as scene graphs usually do.*
+    pub fn then(&self, parent: &GkTransform) -> GkTransform
+    {
+        GkTransform
+        {
+            translation: parent.transform_point(&self.translation),
+            rotation: glm::quat_normalize(&(parent.rotation * self.rotation)),
+            scale: parent.scale.component_mul(&self.scale),
+        }
+    }
+
+    /// ### fn GkTransform::inverse( &self ) -> GkTransform
+    /// *Undoes self. Exact for uniform scale, the same approximation as then() otherwise. A zero scale axis stays zero.*
+    pub fn inverse(&self) -> GkTransform
+    {
+        let scale = self.scale.map(|s| if s == 0.0 { 0.0 } else { 1.0 / s });
+        let rotation = glm::quat_conjugate(&self.rotation);
+        let translation = -glm::quat_rotate_vec3(&rotation, &self.translation).component_mul(&scale);
+        GkTransform { translation, rotation, scale }
+    }
+
+    /// ### fn GkTransform::lerp( &self, ... ) -> GkTransform
+    /// *Blends towards other by t in [0, 1], along the shortest rotation.*
+    pub fn lerp(&self, other: &GkTransform, t: f32) -> GkTransform
+    {
+        GkTransform
+        {
+            translation: glm::lerp(&self.translation, &other.translation, t),
+            rotation: glm::quat_normalize(&glm::quat_short_mix(&self.rotation, &other.rotation, t)),
+            scale: glm::lerp(&self.scale, &other.scale, t),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::math::prop_test::{ gk_check, assert_vec_near, GkTestRng };
+
+    fn random_transform(rng: &mut GkTestRng) -> GkTransform
+    {
+        GkTransform::from_translation(rng.vec3(-50.0, 50.0))
+            .with_rotation(rng.rotation())
+            .with_uniform_scale(rng.range(0.1, 5.0))
+    }
+
+    fn matrix_point(matrix: &glm::Mat4, point: &glm::Vec3) -> glm::Vec3
+    {
+        let p = matrix * glm::Vec4::new(point.x, point.y, point.z, 1.0);
+        glm::Vec3::new(p.x, p.y, p.z)
+    }
+
+    #[test]
+    fn matrix_agrees_with_transform_point()
+    {
+        gk_check(256, |rng|
+        {
+            let transform = random_transform(rng).with_scale(rng.vec3(0.1, 4.0));
+            let point = rng.vec3(-10.0, 10.0);
+            assert_vec_near(&matrix_point(&transform.to_matrix(), &point), &transform.transform_point(&point), 1e-4);
+        });
+    }
+
+    #[test]
+    fn then_matches_the_matrix_product()
+    {
+        gk_check(256, |rng|
+        {
+            let (child, parent) = (random_transform(rng), random_transform(rng));
+            let combined = child.then(&parent);
+            let point = rng.vec3(-10.0, 10.0);
+            assert_vec_near(&combined.transform_point(&point), &parent.transform_point(&child.transform_point(&point)), 1e-4);
+            assert_vec_near(&matrix_point(&(parent.to_matrix() * child.to_matrix()), &point), &combined.transform_point(&point), 1e-4);
+        });
+    }
+
+    #[test]
+    fn then_is_associative()
+    {
+        gk_check(256, |rng|
+        {
+            let (a, b, c) = (random_transform(rng), random_transform(rng), random_transform(rng));
+            let point = rng.vec3(-10.0, 10.0);
+            assert_vec_near(&a.then(&b).then(&c).transform_point(&point), &a.then(&b.then(&c)).transform_point(&point), 1e-3);
+        });
+    }
+
+    #[test]
+    fn inverse_undoes_the_transform()
+    {
+        gk_check(256, |rng|
+        {
+            let transform = random_transform(rng);
+            let point = rng.vec3(-10.0, 10.0);
+            assert_vec_near(&transform.inverse().transform_point(&transform.transform_point(&point)), &point, 1e-4);
+            assert_vec_near(&transform.then(&transform.inverse()).transform_point(&point), &point, 1e-4);
+        });
+    }
+
+    #[test]
+    fn lerp_hits_both_ends()
+    {
+        gk_check(64, |rng|
+        {
+            let (a, b) = (random_transform(rng), random_transform(rng));
+            let point = rng.vec3(-10.0, 10.0);
+            assert_vec_near(&a.lerp(&b, 0.0).transform_point(&point), &a.transform_point(&point), 1e-4);
+            assert_vec_near(&a.lerp(&b, 1.0).transform_point(&point), &b.transform_point(&point), 1e-4);
+        });
+    }
+}
@@ -0,0 +1,757 @@
+use std::collections::{ BTreeMap, HashMap };
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{ Deserialize, Serialize };
+use winit::event::MouseButton;
+use winit::keyboard::{ KeyCode, ModifiersState };
+
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::platform::input::{ EGamepadAxis, EGamepadButton, GkInput, GK_KEY_CODES };
+
+/// Axis deflection past which an analog input counts as held, for chords and rebinding.
+const GK_AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// ### EMouseAxis enum
+/// *Mouse movement and wheel axes. X and Y are raw device motion of the frame.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EMouseAxis
+{
+    X,
+    Y,
+    WheelX,
+    WheelY,
+}
+
+/// ### EActionAxis enum
+/// *Component of an action's value a binding feeds. X for one dimensional actions.*
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EActionAxis
+{
+    #[default]
+    X,
+    Y,
+}
+
+/// ### EInputSource enum
+/// *A single physical input a binding can read.*
+/// <pre>
+/// - Variants
+///     Key(KeyCode)                    <i>// Written as its KeyCode name, e.g. KeyW or ArrowUp.</i>
+///     MouseButton(MouseButton)        <i>// Mouse.Left, Mouse.Right, Mouse.Middle, Mouse.Back, Mouse.Forward or Mouse.Button&lt;n&gt;.</i>
+///     MouseAxis(EMouseAxis)           <i>// Mouse.X, Mouse.Y, Mouse.WheelX or Mouse.WheelY.</i>
+///     GamepadButton(EGamepadButton)   <i>// e.g. Gamepad.South.</i>
+///     GamepadAxis(EGamepadAxis)       <i>// e.g. Gamepad.LeftStickX.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EInputSource
+{
+    Key(KeyCode),
+    MouseButton(MouseButton),
+    MouseAxis(EMouseAxis),
+    GamepadButton(EGamepadButton),
+    GamepadAxis(EGamepadAxis),
+}
+
+impl EInputSource
+{
+    /// ### fn EInputSource::raw_value( &self, ... ) -> f32
+    /// *1 or 0 for buttons, the position or frame delta for axes.*
+    pub fn raw_value(&self, input: &GkInput) -> f32
+    {
+        let button = |b_held: bool| if b_held { 1.0 } else { 0.0 };
+        match *self
+        {
+            EInputSource::Key(code) => button(input.is_key_held(code)),
+            EInputSource::MouseButton(mouse_button) => button(input.is_button_held(mouse_button)),
+            EInputSource::MouseAxis(EMouseAxis::X) => input.mouse_delta().0 as f32,
+            EInputSource::MouseAxis(EMouseAxis::Y) => input.mouse_delta().1 as f32,
+            EInputSource::MouseAxis(EMouseAxis::WheelX) => input.scroll_delta().0,
+            EInputSource::MouseAxis(EMouseAxis::WheelY) => input.scroll_delta().1,
+            EInputSource::GamepadButton(pad_button) => button(input.is_gamepad_button_held(pad_button)),
+            EInputSource::GamepadAxis(axis) => input.gamepad_axis(axis),
+        }
+    }
+
+    /// ### fn EInputSource::is_held( &self, ... ) -> bool
+    /// *Whether a button is down or an axis is deflected past half way. Mouse axes count while moving.*
+    pub fn is_held(&self, input: &GkInput) -> bool
+    {
+        match self
+        {
+            EInputSource::GamepadAxis(_) => self.raw_value(input).abs() >= GK_AXIS_PRESS_THRESHOLD,
+            _ => self.raw_value(input) != 0.0,
+        }
+    }
+
+    /// ### fn EInputSource::is_bounded( &self ) -> bool
+    /// *False for mouse axes, whose deltas have no fixed range.*
+    pub fn is_bounded(&self) -> bool
+    {
+        !matches!(self, EInputSource::MouseAxis(_))
+    }
+
+    fn is_modifier_key(&self) -> bool
+    {
+        matches!(self, EInputSource::Key(
+            KeyCode::ControlLeft | KeyCode::ControlRight | KeyCode::ShiftLeft | KeyCode::ShiftRight
+            | KeyCode::AltLeft | KeyCode::AltRight | KeyCode::SuperLeft | KeyCode::SuperRight
+        ))
+    }
+}
+
+impl std::fmt::Display for EInputSource
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            EInputSource::Key(code) => write!(f, "{:?}", code),
+            EInputSource::MouseButton(MouseButton::Other(index)) => write!(f, "Mouse.Button{}", index),
+            EInputSource::MouseButton(mouse_button) => write!(f, "Mouse.{:?}", mouse_button),
+            EInputSource::MouseAxis(axis) => write!(f, "Mouse.{:?}", axis),
+            EInputSource::GamepadButton(pad_button) => write!(f, "Gamepad.{:?}", pad_button),
+            EInputSource::GamepadAxis(axis) => write!(f, "Gamepad.{:?}", axis),
+        }
+    }
+}
+
+impl FromStr for EInputSource
+{
+    type Err = GkError;
+
+    fn from_str(name: &str) -> GkResult<Self>
+    {
+        let unknown = || GkError::Config(format!("unknown input '{}'", name));
+        if let Some(mouse_name) = name.strip_prefix("Mouse.")
+        {
+            let source = match mouse_name
+            {
+                "Left" => EInputSource::MouseButton(MouseButton::Left),
+                "Right" => EInputSource::MouseButton(MouseButton::Right),
+                "Middle" => EInputSource::MouseButton(MouseButton::Middle),
+                "Back" => EInputSource::MouseButton(MouseButton::Back),
+                "Forward" => EInputSource::MouseButton(MouseButton::Forward),
+                "X" => EInputSource::MouseAxis(EMouseAxis::X),
+                "Y" => EInputSource::MouseAxis(EMouseAxis::Y),
+                "WheelX" => EInputSource::MouseAxis(EMouseAxis::WheelX),
+                "WheelY" => EInputSource::MouseAxis(EMouseAxis::WheelY),
+                _ =>
+                {
+                    let index = mouse_name.strip_prefix("Button").and_then(|index| index.parse::<u16>().ok()).ok_or_else(unknown)?;
+                    EInputSource::MouseButton(MouseButton::Other(index))
+                },
+            };
+            return Ok(source);
+        }
+        if let Some(pad_name) = name.strip_prefix("Gamepad.")
+        {
+            if let Some(pad_button) = EGamepadButton::ALL.iter().find(|pad_button| format!("{:?}", pad_button) == pad_name)
+            {
+                return Ok(EInputSource::GamepadButton(*pad_button));
+            }
+            let axis = EGamepadAxis::ALL.iter().find(|axis| format!("{:?}", axis) == pad_name).ok_or_else(unknown)?;
+            return Ok(EInputSource::GamepadAxis(*axis));
+        }
+        GK_KEY_CODES.iter().find(|code| format!("{:?}", code) == name).map(|code| EInputSource::Key(*code)).ok_or_else(unknown)
+    }
+}
+
+/// ### GkBinding struct
+/// *Binds an input source to an action.*<br>
+/// *Written as `[Modifiers+][Chord+]Source [deadzone=d] [scale=s] [axis=y]`, e.g. `"Ctrl+KeyS"`,
+/// `"Gamepad.LeftBumper+Gamepad.South"` or `"Gamepad.LeftStickY deadzone=0.15 axis=y"`.*
+/// <pre>
+/// - Members
+///     source:     EInputSource
+///     modifiers:  ModifiersState      <i>// Ctrl, Shift, Alt and Super that must be held, either side.</i>
+///     chord:      Vec&lt;EInputSource&gt;   <i>// Other inputs that must be held along with source.</i>
+///     dead_zone:  f32                 <i>// Values within it read as 0. Bounded axes are rescaled so the output still reaches 1.</i>
+///     scale:      f32                 <i>// Multiplies the value, e.g. -1 for the S key of a forward axis.</i>
+///     axis:       EActionAxis
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkBinding
+{
+    pub source:     EInputSource,
+    pub modifiers:  ModifiersState,
+    pub chord:      Vec<EInputSource>,
+    pub dead_zone:  f32,
+    pub scale:      f32,
+    pub axis:       EActionAxis,
+}
+
+impl GkBinding
+{
+    pub fn new(source: EInputSource) -> Self
+    {
+        Self { source, modifiers: ModifiersState::empty(), chord: Vec::new(), dead_zone: 0.0, scale: 1.0, axis: EActionAxis::X }
+    }
+
+    pub fn key(code: KeyCode) -> Self
+    {
+        Self::new(EInputSource::Key(code))
+    }
+
+    pub fn with_modifiers(mut self, modifiers: ModifiersState) -> Self
+    {
+        self.modifiers = modifiers;
+        self
+    }
+
+    pub fn with_chord(mut self, source: EInputSource) -> Self
+    {
+        self.chord.push(source);
+        self
+    }
+
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self
+    {
+        self.dead_zone = dead_zone.clamp(0.0, f32::MAX);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: f32) -> Self
+    {
+        self.scale = scale;
+        self
+    }
+
+    pub fn on_axis(mut self, axis: EActionAxis) -> Self
+    {
+        self.axis = axis;
+        self
+    }
+
+    /// ### fn GkBinding::capture( ... ) -> Option\<GkBinding\>
+    /// *A binding for the first input pressed this frame together with the held modifiers, for rebinding screens.<br>
+    /// Modifier keys alone are skipped so Ctrl+S can be captured by pressing Ctrl then S.*
+    pub fn capture(input: &GkInput) -> Option<GkBinding>
+    {
+        let source = input.keys_pressed().map(|code| EInputSource::Key(*code))
+            .filter(|source| !source.is_modifier_key())
+            .chain(input.buttons_pressed().map(|mouse_button| EInputSource::MouseButton(*mouse_button)))
+            .chain(input.gamepad_buttons_pressed().map(|pad_button| EInputSource::GamepadButton(*pad_button)))
+            .chain(EGamepadAxis::ALL.iter().map(|axis| EInputSource::GamepadAxis(*axis)).filter(|source| source.is_held(input)))
+            .next()?;
+        Some(GkBinding::new(source).with_modifiers(input.modifiers()))
+    }
+
+    /// ### fn GkBinding::value( &self, ... ) -> f32
+    /// *The source's value after the dead zone and scale. Modifiers and chord are not checked.*
+    pub fn value(&self, input: &GkInput) -> f32
+    {
+        let raw = self.source.raw_value(input);
+        if raw.abs() <= self.dead_zone { return 0.0; }
+
+        let value = if self.source.is_bounded() && self.dead_zone < 1.0
+        {
+            raw.signum() * (raw.abs() - self.dead_zone) / (1.0 - self.dead_zone)
+        }
+        else
+        {
+            raw
+        };
+        value * self.scale
+    }
+
+    /// ### fn GkBinding::is_satisfied( &self, ... ) -> bool
+    /// *Whether the modifiers and chord are held.*
+    pub fn is_satisfied(&self, input: &GkInput) -> bool
+    {
+        input.modifiers().contains(self.modifiers) && self.chord.iter().all(|source| source.is_held(input))
+    }
+
+    /// ### fn GkBinding::shadows( &self, ... ) -> bool
+    /// *Whether self reads the same source as other while requiring strictly more, like Ctrl+S over S.
+    /// A satisfied binding shadows the ones it outranks so Ctrl+S doesn't also trigger S.*
+    pub fn shadows(&self, other: &GkBinding) -> bool
+    {
+        self.source == other.source
+            && self.modifiers.contains(other.modifiers)
+            && other.chord.iter().all(|source| self.chord.contains(source))
+            && (self.modifiers != other.modifiers || self.chord.len() > other.chord.len())
+    }
+}
+
+impl std::fmt::Display for GkBinding
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        for (modifier, name) in MODIFIER_NAMES
+        {
+            if self.modifiers.contains(modifier) { write!(f, "{}+", name)?; }
+        }
+        for source in &self.chord
+        {
+            write!(f, "{}+", source)?;
+        }
+        write!(f, "{}", self.source)?;
+        if self.dead_zone != 0.0 { write!(f, " deadzone={}", self.dead_zone)?; }
+        if self.scale != 1.0 { write!(f, " scale={}", self.scale)?; }
+        if self.axis == EActionAxis::Y { write!(f, " axis=y")?; }
+        Ok(())
+    }
+}
+
+impl FromStr for GkBinding
+{
+    type Err = GkError;
+
+    fn from_str(text: &str) -> GkResult<Self>
+    {
+        let mut words = text.split_whitespace();
+        let combo = words.next().ok_or_else(|| GkError::Config(String::from("empty binding")))?;
+
+        let mut modifiers = ModifiersState::empty();
+        let mut sources = Vec::new();
+        for part in combo.split('+')
+        {
+            match MODIFIER_NAMES.iter().find(|(_, name)| name.eq_ignore_ascii_case(part))
+            {
+                Some((modifier, _)) => modifiers |= *modifier,
+                None => sources.push(part.parse::<EInputSource>()?),
+            }
+        }
+        let source = sources.pop().ok_or_else(|| GkError::Config(format!("binding '{}' has no input", text)))?;
+
+        let mut binding = GkBinding::new(source).with_modifiers(modifiers);
+        binding.chord = sources;
+        for word in words
+        {
+            let invalid = || GkError::Config(format!("invalid option '{}' in binding '{}'", word, text));
+            let (key, value) = word.split_once('=').ok_or_else(invalid)?;
+            match key
+            {
+                "deadzone" => binding = binding.with_dead_zone(value.parse().map_err(|_| invalid())?),
+                "scale" => binding = binding.with_scale(value.parse().map_err(|_| invalid())?),
+                "axis" if value.eq_ignore_ascii_case("x") => binding = binding.on_axis(EActionAxis::X),
+                "axis" if value.eq_ignore_ascii_case("y") => binding = binding.on_axis(EActionAxis::Y),
+                _ => return Err(invalid()),
+            }
+        }
+        Ok(binding)
+    }
+}
+
+/// Modifier names in the order bindings write them.
+const MODIFIER_NAMES: [(ModifiersState, &str); 4] = [
+    (ModifiersState::CONTROL, "Ctrl"),
+    (ModifiersState::SHIFT, "Shift"),
+    (ModifiersState::ALT, "Alt"),
+    (ModifiersState::SUPER, "Super"),
+];
+
+/// ### GkActionState struct
+/// *An action's state for the current frame.*
+/// <pre>
+/// - Members
+///     value:      (f32, f32)  <i>// Summed binding values. Bounded inputs together never exceed one binding's scale.</i>
+///     b_held:     bool        <i>// Some binding reads non zero.</i>
+///     b_pressed:  bool        <i>// Held this frame but not the previous one.</i>
+///     b_released: bool        <i>// Held the previous frame but not this one.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GkActionState
+{
+    pub value:      (f32, f32),
+    pub b_held:     bool,
+    pub b_pressed:  bool,
+    pub b_released: bool,
+}
+
+/// ### GkActionMap struct
+/// *Named actions such as "MoveForward" or "Look", each bound to any number of inputs.*<br>
+/// *update() reads GkInput once a frame. Bindings load from and save to a TOML file with a table per action:*
+/// <pre>
+/// [Jump]
+/// bindings = ["Space", "Gamepad.South"]
+///
+/// [MoveForward]
+/// bindings = ["KeyW", "KeyS scale=-1", "Gamepad.LeftStickY deadzone=0.15"]
+/// </pre>
+#[derive(Clone, Debug, Default)]
+pub struct GkActionMap
+{
+    actions:    BTreeMap<String, Vec<GkBinding>>,
+    states:     HashMap<String, GkActionState>,
+}
+
+impl GkActionMap
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkActionMap::bind( &mut self, ... ) -> &mut GkActionMap
+    /// *Adds a binding to action, creating the action if needed.*
+    pub fn bind(&mut self, action: &str, binding: GkBinding) -> &mut Self
+    {
+        self.actions.entry(String::from(action)).or_default().push(binding);
+        self
+    }
+
+    /// ### fn GkActionMap::set_bindings( &mut self, ... )
+    /// *Replaces all of action's bindings. An empty list keeps the action, unbound.*
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<GkBinding>)
+    {
+        self.actions.insert(String::from(action), bindings);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[GkBinding]
+    {
+        self.actions.get(action).map_or(&[], |bindings| bindings.as_slice())
+    }
+
+    pub fn remove_action(&mut self, action: &str)
+    {
+        self.actions.remove(action);
+        self.states.remove(action);
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str>
+    {
+        self.actions.keys().map(|action| action.as_str())
+    }
+
+    /// ### fn GkActionMap::update( &mut self, ... )
+    /// *Reads every action's bindings from input. Call once a frame, before input.end_frame().*
+    pub fn update(&mut self, input: &GkInput)
+    {
+        let satisfied: Vec<&GkBinding> = self.actions.values().flatten().filter(|binding| binding.is_satisfied(input)).collect();
+
+        for (action, bindings) in &self.actions
+        {
+            let mut bounded = (0.0, 0.0);
+            let mut unbounded = (0.0, 0.0);
+            let mut limit: f32 = 0.0;
+            let mut b_held = false;
+            for binding in bindings
+            {
+                if !binding.is_satisfied(input) || satisfied.iter().any(|other| other.shadows(binding)) { continue; }
+
+                let value = binding.value(input);
+                if value == 0.0 { continue; }
+                b_held = true;
+
+                let sum = if binding.source.is_bounded()
+                {
+                    limit = limit.max(binding.scale.abs());
+                    &mut bounded
+                }
+                else
+                {
+                    &mut unbounded
+                };
+                match binding.axis
+                {
+                    EActionAxis::X => sum.0 += value,
+                    EActionAxis::Y => sum.1 += value,
+                }
+            }
+
+            let value = (bounded.0.clamp(-limit, limit) + unbounded.0, bounded.1.clamp(-limit, limit) + unbounded.1);
+            let b_was_held = self.states.get(action).is_some_and(|state| state.b_held);
+            self.states.insert(action.clone(), GkActionState
+            {
+                value,
+                b_held,
+                b_pressed: b_held && !b_was_held,
+                b_released: !b_held && b_was_held,
+            });
+        }
+    }
+
+    /// ### fn GkActionMap::state( &self, ... ) -> GkActionState
+    /// *The action's state, all zero for unknown actions or before the first update().*
+    pub fn state(&self, action: &str) -> GkActionState
+    {
+        self.states.get(action).copied().unwrap_or_default()
+    }
+
+    pub fn value(&self, action: &str) -> f32
+    {
+        self.state(action).value.0
+    }
+
+    pub fn value_2d(&self, action: &str) -> (f32, f32)
+    {
+        self.state(action).value
+    }
+
+    pub fn is_held(&self, action: &str) -> bool
+    {
+        self.state(action).b_held
+    }
+
+    pub fn was_pressed(&self, action: &str) -> bool
+    {
+        self.state(action).b_pressed
+    }
+
+    pub fn was_released(&self, action: &str) -> bool
+    {
+        self.state(action).b_released
+    }
+
+    /// ### fn GkActionMap::parse( ... ) -> GkResult\<GkActionMap\>
+    /// *Reads bindings from TOML text, see GkActionMap. Each action table may only hold a `bindings` array of strings.*
+    pub fn parse(text: &str) -> GkResult<Self>
+    {
+        let line_error = |offset: usize, msg: &str| GkError::Config(format!("line {}: {}", text[..offset].matches('\n').count() + 1, msg));
+        let tables: BTreeMap<String, GkActionTable<toml::Spanned<String>>> = toml::from_str(text)
+            .map_err(|e| match e.span()
+            {
+                Some(span) => line_error(span.start, e.message()),
+                None => GkError::Config(String::from(e.message())),
+            })?;
+
+        let mut map = Self::new();
+        for (action, table) in tables
+        {
+            if action.is_empty() { return Err(GkError::Config(String::from("empty action name"))); }
+            map.actions.entry(action.clone()).or_default();
+            for item in table.bindings
+            {
+                let binding = item.get_ref().parse::<GkBinding>().map_err(|e| match e
+                {
+                    GkError::Config(msg) => line_error(item.span().start, &msg),
+                    other => other,
+                })?;
+                map.bind(&action, binding);
+            }
+        }
+        Ok(map)
+    }
+
+    /// ### fn GkActionMap::to_toml( &self ) -> String
+    /// *The bindings as TOML text that parse() reads back.*
+    pub fn to_toml(&self) -> String
+    {
+        let tables: BTreeMap<&str, GkActionTable<String>> = self.actions.iter()
+            .map(|(action, bindings)| (action.as_str(), GkActionTable { bindings: bindings.iter().map(|binding| binding.to_string()).collect() }))
+            .collect();
+        // Only string keys and arrays of strings, which always serialize.
+        toml::to_string(&tables).unwrap_or_default()
+    }
+
+    /// ### fn GkActionMap::load( ... ) -> GkResult\<GkActionMap\>
+    /// <pre>
+    /// - Params
+    ///     path:   &Path
+    /// - Return
+    ///     GkResult&lt;GkActionMap&gt;
+    /// </pre>
+    pub fn load(path: &Path) -> GkResult<Self>
+    {
+        let text = std::fs::read_to_string(path).map_err(|e| GkError::io(path, e))?;
+        Self::parse(&text).map_err(|e| match e
+        {
+            GkError::Config(msg) => GkError::Config(format!("{}: {}", path.display(), msg)),
+            e => e,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> GkResult<()>
+    {
+        std::fs::write(path, self.to_toml()).map_err(|e| GkError::io(path, e))
+    }
+}
+
+/// One action's table in a bindings file. Bindings are strings in GkBinding's text form.
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct GkActionTable<T>
+{
+    #[serde(default = "Vec::new")]
+    bindings:   Vec<T>,
+}
+
+#[cfg(test)]
+mod tests
+{
+    use winit::event::MouseButton;
+    use winit::keyboard::{ KeyCode, ModifiersState };
+
+    use crate::platform::input::{ EGamepadAxis, EGamepadButton, GkInput };
+
+    use super::{ EActionAxis, EInputSource, EMouseAxis, GkActionMap, GkBinding };
+
+    #[test]
+    fn bindings_round_trip_through_text()
+    {
+        let bindings = [
+            GkBinding::key(KeyCode::KeyS).with_modifiers(ModifiersState::CONTROL | ModifiersState::SHIFT),
+            GkBinding::new(EInputSource::GamepadButton(EGamepadButton::South)).with_chord(EInputSource::GamepadButton(EGamepadButton::LeftBumper)),
+            GkBinding::new(EInputSource::GamepadAxis(EGamepadAxis::LeftStickY)).with_dead_zone(0.15).on_axis(EActionAxis::Y),
+            GkBinding::new(EInputSource::MouseButton(MouseButton::Other(7))),
+            GkBinding::new(EInputSource::MouseAxis(EMouseAxis::WheelY)).with_scale(-0.5),
+        ];
+        let texts: Vec<String> = bindings.iter().map(|binding| binding.to_string()).collect();
+        assert_eq!(texts, vec![
+            "Ctrl+Shift+KeyS",
+            "Gamepad.LeftBumper+Gamepad.South",
+            "Gamepad.LeftStickY deadzone=0.15 axis=y",
+            "Mouse.Button7",
+            "Mouse.WheelY scale=-0.5",
+        ]);
+        for (binding, text) in bindings.iter().zip(&texts)
+        {
+            assert_eq!(&text.parse::<GkBinding>().unwrap(), binding);
+        }
+        assert!("Ctrl+KeyQQ".parse::<GkBinding>().is_err());
+    }
+
+    #[test]
+    fn config_files_round_trip_and_report_bad_lines()
+    {
+        let text = "\
+# Player controls
+[MoveForward]
+bindings = [
+    \"KeyW\", \"ArrowUp\",   # either works
+    \"KeyS scale=-1\",
+]
+
+[\"Quick Save\"]
+bindings = [\"Ctrl+KeyS\"]
+";
+        let map = GkActionMap::parse(text).unwrap();
+        assert_eq!(map.bindings("MoveForward").len(), 3);
+        assert_eq!(map.bindings("Quick Save"), &[GkBinding::key(KeyCode::KeyS).with_modifiers(ModifiersState::CONTROL)]);
+
+        let saved = map.to_toml();
+        assert!(saved.contains("[\"Quick Save\"]\nbindings = [\"Ctrl+KeyS\"]"));
+        let reloaded = GkActionMap::parse(&saved).unwrap();
+        assert_eq!(reloaded.bindings("MoveForward"), map.bindings("MoveForward"));
+
+        let error = GkActionMap::parse("[Jump]\nbindings = [\"Space\"]\n\n[Crouch]\nbindings = [\"Spacebar\"]\n").unwrap_err();
+        assert_eq!(error.to_string(), "config error: line 5: unknown input 'Spacebar'");
+        let error = GkActionMap::parse("[Jump]\nbindings = [\"Space\"]\nkeys = [\"KeyJ\"]\n").unwrap_err();
+        assert!(error.to_string().starts_with("config error: line 3: "), "{}", error);
+        assert!(GkActionMap::parse("[Jump]\nbindings = [\"Space\"]\nbindings = [\"KeyJ\"]\n").is_err());
+    }
+
+    #[test]
+    fn load_errors_name_the_file_once()
+    {
+        let path = std::env::temp_dir().join(format!("gunk_action_map_{}.toml", std::process::id()));
+        std::fs::write(&path, "[Jump]\nbindings = [\"Spacebar\"]\n").unwrap();
+        let error = GkActionMap::load(&path).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(error.to_string(), format!("config error: {}: line 2: unknown input 'Spacebar'", path.display()));
+    }
+
+    #[test]
+    fn config_files_accept_any_valid_toml()
+    {
+        let text = "\
+Jump = { bindings = ['Space', \"Gamepad\\u002ESouth\"] }
+[\"Say \\\"hi\\\" \\\\ wave\"]
+bindings = [\"\"\"KeyH\"\"\"]
+[Idle]
+";
+        let map = GkActionMap::parse(text).unwrap();
+        assert_eq!(map.bindings("Jump"), &[GkBinding::key(KeyCode::Space), GkBinding::new(EInputSource::GamepadButton(EGamepadButton::South))]);
+        assert_eq!(map.bindings("Say \"hi\" \\ wave"), &[GkBinding::key(KeyCode::KeyH)]);
+        assert!(map.actions().any(|action| action == "Idle"));
+
+        // Names that need escaping come back unchanged.
+        let reloaded = GkActionMap::parse(&map.to_toml()).unwrap();
+        assert_eq!(reloaded.actions().collect::<Vec<_>>(), map.actions().collect::<Vec<_>>());
+        assert_eq!(reloaded.bindings("Say \"hi\" \\ wave"), map.bindings("Say \"hi\" \\ wave"));
+        assert!(GkActionMap::parse("[Jump]\nbindings = \"Space\"\n").is_err());
+    }
+
+    #[test]
+    fn modifier_bindings_shadow_plain_ones()
+    {
+        let mut map = GkActionMap::new();
+        map.bind("MoveBack", GkBinding::key(KeyCode::KeyS));
+        map.bind("Save", GkBinding::key(KeyCode::KeyS).with_modifiers(ModifiersState::CONTROL));
+        map.bind("Sprint", GkBinding::key(KeyCode::ShiftLeft));
+
+        let mut input = GkInput::new();
+        input.press_key(KeyCode::KeyS);
+        map.update(&input);
+        assert!(map.is_held("MoveBack") && !map.is_held("Save"));
+
+        input.press_key(KeyCode::ControlLeft);
+        input.set_modifiers(ModifiersState::CONTROL);
+        map.update(&input);
+        assert!(map.was_pressed("Save"));
+        assert!(map.was_released("MoveBack"));
+
+        // Extra modifiers don't block a binding nothing else claims.
+        input.release_key(KeyCode::ControlLeft);
+        input.press_key(KeyCode::ShiftLeft);
+        input.set_modifiers(ModifiersState::SHIFT);
+        map.update(&input);
+        assert!(map.is_held("MoveBack") && map.is_held("Sprint"));
+    }
+
+    #[test]
+    fn chords_need_every_input_held()
+    {
+        let mut map = GkActionMap::new();
+        map.bind("Jump", GkBinding::new(EInputSource::GamepadButton(EGamepadButton::South)));
+        map.bind("SuperJump", GkBinding::new(EInputSource::GamepadButton(EGamepadButton::South))
+            .with_chord(EInputSource::GamepadAxis(EGamepadAxis::LeftTrigger)));
+
+        let mut input = GkInput::new();
+        input.press_gamepad_button(EGamepadButton::South);
+        input.set_gamepad_axis(EGamepadAxis::LeftTrigger, 0.3);
+        map.update(&input);
+        assert!(map.is_held("Jump") && !map.is_held("SuperJump"));
+
+        input.set_gamepad_axis(EGamepadAxis::LeftTrigger, 0.9);
+        map.update(&input);
+        assert!(!map.is_held("Jump") && map.is_held("SuperJump"));
+    }
+
+    #[test]
+    fn axes_apply_dead_zones_and_combine_bindings()
+    {
+        let mut map = GkActionMap::new();
+        map.bind("Move", GkBinding::key(KeyCode::KeyW).on_axis(EActionAxis::Y))
+            .bind("Move", GkBinding::key(KeyCode::ArrowUp).on_axis(EActionAxis::Y))
+            .bind("Move", GkBinding::key(KeyCode::KeyA).with_scale(-1.0))
+            .bind("Move", GkBinding::new(EInputSource::GamepadAxis(EGamepadAxis::LeftStickX)).with_dead_zone(0.2));
+        map.bind("Look", GkBinding::new(EInputSource::MouseAxis(EMouseAxis::X)).with_scale(0.1));
+
+        let mut input = GkInput::new();
+        input.press_key(KeyCode::KeyW);
+        input.press_key(KeyCode::ArrowUp);
+        input.set_gamepad_axis(EGamepadAxis::LeftStickX, 0.1);
+        input.add_mouse_motion(30.0, 0.0);
+        map.update(&input);
+        assert_eq!(map.value_2d("Move"), (0.0, 1.0));
+        assert!((map.value("Look") - 3.0).abs() < 1e-6);
+
+        input.set_gamepad_axis(EGamepadAxis::LeftStickX, 0.6);
+        map.update(&input);
+        assert!((map.value("Move") - 0.5).abs() < 1e-6);
+
+        input.press_key(KeyCode::KeyA);
+        map.update(&input);
+        assert!((map.value("Move") + 0.5).abs() < 1e-6);
+
+        input.end_frame();
+        map.update(&input);
+        assert!(map.was_released("Look"));
+    }
+
+    #[test]
+    fn capture_skips_lone_modifier_keys()
+    {
+        let mut input = GkInput::new();
+        input.press_key(KeyCode::ControlLeft);
+        input.set_modifiers(ModifiersState::CONTROL);
+        assert_eq!(GkBinding::capture(&input), None);
+
+        input.end_frame();
+        input.press_key(KeyCode::KeyK);
+        assert_eq!(GkBinding::capture(&input), Some(GkBinding::key(KeyCode::KeyK).with_modifiers(ModifiersState::CONTROL)));
+    }
+}
@@ -0,0 +1,239 @@
+use std::any::{ Any, TypeId };
+use std::collections::HashMap;
+
+/// ### GkSubscription struct
+/// *Handle returned by GkEventBus::subscribe(), pass it to GkEventBus::unsubscribe() to stop receiving events.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GkSubscription
+{
+    id:         u64,
+    type_id:    TypeId,
+}
+
+struct GkSubscriber<T>
+{
+    id:         u64,
+    priority:   i32,
+    handler:    Box<dyn FnMut(&T)>,
+}
+
+/// Type erased subscriber list, so unsubscribe() can find a subscriber knowing only its type id.
+trait IEventChannel
+{
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn remove(&mut self, id: u64) -> bool;
+    fn len(&self) -> usize;
+}
+
+struct GkEventChannel<T>
+{
+    subscribers: Vec<GkSubscriber<T>>,
+}
+
+impl<T: 'static> IEventChannel for GkEventChannel<T>
+{
+    fn as_any_mut(&mut self) -> &mut dyn Any
+    {
+        self
+    }
+
+    fn remove(&mut self, id: u64) -> bool
+    {
+        let count = self.subscribers.len();
+        self.subscribers.retain(|subscriber| subscriber.id != id);
+        self.subscribers.len() != count
+    }
+
+    fn len(&self) -> usize
+    {
+        self.subscribers.len()
+    }
+}
+
+/// A queued event, bound to the publish() call that delivers it.
+type GkQueuedEvent = Box<dyn FnOnce(&mut GkEventBus)>;
+
+/// ### GkEventBus struct
+/// *Typed publish and subscribe. Any 'static type is an event, so the engine's window events
+/// and an application's own events share the bus.*<br>
+/// *Subscribers run from highest priority to lowest, in subscription order within a priority.
+/// publish() runs them right away, queue() holds the event until dispatch_queued().*
+#[derive(Default)]
+pub struct GkEventBus
+{
+    channels:   HashMap<TypeId, Box<dyn IEventChannel>>,
+    queued:     Vec<GkQueuedEvent>,
+    next_id:    u64,
+}
+
+impl GkEventBus
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkEventBus::subscribe\<T\>( &mut self, ... ) -> GkSubscription
+    /// *Calls handler for every T published, at priority 0.*
+    pub fn subscribe<T: 'static>(&mut self, handler: impl FnMut(&T) + 'static) -> GkSubscription
+    {
+        self.subscribe_with_priority(0, handler)
+    }
+
+    /// ### fn GkEventBus::subscribe_with_priority\<T\>( &mut self, ... ) -> GkSubscription
+    /// *Calls handler for every T published, before any subscriber with a lower priority.*
+    /// <pre>
+    /// - Params
+    ///     priority:   i32                     <i>// Higher runs first.</i>
+    ///     handler:    impl FnMut(&T)
+    /// - Return
+    ///     GkSubscription
+    /// </pre>
+    pub fn subscribe_with_priority<T: 'static>(&mut self, priority: i32, handler: impl FnMut(&T) + 'static) -> GkSubscription
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let subscribers = &mut self.channel_mut::<T>().subscribers;
+        let index = subscribers.partition_point(|subscriber| subscriber.priority >= priority);
+        subscribers.insert(index, GkSubscriber { id, priority, handler: Box::new(handler) });
+        GkSubscription { id, type_id: TypeId::of::<T>() }
+    }
+
+    /// ### fn GkEventBus::unsubscribe( &mut self, ... ) -> bool
+    /// *Removes a subscriber. Returns false if it was already removed.*
+    pub fn unsubscribe(&mut self, subscription: GkSubscription) -> bool
+    {
+        self.channels.get_mut(&subscription.type_id).is_some_and(|channel| channel.remove(subscription.id))
+    }
+
+    pub fn subscriber_count<T: 'static>(&self) -> usize
+    {
+        self.channels.get(&TypeId::of::<T>()).map_or(0, |channel| channel.len())
+    }
+
+    /// ### fn GkEventBus::publish\<T\>( &mut self, ... )
+    /// *Immediately passes event to every T subscriber.*
+    pub fn publish<T: 'static>(&mut self, event: T)
+    {
+        let Some(channel) = self.channels.get_mut(&TypeId::of::<T>()) else { return; };
+        let channel = channel.as_any_mut().downcast_mut::<GkEventChannel<T>>().expect("event channel holds another type");
+        for subscriber in channel.subscribers.iter_mut()
+        {
+            (subscriber.handler)(&event);
+        }
+    }
+
+    /// ### fn GkEventBus::queue\<T\>( &mut self, ... )
+    /// *Holds event until the next dispatch_queued(). Queued events keep their order across types.*
+    pub fn queue<T: 'static>(&mut self, event: T)
+    {
+        self.queued.push(Box::new(move |bus: &mut GkEventBus| bus.publish(event)));
+    }
+
+    pub fn queued_count(&self) -> usize
+    {
+        self.queued.len()
+    }
+
+    /// ### fn GkEventBus::dispatch_queued( &mut self )
+    /// *Publishes the queued events in the order they were queued.
+    /// Events queued while dispatching wait for the next call.*
+    pub fn dispatch_queued(&mut self)
+    {
+        for publish in std::mem::take(&mut self.queued)
+        {
+            publish(self);
+        }
+    }
+
+    fn channel_mut<T: 'static>(&mut self) -> &mut GkEventChannel<T>
+    {
+        self.channels
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(GkEventChannel::<T> { subscribers: Vec::new() }))
+            .as_any_mut()
+            .downcast_mut::<GkEventChannel<T>>()
+            .expect("event channel holds another type")
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Explosion { radius: f32 }
+
+    #[test]
+    fn subscribers_run_by_priority_then_subscription_order()
+    {
+        let mut bus = GkEventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        for (name, priority) in [("ui", 10), ("game", 0), ("audio", 0), ("debug", 20), ("late", -5)]
+        {
+            let log = log.clone();
+            bus.subscribe_with_priority(priority, move |event: &Explosion| log.borrow_mut().push((name, event.radius)));
+        }
+
+        bus.publish(Explosion { radius: 2.0 });
+        let names: Vec<&str> = log.borrow().iter().map(|&(name, _)| name).collect();
+        assert_eq!(names, ["debug", "ui", "game", "audio", "late"]);
+        assert!(log.borrow().iter().all(|&(_, radius)| radius == 2.0));
+    }
+
+    #[test]
+    fn unsubscribed_handlers_stop_receiving_events()
+    {
+        let mut bus = GkEventBus::new();
+        let count = Rc::new(RefCell::new(0));
+        let first = { let count = count.clone(); bus.subscribe(move |_: &Explosion| *count.borrow_mut() += 1) };
+        { let count = count.clone(); bus.subscribe(move |_: &Explosion| *count.borrow_mut() += 10); }
+
+        bus.publish(Explosion { radius: 1.0 });
+        assert!(bus.unsubscribe(first));
+        assert!(!bus.unsubscribe(first));
+        bus.publish(Explosion { radius: 1.0 });
+
+        assert_eq!(*count.borrow(), 21);
+        assert_eq!(bus.subscriber_count::<Explosion>(), 1);
+    }
+
+    #[test]
+    fn queued_events_wait_for_dispatch_and_keep_their_order()
+    {
+        let mut bus = GkEventBus::new();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        { let log = log.clone(); bus.subscribe(move |event: &Explosion| log.borrow_mut().push(format!("boom {}", event.radius))); }
+        { let log = log.clone(); bus.subscribe(move |event: &String| log.borrow_mut().push(event.clone())); }
+
+        bus.queue(Explosion { radius: 1.0 });
+        bus.queue(String::from("message"));
+        bus.queue(Explosion { radius: 3.0 });
+        bus.publish(String::from("now"));
+        assert_eq!(*log.borrow(), ["now"]);
+        assert_eq!(bus.queued_count(), 3);
+
+        bus.dispatch_queued();
+        assert_eq!(*log.borrow(), ["now", "boom 1", "message", "boom 3"]);
+        assert_eq!(bus.queued_count(), 0);
+    }
+
+    #[test]
+    fn events_only_reach_subscribers_of_their_type()
+    {
+        let mut bus = GkEventBus::new();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        { let seen = seen.clone(); bus.subscribe(move |event: &u32| seen.borrow_mut().push(*event)); }
+
+        bus.publish(7u32);
+        bus.publish(7u64);
+        bus.publish(Explosion { radius: 7.0 });
+        assert_eq!(*seen.borrow(), [7]);
+        assert_eq!(bus.subscriber_count::<u64>(), 0);
+    }
+}
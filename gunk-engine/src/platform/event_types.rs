@@ -1,155 +1,31 @@
-#![allow(dead_code)]
+use std::path::PathBuf;
 
-use std::{sync::{ Arc, RwLock }, ops::DerefMut};
+// Window events published on Application::events by main_loop.
+// Input is published as EInputEvent, after GkInput has applied it.
 
-use winit::event::ElementState;
-
-
-// Key Events
-
-pub trait IKeyListener
-{
-    fn fire_callback(&self, state: &ElementState);
-}
-
-pub struct DelegateKeyListener<T>
-{
-    obj: Arc<RwLock<T>>,
-    callback: fn(&mut T, state: &ElementState),
-}
-
-impl<T> DelegateKeyListener<T>
-{
-    pub fn new(obj: Arc<RwLock<T>>, callback: fn(&mut T, state: &ElementState)) -> Self
-    {
-        Self{ obj, callback }
-    }
-}
-
-impl<T> IKeyListener for DelegateKeyListener<T>
-{
-    fn fire_callback(&self, state: &ElementState)
-    {
-        let mut w_obj = self.obj.write().unwrap();
-        (self.callback)(w_obj.deref_mut(), state);
-        drop(w_obj);
-    }
-}
-
-pub struct KeyEvent
-{
-    listener: Option<Box<dyn IKeyListener>>
-}
-
-impl KeyEvent
-{
-    pub const fn new(listener: Option<Box<dyn IKeyListener>>) -> Self
-    {
-        Self{ listener }
-    }
-
-    pub fn set_listener(&mut self, new_listener: Option<Box<dyn IKeyListener>>)
-    {
-        self.listener = new_listener;
-    }
-
-    pub fn notify_listener(&self, state: &ElementState)
-    {
-        if self.listener.is_some()
-        {
-            self.listener.as_ref().unwrap().fire_callback(state);
-        }
-    }
-}
-
-
-// Mouse Events
-// Mouse Motion Events
-
-pub struct MouseMotionData
-{
-    pub x: f64,
-    pub y: f64,
-}
-
-pub trait IMouseMotionListener
-{
-    fn fire_callback(&self, mouse_motion_data: &MouseMotionData);
-}
-
-pub struct DelegateMouseMotionListener<T>
-{
-    obj: Arc<RwLock<T>>,
-    callback: fn(&mut T, mouse_motion_data: &MouseMotionData),
-}
-
-pub struct MouseMotionEvent
-{
-    listener: Option<Box<dyn IMouseMotionListener>>
-}
-
-impl MouseMotionEvent
-{
-    pub const fn new(listener: Option<Box<dyn IMouseMotionListener>>) -> Self
-    {
-        Self{ listener }
-    }
-
-    pub fn set_listener(&mut self, new_listener: Option<Box<dyn IMouseMotionListener>>)
-    {
-        self.listener = new_listener;
-    }
-
-    pub fn notify_listener(&self, mouse_motion_data: &MouseMotionData)
-    {
-        if self.listener.is_some()
-        {
-            self.listener.as_ref().unwrap().fire_callback(mouse_motion_data);
-        }
-    }
-}
-
-// Mouse Button Events
-
-// pub struct MouseButtonData
-// {
-//     pub button: u32,
-//     pub state: ElementState,
-// }
-
-pub trait IMouseButtonListener
+/// ### GkWindowResized struct
+/// *The window's inner size changed. Zero sized while minimized.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkWindowResized
 {
-    fn fire_callback(&self, state: &ElementState);
+    pub width:  u32,
+    pub height: u32,
 }
 
-pub struct DelegateMouseButtonListener<T>
+/// ### GkWindowFocused struct
+/// *The window gained or lost keyboard focus.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkWindowFocused
 {
-    obj: Arc<RwLock<T>>,
-    callback: fn(&mut T, state: &ElementState),
+    pub b_focused: bool,
 }
 
-pub struct MouseButtonEvent
+/// ### EFileDrop enum
+/// *A file dragged over or dropped onto the window. Several files give one event each.*
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EFileDrop
 {
-    listener: Option<Box<dyn IMouseButtonListener>>
+    Hovered(PathBuf),
+    HoverCancelled,
+    Dropped(PathBuf),
 }
-
-impl MouseButtonEvent
-{
-    pub const fn new(listener: Option<Box<dyn IMouseButtonListener>>) -> Self
-    {
-        Self{ listener }
-    }
-
-    pub fn set_listener(&mut self, new_listener: Option<Box<dyn IMouseButtonListener>>)
-    {
-        self.listener = new_listener;
-    }
-
-    pub fn notify_listener(&self, state: &ElementState)
-    {
-        if self.listener.is_some()
-        {
-            self.listener.as_ref().unwrap().fire_callback(&state);
-        }
-    }
-}
\ No newline at end of file
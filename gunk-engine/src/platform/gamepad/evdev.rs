@@ -0,0 +1,447 @@
+use std::collections::{ HashMap, HashSet };
+use std::fs::{ File, OpenOptions };
+use std::io::{ ErrorKind, Read, Write };
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::path::{ Path, PathBuf };
+use std::time::{ Duration, Instant };
+
+use crate::log_debug;
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::platform::gamepad::{ EGamepadEvent, GkGamepadBackend, GkGamepadId, GkRumble };
+use crate::platform::input::{ EGamepadAxis, EGamepadButton };
+
+/// Where evdev device nodes live.
+const INPUT_DIR: &str = "/dev/input";
+
+/// How often /dev/input is rescanned for newly plugged pads.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(1);
+
+// Event types and codes from linux/input-event-codes.h.
+const EV_SYN: u16 = 0x00;
+const EV_KEY: u16 = 0x01;
+const EV_ABS: u16 = 0x03;
+const EV_FF: u16 = 0x15;
+const SYN_DROPPED: u16 = 3;
+const BTN_GAMEPAD: u16 = 0x130;
+const ABS_HAT0X: u16 = 0x10;
+const ABS_HAT0Y: u16 = 0x11;
+const FF_RUMBLE: u16 = 0x50;
+const KEY_CODE_COUNT: usize = 0x300;
+
+/// ### fn evdev_button( ... ) -> Option\<EGamepadButton\>
+/// *The pad button of an EV_KEY code. The kernel names BTN_NORTH and BTN_WEST after the top and left buttons,
+/// which some drivers still swap on Xbox pads.*
+fn evdev_button(code: u16) -> Option<EGamepadButton>
+{
+    let button = match code
+    {
+        0x130 => EGamepadButton::South,
+        0x131 => EGamepadButton::East,
+        0x133 => EGamepadButton::North,
+        0x134 => EGamepadButton::West,
+        0x136 => EGamepadButton::LeftBumper,
+        0x137 => EGamepadButton::RightBumper,
+        0x13a => EGamepadButton::Select,
+        0x13b => EGamepadButton::Start,
+        0x13c => EGamepadButton::Mode,
+        0x13d => EGamepadButton::LeftThumb,
+        0x13e => EGamepadButton::RightThumb,
+        0x220 => EGamepadButton::DPadUp,
+        0x221 => EGamepadButton::DPadDown,
+        0x222 => EGamepadButton::DPadLeft,
+        0x223 => EGamepadButton::DPadRight,
+        _ => return None,
+    };
+    Some(button)
+}
+
+/// The pad axis of an EV_ABS code, and whether evdev's direction is flipped from ours.
+fn evdev_axis(code: u16) -> Option<(EGamepadAxis, bool)>
+{
+    let axis = match code
+    {
+        0x00 => (EGamepadAxis::LeftStickX, false),
+        0x01 => (EGamepadAxis::LeftStickY, true),
+        0x03 => (EGamepadAxis::RightStickX, false),
+        0x04 => (EGamepadAxis::RightStickY, true),
+        0x02 => (EGamepadAxis::LeftTrigger, false),
+        0x05 => (EGamepadAxis::RightTrigger, false),
+        _ => return None,
+    };
+    Some(axis)
+}
+
+/// ### fn normalize_axis( ... ) -> f32
+/// *Maps a raw evdev axis value within [min, max] to [-1, 1] for sticks or [0, 1] for triggers.*
+fn normalize_axis(axis: EGamepadAxis, value: i32, min: i32, max: i32, b_flipped: bool) -> f32
+{
+    if max <= min { return 0.0; }
+    let unit = ((value.clamp(min, max) - min) as f64 / (max - min) as f64) as f32;
+    match axis
+    {
+        EGamepadAxis::LeftTrigger | EGamepadAxis::RightTrigger => unit,
+        _ if b_flipped => 1.0 - unit * 2.0,
+        _ => unit * 2.0 - 1.0,
+    }
+}
+
+const IOC_WRITE: u32 = 1;
+const IOC_READ: u32 = 2;
+
+/// The _IOC() request number for an evdev ioctl.
+const fn evdev_ioc(dir: u32, nr: u32, size: usize) -> u32
+{
+    (dir << 30) | ((size as u32) << 16) | ((b'E' as u32) << 8) | nr
+}
+
+/// struct ff_effect from linux/input.h holding an ff_rumble_effect. The union is 32 bytes on 64 bit targets.
+#[repr(C)]
+#[derive(Default)]
+struct FfRumbleEffect
+{
+    kind:               u16,
+    id:                 i16,
+    direction:          u16,
+    trigger_button:     u16,
+    trigger_interval:   u16,
+    replay_length:      u16,
+    replay_delay:       u16,
+    padding:            u16,
+    strong_magnitude:   u16,
+    weak_magnitude:     u16,
+    union_rest:         [u16; 14],
+}
+
+const _: () = assert!(std::mem::size_of::<FfRumbleEffect>() == 48);
+
+/// ### fn read_ioctl( ... ) -> std::io::Result\<()\>
+/// *Runs an ioctl that fills buffer. The request's size must match the buffer's.*
+fn read_ioctl<T>(file: &File, request: u32, buffer: &mut T) -> std::io::Result<()>
+{
+    // The kernel writes at most size_of::<T>() bytes, encoded in request, into buffer.
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), request as _, buffer as *mut T) };
+    if result < 0 { Err(std::io::Error::last_os_error()) } else { Ok(()) }
+}
+
+fn has_bit(bits: &[u8], bit: usize) -> bool
+{
+    bits.get(bit / 8).is_some_and(|byte| byte & (1 << (bit % 8)) != 0)
+}
+
+/// Range of an EV_ABS axis, from struct input_absinfo.
+#[derive(Clone, Copy, Debug, Default)]
+struct GkAbsRange
+{
+    min: i32,
+    max: i32,
+}
+
+/// An open evdev gamepad.
+struct GkEvdevPad
+{
+    id:             GkGamepadId,
+    path:           PathBuf,
+    file:           File,
+    ranges:         HashMap<u16, GkAbsRange>,
+    hat:            (i32, i32),
+    b_writable:     bool,
+    b_rumble:       bool,
+    effect_id:      i16,
+}
+
+impl GkEvdevPad
+{
+    /// ### fn GkEvdevPad::open( ... ) -> std::io::Result\<Option\<(GkEvdevPad, String)\>\>
+    /// *Opens path and returns the pad and its name, or None if the device is not a gamepad.*
+    fn open(path: &Path, id: GkGamepadId) -> std::io::Result<Option<(GkEvdevPad, String)>>
+    {
+        let open = |b_write: bool| OpenOptions::new().read(true).write(b_write).custom_flags(libc::O_NONBLOCK).open(path);
+        // Rumble needs write access, which udev rules often don't grant.
+        let (file, b_writable) = match open(true)
+        {
+            Ok(file) => (file, true),
+            Err(_) => (open(false)?, false),
+        };
+
+        let mut key_bits = [0u8; KEY_CODE_COUNT / 8];
+        read_ioctl(&file, evdev_ioc(IOC_READ, 0x20 + EV_KEY as u32, key_bits.len()), &mut key_bits)?;
+        if !has_bit(&key_bits, BTN_GAMEPAD as usize) { return Ok(None); }
+
+        let mut name = [0u8; 256];
+        read_ioctl(&file, evdev_ioc(IOC_READ, 0x06, name.len()), &mut name)?;
+        let name_length = name.iter().position(|byte| *byte == 0).unwrap_or(name.len());
+        let name = String::from_utf8_lossy(&name[..name_length]).into_owned();
+
+        let mut abs_bits = [0u8; 8];
+        read_ioctl(&file, evdev_ioc(IOC_READ, 0x20 + EV_ABS as u32, abs_bits.len()), &mut abs_bits)?;
+        let mut ranges = HashMap::new();
+        for code in (0..6).chain([ABS_HAT0X, ABS_HAT0Y])
+        {
+            if !has_bit(&abs_bits, code as usize) { continue; }
+            // struct input_absinfo: value, minimum, maximum, fuzz, flat, resolution.
+            let mut absinfo = [0i32; 6];
+            read_ioctl(&file, evdev_ioc(IOC_READ, 0x40 + code as u32, std::mem::size_of_val(&absinfo)), &mut absinfo)?;
+            ranges.insert(code, GkAbsRange { min: absinfo[1], max: absinfo[2] });
+        }
+
+        let mut ff_bits = [0u8; 16];
+        let b_rumble = read_ioctl(&file, evdev_ioc(IOC_READ, 0x20 + EV_FF as u32, ff_bits.len()), &mut ff_bits).is_ok()
+            && has_bit(&ff_bits, FF_RUMBLE as usize);
+
+        let pad = GkEvdevPad { id, path: path.to_path_buf(), file, ranges, hat: (0, 0), b_writable, b_rumble, effect_id: -1 };
+        Ok(Some((pad, name)))
+    }
+
+    /// ### fn GkEvdevPad::read_events( &mut self, ... ) -> bool
+    /// *Reads every pending input_event. Returns false once the device is gone.*
+    fn read_events(&mut self, events: &mut Vec<EGamepadEvent>) -> bool
+    {
+        let event_size = std::mem::size_of::<libc::input_event>();
+        let time_size = std::mem::size_of::<libc::timeval>();
+        let mut buffer = vec![0u8; event_size * 64];
+        loop
+        {
+            let length = match self.file.read(&mut buffer)
+            {
+                Ok(0) => return false,
+                Ok(length) => length,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            };
+            for raw in buffer[..length].chunks_exact(event_size)
+            {
+                let kind = u16::from_ne_bytes([raw[time_size], raw[time_size + 1]]);
+                let code = u16::from_ne_bytes([raw[time_size + 2], raw[time_size + 3]]);
+                let value = i32::from_ne_bytes([raw[time_size + 4], raw[time_size + 5], raw[time_size + 6], raw[time_size + 7]]);
+                self.translate(kind, code, value, events);
+            }
+        }
+    }
+
+    fn translate(&mut self, kind: u16, code: u16, value: i32, events: &mut Vec<EGamepadEvent>)
+    {
+        let id = self.id;
+        match kind
+        {
+            // Key repeats (value 2) carry nothing new.
+            EV_KEY if value != 2 =>
+            {
+                if let Some(button) = evdev_button(code)
+                {
+                    events.push(EGamepadEvent::Button { id, button, b_pressed: value == 1 });
+                }
+            },
+            EV_ABS if code == ABS_HAT0X || code == ABS_HAT0Y =>
+            {
+                // Hats report the d-pad as -1, 0 or 1 per direction, turned into button presses here.
+                let (previous, negative, positive) = if code == ABS_HAT0X
+                {
+                    (std::mem::replace(&mut self.hat.0, value.signum()), EGamepadButton::DPadLeft, EGamepadButton::DPadRight)
+                }
+                else
+                {
+                    (std::mem::replace(&mut self.hat.1, value.signum()), EGamepadButton::DPadUp, EGamepadButton::DPadDown)
+                };
+                let current = value.signum();
+                if previous == current { return; }
+                if previous != 0
+                {
+                    let button = if previous < 0 { negative } else { positive };
+                    events.push(EGamepadEvent::Button { id, button, b_pressed: false });
+                }
+                if current != 0
+                {
+                    let button = if current < 0 { negative } else { positive };
+                    events.push(EGamepadEvent::Button { id, button, b_pressed: true });
+                }
+            },
+            EV_ABS =>
+            {
+                let (Some((axis, b_flipped)), Some(range)) = (evdev_axis(code), self.ranges.get(&code)) else { return; };
+                events.push(EGamepadEvent::Axis { id, axis, value: normalize_axis(axis, value, range.min, range.max, b_flipped) });
+            },
+            EV_SYN if code == SYN_DROPPED =>
+            {
+                // The kernel buffer overflowed and events were lost. Later events bring the state back in line.
+                log_debug!(format!("Gamepad {} dropped input events", id));
+            },
+            _ => {}
+        }
+    }
+
+    fn rumble(&mut self, rumble: GkRumble) -> GkResult<()>
+    {
+        if !self.b_rumble { return Err(GkError::Unsupported(format!("{} can't rumble", self.path.display()))); }
+        if !self.b_writable { return Err(GkError::Unsupported(format!("{} is not writable, rumble needs write access", self.path.display()))); }
+
+        let magnitude = |strength: f32| (strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16;
+        let mut effect = FfRumbleEffect
+        {
+            kind: FF_RUMBLE,
+            id: self.effect_id,
+            replay_length: rumble.duration.as_millis().min(u16::MAX as u128) as u16,
+            strong_magnitude: magnitude(rumble.strong),
+            weak_magnitude: magnitude(rumble.weak),
+            ..Default::default()
+        };
+        // EVIOCSFF uploads the effect, or updates it when id is already set, and writes the assigned id back.
+        read_ioctl(&self.file, evdev_ioc(IOC_WRITE, 0x80, std::mem::size_of::<FfRumbleEffect>()), &mut effect)
+            .map_err(|e| GkError::io(&self.path, e))?;
+        self.effect_id = effect.id;
+
+        // Playing is an EV_FF input_event with the effect id as code and the repeat count as value.
+        let event_size = std::mem::size_of::<libc::input_event>();
+        let time_size = std::mem::size_of::<libc::timeval>();
+        let mut play = vec![0u8; event_size];
+        play[time_size..time_size + 2].copy_from_slice(&EV_FF.to_ne_bytes());
+        play[time_size + 2..time_size + 4].copy_from_slice(&(effect.id as u16).to_ne_bytes());
+        play[time_size + 4..time_size + 8].copy_from_slice(&1i32.to_ne_bytes());
+        self.file.write_all(&play).map_err(|e| GkError::io(&self.path, e))
+    }
+}
+
+/// ### GkEvdevBackend struct
+/// *Gamepads read from the Linux evdev nodes in /dev/input, which need read access, usually through the input group.*<br>
+/// *Pads plugged in later are found by rescanning every second. Unplugged ones are noticed when reading fails.*
+/// <pre>
+/// - Members
+///     pads:       Vec&lt;GkEvdevPad&gt;
+///     ignored:    HashSet&lt;PathBuf&gt;    <i>// Nodes that aren't gamepads or couldn't be opened, skipped until they disappear.</i>
+///     next_id:    GkGamepadId
+///     last_scan:  Option&lt;Instant&gt;
+/// </pre>
+pub struct GkEvdevBackend
+{
+    pads:       Vec<GkEvdevPad>,
+    ignored:    HashSet<PathBuf>,
+    next_id:    GkGamepadId,
+    last_scan:  Option<Instant>,
+}
+
+impl Default for GkEvdevBackend
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl GkEvdevBackend
+{
+    pub fn new() -> Self
+    {
+        Self { pads: Vec::new(), ignored: HashSet::new(), next_id: 0, last_scan: None }
+    }
+
+    fn scan(&mut self, events: &mut Vec<EGamepadEvent>)
+    {
+        self.last_scan = Some(Instant::now());
+        let Ok(entries) = std::fs::read_dir(INPUT_DIR) else { return; };
+
+        let mut present = HashSet::new();
+        for entry in entries.flatten()
+        {
+            let path = entry.path();
+            if !entry.file_name().to_string_lossy().starts_with("event") { continue; }
+            present.insert(path.clone());
+            if self.ignored.contains(&path) || self.pads.iter().any(|pad| pad.path == path) { continue; }
+
+            match GkEvdevPad::open(&path, self.next_id)
+            {
+                Ok(Some((pad, name))) =>
+                {
+                    self.next_id += 1;
+                    events.push(EGamepadEvent::Connected { id: pad.id, name });
+                    self.pads.push(pad);
+                },
+                Ok(None) => { self.ignored.insert(path); },
+                Err(e) =>
+                {
+                    log_debug!(format!("Skipping {}: {}", path.display(), e));
+                    self.ignored.insert(path);
+                },
+            }
+        }
+        self.ignored.retain(|path| present.contains(path));
+    }
+}
+
+impl GkGamepadBackend for GkEvdevBackend
+{
+    fn name(&self) -> &str
+    {
+        "evdev"
+    }
+
+    fn poll(&mut self, events: &mut Vec<EGamepadEvent>)
+    {
+        if self.last_scan.is_none_or(|last_scan| last_scan.elapsed() >= RESCAN_INTERVAL)
+        {
+            self.scan(events);
+        }
+        self.pads.retain_mut(|pad|
+        {
+            let b_alive = pad.read_events(events);
+            if !b_alive { events.push(EGamepadEvent::Disconnected { id: pad.id }); }
+            b_alive
+        });
+    }
+
+    fn rumble(&mut self, id: GkGamepadId, rumble: GkRumble) -> GkResult<()>
+    {
+        let pad = self.pads.iter_mut().find(|pad| pad.id == id)
+            .ok_or_else(|| GkError::Unsupported(format!("evdev gamepad {} is not connected", id)))?;
+        pad.rumble(rumble)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::platform::gamepad::EGamepadEvent;
+    use crate::platform::input::{ EGamepadAxis, EGamepadButton };
+
+    use super::{ evdev_ioc, normalize_axis, IOC_READ, IOC_WRITE, FfRumbleEffect };
+
+    #[test]
+    fn ioctl_numbers_match_the_kernel_headers()
+    {
+        // EVIOCGNAME(256), EVIOCGABS(ABS_Y) and EVIOCSFF from linux/input.h.
+        assert_eq!(evdev_ioc(IOC_READ, 0x06, 256), 0x8100_4506);
+        assert_eq!(evdev_ioc(IOC_READ, 0x41, 24), 0x8018_4541);
+        assert_eq!(evdev_ioc(IOC_WRITE, 0x80, std::mem::size_of::<FfRumbleEffect>()), 0x4030_4580);
+    }
+
+    #[test]
+    fn axes_normalize_with_y_pointing_up()
+    {
+        assert_eq!(normalize_axis(EGamepadAxis::LeftStickX, -32768, -32768, 32767, false), -1.0);
+        assert_eq!(normalize_axis(EGamepadAxis::LeftStickY, -32768, -32768, 32767, true), 1.0);
+        assert_eq!(normalize_axis(EGamepadAxis::RightTrigger, 255, 0, 255, false), 1.0);
+        assert_eq!(normalize_axis(EGamepadAxis::LeftTrigger, 0, 0, 255, false), 0.0);
+        assert_eq!(normalize_axis(EGamepadAxis::LeftStickX, 5, 0, 0, false), 0.0);
+    }
+
+    #[test]
+    fn hat_moves_become_dpad_presses()
+    {
+        let file = std::fs::File::open("/dev/null").unwrap();
+        let mut pad = super::GkEvdevPad
+        {
+            id: 3, path: std::path::PathBuf::from("/dev/null"), file, ranges: Default::default(),
+            hat: (0, 0), b_writable: false, b_rumble: false, effect_id: -1,
+        };
+        let mut events = Vec::new();
+        pad.translate(super::EV_ABS, super::ABS_HAT0X, -1, &mut events);
+        pad.translate(super::EV_ABS, super::ABS_HAT0X, 1, &mut events);
+        pad.translate(super::EV_ABS, super::ABS_HAT0X, 0, &mut events);
+        assert_eq!(events, vec![
+            EGamepadEvent::Button { id: 3, button: EGamepadButton::DPadLeft, b_pressed: true },
+            EGamepadEvent::Button { id: 3, button: EGamepadButton::DPadLeft, b_pressed: false },
+            EGamepadEvent::Button { id: 3, button: EGamepadButton::DPadRight, b_pressed: true },
+            EGamepadEvent::Button { id: 3, button: EGamepadButton::DPadRight, b_pressed: false },
+        ]);
+    }
+}
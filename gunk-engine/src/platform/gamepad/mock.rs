@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use std::sync::{ Arc, Mutex };
+
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::platform::gamepad::{ EGamepadEvent, GkGamepadBackend, GkGamepadId, GkRumble };
+use crate::platform::input::{ EGamepadAxis, EGamepadButton };
+
+#[derive(Default)]
+struct GkMockPads
+{
+    next_id:    GkGamepadId,
+    connected:  HashSet<GkGamepadId>,
+    queued:     Vec<EGamepadEvent>,
+    rumbles:    Vec<(GkGamepadId, GkRumble)>,
+}
+
+/// ### GkMockGamepads struct
+/// *Virtual gamepads scripted by tests and tools.*<br>
+/// *Clones share the same pads, so one clone goes to GkGamepads::add_backend() and another scripts it.
+/// Scripted changes are delivered on the next poll.*
+#[derive(Clone, Default)]
+pub struct GkMockGamepads
+{
+    pads: Arc<Mutex<GkMockPads>>,
+}
+
+impl GkMockGamepads
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkMockGamepads::connect( &self, ... ) -> GkGamepadId
+    /// *Plugs in a pad and returns its id within this backend.*
+    pub fn connect(&self, name: &str) -> GkGamepadId
+    {
+        let mut pads = self.pads.lock().unwrap();
+        let id = pads.next_id;
+        pads.next_id += 1;
+        pads.connected.insert(id);
+        pads.queued.push(EGamepadEvent::Connected { id, name: String::from(name) });
+        id
+    }
+
+    pub fn disconnect(&self, id: GkGamepadId)
+    {
+        let mut pads = self.pads.lock().unwrap();
+        if pads.connected.remove(&id) { pads.queued.push(EGamepadEvent::Disconnected { id }); }
+    }
+
+    pub fn press(&self, id: GkGamepadId, button: EGamepadButton)
+    {
+        self.push(EGamepadEvent::Button { id, button, b_pressed: true });
+    }
+
+    pub fn release(&self, id: GkGamepadId, button: EGamepadButton)
+    {
+        self.push(EGamepadEvent::Button { id, button, b_pressed: false });
+    }
+
+    pub fn set_axis(&self, id: GkGamepadId, axis: EGamepadAxis, value: f32)
+    {
+        self.push(EGamepadEvent::Axis { id, axis, value });
+    }
+
+    /// ### fn GkMockGamepads::rumbles( &self ) -> Vec\<(GkGamepadId, GkRumble)\>
+    /// *Every rumble requested so far, oldest first.*
+    pub fn rumbles(&self) -> Vec<(GkGamepadId, GkRumble)>
+    {
+        self.pads.lock().unwrap().rumbles.clone()
+    }
+
+    fn push(&self, event: EGamepadEvent)
+    {
+        let mut pads = self.pads.lock().unwrap();
+        if pads.connected.contains(&event.id()) { pads.queued.push(event); }
+    }
+}
+
+impl GkGamepadBackend for GkMockGamepads
+{
+    fn name(&self) -> &str
+    {
+        "mock"
+    }
+
+    fn poll(&mut self, events: &mut Vec<EGamepadEvent>)
+    {
+        events.append(&mut self.pads.lock().unwrap().queued);
+    }
+
+    fn rumble(&mut self, id: GkGamepadId, rumble: GkRumble) -> GkResult<()>
+    {
+        let mut pads = self.pads.lock().unwrap();
+        if !pads.connected.contains(&id)
+        {
+            return Err(GkError::Unsupported(format!("mock gamepad {} is not connected", id)));
+        }
+        pads.rumbles.push((id, rumble));
+        Ok(())
+    }
+}
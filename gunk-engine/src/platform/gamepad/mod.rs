@@ -0,0 +1,418 @@
+use std::collections::{ BTreeMap, HashMap, HashSet };
+use std::time::Duration;
+
+use crate::{ log_info, log_warn };
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::platform::input::{ EGamepadAxis, EGamepadButton, GkInput };
+
+pub mod mock;
+#[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+pub mod evdev;
+
+/// Identifies a connected gamepad. Never reused while the GkGamepads lives, so a reconnected pad gets a new id.
+pub type GkGamepadId = u32;
+
+/// ### EGamepadEvent enum
+/// *What a gamepad backend reports. Ids are the backend's own in GkGamepadBackend::poll() and global ones from GkGamepads.*
+#[derive(Clone, Debug, PartialEq)]
+pub enum EGamepadEvent
+{
+    Connected { id: GkGamepadId, name: String },
+    Disconnected { id: GkGamepadId },
+    Button { id: GkGamepadId, button: EGamepadButton, b_pressed: bool },
+    Axis { id: GkGamepadId, axis: EGamepadAxis, value: f32 },
+}
+
+impl EGamepadEvent
+{
+    pub fn id(&self) -> GkGamepadId
+    {
+        match *self
+        {
+            EGamepadEvent::Connected { id, .. }
+            | EGamepadEvent::Disconnected { id }
+            | EGamepadEvent::Button { id, .. }
+            | EGamepadEvent::Axis { id, .. } => id,
+        }
+    }
+
+    fn with_id(self, id: GkGamepadId) -> Self
+    {
+        match self
+        {
+            EGamepadEvent::Connected { name, .. } => EGamepadEvent::Connected { id, name },
+            EGamepadEvent::Disconnected { .. } => EGamepadEvent::Disconnected { id },
+            EGamepadEvent::Button { button, b_pressed, .. } => EGamepadEvent::Button { id, button, b_pressed },
+            EGamepadEvent::Axis { axis, value, .. } => EGamepadEvent::Axis { id, axis, value },
+        }
+    }
+}
+
+/// ### GkRumble struct
+/// *A rumble request. Motor strengths range over [0, 1].*
+/// <pre>
+/// - Members
+///     strong:     f32         <i>// Low frequency motor.</i>
+///     weak:       f32         <i>// High frequency motor.</i>
+///     duration:   Duration
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkRumble
+{
+    pub strong:     f32,
+    pub weak:       f32,
+    pub duration:   Duration,
+}
+
+/// ### GkGamepadBackend trait
+/// *A source of gamepads, such as the Linux evdev devices or scripted mock pads.*
+pub trait GkGamepadBackend: Send
+{
+    fn name(&self) -> &str;
+
+    /// ### fn GkGamepadBackend::poll( &mut self, ... )
+    /// *Appends what happened since the last poll, in order. A pad's Connected comes before its other events.*
+    fn poll(&mut self, events: &mut Vec<EGamepadEvent>);
+
+    /// ### fn GkGamepadBackend::rumble( &mut self, ... ) -> GkResult\<()\>
+    /// *Starts rumbling pad id, replacing any rumble still playing on it.*
+    fn rumble(&mut self, id: GkGamepadId, rumble: GkRumble) -> GkResult<()>;
+}
+
+/// ### GkGamepadState struct
+/// *Buttons and axes of one connected gamepad.*
+#[derive(Clone, Debug, Default)]
+pub struct GkGamepadState
+{
+    pub name:   String,
+    buttons:    HashSet<EGamepadButton>,
+    axes:       HashMap<EGamepadAxis, f32>,
+}
+
+impl GkGamepadState
+{
+    pub fn is_button_held(&self, button: EGamepadButton) -> bool
+    {
+        self.buttons.contains(&button)
+    }
+
+    pub fn axis(&self, axis: EGamepadAxis) -> f32
+    {
+        self.axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    pub fn buttons_held(&self) -> impl Iterator<Item = &EGamepadButton>
+    {
+        self.buttons.iter()
+    }
+
+    pub fn axes(&self) -> impl Iterator<Item = (EGamepadAxis, f32)> + '_
+    {
+        self.axes.iter().map(|(axis, value)| (*axis, *value))
+    }
+}
+
+/// ### GkGamepads struct
+/// *Polls the gamepad backends, tracks every connected pad and feeds them, merged, into GkInput.*<br>
+/// *A button is held while any pad holds it and an axis reads the pad deflecting it most,
+/// so single player games and the action map need not care which pad is in use.*
+/// <pre>
+/// - Members
+///     backends:   Vec&lt;Box&lt;dyn GkGamepadBackend&gt;&gt;
+///     pads:       BTreeMap&lt;GkGamepadId, GkGamepadState&gt;
+///     routes:     HashMap&lt;GkGamepadId, (usize, GkGamepadId)&gt;   <i>// Global id to backend index and the backend's id.</i>
+///     ids:        HashMap&lt;(usize, GkGamepadId), GkGamepadId&gt;
+///     next_id:    GkGamepadId
+///     events:     Vec&lt;EGamepadEvent&gt;      <i>// The last poll's events, with global ids.</i>
+/// </pre>
+#[derive(Default)]
+pub struct GkGamepads
+{
+    backends:   Vec<Box<dyn GkGamepadBackend>>,
+    pads:       BTreeMap<GkGamepadId, GkGamepadState>,
+    routes:     HashMap<GkGamepadId, (usize, GkGamepadId)>,
+    ids:        HashMap<(usize, GkGamepadId), GkGamepadId>,
+    next_id:    GkGamepadId,
+    events:     Vec<EGamepadEvent>,
+}
+
+impl GkGamepads
+{
+    /// ### fn GkGamepads::new() -> GkGamepads
+    /// *No backends, add them with add_backend().*
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkGamepads::with_default_backends() -> GkGamepads
+    /// *The platform's native backend, if it has one. Linux reads evdev devices.*
+    pub fn with_default_backends() -> Self
+    {
+        #[allow(unused_mut)]
+        let mut gamepads = Self::new();
+        #[cfg(all(target_os = "linux", target_pointer_width = "64"))]
+        gamepads.add_backend(Box::new(evdev::GkEvdevBackend::new()));
+        gamepads
+    }
+
+    pub fn add_backend(&mut self, backend: Box<dyn GkGamepadBackend>)
+    {
+        self.backends.push(backend);
+    }
+
+    /// ### fn GkGamepads::poll( &mut self, ... ) -> &\[EGamepadEvent\]
+    /// *Polls every backend and applies what happened to the pads and to input. Call once a frame.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     input:      &mut GkInput
+    /// - Return
+    ///     &[EGamepadEvent]    <i>// This poll's events, including connects and disconnects.</i>
+    /// </pre>
+    pub fn poll(&mut self, input: &mut GkInput) -> &[EGamepadEvent]
+    {
+        let mut polled = Vec::new();
+        let mut backend_events = Vec::new();
+        for (index, backend) in self.backends.iter_mut().enumerate()
+        {
+            backend.poll(&mut backend_events);
+            polled.extend(backend_events.drain(..).map(|event| (index, event)));
+        }
+
+        self.events.clear();
+        for (index, event) in polled
+        {
+            if let Some(event) = self.route(index, event)
+            {
+                self.apply(&event, input);
+                self.events.push(event);
+            }
+        }
+        &self.events
+    }
+
+    /// ### fn GkGamepads::apply_replayed( &mut self, ... )
+    /// *Applies recorded events, with their global ids, in place of polling the backends.<br>
+    /// Replayed pads have no backend, so rumbling them fails.*
+    pub fn apply_replayed(&mut self, events: &[EGamepadEvent], input: &mut GkInput)
+    {
+        self.events.clear();
+        for event in events
+        {
+            if let EGamepadEvent::Connected { id, .. } = event { self.next_id = self.next_id.max(id + 1); }
+            self.apply(event, input);
+            self.events.push(event.clone());
+        }
+    }
+
+    /// ### fn GkGamepads::events( &self ) -> &\[EGamepadEvent\]
+    /// *The last poll's events.*
+    pub fn events(&self) -> &[EGamepadEvent]
+    {
+        &self.events
+    }
+
+    pub fn connected(&self) -> impl Iterator<Item = (GkGamepadId, &GkGamepadState)>
+    {
+        self.pads.iter().map(|(id, state)| (*id, state))
+    }
+
+    pub fn gamepad(&self, id: GkGamepadId) -> Option<&GkGamepadState>
+    {
+        self.pads.get(&id)
+    }
+
+    /// ### fn GkGamepads::rumble( &mut self, ... ) -> GkResult\<()\>
+    /// *Asks pad id's backend to rumble it. Fails if the pad is gone or can't rumble.*
+    pub fn rumble(&mut self, id: GkGamepadId, rumble: GkRumble) -> GkResult<()>
+    {
+        let &(index, local_id) = self.routes.get(&id)
+            .ok_or_else(|| GkError::Unsupported(format!("gamepad {} is not connected", id)))?;
+        self.backends[index].rumble(local_id, rumble)
+    }
+
+    /// Translates a backend's ids to global ones, None for events of pads never announced.
+    fn route(&mut self, index: usize, event: EGamepadEvent) -> Option<EGamepadEvent>
+    {
+        let local_id = event.id();
+        let id = match event
+        {
+            EGamepadEvent::Connected { .. } =>
+            {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.ids.insert((index, local_id), id);
+                self.routes.insert(id, (index, local_id));
+                id
+            },
+            EGamepadEvent::Disconnected { .. } =>
+            {
+                let id = self.ids.remove(&(index, local_id))?;
+                self.routes.remove(&id);
+                id
+            },
+            _ => *self.ids.get(&(index, local_id))?,
+        };
+        Some(event.with_id(id))
+    }
+
+    fn apply(&mut self, event: &EGamepadEvent, input: &mut GkInput)
+    {
+        match event
+        {
+            EGamepadEvent::Connected { id, name } =>
+            {
+                log_info!(format!("Gamepad {} connected: {}", id, name));
+                self.pads.insert(*id, GkGamepadState { name: name.clone(), ..Default::default() });
+            },
+            EGamepadEvent::Disconnected { id } =>
+            {
+                let Some(state) = self.pads.remove(id) else { return; };
+                log_info!(format!("Gamepad {} disconnected: {}", id, state.name));
+                for button in state.buttons
+                {
+                    self.sync_button(button, input);
+                }
+                for axis in state.axes.into_keys()
+                {
+                    self.sync_axis(axis, input);
+                }
+            },
+            EGamepadEvent::Button { id, button, b_pressed } =>
+            {
+                let Some(state) = self.pads.get_mut(id) else { return; };
+                if *b_pressed { state.buttons.insert(*button); } else { state.buttons.remove(button); }
+                self.sync_button(*button, input);
+            },
+            EGamepadEvent::Axis { id, axis, value } =>
+            {
+                let Some(state) = self.pads.get_mut(id) else { return; };
+                if !value.is_finite()
+                {
+                    log_warn!(format!("Gamepad {} reported {} for {:?}, ignoring it", id, value, axis));
+                    return;
+                }
+                state.axes.insert(*axis, value.clamp(-1.0, 1.0));
+                self.sync_axis(*axis, input);
+            },
+        }
+    }
+
+    /// Presses or releases button in input so it is held while any pad holds it.
+    /// Done per event rather than once per poll so a press and release within one frame still registers.
+    fn sync_button(&self, button: EGamepadButton, input: &mut GkInput)
+    {
+        let b_held = self.pads.values().any(|state| state.is_button_held(button));
+        if b_held { input.press_gamepad_button(button); } else { input.release_gamepad_button(button); }
+    }
+
+    fn sync_axis(&self, axis: EGamepadAxis, input: &mut GkInput)
+    {
+        let value = self.pads.values().map(|state| state.axis(axis)).fold(0.0, |strongest: f32, value|
+        {
+            if value.abs() > strongest.abs() { value } else { strongest }
+        });
+        input.set_gamepad_axis(axis, value);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use crate::platform::action_map::{ EInputSource, GkActionMap, GkBinding };
+    use crate::platform::input::{ EGamepadAxis, EGamepadButton, GkInput };
+
+    use super::mock::GkMockGamepads;
+    use super::{ EGamepadEvent, GkGamepads, GkRumble };
+
+    #[test]
+    fn mock_pads_feed_input_and_the_action_map()
+    {
+        let mock = GkMockGamepads::new();
+        let mut gamepads = GkGamepads::new();
+        gamepads.add_backend(Box::new(mock.clone()));
+        let mut input = GkInput::new();
+        let mut actions = GkActionMap::new();
+        actions.bind("Jump", GkBinding::new(EInputSource::GamepadButton(EGamepadButton::South)));
+
+        let pad = mock.connect("Virtual Pad");
+        mock.press(pad, EGamepadButton::South);
+        mock.set_axis(pad, EGamepadAxis::LeftStickX, -0.75);
+        let events = gamepads.poll(&mut input).to_vec();
+        assert_eq!(events[0], EGamepadEvent::Connected { id: 0, name: String::from("Virtual Pad") });
+        assert_eq!(events.len(), 3);
+
+        actions.update(&input);
+        assert!(actions.was_pressed("Jump"));
+        assert_eq!(input.gamepad_axis(EGamepadAxis::LeftStickX), -0.75);
+        assert!(gamepads.gamepad(0).unwrap().is_button_held(EGamepadButton::South));
+
+        // A tap within one poll still shows as pressed and released.
+        input.end_frame();
+        mock.release(pad, EGamepadButton::South);
+        mock.press(pad, EGamepadButton::East);
+        mock.release(pad, EGamepadButton::East);
+        gamepads.poll(&mut input);
+        assert!(input.was_gamepad_button_pressed(EGamepadButton::East));
+        assert!(input.was_gamepad_button_released(EGamepadButton::East));
+        assert!(input.was_gamepad_button_released(EGamepadButton::South));
+    }
+
+    #[test]
+    fn pads_merge_and_unplugging_releases_what_they_held()
+    {
+        let mock = GkMockGamepads::new();
+        let mut gamepads = GkGamepads::new();
+        gamepads.add_backend(Box::new(mock.clone()));
+        let mut input = GkInput::new();
+
+        let first = mock.connect("First");
+        let second = mock.connect("Second");
+        mock.press(first, EGamepadButton::Start);
+        mock.press(second, EGamepadButton::Start);
+        mock.set_axis(first, EGamepadAxis::RightStickY, 0.25);
+        mock.set_axis(second, EGamepadAxis::RightStickY, -0.5);
+        gamepads.poll(&mut input);
+        assert_eq!(gamepads.connected().count(), 2);
+        assert_eq!(input.gamepad_axis(EGamepadAxis::RightStickY), -0.5);
+
+        mock.disconnect(second);
+        gamepads.poll(&mut input);
+        assert!(input.is_gamepad_button_held(EGamepadButton::Start));
+        assert_eq!(input.gamepad_axis(EGamepadAxis::RightStickY), 0.25);
+
+        mock.disconnect(first);
+        gamepads.poll(&mut input);
+        assert!(!input.is_gamepad_button_held(EGamepadButton::Start));
+        assert_eq!(input.gamepad_axis(EGamepadAxis::RightStickY), 0.0);
+
+        // Ids are not reused, a pad plugged in again gets a fresh one.
+        mock.connect("First again");
+        gamepads.poll(&mut input);
+        assert_eq!(gamepads.connected().map(|(id, _)| id).collect::<Vec<_>>(), vec![2]);
+    }
+
+    #[test]
+    fn rumble_reaches_the_owning_backend()
+    {
+        let first_backend = GkMockGamepads::new();
+        let second_backend = GkMockGamepads::new();
+        let mut gamepads = GkGamepads::new();
+        gamepads.add_backend(Box::new(first_backend.clone()));
+        gamepads.add_backend(Box::new(second_backend.clone()));
+        let mut input = GkInput::new();
+
+        first_backend.connect("Pad A");
+        let local_b = second_backend.connect("Pad B");
+        gamepads.poll(&mut input);
+
+        let rumble = GkRumble { strong: 1.0, weak: 0.25, duration: Duration::from_millis(200) };
+        gamepads.rumble(1, rumble).unwrap();
+        assert!(first_backend.rumbles().is_empty());
+        assert_eq!(second_backend.rumbles(), vec![(local_b, rumble)]);
+        assert!(gamepads.rumble(7, rumble).is_err());
+    }
+}
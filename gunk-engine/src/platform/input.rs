@@ -0,0 +1,527 @@
+use std::collections::{ HashMap, HashSet };
+
+use winit::event::{ DeviceEvent, ElementState, MouseButton, MouseScrollDelta, WindowEvent };
+use winit::keyboard::{ KeyCode, ModifiersState, PhysicalKey };
+
+/// Pixels of a touchpad scroll counted as one wheel line.
+const GK_PIXELS_PER_SCROLL_LINE: f64 = 40.0;
+
+/// Every KeyCode winit defines. Bindings name keys by their Debug name, recordings by their index here.
+pub const GK_KEY_CODES: [KeyCode; 194] = [
+    KeyCode::Backquote, KeyCode::Backslash, KeyCode::BracketLeft, KeyCode::BracketRight, KeyCode::Comma,
+    KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+    KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9, KeyCode::Equal, KeyCode::IntlBackslash,
+    KeyCode::IntlRo, KeyCode::IntlYen, KeyCode::KeyA, KeyCode::KeyB, KeyCode::KeyC, KeyCode::KeyD, KeyCode::KeyE,
+    KeyCode::KeyF, KeyCode::KeyG, KeyCode::KeyH, KeyCode::KeyI, KeyCode::KeyJ, KeyCode::KeyK, KeyCode::KeyL,
+    KeyCode::KeyM, KeyCode::KeyN, KeyCode::KeyO, KeyCode::KeyP, KeyCode::KeyQ, KeyCode::KeyR, KeyCode::KeyS,
+    KeyCode::KeyT, KeyCode::KeyU, KeyCode::KeyV, KeyCode::KeyW, KeyCode::KeyX, KeyCode::KeyY, KeyCode::KeyZ,
+    KeyCode::Minus, KeyCode::Period, KeyCode::Quote, KeyCode::Semicolon, KeyCode::Slash, KeyCode::AltLeft,
+    KeyCode::AltRight, KeyCode::Backspace, KeyCode::CapsLock, KeyCode::ContextMenu, KeyCode::ControlLeft,
+    KeyCode::ControlRight, KeyCode::Enter, KeyCode::SuperLeft, KeyCode::SuperRight, KeyCode::ShiftLeft,
+    KeyCode::ShiftRight, KeyCode::Space, KeyCode::Tab, KeyCode::Convert, KeyCode::KanaMode, KeyCode::Lang1,
+    KeyCode::Lang2, KeyCode::Lang3, KeyCode::Lang4, KeyCode::Lang5, KeyCode::NonConvert, KeyCode::Delete, KeyCode::End,
+    KeyCode::Help, KeyCode::Home, KeyCode::Insert, KeyCode::PageDown, KeyCode::PageUp, KeyCode::ArrowDown,
+    KeyCode::ArrowLeft, KeyCode::ArrowRight, KeyCode::ArrowUp, KeyCode::NumLock, KeyCode::Numpad0, KeyCode::Numpad1,
+    KeyCode::Numpad2, KeyCode::Numpad3, KeyCode::Numpad4, KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7,
+    KeyCode::Numpad8, KeyCode::Numpad9, KeyCode::NumpadAdd, KeyCode::NumpadBackspace, KeyCode::NumpadClear,
+    KeyCode::NumpadClearEntry, KeyCode::NumpadComma, KeyCode::NumpadDecimal, KeyCode::NumpadDivide,
+    KeyCode::NumpadEnter, KeyCode::NumpadEqual, KeyCode::NumpadHash, KeyCode::NumpadMemoryAdd,
+    KeyCode::NumpadMemoryClear, KeyCode::NumpadMemoryRecall, KeyCode::NumpadMemoryStore, KeyCode::NumpadMemorySubtract,
+    KeyCode::NumpadMultiply, KeyCode::NumpadParenLeft, KeyCode::NumpadParenRight, KeyCode::NumpadStar,
+    KeyCode::NumpadSubtract, KeyCode::Escape, KeyCode::Fn, KeyCode::FnLock, KeyCode::PrintScreen, KeyCode::ScrollLock,
+    KeyCode::Pause, KeyCode::BrowserBack, KeyCode::BrowserFavorites, KeyCode::BrowserForward, KeyCode::BrowserHome,
+    KeyCode::BrowserRefresh, KeyCode::BrowserSearch, KeyCode::BrowserStop, KeyCode::Eject, KeyCode::LaunchApp1,
+    KeyCode::LaunchApp2, KeyCode::LaunchMail, KeyCode::MediaPlayPause, KeyCode::MediaSelect, KeyCode::MediaStop,
+    KeyCode::MediaTrackNext, KeyCode::MediaTrackPrevious, KeyCode::Power, KeyCode::Sleep, KeyCode::AudioVolumeDown,
+    KeyCode::AudioVolumeMute, KeyCode::AudioVolumeUp, KeyCode::WakeUp, KeyCode::Meta, KeyCode::Hyper, KeyCode::Turbo,
+    KeyCode::Abort, KeyCode::Resume, KeyCode::Suspend, KeyCode::Again, KeyCode::Copy, KeyCode::Cut, KeyCode::Find,
+    KeyCode::Open, KeyCode::Paste, KeyCode::Props, KeyCode::Select, KeyCode::Undo, KeyCode::Hiragana, KeyCode::Katakana,
+    KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8, KeyCode::F9,
+    KeyCode::F10, KeyCode::F11, KeyCode::F12, KeyCode::F13, KeyCode::F14, KeyCode::F15, KeyCode::F16, KeyCode::F17,
+    KeyCode::F18, KeyCode::F19, KeyCode::F20, KeyCode::F21, KeyCode::F22, KeyCode::F23, KeyCode::F24, KeyCode::F25,
+    KeyCode::F26, KeyCode::F27, KeyCode::F28, KeyCode::F29, KeyCode::F30, KeyCode::F31, KeyCode::F32, KeyCode::F33,
+    KeyCode::F34, KeyCode::F35,
+];
+
+/// ### EGamepadButton enum
+/// *Gamepad buttons by position, South being A on an Xbox pad and Cross on a PlayStation pad.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EGamepadButton
+{
+    South,
+    East,
+    West,
+    North,
+    LeftBumper,
+    RightBumper,
+    LeftThumb,
+    RightThumb,
+    Select,
+    Start,
+    Mode,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+impl EGamepadButton
+{
+    pub const ALL: [EGamepadButton; 15] = [
+        EGamepadButton::South, EGamepadButton::East, EGamepadButton::West, EGamepadButton::North,
+        EGamepadButton::LeftBumper, EGamepadButton::RightBumper, EGamepadButton::LeftThumb, EGamepadButton::RightThumb,
+        EGamepadButton::Select, EGamepadButton::Start, EGamepadButton::Mode,
+        EGamepadButton::DPadUp, EGamepadButton::DPadDown, EGamepadButton::DPadLeft, EGamepadButton::DPadRight,
+    ];
+}
+
+/// ### EGamepadAxis enum
+/// *Gamepad axes. Sticks range over [-1, 1] with up and right positive, triggers over [0, 1].*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EGamepadAxis
+{
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl EGamepadAxis
+{
+    pub const ALL: [EGamepadAxis; 6] = [
+        EGamepadAxis::LeftStickX, EGamepadAxis::LeftStickY, EGamepadAxis::RightStickX, EGamepadAxis::RightStickY,
+        EGamepadAxis::LeftTrigger, EGamepadAxis::RightTrigger,
+    ];
+}
+
+/// ### EInputEvent enum
+/// *Keyboard and mouse input as GkInput consumes it, translated from winit events.*<br>
+/// *Unlike winit's events these can be built by hand, so they are what tests, recordings and replays feed in.*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EInputEvent
+{
+    KeyPressed(KeyCode),
+    KeyReleased(KeyCode),
+    ButtonPressed(MouseButton),
+    ButtonReleased(MouseButton),
+    CursorMoved { x: f64, y: f64 },
+    CursorLeft,
+    MouseMotion { dx: f64, dy: f64 },
+    Scroll { x: f32, y: f32 },
+    Modifiers(ModifiersState),
+    FocusLost,
+}
+
+/// ### GkInput struct
+/// *Keyboard, mouse and gamepad state of the current frame, fed from winit events and gamepad backends.*<br>
+/// *pressed and released hold what changed since the last end_frame(), held what is down right now.*
+/// <pre>
+/// - Members
+///     keys_held:              HashSet&lt;KeyCode&gt;
+///     keys_pressed:           HashSet&lt;KeyCode&gt;
+///     keys_released:          HashSet&lt;KeyCode&gt;
+///     buttons_held:           HashSet&lt;MouseButton&gt;
+///     buttons_pressed:        HashSet&lt;MouseButton&gt;
+///     buttons_released:       HashSet&lt;MouseButton&gt;
+///     cursor_position:        Option&lt;(f64, f64)&gt;  <i>// Physical pixels in the window, None while outside it.</i>
+///     cursor_delta:           (f64, f64)          <i>// Cursor movement this frame.</i>
+///     mouse_delta:            (f64, f64)          <i>// Raw device movement this frame, unaffected by cursor acceleration or clamping.</i>
+///     scroll_delta:           (f32, f32)          <i>// Wheel lines this frame, positive is up and right.</i>
+///     modifiers:              ModifiersState
+///     pad_buttons_held:       HashSet&lt;EGamepadButton&gt;    <i>// Gamepad state, merged over every connected pad.</i>
+///     pad_buttons_pressed:    HashSet&lt;EGamepadButton&gt;
+///     pad_buttons_released:   HashSet&lt;EGamepadButton&gt;
+///     pad_axes:               HashMap&lt;EGamepadAxis, f32&gt;
+/// </pre>
+#[derive(Clone, Debug, Default)]
+pub struct GkInput
+{
+    keys_held:              HashSet<KeyCode>,
+    keys_pressed:           HashSet<KeyCode>,
+    keys_released:          HashSet<KeyCode>,
+    buttons_held:           HashSet<MouseButton>,
+    buttons_pressed:        HashSet<MouseButton>,
+    buttons_released:       HashSet<MouseButton>,
+    cursor_position:        Option<(f64, f64)>,
+    cursor_delta:           (f64, f64),
+    mouse_delta:            (f64, f64),
+    scroll_delta:           (f32, f32),
+    modifiers:              ModifiersState,
+    pad_buttons_held:       HashSet<EGamepadButton>,
+    pad_buttons_pressed:    HashSet<EGamepadButton>,
+    pad_buttons_released:   HashSet<EGamepadButton>,
+    pad_axes:               HashMap<EGamepadAxis, f32>,
+}
+
+impl GkInput
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkInput::translate_window_event( ... ) -> Option\<EInputEvent\>
+    /// *The input a window event carries, None for events GkInput doesn't track such as resizes or key repeats.*
+    pub fn translate_window_event(event: &WindowEvent) -> Option<EInputEvent>
+    {
+        let input_event = match event
+        {
+            WindowEvent::KeyboardInput { event, .. } =>
+            {
+                // Keys without a KeyCode (PhysicalKey::Unidentified) can't be queried, so they are skipped.
+                let PhysicalKey::Code(code) = event.physical_key else { return None; };
+                match event.state
+                {
+                    ElementState::Pressed if event.repeat => return None,
+                    ElementState::Pressed => EInputEvent::KeyPressed(code),
+                    ElementState::Released => EInputEvent::KeyReleased(code),
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } =>
+            {
+                match state
+                {
+                    ElementState::Pressed => EInputEvent::ButtonPressed(*button),
+                    ElementState::Released => EInputEvent::ButtonReleased(*button),
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => EInputEvent::CursorMoved { x: position.x, y: position.y },
+            WindowEvent::CursorLeft { .. } => EInputEvent::CursorLeft,
+            WindowEvent::MouseWheel { delta, .. } =>
+            {
+                match delta
+                {
+                    MouseScrollDelta::LineDelta(x, y) => EInputEvent::Scroll { x: *x, y: *y },
+                    MouseScrollDelta::PixelDelta(position) => EInputEvent::Scroll
+                    {
+                        x: (position.x / GK_PIXELS_PER_SCROLL_LINE) as f32,
+                        y: (position.y / GK_PIXELS_PER_SCROLL_LINE) as f32,
+                    },
+                }
+            },
+            WindowEvent::ModifiersChanged(modifiers) => EInputEvent::Modifiers(modifiers.state()),
+            WindowEvent::Focused(false) => EInputEvent::FocusLost,
+            _ => return None,
+        };
+        Some(input_event)
+    }
+
+    /// ### fn GkInput::translate_device_event( ... ) -> Option\<EInputEvent\>
+    /// *Raw mouse motion, None for any other device event.*
+    pub fn translate_device_event(event: &DeviceEvent) -> Option<EInputEvent>
+    {
+        match event
+        {
+            DeviceEvent::MouseMotion { delta } => Some(EInputEvent::MouseMotion { dx: delta.0, dy: delta.1 }),
+            _ => None,
+        }
+    }
+
+    /// ### fn GkInput::apply( &mut self, ... )
+    /// *Updates the state from one input event.*
+    pub fn apply(&mut self, event: &EInputEvent)
+    {
+        match *event
+        {
+            EInputEvent::KeyPressed(code) => self.press_key(code),
+            EInputEvent::KeyReleased(code) => self.release_key(code),
+            EInputEvent::ButtonPressed(button) => self.press_button(button),
+            EInputEvent::ButtonReleased(button) => self.release_button(button),
+            EInputEvent::CursorMoved { x, y } => self.move_cursor(x, y),
+            EInputEvent::CursorLeft => self.cursor_position = None,
+            EInputEvent::MouseMotion { dx, dy } => self.add_mouse_motion(dx, dy),
+            EInputEvent::Scroll { x, y } => self.scroll(x, y),
+            EInputEvent::Modifiers(modifiers) => self.modifiers = modifiers,
+            // Releases made while unfocused never arrive, so nothing can stay held.
+            EInputEvent::FocusLost => self.release_all(),
+        }
+    }
+
+    /// ### fn GkInput::handle_window_event( &mut self, ... )
+    /// *Updates the state from keyboard, mouse button, cursor, wheel, modifier and focus events.*
+    pub fn handle_window_event(&mut self, event: &WindowEvent)
+    {
+        if let Some(input_event) = Self::translate_window_event(event) { self.apply(&input_event); }
+    }
+
+    /// ### fn GkInput::handle_device_event( &mut self, ... )
+    /// *Accumulates raw mouse motion.*
+    pub fn handle_device_event(&mut self, event: &DeviceEvent)
+    {
+        if let Some(input_event) = Self::translate_device_event(event) { self.apply(&input_event); }
+    }
+
+    /// ### fn GkInput::end_frame( &mut self )
+    /// *Clears this frame's presses, releases and deltas. Call once the frame has read them.*
+    pub fn end_frame(&mut self)
+    {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.pad_buttons_pressed.clear();
+        self.pad_buttons_released.clear();
+        self.cursor_delta = (0.0, 0.0);
+        self.mouse_delta = (0.0, 0.0);
+        self.scroll_delta = (0.0, 0.0);
+    }
+
+    pub fn press_key(&mut self, code: KeyCode)
+    {
+        if self.keys_held.insert(code) { self.keys_pressed.insert(code); }
+    }
+
+    pub fn release_key(&mut self, code: KeyCode)
+    {
+        if self.keys_held.remove(&code) { self.keys_released.insert(code); }
+    }
+
+    pub fn press_button(&mut self, button: MouseButton)
+    {
+        if self.buttons_held.insert(button) { self.buttons_pressed.insert(button); }
+    }
+
+    pub fn release_button(&mut self, button: MouseButton)
+    {
+        if self.buttons_held.remove(&button) { self.buttons_released.insert(button); }
+    }
+
+    pub fn press_gamepad_button(&mut self, button: EGamepadButton)
+    {
+        if self.pad_buttons_held.insert(button) { self.pad_buttons_pressed.insert(button); }
+    }
+
+    pub fn release_gamepad_button(&mut self, button: EGamepadButton)
+    {
+        if self.pad_buttons_held.remove(&button) { self.pad_buttons_released.insert(button); }
+    }
+
+    /// ### fn GkInput::set_gamepad_axis( &mut self, ... )
+    /// *Sets an axis position, clamped to [-1, 1]. Unlike deltas it stays until set again.*
+    pub fn set_gamepad_axis(&mut self, axis: EGamepadAxis, value: f32)
+    {
+        self.pad_axes.insert(axis, value.clamp(-1.0, 1.0));
+    }
+
+    /// ### fn GkInput::move_cursor( &mut self, ... )
+    /// *Moves the cursor to x, y. The first position after entering the window adds no delta.*
+    pub fn move_cursor(&mut self, x: f64, y: f64)
+    {
+        if let Some((last_x, last_y)) = self.cursor_position
+        {
+            self.cursor_delta.0 += x - last_x;
+            self.cursor_delta.1 += y - last_y;
+        }
+        self.cursor_position = Some((x, y));
+    }
+
+    pub fn add_mouse_motion(&mut self, dx: f64, dy: f64)
+    {
+        self.mouse_delta.0 += dx;
+        self.mouse_delta.1 += dy;
+    }
+
+    pub fn scroll(&mut self, x: f32, y: f32)
+    {
+        self.scroll_delta.0 += x;
+        self.scroll_delta.1 += y;
+    }
+
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState)
+    {
+        self.modifiers = modifiers;
+    }
+
+    /// ### fn GkInput::release_all( &mut self )
+    /// *Releases every held key and button, reporting them as released this frame.*
+    pub fn release_all(&mut self)
+    {
+        self.keys_released.extend(self.keys_held.drain());
+        self.buttons_released.extend(self.buttons_held.drain());
+        self.modifiers = ModifiersState::empty();
+    }
+
+    pub fn is_key_held(&self, code: KeyCode) -> bool
+    {
+        self.keys_held.contains(&code)
+    }
+
+    pub fn was_key_pressed(&self, code: KeyCode) -> bool
+    {
+        self.keys_pressed.contains(&code)
+    }
+
+    pub fn was_key_released(&self, code: KeyCode) -> bool
+    {
+        self.keys_released.contains(&code)
+    }
+
+    pub fn is_button_held(&self, button: MouseButton) -> bool
+    {
+        self.buttons_held.contains(&button)
+    }
+
+    pub fn was_button_pressed(&self, button: MouseButton) -> bool
+    {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn was_button_released(&self, button: MouseButton) -> bool
+    {
+        self.buttons_released.contains(&button)
+    }
+
+    pub fn is_gamepad_button_held(&self, button: EGamepadButton) -> bool
+    {
+        self.pad_buttons_held.contains(&button)
+    }
+
+    pub fn was_gamepad_button_pressed(&self, button: EGamepadButton) -> bool
+    {
+        self.pad_buttons_pressed.contains(&button)
+    }
+
+    pub fn was_gamepad_button_released(&self, button: EGamepadButton) -> bool
+    {
+        self.pad_buttons_released.contains(&button)
+    }
+
+    /// ### fn GkInput::gamepad_axis( &self, ... ) -> f32
+    /// *Raw axis position, 0 if no pad reported it. Dead zones are left to the reader, see GkBinding.*
+    pub fn gamepad_axis(&self, axis: EGamepadAxis) -> f32
+    {
+        self.pad_axes.get(&axis).copied().unwrap_or(0.0)
+    }
+
+    pub fn keys_held(&self) -> impl Iterator<Item = &KeyCode>
+    {
+        self.keys_held.iter()
+    }
+
+    pub fn buttons_held(&self) -> impl Iterator<Item = &MouseButton>
+    {
+        self.buttons_held.iter()
+    }
+
+    pub fn keys_pressed(&self) -> impl Iterator<Item = &KeyCode>
+    {
+        self.keys_pressed.iter()
+    }
+
+    pub fn buttons_pressed(&self) -> impl Iterator<Item = &MouseButton>
+    {
+        self.buttons_pressed.iter()
+    }
+
+    pub fn gamepad_buttons_pressed(&self) -> impl Iterator<Item = &EGamepadButton>
+    {
+        self.pad_buttons_pressed.iter()
+    }
+
+    pub fn cursor_position(&self) -> Option<(f64, f64)>
+    {
+        self.cursor_position
+    }
+
+    pub fn cursor_delta(&self) -> (f64, f64)
+    {
+        self.cursor_delta
+    }
+
+    pub fn mouse_delta(&self) -> (f64, f64)
+    {
+        self.mouse_delta
+    }
+
+    pub fn scroll_delta(&self) -> (f32, f32)
+    {
+        self.scroll_delta
+    }
+
+    pub fn modifiers(&self) -> ModifiersState
+    {
+        self.modifiers
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use winit::event::{ DeviceEvent, MouseButton };
+    use winit::keyboard::KeyCode;
+
+    use super::GkInput;
+
+    #[test]
+    fn keys_report_pressed_held_and_released_per_frame()
+    {
+        let mut input = GkInput::new();
+        input.press_key(KeyCode::KeyW);
+        assert!(input.was_key_pressed(KeyCode::KeyW));
+        assert!(input.is_key_held(KeyCode::KeyW));
+
+        input.end_frame();
+        input.press_key(KeyCode::KeyW);
+        assert!(!input.was_key_pressed(KeyCode::KeyW));
+        assert!(input.is_key_held(KeyCode::KeyW));
+
+        input.release_key(KeyCode::KeyW);
+        assert!(input.was_key_released(KeyCode::KeyW));
+        assert!(!input.is_key_held(KeyCode::KeyW));
+
+        input.end_frame();
+        assert!(!input.was_key_released(KeyCode::KeyW));
+    }
+
+    #[test]
+    fn tap_within_a_frame_is_pressed_and_released()
+    {
+        let mut input = GkInput::new();
+        input.press_button(MouseButton::Left);
+        input.release_button(MouseButton::Left);
+
+        assert!(input.was_button_pressed(MouseButton::Left));
+        assert!(input.was_button_released(MouseButton::Left));
+        assert!(!input.is_button_held(MouseButton::Left));
+    }
+
+    #[test]
+    fn motion_and_scroll_accumulate_until_end_frame()
+    {
+        let mut input = GkInput::new();
+        input.move_cursor(10.0, 10.0);
+        input.move_cursor(14.0, 7.0);
+        input.move_cursor(16.0, 8.0);
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (3.0, -1.0) });
+        input.handle_device_event(&DeviceEvent::MouseMotion { delta: (2.0, -1.0) });
+        input.scroll(0.0, 1.0);
+        input.scroll(0.0, 0.5);
+
+        assert_eq!(input.cursor_position(), Some((16.0, 8.0)));
+        assert_eq!(input.cursor_delta(), (6.0, -2.0));
+        assert_eq!(input.mouse_delta(), (5.0, -2.0));
+        assert_eq!(input.scroll_delta(), (0.0, 1.5));
+
+        input.end_frame();
+        assert_eq!(input.cursor_delta(), (0.0, 0.0));
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+        assert_eq!(input.scroll_delta(), (0.0, 0.0));
+        assert_eq!(input.cursor_position(), Some((16.0, 8.0)));
+    }
+
+    #[test]
+    fn release_all_lets_go_of_everything()
+    {
+        let mut input = GkInput::new();
+        input.press_key(KeyCode::ShiftLeft);
+        input.press_button(MouseButton::Right);
+        input.end_frame();
+
+        input.release_all();
+        assert!(input.was_key_released(KeyCode::ShiftLeft));
+        assert!(input.was_button_released(MouseButton::Right));
+        assert_eq!(input.keys_held().count(), 0);
+    }
+}
@@ -0,0 +1,588 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::time::Duration;
+
+use winit::event::MouseButton;
+use winit::keyboard::ModifiersState;
+
+use crate::core::gk_error::{ GkError, GkResult };
+use crate::platform::gamepad::{ EGamepadEvent, GkGamepads };
+use crate::platform::input::{ EGamepadAxis, EGamepadButton, EInputEvent, GkInput, GK_KEY_CODES };
+
+/// Start of every recording file, followed by the format version.
+const GK_RECORDING_MAGIC: &[u8; 4] = b"GKIR";
+const GK_RECORDING_VERSION: u8 = 1;
+
+/// ### ERecordedEvent enum
+/// *A window or device event main_loop consumed.*
+#[derive(Clone, Debug, PartialEq)]
+pub enum ERecordedEvent
+{
+    Input(EInputEvent),
+    Resized { width: u32, height: u32 },
+}
+
+/// ### GkRecordedFrame struct
+/// *Everything one frame consumed, in the order main_loop consumed it.*
+/// <pre>
+/// - Members
+///     events:         Vec&lt;ERecordedEvent&gt;    <i>// Window and device events since the previous frame.</i>
+///     gamepad_events: Vec&lt;EGamepadEvent&gt;     <i>// The frame's gamepad poll, with global ids.</i>
+///     real_delta:     Duration                <i>// Wall clock time of the frame.</i>
+/// </pre>
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GkRecordedFrame
+{
+    pub events:         Vec<ERecordedEvent>,
+    pub gamepad_events: Vec<EGamepadEvent>,
+    pub real_delta:     Duration,
+}
+
+/// ### GkInputRecording struct
+/// *A recorded session, frame by frame. Saved as a compact binary file.*
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GkInputRecording
+{
+    pub frames: Vec<GkRecordedFrame>,
+}
+
+impl GkInputRecording
+{
+    /// ### fn GkInputRecording::encode( &self ) -> Vec\<u8\>
+    /// *The recording in its file format. Integers are LEB128 varints, floats little endian.*
+    pub fn encode(&self) -> Vec<u8>
+    {
+        let mut writer = GkByteWriter::default();
+        writer.bytes.extend_from_slice(GK_RECORDING_MAGIC);
+        writer.u8(GK_RECORDING_VERSION);
+        for frame in &self.frames
+        {
+            writer.varint(frame.real_delta.as_nanos() as u64);
+            writer.varint(frame.events.len() as u64);
+            for event in &frame.events
+            {
+                writer.event(event);
+            }
+            writer.varint(frame.gamepad_events.len() as u64);
+            for event in &frame.gamepad_events
+            {
+                writer.gamepad_event(event);
+            }
+        }
+        writer.bytes
+    }
+
+    /// ### fn GkInputRecording::decode( ... ) -> GkResult\<GkInputRecording\>
+    /// *Reads a recording written by encode().*
+    pub fn decode(bytes: &[u8]) -> GkResult<Self>
+    {
+        let mut reader = GkByteReader { bytes, position: 0 };
+        if reader.take(4)? != GK_RECORDING_MAGIC { return Err(recording_error("not an input recording")); }
+        let version = reader.u8()?;
+        if version != GK_RECORDING_VERSION
+        {
+            return Err(recording_error(&format!("unsupported version {}", version)));
+        }
+
+        let mut recording = Self::default();
+        while !reader.is_empty()
+        {
+            let real_delta = Duration::from_nanos(reader.varint()?);
+            let events = (0..reader.varint()?).map(|_| reader.event()).collect::<GkResult<Vec<_>>>()?;
+            let gamepad_events = (0..reader.varint()?).map(|_| reader.gamepad_event()).collect::<GkResult<Vec<_>>>()?;
+            recording.frames.push(GkRecordedFrame { events, gamepad_events, real_delta });
+        }
+        Ok(recording)
+    }
+
+    pub fn save(&self, path: &Path) -> GkResult<()>
+    {
+        std::fs::write(path, self.encode()).map_err(|e| GkError::io(path, e))
+    }
+
+    pub fn load(path: &Path) -> GkResult<Self>
+    {
+        let bytes = std::fs::read(path).map_err(|e| GkError::io(path, e))?;
+        Self::decode(&bytes).map_err(|e| match e
+        {
+            GkError::Asset(msg) => GkError::Asset(format!("{}: {}", path.display(), msg)),
+            e => e,
+        })
+    }
+
+    /// ### fn GkInputRecording::duration( &self ) -> Duration
+    /// *Wall clock time the recorded frames took.*
+    pub fn duration(&self) -> Duration
+    {
+        self.frames.iter().map(|frame| frame.real_delta).sum()
+    }
+}
+
+/// ### GkInputRecorder struct
+/// *Records what main_loop consumes into a GkInputRecording.*
+/// <pre>
+/// - Members
+///     recording:  GkInputRecording
+///     current:    GkRecordedFrame     <i>// Events of the frame in progress.</i>
+/// </pre>
+pub struct GkInputRecorder
+{
+    recording:  GkInputRecording,
+    current:    GkRecordedFrame,
+}
+
+impl GkInputRecorder
+{
+    /// ### fn GkInputRecorder::new( ... ) -> GkInputRecorder
+    /// *Starts recording. Keys, buttons and pads already held or connected are recorded as pressed and connected
+    /// so that a replay starts from the same state.*
+    /// <pre>
+    /// - Params
+    ///     input:      &GkInput
+    ///     gamepads:   &GkGamepads
+    /// - Return
+    ///     GkInputRecorder
+    /// </pre>
+    pub fn new(input: &GkInput, gamepads: &GkGamepads) -> Self
+    {
+        let mut current = GkRecordedFrame::default();
+        let events = &mut current.events;
+        events.push(ERecordedEvent::Input(EInputEvent::Modifiers(input.modifiers())));
+        if let Some((x, y)) = input.cursor_position()
+        {
+            events.push(ERecordedEvent::Input(EInputEvent::CursorMoved { x, y }));
+        }
+        events.extend(input.keys_held().map(|code| ERecordedEvent::Input(EInputEvent::KeyPressed(*code))));
+        events.extend(input.buttons_held().map(|button| ERecordedEvent::Input(EInputEvent::ButtonPressed(*button))));
+
+        for (id, state) in gamepads.connected()
+        {
+            current.gamepad_events.push(EGamepadEvent::Connected { id, name: state.name.clone() });
+            current.gamepad_events.extend(state.buttons_held().map(|button| EGamepadEvent::Button { id, button: *button, b_pressed: true }));
+            current.gamepad_events.extend(state.axes().map(|(axis, value)| EGamepadEvent::Axis { id, axis, value }));
+        }
+        Self { recording: GkInputRecording::default(), current }
+    }
+
+    pub fn record(&mut self, event: ERecordedEvent)
+    {
+        self.current.events.push(event);
+    }
+
+    pub fn record_gamepad(&mut self, events: &[EGamepadEvent])
+    {
+        self.current.gamepad_events.extend_from_slice(events);
+    }
+
+    /// ### fn GkInputRecorder::end_frame( &mut self, ... )
+    /// *Closes the frame in progress. Events recorded after this belong to the next frame.*
+    pub fn end_frame(&mut self, real_delta: Duration)
+    {
+        let mut frame = std::mem::take(&mut self.current);
+        frame.real_delta = real_delta;
+        self.recording.frames.push(frame);
+    }
+
+    pub fn frame_count(&self) -> usize
+    {
+        self.recording.frames.len()
+    }
+
+    /// ### fn GkInputRecorder::finish( self ) -> GkInputRecording
+    /// *The recorded frames. Events of an unfinished frame were never consumed and are dropped.*
+    pub fn finish(self) -> GkInputRecording
+    {
+        self.recording
+    }
+}
+
+/// ### GkInputReplay struct
+/// *Hands a recording's frames back out, one per frame, in place of live input.*
+pub struct GkInputReplay
+{
+    frames:     VecDeque<GkRecordedFrame>,
+    total:      usize,
+}
+
+impl GkInputReplay
+{
+    pub fn new(recording: GkInputRecording) -> Self
+    {
+        let total = recording.frames.len();
+        Self { frames: recording.frames.into(), total }
+    }
+
+    /// ### fn GkInputReplay::next_frame( &mut self ) -> Option\<GkRecordedFrame\>
+    /// *The next frame to play, None once the recording is over.*
+    pub fn next_frame(&mut self) -> Option<GkRecordedFrame>
+    {
+        self.frames.pop_front()
+    }
+
+    pub fn is_finished(&self) -> bool
+    {
+        self.frames.is_empty()
+    }
+
+    /// ### fn GkInputReplay::progress( &self ) -> (usize, usize)
+    /// *Frames played so far and frames in total.*
+    pub fn progress(&self) -> (usize, usize)
+    {
+        (self.total - self.frames.len(), self.total)
+    }
+}
+
+fn recording_error(msg: &str) -> GkError
+{
+    GkError::Asset(format!("input recording: {}", msg))
+}
+
+#[derive(Default)]
+struct GkByteWriter
+{
+    bytes: Vec<u8>,
+}
+
+impl GkByteWriter
+{
+    fn u8(&mut self, value: u8)
+    {
+        self.bytes.push(value);
+    }
+
+    fn varint(&mut self, mut value: u64)
+    {
+        while value >= 0x80
+        {
+            self.bytes.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.bytes.push(value as u8);
+    }
+
+    fn f32(&mut self, value: f32)
+    {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64)
+    {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn mouse_button(&mut self, button: MouseButton)
+    {
+        match button
+        {
+            MouseButton::Left => self.u8(0),
+            MouseButton::Right => self.u8(1),
+            MouseButton::Middle => self.u8(2),
+            MouseButton::Back => self.u8(3),
+            MouseButton::Forward => self.u8(4),
+            MouseButton::Other(index) =>
+            {
+                self.u8(5);
+                self.varint(index as u64);
+            },
+        }
+    }
+
+    fn event(&mut self, event: &ERecordedEvent)
+    {
+        let key_index = |code| GK_KEY_CODES.iter().position(|known| *known == code).unwrap_or(0) as u8;
+        match *event
+        {
+            ERecordedEvent::Input(EInputEvent::KeyPressed(code)) => { self.u8(0); self.u8(key_index(code)); },
+            ERecordedEvent::Input(EInputEvent::KeyReleased(code)) => { self.u8(1); self.u8(key_index(code)); },
+            ERecordedEvent::Input(EInputEvent::ButtonPressed(button)) => { self.u8(2); self.mouse_button(button); },
+            ERecordedEvent::Input(EInputEvent::ButtonReleased(button)) => { self.u8(3); self.mouse_button(button); },
+            ERecordedEvent::Input(EInputEvent::CursorMoved { x, y }) => { self.u8(4); self.f64(x); self.f64(y); },
+            ERecordedEvent::Input(EInputEvent::CursorLeft) => self.u8(5),
+            ERecordedEvent::Input(EInputEvent::MouseMotion { dx, dy }) => { self.u8(6); self.f64(dx); self.f64(dy); },
+            ERecordedEvent::Input(EInputEvent::Scroll { x, y }) => { self.u8(7); self.f32(x); self.f32(y); },
+            ERecordedEvent::Input(EInputEvent::Modifiers(modifiers)) => { self.u8(8); self.varint(modifiers.bits() as u64); },
+            ERecordedEvent::Input(EInputEvent::FocusLost) => self.u8(9),
+            ERecordedEvent::Resized { width, height } => { self.u8(10); self.varint(width as u64); self.varint(height as u64); },
+        }
+    }
+
+    fn gamepad_event(&mut self, event: &EGamepadEvent)
+    {
+        match event
+        {
+            EGamepadEvent::Connected { id, name } =>
+            {
+                self.u8(0);
+                self.varint(*id as u64);
+                self.varint(name.len() as u64);
+                self.bytes.extend_from_slice(name.as_bytes());
+            },
+            EGamepadEvent::Disconnected { id } => { self.u8(1); self.varint(*id as u64); },
+            EGamepadEvent::Button { id, button, b_pressed } =>
+            {
+                self.u8(if *b_pressed { 2 } else { 3 });
+                self.varint(*id as u64);
+                self.u8(EGamepadButton::ALL.iter().position(|known| known == button).unwrap_or(0) as u8);
+            },
+            EGamepadEvent::Axis { id, axis, value } =>
+            {
+                self.u8(4);
+                self.varint(*id as u64);
+                self.u8(EGamepadAxis::ALL.iter().position(|known| known == axis).unwrap_or(0) as u8);
+                self.f32(*value);
+            },
+        }
+    }
+}
+
+struct GkByteReader<'a>
+{
+    bytes:      &'a [u8],
+    position:   usize,
+}
+
+impl<'a> GkByteReader<'a>
+{
+    fn is_empty(&self) -> bool
+    {
+        self.position >= self.bytes.len()
+    }
+
+    fn take(&mut self, count: usize) -> GkResult<&'a [u8]>
+    {
+        let end = self.position.checked_add(count).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| recording_error("truncated"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> GkResult<u8>
+    {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> GkResult<u64>
+    {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7)
+        {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 { return Ok(value); }
+        }
+        Err(recording_error("varint too long"))
+    }
+
+    fn u32(&mut self) -> GkResult<u32>
+    {
+        u32::try_from(self.varint()?).map_err(|_| recording_error("value out of range"))
+    }
+
+    fn f32(&mut self) -> GkResult<f32>
+    {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f64(&mut self) -> GkResult<f64>
+    {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn indexed<T: Copy>(&mut self, table: &[T], what: &str) -> GkResult<T>
+    {
+        let index = self.u8()? as usize;
+        table.get(index).copied().ok_or_else(|| recording_error(&format!("unknown {} {}", what, index)))
+    }
+
+    fn mouse_button(&mut self) -> GkResult<MouseButton>
+    {
+        let button = match self.u8()?
+        {
+            0 => MouseButton::Left,
+            1 => MouseButton::Right,
+            2 => MouseButton::Middle,
+            3 => MouseButton::Back,
+            4 => MouseButton::Forward,
+            5 => MouseButton::Other(u16::try_from(self.varint()?).map_err(|_| recording_error("value out of range"))?),
+            tag => return Err(recording_error(&format!("unknown mouse button tag {}", tag))),
+        };
+        Ok(button)
+    }
+
+    fn event(&mut self) -> GkResult<ERecordedEvent>
+    {
+        let input = |event| Ok(ERecordedEvent::Input(event));
+        match self.u8()?
+        {
+            0 => input(EInputEvent::KeyPressed(self.indexed(&GK_KEY_CODES, "key")?)),
+            1 => input(EInputEvent::KeyReleased(self.indexed(&GK_KEY_CODES, "key")?)),
+            2 => input(EInputEvent::ButtonPressed(self.mouse_button()?)),
+            3 => input(EInputEvent::ButtonReleased(self.mouse_button()?)),
+            4 => input(EInputEvent::CursorMoved { x: self.f64()?, y: self.f64()? }),
+            5 => input(EInputEvent::CursorLeft),
+            6 => input(EInputEvent::MouseMotion { dx: self.f64()?, dy: self.f64()? }),
+            7 => input(EInputEvent::Scroll { x: self.f32()?, y: self.f32()? }),
+            8 => input(EInputEvent::Modifiers(ModifiersState::from_bits_truncate(self.u32()?))),
+            9 => input(EInputEvent::FocusLost),
+            10 => Ok(ERecordedEvent::Resized { width: self.u32()?, height: self.u32()? }),
+            tag => Err(recording_error(&format!("unknown event tag {}", tag))),
+        }
+    }
+
+    fn gamepad_event(&mut self) -> GkResult<EGamepadEvent>
+    {
+        let tag = self.u8()?;
+        let id = self.u32()?;
+        let event = match tag
+        {
+            0 =>
+            {
+                let length = self.varint()? as usize;
+                let name = String::from_utf8_lossy(self.take(length)?).into_owned();
+                EGamepadEvent::Connected { id, name }
+            },
+            1 => EGamepadEvent::Disconnected { id },
+            2 | 3 => EGamepadEvent::Button { id, button: self.indexed(&EGamepadButton::ALL, "gamepad button")?, b_pressed: tag == 2 },
+            4 => EGamepadEvent::Axis { id, axis: self.indexed(&EGamepadAxis::ALL, "gamepad axis")?, value: self.f32()? },
+            _ => return Err(recording_error(&format!("unknown gamepad event tag {}", tag))),
+        };
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use winit::event::MouseButton;
+    use winit::keyboard::{ KeyCode, ModifiersState };
+
+    use crate::core::time::GkTime;
+    use crate::core::frame_scheduler::GkFixedTimestep;
+    use crate::platform::action_map::{ EActionAxis, EInputSource, GkActionMap, GkActionState, GkBinding };
+    use crate::platform::gamepad::{ EGamepadEvent, GkGamepads };
+    use crate::platform::gamepad::mock::GkMockGamepads;
+    use crate::platform::input::{ EGamepadAxis, EGamepadButton, EInputEvent, GkInput };
+
+    use super::{ ERecordedEvent, GkInputRecorder, GkInputRecording, GkInputReplay, GkRecordedFrame };
+
+    #[test]
+    fn recordings_round_trip_through_bytes()
+    {
+        let recording = GkInputRecording
+        {
+            frames: vec![
+                GkRecordedFrame
+                {
+                    events: vec![
+                        ERecordedEvent::Input(EInputEvent::KeyPressed(KeyCode::F35)),
+                        ERecordedEvent::Input(EInputEvent::ButtonReleased(MouseButton::Other(300))),
+                        ERecordedEvent::Input(EInputEvent::CursorMoved { x: 10.25, y: -3.5 }),
+                        ERecordedEvent::Input(EInputEvent::Modifiers(ModifiersState::SHIFT | ModifiersState::SUPER)),
+                        ERecordedEvent::Input(EInputEvent::Scroll { x: 0.0, y: -1.5 }),
+                        ERecordedEvent::Resized { width: 1920, height: 1080 },
+                    ],
+                    gamepad_events: vec![
+                        EGamepadEvent::Connected { id: 4, name: String::from("Pad ✓") },
+                        EGamepadEvent::Button { id: 4, button: EGamepadButton::DPadRight, b_pressed: false },
+                        EGamepadEvent::Axis { id: 4, axis: EGamepadAxis::RightTrigger, value: 0.125 },
+                    ],
+                    real_delta: Duration::from_nanos(16_666_667),
+                },
+                GkRecordedFrame { events: vec![ERecordedEvent::Input(EInputEvent::FocusLost)], gamepad_events: Vec::new(), real_delta: Duration::ZERO },
+            ],
+        };
+        let bytes = recording.encode();
+        assert_eq!(GkInputRecording::decode(&bytes).unwrap(), recording);
+
+        assert!(GkInputRecording::decode(&bytes[..bytes.len() - 3]).is_err());
+        assert!(GkInputRecording::decode(b"GKIR\x07").is_err());
+        assert!(GkInputRecording::decode(b"nope").is_err());
+
+        let path = std::env::temp_dir().join(format!("gunk_input_recording_{}.gkir", std::process::id()));
+        std::fs::write(&path, &bytes[..bytes.len() - 3]).unwrap();
+        let error = GkInputRecording::load(&path).unwrap_err();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(error.to_string(), format!("asset error: {}: input recording: truncated", path.display()));
+    }
+
+    /// Runs frames like main_loop and returns what each frame's "Move" and "Jump" actions read, plus the ticks run.
+    fn simulate(frames: &[GkRecordedFrame], input: &mut GkInput, gamepads: &mut GkGamepads) -> Vec<(GkActionState, GkActionState, u32)>
+    {
+        let mut actions = GkActionMap::new();
+        actions.bind("Move", GkBinding::key(KeyCode::KeyD))
+            .bind("Move", GkBinding::new(EInputSource::GamepadAxis(EGamepadAxis::LeftStickY)).on_axis(EActionAxis::Y));
+        actions.bind("Jump", GkBinding::key(KeyCode::Space).with_modifiers(ModifiersState::SHIFT));
+        let mut time = GkTime::new();
+        let mut fixed = GkFixedTimestep::new(60.0, 8);
+
+        let mut states = Vec::new();
+        for frame in frames
+        {
+            for event in &frame.events
+            {
+                if let ERecordedEvent::Input(input_event) = event { input.apply(input_event); }
+            }
+            gamepads.apply_replayed(&frame.gamepad_events, input);
+            let ticks = fixed.advance(time.advance(frame.real_delta));
+            actions.update(input);
+            states.push((actions.state("Move"), actions.state("Jump"), ticks));
+            input.end_frame();
+        }
+        states
+    }
+
+    #[test]
+    fn replay_reproduces_the_recorded_session()
+    {
+        let mock = GkMockGamepads::new();
+        let mut gamepads = GkGamepads::new();
+        gamepads.add_backend(Box::new(mock.clone()));
+        let mut input = GkInput::new();
+
+        // The pad and the held key exist before recording starts.
+        let pad = mock.connect("Pad");
+        gamepads.poll(&mut input);
+        input.press_key(KeyCode::KeyD);
+        input.end_frame();
+
+        let mut recorder = GkInputRecorder::new(&input, &gamepads);
+        let script: [(&[EInputEvent], Option<f32>, u64); 4] = [
+            (&[EInputEvent::Modifiers(ModifiersState::SHIFT)], Some(0.5), 16),
+            (&[EInputEvent::KeyPressed(KeyCode::Space)], None, 40),
+            (&[EInputEvent::KeyReleased(KeyCode::Space), EInputEvent::KeyReleased(KeyCode::KeyD)], Some(-1.0), 7),
+            (&[], Some(0.0), 17),
+        ];
+        for (events, axis, frame_ms) in script
+        {
+            for event in events
+            {
+                input.apply(event);
+                recorder.record(ERecordedEvent::Input(*event));
+            }
+            if let Some(value) = axis { mock.set_axis(pad, EGamepadAxis::LeftStickY, value); }
+            recorder.record_gamepad(gamepads.poll(&mut input));
+            recorder.end_frame(Duration::from_millis(frame_ms));
+            input.end_frame();
+        }
+        let recording = GkInputRecording::decode(&recorder.finish().encode()).unwrap();
+
+        let mut replay = GkInputReplay::new(recording.clone());
+        let mut frames = Vec::new();
+        while let Some(frame) = replay.next_frame() { frames.push(frame); }
+        assert_eq!(replay.progress(), (4, 4));
+
+        let first = simulate(&frames, &mut GkInput::new(), &mut GkGamepads::new());
+        let second = simulate(&recording.frames, &mut GkInput::new(), &mut GkGamepads::new());
+        assert_eq!(first, second);
+
+        let moves: Vec<(f32, f32)> = first.iter().map(|(movement, _, _)| movement.value).collect();
+        assert_eq!(moves, vec![(1.0, 0.5), (1.0, 0.5), (0.0, -1.0), (0.0, 0.0)]);
+        let jumps: Vec<bool> = first.iter().map(|(_, jump, _)| jump.b_pressed).collect();
+        assert_eq!(jumps, vec![false, true, false, false]);
+        let ticks: Vec<u32> = first.iter().map(|(_, _, ticks)| *ticks).collect();
+        assert_eq!(ticks, vec![0, 3, 0, 1]);
+    }
+}
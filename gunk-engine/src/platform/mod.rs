@@ -1,16 +1,24 @@
 #![allow(unused_variables)]
 
 pub mod event_types;
+pub mod event_bus;
+pub mod input;
+pub mod action_map;
+pub mod gamepad;
+pub mod input_recording;
 
 use winit::{
+    dpi::PhysicalSize,
     event::{ DeviceEvent, DeviceId, WindowEvent, Event },
     event_loop::{ EventLoop, ControlFlow, EventLoopWindowTarget }, window::WindowId, 
 };
 
-use crate::core::{ 
-    application::Application, 
-    fps_limiter::FPSLimiter
-};
+use crate::{ log_err, log_info, profile_frame, profile_scope };
+use crate::core::application::Application;
+use crate::core::frame_scheduler::GkFrameTiming;
+use crate::platform::event_types::{ EFileDrop, GkWindowFocused, GkWindowResized };
+use crate::platform::input::{ EInputEvent, GkInput };
+use crate::platform::input_recording::ERecordedEvent;
 
 /// ### fn main_loop( ... )
 /// *The main device loop*
@@ -22,8 +30,6 @@ use crate::core::{
 /// </pre>
 pub fn main_loop(mut app: Application, evloop: EventLoop<()>)
 {
-    let mut tick_counter = FPSLimiter::new();
-
     evloop.set_control_flow(ControlFlow::Poll);
     evloop.run(move | events, elwt|
     {
@@ -31,7 +37,7 @@ pub fn main_loop(mut app: Application, evloop: EventLoop<()>)
         {
             Event::WindowEvent { window_id, event } =>
             {
-                handle_window_events(&mut app, event, window_id, &elwt, &mut tick_counter);
+                handle_window_events(&mut app, event, window_id, elwt);
             },
             Event::DeviceEvent { device_id, event } =>
             {
@@ -65,8 +71,12 @@ pub fn main_loop(mut app: Application, evloop: EventLoop<()>)
 
 /// ### fn handle_window_events( ... )
 /// *Window events go here*
-pub fn handle_window_events(app: &mut Application, events: WindowEvent, _window_id: WindowId, elwt: &EventLoopWindowTarget<()>, tick_counter: &mut FPSLimiter)
+pub fn handle_window_events(app: &mut Application, events: WindowEvent, _window_id: WindowId, elwt: &EventLoopWindowTarget<()>)
 {
+    if let Some(input_event) = GkInput::translate_window_event(&events)
+    {
+        consume_input(app, input_event);
+    }
     match events
     {
         WindowEvent::CloseRequested =>
@@ -75,20 +85,49 @@ pub fn handle_window_events(app: &mut Application, events: WindowEvent, _window_
         }
         WindowEvent::Resized(size) => 
         {
-            if size.width == 0 || size.height == 0
+            if let Some(recorder) = app.recorder.as_mut()
             {
-                app.minimized = true;
+                recorder.record(ERecordedEvent::Resized { width: size.width, height: size.height });
             }
+            // Restoring the window reports a non zero size again.
+            app.minimized = size.width == 0 || size.height == 0;
             app.resized();
+            app.events.publish(GkWindowResized { width: size.width, height: size.height });
         },
+        WindowEvent::Focused(b_focused) => app.events.publish(GkWindowFocused { b_focused }),
+        WindowEvent::HoveredFile(path) => app.events.publish(EFileDrop::Hovered(path)),
+        WindowEvent::HoveredFileCancelled => app.events.publish(EFileDrop::HoverCancelled),
+        WindowEvent::DroppedFile(path) => app.events.publish(EFileDrop::Dropped(path)),
         WindowEvent::RedrawRequested => 
         {
             if !app.minimized
             {
-                let delta_time = tick_counter.delta_time();
-                app.renderer.render(&app.window, delta_time);
-                tick_counter.tick_frame();
+                profile_scope!("main_loop frame");
+                let Some(frame) = begin_frame(app) else
+                {
+                    elwt.exit();
+                    return;
+                };
+                app.events.dispatch_queued();
+                app.actions.update(&app.input);
+                for _ in 0..frame.ticks
+                {
+                    app.fixed_update(frame.step);
+                }
+                app.update_camera(frame.delta);
+                app.sync_scene(frame.alpha);
+                if let Err(e) = app.renderer.render(&app.window, frame.delta)
+                {
+                    log_err!(format!("Renderer failed, shutting down: {}", e));
+                    elwt.exit();
+                    return;
+                }
+                app.scheduler.end_frame(app.renderer.is_present_paced());
+                // Only frames that began clear input edges. Events consumed while minimized stay
+                // in the recorder's open frame, so the replay sees them in the same frame as the live run.
+                app.input.end_frame();
             }
+            profile_frame!();
         },
         _ => {}
     }
@@ -98,5 +137,70 @@ pub fn handle_window_events(app: &mut Application, events: WindowEvent, _window_
 /// *Device events go here*
 pub fn handle_device_events(app: &mut Application, events: DeviceEvent, device_id: DeviceId, elwt: &EventLoopWindowTarget<()>)
 {
+    if let Some(input_event) = GkInput::translate_device_event(&events)
+    {
+        consume_input(app, input_event);
+    }
+}
+
+/// ### fn consume_input( ... )
+/// *Applies live input and records it. Ignored while a replay drives the application.*
+fn consume_input(app: &mut Application, input_event: EInputEvent)
+{
+    if app.replay.is_some() { return; }
+    if let Some(recorder) = app.recorder.as_mut()
+    {
+        recorder.record(ERecordedEvent::Input(input_event));
+    }
+    apply_input(app, input_event);
+}
+
+/// ### fn apply_input( ... )
+/// *Updates GkInput, then publishes the event so subscribers see the updated state.*
+fn apply_input(app: &mut Application, input_event: EInputEvent)
+{
+    app.input.apply(&input_event);
+    app.events.publish(input_event);
+}
 
-}
\ No newline at end of file
+/// ### fn begin_frame( ... ) -> Option\<GkFrameTiming\>
+/// *Polls the gamepads and starts the frame's timing, or feeds in the next recorded frame while replaying.*
+/// <pre>
+/// - Params
+///     app:    &mut Application
+/// - Return
+///     Option&lt;GkFrameTiming&gt;     <i>// None once a replay is over, which ends the application.</i>
+/// </pre>
+fn begin_frame(app: &mut Application) -> Option<GkFrameTiming>
+{
+    let Some(replay) = app.replay.as_mut() else
+    {
+        let frame = app.scheduler.begin_frame(&mut app.time);
+        let gamepad_events = app.gamepads.poll(&mut app.input);
+        if let Some(recorder) = app.recorder.as_mut()
+        {
+            recorder.record_gamepad(gamepad_events);
+            recorder.end_frame(app.scheduler.limiter.delta());
+        }
+        return Some(frame);
+    };
+
+    let Some(recorded) = replay.next_frame() else
+    {
+        log_info!(format!("Input replay finished after {} frames", replay.progress().1));
+        return None;
+    };
+    for event in &recorded.events
+    {
+        match *event
+        {
+            ERecordedEvent::Input(input_event) => apply_input(app, input_event),
+            ERecordedEvent::Resized { width, height } =>
+            {
+                if width > 0 && height > 0 { let _ = app.window.request_inner_size(PhysicalSize::new(width, height)); }
+            },
+        }
+    }
+    app.gamepads.apply_replayed(&recorded.gamepad_events, &mut app.input);
+    Some(app.scheduler.begin_replayed_frame(&mut app.time, recorded.real_delta))
+}
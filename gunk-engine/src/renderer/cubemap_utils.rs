@@ -5,6 +5,7 @@ use image::DynamicImage;
 use nalgebra_glm as glm;
 
 use crate::log_err;
+use crate::core::gk_error::{ GkError, GkResult };
 
 use super::gk_bitmap::{
     GkBitMap, EBitMapType,
@@ -296,9 +297,9 @@ pub fn convert_equirectangle_to_cubemap_faces(bitmap: &GkBitMap) -> GkBitMap
     convert_vertical_cross_to_cubemap_faces(&convert_equirectangle_to_vertical_cross(bitmap))
 }
 
-pub fn convert_multi_file_to_cubemap_faces(files: &Vec<std::path::PathBuf>) -> Result<GkBitMap, String>
+pub fn convert_multi_file_to_cubemap_faces(files: &[std::path::PathBuf]) -> GkResult<GkBitMap>
 {
-    if files.len() != 6 { return Err(String::from("convert_multi_file_to_cubemap_faces() needs 6 file paths!")); }
+    if files.len() != 6 { return Err(GkError::Asset(String::from("convert_multi_file_to_cubemap_faces() needs 6 file paths!"))); }
 
     let mut img_data: Vec<u8> = Vec::new();
     let mut img_width: u32 = 0;
@@ -313,8 +314,8 @@ pub fn convert_multi_file_to_cubemap_faces(files: &Vec<std::path::PathBuf>) -> R
             Ok(i) => img = i,
             Err(e) => 
             {
-                log_err!(e.to_string());
-                return Err(e.to_string()); 
+                log_err!(format!("{}: {}", file.display(), e));
+                return Err(GkError::from(e)); 
             }
         }
 
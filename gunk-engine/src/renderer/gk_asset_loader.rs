@@ -0,0 +1,365 @@
+use std::path::PathBuf;
+use std::sync::{ Arc, Mutex, mpsc };
+use std::thread;
+
+use image::{ self, EncodableLayout };
+
+use crate::{ log_info, log_err, profile_fn, profile_scope };
+use crate::core::gk_error::{ GkError, GkResult };
+
+use super::cubemap_utils::{
+    convert_equirectangle_to_vertical_cross,
+    convert_multi_file_to_cubemap_faces,
+    convert_vertical_cross_to_cubemap_faces
+};
+use super::gk_bitmap::{ EBitMapFormat, GkBitMap };
+use super::vulkan_renderer::gk_vulkan::vertex_data::VertexData;
+
+/// ### GkMeshData struct
+/// *Decoded mesh ready to be uploaded to the gpu.*
+/// <pre>
+/// - Members
+///     vertices:   Vec&lt;VertexData&gt;
+///     indices:    Vec&lt;u32&gt;
+/// </pre>
+#[derive(Clone)]
+pub struct GkMeshData
+{
+    pub vertices:   Vec<VertexData>,
+    pub indices:    Vec<u32>,
+}
+
+/// ### EAssetRequest enum
+/// *Describes what a worker should load and decode.*
+#[derive(Clone, Debug)]
+pub enum EAssetRequest
+{
+    Texture2d(PathBuf),
+    Cubemap(Vec<PathBuf>),
+    Mesh(PathBuf),
+}
+
+/// ### EAssetData enum
+/// *Result of a decoded EAssetRequest.*
+pub enum EAssetData
+{
+    Texture2d(GkBitMap),
+    Cubemap(GkBitMap),
+    Mesh(GkMeshData),
+}
+
+/// ### GkAssetHandle struct
+/// *Identifies a request made to GkAssetLoader.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GkAssetHandle(pub u64);
+
+/// ### GkLoadedAsset struct
+/// *An asset handed back by the workers.*
+/// <pre>
+/// - Members
+///     handle:     GkAssetHandle
+///     data:       GkResult&lt;EAssetData&gt;
+/// </pre>
+pub struct GkLoadedAsset
+{
+    pub handle: GkAssetHandle,
+    pub data:   GkResult<EAssetData>,
+}
+
+type AssetJob = (GkAssetHandle, EAssetRequest);
+
+/// ### GkAssetLoader struct
+/// *Decodes assets on a pool of worker threads.*<br>
+/// *Requests return immediately with a handle, results are collected with poll().*
+/// <pre>
+/// - Members
+///     job_sender:     Option&lt;mpsc::Sender&lt;AssetJob&gt;&gt;
+///     result_receiver: mpsc::Receiver&lt;GkLoadedAsset&gt;
+///     workers:        Vec&lt;thread::JoinHandle&lt;()&gt;&gt;
+///     next_handle:    u64
+///     pending:        usize       <i>// Requests not yet returned by poll().</i>
+/// </pre>
+pub struct GkAssetLoader
+{
+    job_sender:         Option<mpsc::Sender<AssetJob>>,
+    result_receiver:    mpsc::Receiver<GkLoadedAsset>,
+    workers:            Vec<thread::JoinHandle<()>>,
+    next_handle:        u64,
+    pending:            usize,
+}
+
+impl GkAssetLoader
+{
+    /// ### fn GkAssetLoader::new( ... ) -> GkAssetLoader
+    /// *Spawns the worker threads.*
+    /// <pre>
+    /// - Params
+    ///     num_workers:    usize       <i>// Clamped to at least 1.</i>
+    /// - Return
+    ///     GkAssetLoader
+    /// </pre>
+    pub fn new(num_workers: usize) -> Self
+    {
+        let num_workers = num_workers.max(1);
+        log_info!(format!("Creating GkAssetLoader with {} workers...", num_workers));
+
+        let (job_sender, job_receiver) = mpsc::channel::<AssetJob>();
+        let (result_sender, result_receiver) = mpsc::channel::<GkLoadedAsset>();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        let mut workers = Vec::with_capacity(num_workers);
+        for i in 0..num_workers
+        {
+            let job_receiver = Arc::clone(&job_receiver);
+            let result_sender = result_sender.clone();
+            let worker = thread::Builder::new()
+                .name(format!("gk_asset_worker_{}", i))
+                .spawn(move || {
+                    loop
+                    {
+                        // The lock is released before decoding so other workers can pick up jobs.
+                        let job = job_receiver.lock().unwrap().recv();
+                        let (handle, request) = match job
+                        {
+                            Ok(job) => job,
+                            Err(_) => break // Loader was destroyed
+                        };
+                        let data = load_asset(&request);
+                        if result_sender.send(GkLoadedAsset { handle, data }).is_err() { break; }
+                    }
+                })
+                .map_err(|e| { log_err!(e); })
+                .unwrap();
+            workers.push(worker);
+        }
+
+        log_info!("GkAssetLoader created.");
+        Self
+        {
+            job_sender: Some(job_sender),
+            result_receiver,
+            workers,
+            next_handle: 0,
+            pending: 0
+        }
+    }
+
+    /// ### fn GkAssetLoader::request( &mut self, ... ) -> GkAssetHandle
+    /// *Queues an asset to be loaded on a worker thread.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     request:    EAssetRequest
+    /// - Return
+    ///     GkAssetHandle
+    /// </pre>
+    pub fn request(&mut self, request: EAssetRequest) -> GkAssetHandle
+    {
+        let handle = GkAssetHandle(self.next_handle);
+        self.next_handle += 1;
+        self.job_sender.as_ref().unwrap().send((handle, request)).map_err(|e| { log_err!(e); }).unwrap();
+        self.pending += 1;
+        handle
+    }
+
+    /// ### fn GkAssetLoader::poll( &mut self ) -> Vec\<GkLoadedAsset\>
+    /// *Returns every asset finished since the last call. Never blocks.*
+    pub fn poll(&mut self) -> Vec<GkLoadedAsset>
+    {
+        let loaded: Vec<GkLoadedAsset> = self.result_receiver.try_iter().collect();
+        self.pending -= loaded.len();
+        loaded
+    }
+
+    /// ### fn GkAssetLoader::get_pending_count( &self ) -> usize
+    /// *Number of requests that have not been returned by poll() yet.*
+    pub fn get_pending_count(&self) -> usize
+    {
+        self.pending
+    }
+
+    /// ### fn GkAssetLoader::destroy( &mut self )
+    /// *Stops accepting requests and joins the workers once they finish their current job.*
+    pub fn destroy(&mut self)
+    {
+        drop(self.job_sender.take());
+        for worker in self.workers.drain(..)
+        {
+            if worker.join().is_err() { log_err!("Asset worker thread panicked"); }
+        }
+    }
+}
+
+fn load_asset(request: &EAssetRequest) -> GkResult<EAssetData>
+{
+    profile_scope!("load_asset");
+    match request
+    {
+        EAssetRequest::Texture2d(file) => load_image_bitmap(file).map(EAssetData::Texture2d),
+        EAssetRequest::Cubemap(files) => load_cubemap_bitmap(files).map(EAssetData::Cubemap),
+        EAssetRequest::Mesh(file) => load_obj_mesh(file).map(EAssetData::Mesh),
+    }
+}
+
+/// ### fn load_image_bitmap( ... ) -> GkResult\<GkBitMap\>
+/// *Decodes an image file into an RGBA8 GkBitMap.*
+/// <pre>
+/// - Params
+///     file_name:      &std::path::Path
+/// - Return
+///     GkResult&lt;GkBitMap&gt;
+/// </pre>
+pub fn load_image_bitmap(file_name: &std::path::Path) -> GkResult<GkBitMap>
+{
+    profile_fn!();
+    let img = image::open(file_name).map_err(|e| { log_err!(format!("{}: {}", file_name.display(), e)); GkError::from(e) })?;
+    let pixels = img.to_rgba8().into_raw();
+    Ok(GkBitMap::new(img.width(), img.height(), 1, 4, EBitMapFormat::UByte, pixels))
+}
+
+/// ### fn load_cubemap_bitmap( ... ) -> GkResult\<GkBitMap\>
+/// *Decodes 6 face images, or 1 equirectangular image, into a 6 layer GkBitMap.*
+/// <pre>
+/// - Params
+///     file_names:     &[PathBuf]
+/// - Return
+///     GkResult&lt;GkBitMap&gt;
+/// </pre>
+pub fn load_cubemap_bitmap(file_names: &[PathBuf]) -> GkResult<GkBitMap>
+{
+    profile_fn!();
+    if file_names.len() != 1 && file_names.len() != 6
+    {
+        log_err!("Cubemap file_names count must be either 1 or 6");
+        return Err(GkError::Asset(String::from("Cubemap file_names count must be either 1 or 6")));
+    }
+
+    if file_names.len() == 1
+    {
+        let img = image::open(file_names[0].as_path()).map_err(|e| { log_err!(format!("{}: {}", file_names[0].display(), e)); GkError::from(e) })?;
+        let img = img.flipv();
+        let pixels = img.to_rgba32f().into_raw();
+
+        let bitmap_in = GkBitMap::new(img.width(), img.height(), 1, 4, EBitMapFormat::Float, pixels.as_bytes().to_vec());
+        let bitmap_out = convert_equirectangle_to_vertical_cross(&bitmap_in);
+
+        Ok(convert_vertical_cross_to_cubemap_faces(&bitmap_out))
+    } else {
+        convert_multi_file_to_cubemap_faces(file_names)
+    }
+}
+
+/// ### fn load_obj_mesh( ... ) -> GkResult\<GkMeshData\>
+/// *Parses a wavefront obj file into GkMeshData.*
+/// <pre>
+/// - Params
+///     file_path:      &std::path::Path
+/// - Return
+///     GkResult&lt;GkMeshData&gt;
+/// </pre>
+pub fn load_obj_mesh(file_path: &std::path::Path) -> GkResult<GkMeshData>
+{
+    profile_fn!();
+    let load_options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: false,
+        ignore_points: true,
+        ignore_lines: true
+    };
+    let (models, _materials) = tobj::load_obj(file_path, &load_options).map_err(|e| { log_err!(format!("{}: {}", file_path.display(), e)); GkError::from(e) })?;
+
+    let mut vertices: Vec<VertexData> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    for m in models.iter()
+    {
+        let mesh = &m.mesh;
+
+        if mesh.texcoords.is_empty()
+        {
+            let msg = format!("Missing texture coordinates for {}", file_path.display());
+            log_err!(msg);
+            return Err(GkError::Asset(msg));
+        }
+
+        let base_vertex = vertices.len() as u32;
+        let total_vertices_count = mesh.positions.len() / 3;
+        for i in 0..total_vertices_count
+        {
+            let vertex = VertexData
+            {
+                pos: [
+                    mesh.positions[i * 3],
+                    mesh.positions[i * 3 + 1],
+                    mesh.positions[i * 3 + 2]
+                ],
+                color: [ 0.5, 0.5, 0.5 ],
+                tex_coord: [
+                    mesh.texcoords[ i * 2 ],
+                    -mesh.texcoords[ i * 2 + 1]
+                ]
+            };
+            vertices.push(vertex);
+        }
+        indices.extend(mesh.indices.iter().map(|index| index + base_vertex));
+    }
+
+    Ok(GkMeshData { vertices, indices })
+}
+
+/// ### fn gk_placeholder_bitmap( ... ) -> GkBitMap
+/// *1x1 RGBA8 bitmap used until the real texture is streamed in.*
+/// <pre>
+/// - Params
+///     layers:     u32     <i>// 1 for 2d textures, 6 for cubemaps.</i>
+/// - Return
+///     GkBitMap
+/// </pre>
+pub fn gk_placeholder_bitmap(layers: u32) -> GkBitMap
+{
+    GkBitMap::new(1, 1, layers, 4, EBitMapFormat::UByte, [128u8, 128, 128, 255].repeat(layers as usize))
+}
+
+/// ### fn gk_placeholder_mesh() -> GkMeshData
+/// *Unit cube used until the real mesh is streamed in.*
+pub fn gk_placeholder_mesh() -> GkMeshData
+{
+    let corners: [[f32; 3]; 8] = [
+        [-0.5, -0.5,  0.5], [ 0.5, -0.5,  0.5], [ 0.5,  0.5,  0.5], [-0.5,  0.5,  0.5],
+        [-0.5, -0.5, -0.5], [ 0.5, -0.5, -0.5], [ 0.5,  0.5, -0.5], [-0.5,  0.5, -0.5],
+    ];
+    let vertices = corners.iter()
+        .map(|pos| VertexData { pos: *pos, color: [0.5, 0.5, 0.5], tex_coord: [0.0, 0.0] })
+        .collect();
+    let indices = vec![
+        0, 1, 2, 2, 3, 0,
+        1, 5, 6, 6, 2, 1,
+        7, 6, 5, 5, 4, 7,
+        4, 0, 3, 3, 7, 4,
+        4, 5, 1, 1, 0, 4,
+        3, 2, 6, 6, 7, 3
+    ];
+    GkMeshData { vertices, indices }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::path::PathBuf;
+
+    use crate::core::logger::{ ELogLevel, GkLogCapture };
+
+    use super::load_cubemap_bitmap;
+
+    #[test]
+    fn bad_cubemap_face_count_is_logged()
+    {
+        let capture = GkLogCapture::start();
+        let result = load_cubemap_bitmap(&[PathBuf::from("px.png"), PathBuf::from("nx.png")]);
+
+        assert!(result.is_err());
+        let record = capture.assert_logged(ELogLevel::Error, "Cubemap file_names count must be either 1 or 6");
+        assert_eq!(record.target, "gunk_engine::renderer::gk_asset_loader");
+        assert!(record.file.ends_with("gk_asset_loader.rs"));
+    }
+}
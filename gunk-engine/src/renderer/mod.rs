@@ -1,4 +1,5 @@
 pub mod vulkan_renderer;
 pub mod renderer_utils;
 pub mod cubemap_utils;
-pub mod gk_bitmap;
\ No newline at end of file
+pub mod gk_bitmap;
+pub mod gk_asset_loader;
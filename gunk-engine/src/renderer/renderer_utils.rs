@@ -1,6 +1,51 @@
-
+use ash::vk;
 use winit::window::Window;
 
+use crate::core::gk_error::GkError;
+use crate::renderer::vulkan_renderer::gk_vk_camera::GkCamera;
+use crate::scene::GkSceneGraph;
+
+/// ### ERenderError enum
+/// *Errors a GfxRenderer could not recover from while rendering a frame.*
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ERenderError
+{
+    DeviceLost,         // Device was lost and could not be recreated
+    SurfaceLost,        // Surface was lost and could not be recreated
+    OutOfMemory,        // Host or device memory exhausted
+    Backend(String),    // Any other graphics api error
+}
+
+impl std::fmt::Display for ERenderError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            ERenderError::DeviceLost => write!(f, "graphics device lost"),
+            ERenderError::SurfaceLost => write!(f, "window surface lost"),
+            ERenderError::OutOfMemory => write!(f, "out of memory"),
+            ERenderError::Backend(msg) => write!(f, "graphics backend error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ERenderError {}
+
+impl From<GkError> for ERenderError
+{
+    fn from(err: GkError) -> Self
+    {
+        match err
+        {
+            GkError::Vulkan(vk::Result::ERROR_DEVICE_LOST) => ERenderError::DeviceLost,
+            GkError::Vulkan(vk::Result::ERROR_SURFACE_LOST_KHR) => ERenderError::SurfaceLost,
+            GkError::Vulkan(vk::Result::ERROR_OUT_OF_HOST_MEMORY) | GkError::Vulkan(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => ERenderError::OutOfMemory,
+            e => ERenderError::Backend(e.to_string()),
+        }
+    }
+}
+
 pub trait GfxRenderer
 {
     fn init(&self);
@@ -9,11 +54,38 @@ pub trait GfxRenderer
 
     fn update(&mut self, window: &Window, delta_time: f32);
 
-    fn render(&mut self, window: &Window, delta_time: f32);
+    fn render(&mut self, window: &Window, delta_time: f32) -> Result<(), ERenderError>;
 
     fn resized(&mut self);
 
     fn wait_idle(&self);
+
+    // Turns vsync on or off, taking effect from the next frame.
+    fn set_vsync(&mut self, b_vsync: bool);
+
+    // Whether presenting a frame waits for the display's vertical blank.
+    fn is_present_paced(&self) -> bool;
+
+    // The main camera, the first of cameras().
+    fn camera_mut(&mut self) -> &mut GkCamera;
+
+    // Every camera drawn each frame, into its own viewport of the window or its offscreen texture.
+    fn cameras(&self) -> &[GkCamera];
+
+    // Replaces the cameras, such as for split screen. The first becomes the main camera, an empty list is ignored.
+    fn set_cameras(&mut self, cameras: Vec<GkCamera>);
+
+    // The scene drawn each frame. Its world matrices are brought up to date at the start of every frame.
+    fn scene(&self) -> &GkSceneGraph;
+
+    // Nodes with a camera attachment move their camera, overriding camera_mut() and the camera controller.
+    fn scene_mut(&mut self) -> &mut GkSceneGraph;
+
+    // Graphics api validation errors reported so far, 0 when validation is off.
+    fn validation_error_count(&self) -> u64
+    {
+        0
+    }
 }
 
 pub fn to_asset_path(file_name: &str) -> std::path::PathBuf
@@ -0,0 +1,619 @@
+use nalgebra_glm as glm;
+use winit::event::MouseButton;
+use winit::keyboard::KeyCode;
+
+use crate::platform::action_map::GkActionMap;
+use crate::platform::input::GkInput;
+use super::CamView;
+
+/// World up for every controller. Yaw turns around it, yaw 0 looks down +Y with +X to the right.
+pub const CAMERA_UP: glm::Vec3 = glm::Vec3::new(0.0, 0.0, 1.0);
+
+/// Action names read by GkCameraInput::from_actions().
+pub const ACTION_CAMERA_MOVE: &str = "camera.move";
+pub const ACTION_CAMERA_LIFT: &str = "camera.lift";
+pub const ACTION_CAMERA_LOOK: &str = "camera.look";
+pub const ACTION_CAMERA_PAN: &str = "camera.pan";
+pub const ACTION_CAMERA_ZOOM: &str = "camera.zoom";
+pub const ACTION_CAMERA_FAST: &str = "camera.fast";
+
+/// ### GkCameraInput struct
+/// *One frame of camera intent, independent of where it came from.*
+/// <pre>
+/// - Members
+///     movement:   glm::Vec3   <i>// x right, y forward, z up. Each in [-1, 1].</i>
+///     look:       glm::Vec2   <i>// Mouse counts, x right and y down.</i>
+///     pan:        glm::Vec2   <i>// Mouse counts, x right and y down.</i>
+///     zoom:       f32         <i>// Wheel lines, positive zooms in.</i>
+///     b_fast:     bool
+/// </pre>
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GkCameraInput
+{
+    pub movement:   glm::Vec3,
+    pub look:       glm::Vec2,
+    pub pan:        glm::Vec2,
+    pub zoom:       f32,
+    pub b_fast:     bool,
+}
+
+impl GkCameraInput
+{
+    /// ### fn GkCameraInput::from_input( ... ) -> GkCameraInput
+    /// *The default bindings. WASD moves, E and Q lift, Shift is fast, the wheel zooms and
+    /// dragging with the middle button pans.*
+    /// <pre>
+    /// - Params
+    ///     input:              &GkInput
+    ///     b_cursor_captured:  bool    <i>// Mouse motion always looks, otherwise only while the right button is held.</i>
+    /// - Return
+    ///     GkCameraInput
+    /// </pre>
+    pub fn from_input(input: &GkInput, b_cursor_captured: bool) -> Self
+    {
+        let axis = |positive: KeyCode, negative: KeyCode|
+        {
+            input.is_key_held(positive) as i32 as f32 - input.is_key_held(negative) as i32 as f32
+        };
+        let (dx, dy) = input.mouse_delta();
+        let mouse = glm::Vec2::new(dx as f32, dy as f32);
+        let b_look = b_cursor_captured || input.is_button_held(MouseButton::Right);
+        Self
+        {
+            movement: glm::Vec3::new(axis(KeyCode::KeyD, KeyCode::KeyA), axis(KeyCode::KeyW, KeyCode::KeyS), axis(KeyCode::KeyE, KeyCode::KeyQ)),
+            look: if b_look { mouse } else { glm::Vec2::zeros() },
+            pan: if input.is_button_held(MouseButton::Middle) { mouse } else { glm::Vec2::zeros() },
+            zoom: input.scroll_delta().1,
+            b_fast: input.is_key_held(KeyCode::ShiftLeft) || input.is_key_held(KeyCode::ShiftRight),
+        }
+    }
+
+    /// ### fn GkCameraInput::from_actions( ... ) -> GkCameraInput
+    /// *Reads the ACTION_CAMERA_* actions, so the camera follows the player's bindings.
+    /// Actions that are not bound read as zero.*
+    pub fn from_actions(actions: &GkActionMap) -> Self
+    {
+        let (move_x, move_y) = actions.value_2d(ACTION_CAMERA_MOVE);
+        let (look_x, look_y) = actions.value_2d(ACTION_CAMERA_LOOK);
+        let (pan_x, pan_y) = actions.value_2d(ACTION_CAMERA_PAN);
+        Self
+        {
+            movement: glm::Vec3::new(move_x, move_y, actions.value(ACTION_CAMERA_LIFT)).map(|v| v.clamp(-1.0, 1.0)),
+            look: glm::Vec2::new(look_x, look_y),
+            pan: glm::Vec2::new(pan_x, pan_y),
+            zoom: actions.value(ACTION_CAMERA_ZOOM),
+            b_fast: actions.is_held(ACTION_CAMERA_FAST),
+        }
+    }
+}
+
+/// ### GkCameraController trait
+/// *Turns GkCameraInput into a camera view, once per rendered frame.*
+pub trait GkCameraController
+{
+    fn update(&mut self, input: &GkCameraInput, delta_time: f32);
+
+    fn view(&self) -> CamView;
+}
+
+/// ### fn camera_forward( ... ) -> glm::Vec3
+/// *Unit view direction for a yaw and pitch in radians.*
+pub fn camera_forward(yaw: f32, pitch: f32) -> glm::Vec3
+{
+    glm::Vec3::new(-yaw.sin() * pitch.cos(), yaw.cos() * pitch.cos(), pitch.sin())
+}
+
+/// ### fn camera_angles( ... ) -> (f32, f32)
+/// *Yaw and pitch of a direction, the inverse of camera_forward().*
+pub fn camera_angles(direction: &glm::Vec3) -> (f32, f32)
+{
+    let direction = direction.normalize();
+    ((-direction.x).atan2(direction.y), direction.z.clamp(-1.0, 1.0).asin())
+}
+
+/// Fraction of the remaining distance covered this frame by a smoothing time constant, 1 when smoothing is off.
+fn smoothing_factor(smoothing: f32, delta_time: f32) -> f32
+{
+    if smoothing <= 0.0 { 1.0 } else { 1.0 - (-delta_time / smoothing).exp() }
+}
+
+/// Keeps pitch short of straight up and down, where look_at has no defined up.
+const MAX_PITCH: f32 = 89.0 * std::f32::consts::PI / 180.0;
+
+/// Yaw and pitch that ease towards their targets.
+#[derive(Clone, Copy, Debug)]
+struct GkLookAngles
+{
+    yaw:            f32,
+    pitch:          f32,
+    target_yaw:     f32,
+    target_pitch:   f32,
+}
+
+impl GkLookAngles
+{
+    fn new(yaw: f32, pitch: f32) -> Self
+    {
+        Self { yaw, pitch, target_yaw: yaw, target_pitch: pitch }
+    }
+
+    fn turn(&mut self, look: &glm::Vec2, sensitivity: f32, pitch_limits: (f32, f32))
+    {
+        self.target_yaw -= look.x * sensitivity;
+        self.target_pitch = (self.target_pitch - look.y * sensitivity).clamp(pitch_limits.0, pitch_limits.1);
+    }
+
+    fn ease(&mut self, factor: f32)
+    {
+        self.yaw += (self.target_yaw - self.yaw) * factor;
+        self.pitch += (self.target_pitch - self.pitch) * factor;
+    }
+
+    fn forward(&self) -> glm::Vec3
+    {
+        camera_forward(self.yaw, self.pitch)
+    }
+}
+
+
+/// ### GkFlyCamera struct
+/// *Free flight. Moves along the view direction, lifts along CAMERA_UP and looks with the mouse.*
+/// <pre>
+/// - Members
+///     pos:                glm::Vec3
+///     move_speed:         f32     <i>// Units per second.</i>
+///     fast_multiplier:    f32     <i>// Applied to move_speed while b_fast is held.</i>
+///     look_sensitivity:   f32     <i>// Radians per mouse count.</i>
+///     smoothing:          f32     <i>// Seconds to cover about 63% of a speed or heading change, 0 for none.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug)]
+pub struct GkFlyCamera
+{
+    pub pos:                glm::Vec3,
+    pub move_speed:         f32,
+    pub fast_multiplier:    f32,
+    pub look_sensitivity:   f32,
+    pub smoothing:          f32,
+    angles:                 GkLookAngles,
+    velocity:               glm::Vec3,
+}
+
+impl GkFlyCamera
+{
+    pub fn new(pos: glm::Vec3, yaw: f32, pitch: f32) -> Self
+    {
+        Self
+        {
+            pos,
+            move_speed: 5.0,
+            fast_multiplier: 4.0,
+            look_sensitivity: 0.0025,
+            smoothing: 0.0,
+            angles: GkLookAngles::new(yaw, pitch.clamp(-MAX_PITCH, MAX_PITCH)),
+            velocity: glm::Vec3::zeros(),
+        }
+    }
+
+    /// ### fn GkFlyCamera::from_view( ... ) -> GkFlyCamera
+    /// *Starts where an existing view is, looking the same way.*
+    pub fn from_view(view: &CamView) -> Self
+    {
+        let (yaw, pitch) = camera_angles(&view.front);
+        Self::new(view.pos, yaw, pitch)
+    }
+
+    pub fn with_speed(mut self, move_speed: f32, fast_multiplier: f32) -> Self
+    {
+        self.move_speed = move_speed;
+        self.fast_multiplier = fast_multiplier;
+        self
+    }
+
+    pub fn with_sensitivity(mut self, look_sensitivity: f32) -> Self
+    {
+        self.look_sensitivity = look_sensitivity;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self
+    {
+        self.smoothing = smoothing;
+        self
+    }
+
+    pub fn yaw(&self) -> f32
+    {
+        self.angles.yaw
+    }
+
+    pub fn pitch(&self) -> f32
+    {
+        self.angles.pitch
+    }
+}
+
+impl GkCameraController for GkFlyCamera
+{
+    fn update(&mut self, input: &GkCameraInput, delta_time: f32)
+    {
+        let factor = smoothing_factor(self.smoothing, delta_time);
+        self.angles.turn(&input.look, self.look_sensitivity, (-MAX_PITCH, MAX_PITCH));
+        self.angles.ease(factor);
+
+        let forward = self.angles.forward();
+        let right = forward.cross(&CAMERA_UP).normalize();
+        let speed = if input.b_fast { self.move_speed * self.fast_multiplier } else { self.move_speed };
+        let target_velocity = (right * input.movement.x + forward * input.movement.y + CAMERA_UP * input.movement.z) * speed;
+        self.velocity += (target_velocity - self.velocity) * factor;
+        self.pos += self.velocity * delta_time;
+    }
+
+    fn view(&self) -> CamView
+    {
+        CamView { pos: self.pos, front: self.angles.forward(), up: CAMERA_UP }
+    }
+}
+
+
+/// ### GkFirstPersonCamera struct
+/// *Walks on the plane under CAMERA_UP whichever way it looks, with pitch held inside pitch_limits.*
+/// <pre>
+/// - Members
+///     pos:                glm::Vec3   <i>// Eye position.</i>
+///     move_speed:         f32         <i>// Units per second.</i>
+///     fast_multiplier:    f32         <i>// Applied to move_speed while b_fast is held.</i>
+///     look_sensitivity:   f32         <i>// Radians per mouse count.</i>
+///     pitch_limits:       (f32, f32)  <i>// Lowest and highest pitch in radians.</i>
+///     smoothing:          f32         <i>// Seconds to cover about 63% of a speed or heading change, 0 for none.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug)]
+pub struct GkFirstPersonCamera
+{
+    pub pos:                glm::Vec3,
+    pub move_speed:         f32,
+    pub fast_multiplier:    f32,
+    pub look_sensitivity:   f32,
+    pub pitch_limits:       (f32, f32),
+    pub smoothing:          f32,
+    angles:                 GkLookAngles,
+    velocity:               glm::Vec3,
+}
+
+impl GkFirstPersonCamera
+{
+    pub fn new(pos: glm::Vec3, yaw: f32) -> Self
+    {
+        let limit = 85.0 * std::f32::consts::PI / 180.0;
+        Self
+        {
+            pos,
+            move_speed: 3.0,
+            fast_multiplier: 2.0,
+            look_sensitivity: 0.0025,
+            pitch_limits: (-limit, limit),
+            smoothing: 0.0,
+            angles: GkLookAngles::new(yaw, 0.0),
+            velocity: glm::Vec3::zeros(),
+        }
+    }
+
+    pub fn with_speed(mut self, move_speed: f32, fast_multiplier: f32) -> Self
+    {
+        self.move_speed = move_speed;
+        self.fast_multiplier = fast_multiplier;
+        self
+    }
+
+    pub fn with_sensitivity(mut self, look_sensitivity: f32) -> Self
+    {
+        self.look_sensitivity = look_sensitivity;
+        self
+    }
+
+    /// ### fn GkFirstPersonCamera::with_pitch_limits( self, ... ) -> GkFirstPersonCamera
+    /// *Radians, kept within what look_at can handle. Reversed limits are swapped.*
+    pub fn with_pitch_limits(mut self, min: f32, max: f32) -> Self
+    {
+        let (min, max) = (min.clamp(-MAX_PITCH, MAX_PITCH), max.clamp(-MAX_PITCH, MAX_PITCH));
+        self.pitch_limits = (min.min(max), max.max(min));
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self
+    {
+        self.smoothing = smoothing;
+        self
+    }
+
+    pub fn yaw(&self) -> f32
+    {
+        self.angles.yaw
+    }
+
+    pub fn pitch(&self) -> f32
+    {
+        self.angles.pitch
+    }
+}
+
+impl GkCameraController for GkFirstPersonCamera
+{
+    fn update(&mut self, input: &GkCameraInput, delta_time: f32)
+    {
+        let factor = smoothing_factor(self.smoothing, delta_time);
+        self.angles.turn(&input.look, self.look_sensitivity, self.pitch_limits);
+        self.angles.ease(factor);
+
+        let heading = camera_forward(self.angles.yaw, 0.0);
+        let right = heading.cross(&CAMERA_UP);
+        let mut walk = right * input.movement.x + heading * input.movement.y;
+        if walk.norm() > 1.0 { walk.normalize_mut(); }
+        let speed = if input.b_fast { self.move_speed * self.fast_multiplier } else { self.move_speed };
+        self.velocity += (walk * speed - self.velocity) * factor;
+        self.pos += self.velocity * delta_time;
+    }
+
+    fn view(&self) -> CamView
+    {
+        CamView { pos: self.pos, front: self.angles.forward(), up: CAMERA_UP }
+    }
+}
+
+
+/// ### GkOrbitCamera struct
+/// *Editor camera circling a focus point. Look orbits, pan slides the focus across the view and zoom changes the distance.*
+/// <pre>
+/// - Members
+///     focus:              glm::Vec3
+///     distance:           f32
+///     distance_limits:    (f32, f32)
+///     orbit_sensitivity:  f32     <i>// Radians per mouse count.</i>
+///     pan_speed:          f32     <i>// Focus movement per mouse count, scaled by distance.</i>
+///     zoom_speed:         f32     <i>// Fraction of the distance removed per wheel line.</i>
+///     smoothing:          f32     <i>// Seconds to cover about 63% of a change, 0 for none.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug)]
+pub struct GkOrbitCamera
+{
+    pub focus:              glm::Vec3,
+    pub distance:           f32,
+    pub distance_limits:    (f32, f32),
+    pub orbit_sensitivity:  f32,
+    pub pan_speed:          f32,
+    pub zoom_speed:         f32,
+    pub smoothing:          f32,
+    angles:                 GkLookAngles,
+    target_focus:           glm::Vec3,
+    target_distance:        f32,
+}
+
+impl GkOrbitCamera
+{
+    pub fn new(focus: glm::Vec3, distance: f32) -> Self
+    {
+        let distance_limits = (0.1, 1000.0);
+        let distance = distance.clamp(distance_limits.0, distance_limits.1);
+        Self
+        {
+            focus,
+            distance,
+            distance_limits,
+            orbit_sensitivity: 0.005,
+            pan_speed: 0.0015,
+            zoom_speed: 0.1,
+            smoothing: 0.0,
+            angles: GkLookAngles::new(0.0, 0.0),
+            target_focus: focus,
+            target_distance: distance,
+        }
+    }
+
+    /// ### fn GkOrbitCamera::with_angles( self, ... ) -> GkOrbitCamera
+    /// *Yaw and pitch of the view direction in radians, so a positive pitch looks up at the focus.*
+    pub fn with_angles(mut self, yaw: f32, pitch: f32) -> Self
+    {
+        self.angles = GkLookAngles::new(yaw, pitch.clamp(-MAX_PITCH, MAX_PITCH));
+        self
+    }
+
+    /// ### fn GkOrbitCamera::with_distance_limits( self, ... ) -> GkOrbitCamera
+    /// *Reversed limits are swapped.*
+    pub fn with_distance_limits(mut self, min: f32, max: f32) -> Self
+    {
+        let (min, max) = (min.min(max), max.max(min));
+        self.distance_limits = (min, max);
+        self.distance = self.distance.clamp(min, max);
+        self.target_distance = self.distance;
+        self
+    }
+
+    pub fn with_speeds(mut self, orbit_sensitivity: f32, pan_speed: f32, zoom_speed: f32) -> Self
+    {
+        self.orbit_sensitivity = orbit_sensitivity;
+        self.pan_speed = pan_speed;
+        self.zoom_speed = zoom_speed;
+        self
+    }
+
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self
+    {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// ### fn GkOrbitCamera::set_focus( &mut self, ... )
+    /// *Moves the focus, easing there when smoothing is on.*
+    pub fn set_focus(&mut self, focus: glm::Vec3)
+    {
+        self.target_focus = focus;
+    }
+
+    pub fn position(&self) -> glm::Vec3
+    {
+        self.focus - self.angles.forward() * self.distance
+    }
+}
+
+impl GkCameraController for GkOrbitCamera
+{
+    fn update(&mut self, input: &GkCameraInput, delta_time: f32)
+    {
+        let factor = smoothing_factor(self.smoothing, delta_time);
+        self.angles.turn(&input.look, self.orbit_sensitivity, (-MAX_PITCH, MAX_PITCH));
+
+        let forward = camera_forward(self.angles.target_yaw, self.angles.target_pitch);
+        let right = forward.cross(&CAMERA_UP).normalize();
+        let up = right.cross(&forward);
+        let pan_scale = self.pan_speed * self.target_distance;
+        self.target_focus += (up * input.pan.y - right * input.pan.x) * pan_scale;
+        self.target_distance = (self.target_distance * (1.0 - self.zoom_speed).powf(input.zoom))
+            .clamp(self.distance_limits.0, self.distance_limits.1);
+
+        self.angles.ease(factor);
+        self.focus += (self.target_focus - self.focus) * factor;
+        self.distance += (self.target_distance - self.distance) * factor;
+    }
+
+    fn view(&self) -> CamView
+    {
+        CamView { pos: self.position(), front: self.angles.forward(), up: CAMERA_UP }
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use std::f32::consts::FRAC_PI_2;
+
+    use super::*;
+
+    fn assert_near(actual: &glm::Vec3, expected: &glm::Vec3)
+    {
+        assert!((actual - expected).norm() < 1e-4, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    fn to_view_space(view: &CamView, point: glm::Vec3) -> glm::Vec3
+    {
+        let p = view.get_matrix() * glm::Vec4::new(point.x, point.y, point.z, 1.0);
+        glm::Vec3::new(p.x, p.y, p.z)
+    }
+
+    fn moving(movement: glm::Vec3) -> GkCameraInput
+    {
+        GkCameraInput { movement, ..Default::default() }
+    }
+
+    fn looking(dx: f32, dy: f32) -> GkCameraInput
+    {
+        GkCameraInput { look: glm::Vec2::new(dx, dy), ..Default::default() }
+    }
+
+    #[test]
+    fn view_matrix_looks_along_front_from_pos()
+    {
+        let view = CamView { pos: glm::Vec3::new(1.0, 2.0, 3.0), front: glm::Vec3::new(0.0, 1.0, 0.0), up: CAMERA_UP };
+        assert_near(&to_view_space(&view, view.pos), &glm::Vec3::zeros());
+        assert_near(&to_view_space(&view, glm::Vec3::new(1.0, 7.0, 3.0)), &glm::Vec3::new(0.0, 0.0, -5.0));
+        assert_near(&to_view_space(&view, glm::Vec3::new(2.0, 2.0, 4.0)), &glm::Vec3::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn angles_round_trip_through_forward()
+    {
+        for &(yaw, pitch) in &[(0.0, 0.0), (1.0, 0.5), (-2.5, -1.2), (3.0, 1.5)]
+        {
+            let (y, p) = camera_angles(&camera_forward(yaw, pitch));
+            assert!((y - yaw).abs() < 1e-5 && (p - pitch).abs() < 1e-5, "({}, {}) came back as ({}, {})", yaw, pitch, y, p);
+        }
+    }
+
+    #[test]
+    fn fly_camera_moves_along_its_view_and_turns_with_the_mouse()
+    {
+        let mut camera = GkFlyCamera::new(glm::Vec3::zeros(), 0.0, 0.0).with_speed(2.0, 3.0).with_sensitivity(0.01);
+        camera.update(&moving(glm::Vec3::new(0.0, 1.0, 0.0)), 1.0);
+        assert_near(&camera.pos, &glm::Vec3::new(0.0, 2.0, 0.0));
+        assert_near(&to_view_space(&camera.view(), glm::Vec3::new(0.0, 7.0, 0.0)), &glm::Vec3::new(0.0, 0.0, -5.0));
+
+        // Turning right by a quarter turn faces +X.
+        camera.update(&looking(FRAC_PI_2 / 0.01, 0.0), 0.0);
+        assert_near(&camera.view().front, &glm::Vec3::new(1.0, 0.0, 0.0));
+        camera.update(&GkCameraInput { movement: glm::Vec3::new(0.0, 1.0, 1.0), b_fast: true, ..Default::default() }, 0.5);
+        assert_near(&camera.pos, &glm::Vec3::new(3.0, 2.0, 3.0));
+        assert_near(&to_view_space(&camera.view(), camera.pos + glm::Vec3::new(4.0, 0.0, 0.0)), &glm::Vec3::new(0.0, 0.0, -4.0));
+    }
+
+    #[test]
+    fn fly_camera_starts_from_an_existing_view()
+    {
+        let view = CamView { pos: glm::Vec3::new(0.0, -2.0, 1.0), front: glm::Vec3::new(0.0, 3.0, -1.0).normalize(), up: CAMERA_UP };
+        let camera = GkFlyCamera::from_view(&view);
+        assert!((camera.view().get_matrix() - view.get_matrix()).norm() < 1e-5);
+    }
+
+    #[test]
+    fn first_person_camera_clamps_pitch_and_walks_level()
+    {
+        let mut camera = GkFirstPersonCamera::new(glm::Vec3::new(0.0, 0.0, 1.7), 0.0).with_speed(1.0, 2.0).with_pitch_limits(-1.0, 1.0);
+        camera.update(&looking(0.0, 10_000.0), 0.0);
+        assert!((camera.pitch() + 1.0).abs() < 1e-6);
+
+        camera.update(&moving(glm::Vec3::new(1.0, 1.0, 1.0)), 2.0);
+        let step = std::f32::consts::SQRT_2;
+        assert_near(&camera.pos, &glm::Vec3::new(step, step, 1.7));
+
+        // Looking back up from the limit responds straight away.
+        camera.update(&looking(0.0, -100.0), 0.0);
+        assert!(camera.pitch() > -1.0);
+    }
+
+    #[test]
+    fn limits_outside_the_valid_range_or_reversed_still_clamp()
+    {
+        let mut camera = GkFirstPersonCamera::new(glm::Vec3::zeros(), 0.0).with_pitch_limits(1.6, 2.0);
+        camera.update(&looking(0.0, -10_000.0), 0.0);
+        assert!((camera.pitch() - MAX_PITCH).abs() < 1e-6);
+
+        let mut camera = GkFirstPersonCamera::new(glm::Vec3::zeros(), 0.0).with_pitch_limits(0.5, -0.5);
+        camera.update(&looking(0.0, 10_000.0), 0.0);
+        assert!((camera.pitch() + 0.5).abs() < 1e-6);
+
+        let camera = GkOrbitCamera::new(glm::Vec3::zeros(), 30.0).with_distance_limits(20.0, 2.0);
+        assert_eq!(camera.distance, 20.0);
+    }
+
+    #[test]
+    fn orbit_camera_keeps_the_focus_centered_through_orbit_pan_and_zoom()
+    {
+        let focus = glm::Vec3::new(1.0, 1.0, 0.0);
+        let mut camera = GkOrbitCamera::new(focus, 10.0).with_distance_limits(2.0, 20.0).with_speeds(0.01, 0.001, 0.5);
+        assert_near(&camera.position(), &glm::Vec3::new(1.0, -9.0, 0.0));
+        assert_near(&to_view_space(&camera.view(), focus), &glm::Vec3::new(0.0, 0.0, -10.0));
+
+        camera.update(&looking(FRAC_PI_2 / 0.01, 0.0), 0.0);
+        assert_near(&camera.position(), &glm::Vec3::new(-9.0, 1.0, 0.0));
+        assert_near(&to_view_space(&camera.view(), focus), &glm::Vec3::new(0.0, 0.0, -10.0));
+
+        camera.update(&GkCameraInput { zoom: 1.0, ..Default::default() }, 0.0);
+        assert!((camera.distance - 5.0).abs() < 1e-5);
+        camera.update(&GkCameraInput { zoom: 10.0, ..Default::default() }, 0.0);
+        assert_eq!(camera.distance, 2.0);
+
+        // Dragging right slides the scene right, taking the old focus with it.
+        camera.update(&GkCameraInput { pan: glm::Vec2::new(100.0, 0.0), ..Default::default() }, 0.0);
+        assert_near(&to_view_space(&camera.view(), focus), &glm::Vec3::new(0.2, 0.0, -2.0));
+    }
+
+    #[test]
+    fn smoothing_eases_towards_the_target()
+    {
+        let mut camera = GkFlyCamera::new(glm::Vec3::zeros(), 0.0, 0.0).with_sensitivity(0.01).with_smoothing(0.1);
+        camera.update(&looking(100.0, 0.0), 0.1);
+        let eased = -camera.yaw();
+        assert!(eased > 0.5 && eased < 0.7, "eased to {}", eased);
+
+        for _ in 0..100 { camera.update(&GkCameraInput::default(), 0.1); }
+        assert!((camera.yaw() + 1.0).abs() < 1e-4);
+    }
+}
@@ -2,6 +2,8 @@
 
 use nalgebra_glm as glm;
 
+pub mod camera_controller;
+
 #[derive(Debug, Copy, Clone)]
 pub struct CamView
 {
@@ -14,18 +16,39 @@ impl CamView
 {
     pub fn get_matrix(&self) -> glm::Mat4
     {
-        // glm::look_at(&self.pos, &(self.pos + self.front), &self.up)
-        glm::look_at(&self.pos, &self.front, &self.up)
+        glm::look_at(&self.pos, &(self.pos + self.front), &self.up)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+/// ### EProjectionKind enum
+/// *The shape of a CamProjection's view volume.*
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EProjectionKind
+{
+    Perspective { fov: f32 },       // Vertical field of view in radians
+    Orthographic { height: f32 },   // View volume height in world units, the width follows the aspect
+}
+
+/// ### CamProjection struct
+/// *Maps view space to Vulkan clip space, with y pointing down and depth in [0, 1].*
+/// <pre>
+/// - Members
+///     kind:               EProjectionKind
+///     aspect:             f32     <i>// Width over height. The renderer keeps it matching the camera's viewport.</i>
+///     near:               f32
+///     far:                f32     <i>// Ignored by perspective projections with b_infinite_far.</i>
+///     b_reverse_z:        bool    <i>// Near maps to depth 1 and far to 0. The renderer sets it to match its depth buffer.</i>
+///     b_infinite_far:     bool    <i>// Perspective only, nothing beyond near is clipped.</i>
+/// </pre>
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct CamProjection
 {
-    pub aspect:     f32,
-    pub fov:        f32,
-    pub near:       f32,
-    pub far:        f32,
+    pub kind:           EProjectionKind,
+    pub aspect:         f32,
+    pub near:           f32,
+    pub far:            f32,
+    pub b_reverse_z:    bool,
+    pub b_infinite_far: bool,
 }
 
 pub const Y_CORRECTION: glm::Mat4 = glm::Mat4::new(
@@ -37,21 +60,191 @@ pub const Y_CORRECTION: glm::Mat4 = glm::Mat4::new(
 
 impl CamProjection
 {
+    pub fn perspective(fov: f32, aspect: f32, near: f32, far: f32) -> Self
+    {
+        Self { kind: EProjectionKind::Perspective { fov }, aspect, near, far, b_reverse_z: false, b_infinite_far: false }
+    }
+
+    pub fn orthographic(height: f32, aspect: f32, near: f32, far: f32) -> Self
+    {
+        Self { kind: EProjectionKind::Orthographic { height }, aspect, near, far, b_reverse_z: false, b_infinite_far: false }
+    }
+
+    pub fn with_reverse_z(mut self, b_reverse_z: bool) -> Self
+    {
+        self.b_reverse_z = b_reverse_z;
+        self
+    }
+
+    pub fn with_infinite_far(mut self, b_infinite_far: bool) -> Self
+    {
+        self.b_infinite_far = b_infinite_far;
+        self
+    }
+
+    /// ### fn CamProjection::set_viewport_size( &mut self, ... )
+    /// *Matches the aspect to a viewport in pixels. A zero sized viewport, such as a minimized window, keeps the old aspect.*
+    pub fn set_viewport_size(&mut self, width: u32, height: u32)
+    {
+        if width > 0 && height > 0 { self.aspect = width as f32 / height as f32; }
+    }
+
     pub fn get_matrix(&self) -> glm::Mat4
     {
-        let mut proj = glm::perspective(self.aspect, self.fov, self.near, self.far);
-        proj.m22 *= -1.0;
-        proj
+        let (near, far) = (self.near, self.far);
+        match self.kind
+        {
+            EProjectionKind::Perspective { fov } =>
+            {
+                let focal = 1.0 / (fov * 0.5).tan();
+                // Depth is m22 + m23 / distance, with the distance in front of the camera as w.
+                let (m22, m23) = match (self.b_reverse_z, self.b_infinite_far)
+                {
+                    (false, false) => (far / (near - far), near * far / (near - far)),
+                    (false, true) => (-1.0, -near),
+                    (true, false) => (near / (far - near), near * far / (far - near)),
+                    (true, true) => (0.0, near),
+                };
+                glm::Mat4::new(
+                    focal / self.aspect, 0.0, 0.0, 0.0,
+                    0.0, -focal, 0.0, 0.0,
+                    0.0, 0.0, m22, m23,
+                    0.0, 0.0, -1.0, 0.0,
+                )
+            },
+            EProjectionKind::Orthographic { height } =>
+            {
+                let half_height = height * 0.5;
+                let half_width = half_height * self.aspect;
+                let (m22, m23) = if self.b_reverse_z { (1.0 / (far - near), far / (far - near)) }
+                    else { (-1.0 / (far - near), -near / (far - near)) };
+                glm::Mat4::new(
+                    1.0 / half_width, 0.0, 0.0, 0.0,
+                    0.0, -1.0 / half_height, 0.0, 0.0,
+                    0.0, 0.0, m22, m23,
+                    0.0, 0.0, 0.0, 1.0,
+                )
+            },
+        }
+    }
+}
+
+/// ### GkViewport struct
+/// *The part of its target a camera draws to, as fractions of the target with the origin at the top left. The target is the window unless set by GkCamera::with_target().*
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GkViewport
+{
+    pub x:      f32,
+    pub y:      f32,
+    pub width:  f32,
+    pub height: f32,
+}
+
+impl GkViewport
+{
+    pub const fn full() -> Self
+    {
+        Self { x: 0.0, y: 0.0, width: 1.0, height: 1.0 }
+    }
+
+    /// ### fn GkViewport::grid( ... ) -> GkViewport
+    /// *Cell index of a columns by rows grid, counted left to right then top to bottom.
+    /// grid(2, 1, i) splits the screen in two, grid(2, 2, i) makes a quad view.*
+    pub fn grid(columns: u32, rows: u32, index: u32) -> Self
+    {
+        let (columns, rows) = (columns.max(1), rows.max(1));
+        let (column, row) = (index % columns, (index / columns) % rows);
+        Self
+        {
+            x: column as f32 / columns as f32,
+            y: row as f32 / rows as f32,
+            width: 1.0 / columns as f32,
+            height: 1.0 / rows as f32,
+        }
+    }
+
+    /// ### fn GkViewport::to_pixels( &self, ... ) -> (i32, i32, u32, u32)
+    /// *x, y, width and height in pixels of a width by height target.
+    /// Edges are rounded the same way for every viewport, so neighbours share them without gaps.*
+    pub fn to_pixels(&self, width: u32, height: u32) -> (i32, i32, u32, u32)
+    {
+        let edge = |fraction: f32, size: u32| (fraction.clamp(0.0, 1.0) * size as f32).round() as u32;
+        let (left, right) = (edge(self.x, width), edge(self.x + self.width, width));
+        let (top, bottom) = (edge(self.y, height), edge(self.y + self.height, height));
+        (left as i32, top as i32, right.saturating_sub(left), bottom.saturating_sub(top))
+    }
+}
+
+impl Default for GkViewport
+{
+    fn default() -> Self
+    {
+        Self::full()
+    }
+}
+
+/// ### ECameraTarget enum
+/// *What a camera draws into. Its viewport is a fraction of the target.*
+/// <pre>
+/// - Variants
+///     Swapchain                               <i>// The window.</i>
+///     Texture { width: u32, height: u32 }     <i>// An offscreen color texture that size in pixels, see VulkanRenderer::camera_texture().</i>
+/// </pre>
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ECameraTarget
+{
+    #[default]
+    Swapchain,
+    Texture { width: u32, height: u32 },
+}
+
+impl ECameraTarget
+{
+    /// ### fn ECameraTarget::extent( &self, ... ) -> (u32, u32)
+    /// *Width and height in pixels of the target, window_size for the swapchain.*
+    pub fn extent(&self, window_size: (u32, u32)) -> (u32, u32)
+    {
+        match *self
+        {
+            ECameraTarget::Swapchain => window_size,
+            ECameraTarget::Texture { width, height } => (width, height),
+        }
     }
 }
 
+/// Cameras the renderer draws each frame, one uniform slot apiece.
+pub const MAX_CAMERAS: usize = 4;
+
+#[derive(Debug, Copy, Clone)]
 pub struct GkCamera
 {
     pub view:           CamView,
     pub projection:     CamProjection,
+    pub viewport:       GkViewport,
+    pub target:         ECameraTarget,
     // proj_dirty:         bool,
 }
 
+impl GkCamera
+{
+    pub fn new(view: CamView, projection: CamProjection) -> Self
+    {
+        Self { view, projection, viewport: GkViewport::full(), target: ECameraTarget::Swapchain }
+    }
+
+    pub fn with_viewport(mut self, viewport: GkViewport) -> Self
+    {
+        self.viewport = viewport;
+        self
+    }
+
+    pub fn with_target(mut self, target: ECameraTarget) -> Self
+    {
+        self.target = target;
+        self
+    }
+}
+
 #[repr(C)]
 pub struct GkCameraUniformData
 {
@@ -78,4 +271,106 @@ impl GkCameraUniformData
         self.view = camera.view.get_matrix().as_slice()[..].try_into().unwrap();
         self.proj = camera.projection.get_matrix().as_slice()[..].try_into().unwrap();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// Depth and y of a point straight ahead of the camera, after the perspective divide.
+    fn project(projection: &CamProjection, y: f32, distance: f32) -> (f32, f32)
+    {
+        let clip = projection.get_matrix() * glm::Vec4::new(0.0, y, -distance, 1.0);
+        (clip.y / clip.w, clip.z / clip.w)
+    }
+
+    fn assert_close(actual: f32, expected: f32)
+    {
+        assert!((actual - expected).abs() < 1e-5, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn perspective_maps_near_and_far_to_the_vulkan_depth_range()
+    {
+        let fov = 90f32.to_radians();
+        let projection = CamProjection::perspective(fov, 2.0, 0.5, 50.0);
+        assert_close(project(&projection, 0.0, 0.5).1, 0.0);
+        assert_close(project(&projection, 0.0, 50.0).1, 1.0);
+        // Up in view space is up on screen, which is -y in Vulkan clip space.
+        assert_close(project(&projection, 1.0, 1.0).0, -1.0);
+
+        let reversed = projection.with_reverse_z(true);
+        assert_close(project(&reversed, 0.0, 0.5).1, 1.0);
+        assert_close(project(&reversed, 0.0, 50.0).1, 0.0);
+        assert!(project(&reversed, 0.0, 2.0).1 > project(&reversed, 0.0, 3.0).1);
+    }
+
+    #[test]
+    fn infinite_far_never_clips_distant_points()
+    {
+        let projection = CamProjection::perspective(1.0, 1.0, 0.1, 10.0).with_infinite_far(true);
+        assert_close(project(&projection, 0.0, 0.1).1, 0.0);
+        let far_away = project(&projection, 0.0, 1.0e6).1;
+        assert!(far_away < 1.0 && far_away > 0.999);
+
+        let reversed = projection.with_reverse_z(true);
+        assert_close(project(&reversed, 0.0, 0.1).1, 1.0);
+        let far_away = project(&reversed, 0.0, 1.0e6).1;
+        assert!(far_away > 0.0 && far_away < 1.0e-6);
+    }
+
+    #[test]
+    fn orthographic_is_linear_in_depth_and_ignores_distance_for_size()
+    {
+        let projection = CamProjection::orthographic(4.0, 2.0, 1.0, 11.0);
+        assert_close(project(&projection, 0.0, 1.0).1, 0.0);
+        assert_close(project(&projection, 0.0, 6.0).1, 0.5);
+        assert_close(project(&projection, 0.0, 11.0).1, 1.0);
+        assert_close(project(&projection, 2.0, 3.0).0, -1.0);
+        assert_close(project(&projection, 2.0, 9.0).0, -1.0);
+        let right_edge = projection.get_matrix() * glm::Vec4::new(4.0, 0.0, -5.0, 1.0);
+        assert_close(right_edge.x, 1.0);
+
+        let reversed = projection.with_reverse_z(true).with_infinite_far(true);
+        assert_close(project(&reversed, 0.0, 1.0).1, 1.0);
+        assert_close(project(&reversed, 0.0, 11.0).1, 0.0);
+    }
+
+    #[test]
+    fn aspect_follows_the_viewport_size()
+    {
+        let mut projection = CamProjection::perspective(1.0, 1.0, 0.1, 10.0);
+        projection.set_viewport_size(1600, 900);
+        assert_close(projection.aspect, 16.0 / 9.0);
+        projection.set_viewport_size(0, 900);
+        assert_close(projection.aspect, 16.0 / 9.0);
+    }
+
+    #[test]
+    fn grid_viewports_tile_the_window_without_gaps()
+    {
+        let (width, height) = (1001, 767);
+        let mut covered = 0;
+        for index in 0..4
+        {
+            let (x, y, w, h) = GkViewport::grid(2, 2, index).to_pixels(width, height);
+            covered += w * h;
+            assert!(x >= 0 && y >= 0 && x as u32 + w <= width && y as u32 + h <= height);
+        }
+        assert_eq!(covered, width * height);
+        assert_eq!(GkViewport::grid(2, 1, 1).to_pixels(800, 600), (400, 0, 400, 600));
+        assert_eq!(GkViewport::full().to_pixels(800, 600), (0, 0, 800, 600));
+    }
+
+    #[test]
+    fn viewports_are_fractions_of_the_camera_target()
+    {
+        let camera = GkCamera::new(CamView { pos: glm::Vec3::zeros(), front: glm::Vec3::y(), up: glm::Vec3::z() }, CamProjection::perspective(1.0, 1.0, 0.1, 10.0))
+            .with_viewport(GkViewport::grid(2, 1, 1));
+        assert_eq!(camera.target.extent((800, 600)), (800, 600));
+        let camera = camera.with_target(ECameraTarget::Texture { width: 256, height: 128 });
+        let (width, height) = camera.target.extent((800, 600));
+        assert_eq!(camera.viewport.to_pixels(width, height), (128, 0, 128, 128));
+    }
+}
@@ -8,42 +8,40 @@ use gpu_allocator::{vulkan::{
     AllocationScheme
 }, MemoryLocation};
 
-use tobj;
 
-use crate::{vk_check, log_err};
+use crate::{vk_check, vk_try, log_err};
+use crate::core::gk_error::{ GkError, GkResult };
 use crate::renderer::vulkan_renderer::gk_vulkan::vertex_data::VertexData;
-
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_context::{
-    gk_begin_single_time_vk_command_buffer, 
-    gk_end_single_time_vk_command_buffer
-};
+use crate::renderer::gk_asset_loader::{ GkMeshData, load_obj_mesh };
 
 use super::gunk_vk_context::GkVkContext;
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
 
-/// ### fn find_vk_memory_type_index( ... ) -> u32
+/// ### fn find_vk_memory_type_index( ... ) -> GkResult\<u32\>
 /// <pre>
 /// - Params
 ///     type_filter:            u32
 ///     required_properties:    vk::MemoryPropertyFlags
 ///     avail_mem_properties:   vk::PhysicalDeviceMemoryProperties
 /// - Return
-///     u32
+///     GkResult&lt;u32&gt;
 /// </pre>
-pub fn find_vk_memory_type_index(type_filter: u32, required_properties: vk::MemoryPropertyFlags, avail_mem_properties: vk::PhysicalDeviceMemoryProperties) -> u32
+pub fn find_vk_memory_type_index(type_filter: u32, required_properties: vk::MemoryPropertyFlags, avail_mem_properties: vk::PhysicalDeviceMemoryProperties) -> GkResult<u32>
 {
     for(i, memory_type) in avail_mem_properties.memory_types.iter().enumerate()
     {
         if(type_filter & (1 << i)) > 0 && memory_type.property_flags.contains(required_properties)
         {
-            return i as u32;
+            return Ok(i as u32);
         }
     }
 
     log_err!("Failed to find a suitable memory type!");
-    panic!("Failed to find a suitable memory type!");
+    Err(GkError::Unsupported(String::from("Failed to find a suitable memory type!")))
 }
 
-/// ### fn create_vk_buffer( ... ) -> (vk::Buffer, vulkan::Allocation)
+/// ### fn create_vk_buffer( ... ) -> GkResult\<(vk::Buffer, vulkan::Allocation)\>
 /// *Creates a vk::Buffer and allocates memory.*<br>
 /// <pre>
 /// <b><i>Note:</i></b>
@@ -57,14 +55,14 @@ pub fn find_vk_memory_type_index(type_filter: u32, required_properties: vk::Memo
 ///     usage:          vk::BufferUsageFlags
 ///     mem_location:   gpu_allocator::MemoryLocation    <i>// GpuOnly, CpuToGpu, GpuToCpu, Unknown</i>
 /// - Return
-///     (vk::Buffer, gpu_allocator::vulkan::Allocation)
+///     GkResult&lt;(vk::Buffer, gpu_allocator::vulkan::Allocation)&gt;
 /// </pre>
 pub fn create_vk_buffer(
         device: &ash::Device, allocator: &mut Allocator,
         label: &str, size: vk::DeviceSize,
         usage: vk::BufferUsageFlags,
         mem_location: gpu_allocator::MemoryLocation,
-    ) -> (vk::Buffer, Allocation)
+    ) -> GkResult<(vk::Buffer, Allocation)>
 {
     let buffer_info = vk::BufferCreateInfo
     {
@@ -78,7 +76,7 @@ pub fn create_vk_buffer(
         p_queue_family_indices: std::ptr::null()
     };
 
-    let buffer = unsafe{ vk_check!( device.create_buffer(&buffer_info, None) ).unwrap() };
+    let buffer = unsafe{ vk_try!( device.create_buffer(&buffer_info, None) ) };
     let mem_requirements = unsafe{ device.get_buffer_memory_requirements(buffer) };
 
     let alloc_info = AllocationCreateDesc
@@ -89,10 +87,27 @@ pub fn create_vk_buffer(
         linear: true,
         allocation_scheme: AllocationScheme::DedicatedBuffer(buffer)
     };
-    let allocation = allocator.allocate(&alloc_info).map_err( |e| { log_err!(e); } ).unwrap();
-    unsafe{ vk_check!( device.bind_buffer_memory(buffer, allocation.memory(), allocation.offset()) ).unwrap(); }
+    let allocation = match allocator.allocate(&alloc_info)
+    {
+        Ok(allocation) => allocation,
+        Err(e) =>
+        {
+            unsafe{ device.destroy_buffer(buffer, None); }
+            let err = GkError::from(e);
+            log_err!(err);
+            return Err(err);
+        }
+    };
+    if let Err(e) = unsafe{ device.bind_buffer_memory(buffer, allocation.memory(), allocation.offset()) }
+    {
+        unsafe{ device.destroy_buffer(buffer, None); }
+        vk_check!( allocator.free(allocation) );
+        let err = GkError::from(e);
+        log_err!(err);
+        return Err(err);
+    }
 
-    (buffer, allocation)
+    Ok((buffer, allocation))
 }
 
 /// ### fn map_vk_allocation_data\<T\>( ... )
@@ -120,15 +135,18 @@ pub fn map_vk_allocation_data<T>(allocation: &Allocation, data: &[T], count: usi
 ///     allocation:     &Allocation
 ///     data:           *const c_void
 ///     size:           vk::DeviceSize      <i>// size of data in bytes</i>
+/// - Return
+///     GkResult&lt;()&gt;
 /// </pre>
-pub fn map_vk_buffer_data(device: &ash::Device, allocation: &Allocation, data: &[u8], size: vk::DeviceSize)
+pub fn map_vk_buffer_data(device: &ash::Device, allocation: &Allocation, data: &[u8], size: vk::DeviceSize) -> GkResult<()>
 {
     unsafe
     {
-        let mapped_ptr = vk_check!( device.map_memory(allocation.memory(), allocation.offset(), size, MemoryMapFlags::empty()) ).unwrap() as *mut u8;
+        let mapped_ptr = vk_try!( device.map_memory(allocation.memory(), allocation.offset(), size, MemoryMapFlags::empty()) ) as *mut u8;
             mapped_ptr.copy_from_nonoverlapping(data.as_ptr(), size as usize);
         device.unmap_memory(allocation.memory());
     }
+    Ok(())
 }
 
 /// ### fn copy_vk_buffer( ... )
@@ -155,22 +173,63 @@ pub fn copy_vk_buffer(device: &ash::Device, cmd_buffer: &vk::CommandBuffer, src_
     }
 }
 
-/// ### GkVkBuffer\<T\> struct
+/// ### GkVkBuffer struct
+/// *Owns a vk::Buffer and its memory.*<br>
+/// *Dropping it queues both for destruction once the frames in flight that may use them have finished.*
 /// <pre>
 /// - Members
 ///     handle:         vk::Buffer
 ///     allocation:     vulkan::Allocation
-///     count:          usize                   <i>// count of type T</i>
-///     data_type:      PhantomData&lt;T&gt;          <i>// private zero size placeholder for type</i>
+///     size:           vk::DeviceSize
+///     id:             u64                     <i>// private, id in the allocation registry</i>
+///     shared:         GkVkSharedDevice        <i>// private</i>
 /// </pre>
 pub struct GkVkBuffer
 {
     pub handle:         vk::Buffer,
     pub allocation:     Allocation,
     pub size:           vk::DeviceSize,
+    id:                 u64,
+    shared:             GkVkSharedDevice,
 }
 
-/// ### fn gk_create_vk_buffer( ... ) -> GkVkBuffer
+impl GkVkBuffer
+{
+    /// ### fn GkVkBuffer::from_raw( ... ) -> GkVkBuffer
+    /// *Takes ownership of a buffer made with create_vk_buffer(), tracking it under label.*
+    /// <pre>
+    /// - Params
+    ///     shared:         &GkVkSharedDevice
+    ///     label:          &str
+    ///     location:       MemoryLocation  <i>// The location allocation was made with.</i>
+    ///     handle:         vk::Buffer
+    ///     allocation:     vulkan::Allocation
+    ///     size:           vk::DeviceSize
+    /// - Return
+    ///     GkVkBuffer
+    /// </pre>
+    pub fn from_raw(shared: &GkVkSharedDevice, label: &str, location: MemoryLocation, handle: vk::Buffer, allocation: Allocation, size: vk::DeviceSize) -> Self
+    {
+        let id = shared.track_allocation(label, location, &allocation);
+        shared.set_object_name(handle, label);
+        Self { handle, allocation, size, id, shared: shared.clone() }
+    }
+}
+
+impl Drop for GkVkBuffer
+{
+    fn drop(&mut self)
+    {
+        self.shared.defer(EGkVkDeferred::Buffer
+        {
+            handle: self.handle,
+            allocation: std::mem::take(&mut self.allocation),
+            id: self.id
+        });
+    }
+}
+
+/// ### fn gk_create_vk_buffer( ... ) -> GkResult\<GkVkBuffer\>
 /// *Creates a instance of GkVkBuffer*
 /// <pre>
 /// - Params
@@ -180,7 +239,7 @@ pub struct GkVkBuffer
 ///     mem_location:   MemoryLocation          <i>// CpuToGpu, GpuOnly, GpuToCpu, Unknown
 ///     size:           vk::DeviceSize          <i>// the size of the buffer in bytes
 /// - Return
-///     GkVkBuffer
+///     GkResult&lt;GkVkBuffer&gt;
 /// </pre>
 pub fn gk_create_vk_buffer(
         vk_ctx: &mut GkVkContext,
@@ -188,25 +247,18 @@ pub fn gk_create_vk_buffer(
         usage: vk::BufferUsageFlags,
         mem_location: MemoryLocation,
         size: vk::DeviceSize
-    ) -> GkVkBuffer
+    ) -> GkResult<GkVkBuffer>
 {
-    let handle: vk::Buffer;
-    let allocation: Allocation;
-    (handle, allocation) = create_vk_buffer(
-        &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), 
+    let (handle, allocation) = create_vk_buffer(
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(), 
         label, size, 
         usage, mem_location
-    );
+    )?;
 
-    GkVkBuffer
-    {
-        handle,
-        allocation,
-        size
-    }
+    Ok(GkVkBuffer::from_raw(&vk_ctx.shared, label, mem_location, handle, allocation, size))
 }
 
-/// ### fn gk_create_vk_buffers( ... ) -> Vec\<GkVkBuffer\>
+/// ### fn gk_create_vk_buffers( ... ) -> GkResult\<Vec\<GkVkBuffer\>\>
 /// *Creates {count} number of GkVkBuffer in Vec\<\>*
 /// <pre>
 /// - Params
@@ -217,7 +269,7 @@ pub fn gk_create_vk_buffer(
 ///     size:           vk::DeviceSize          <i>// the size of the buffer in bytes
 ///     count:          usize
 /// - Return
-///     Vec&lt;GkVkBuffer&gt;
+///     GkResult&lt;Vec&lt;GkVkBuffer&gt;&gt;
 /// </pre>
 pub fn gk_create_vk_buffers(
     vk_ctx: &mut GkVkContext,
@@ -225,73 +277,54 @@ pub fn gk_create_vk_buffers(
     usage: vk::BufferUsageFlags,
     mem_location: MemoryLocation,
     size: vk::DeviceSize, count: usize
-) -> Vec<GkVkBuffer>
+) -> GkResult<Vec<GkVkBuffer>>
 {
     let mut gk_vk_buffers: Vec<GkVkBuffer> = Vec::new();
 
     for _i in 0..count
     {
-        let handle: vk::Buffer;
-        let allocation: Allocation;
-        (handle, allocation) = create_vk_buffer(
-            &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), 
-            label, size, 
-            usage, mem_location
-        );
-    
-        gk_vk_buffers.push(
-            GkVkBuffer
-            {
-                handle,
-                allocation,
-                size
-            }
-        );
+        gk_vk_buffers.push(gk_create_vk_buffer(vk_ctx, label, usage, mem_location, size)?);
     }
 
-    gk_vk_buffers
+    Ok(gk_vk_buffers)
 }
 
-/// ### fn gk_destroy_vk_buffer( ... )
-/// *Consumes an instance of GkVkBuffer and frees its resources*
-/// <pre>
-/// - Params
-///     vk_ctx:     <b>&mut</b> GkVkContext
-///     buffer:     GkVkBuffer
-/// </pre>
-pub fn gk_destroy_vk_buffer(vk_ctx: &mut GkVkContext, buffer: GkVkBuffer)
+pub fn gk_create_vk_uniform_buffer<T>(vk_ctx: &mut GkVkContext) -> GkResult<GkVkBuffer>
 {
-    unsafe
-    {
-        vk_ctx.device.destroy_buffer(buffer.handle, None);
-    }
-    vk_check!( vk_ctx.allocator.as_mut().unwrap().free(buffer.allocation) ).unwrap();
+    let size = std::mem::size_of::<T>() as u64;
+    gk_create_vk_buffer(vk_ctx, "uniform buffer", vk::BufferUsageFlags::UNIFORM_BUFFER | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::CpuToGpu, size)
 }
 
-pub fn gk_create_vk_uniform_buffer<T>(vk_ctx: &mut GkVkContext) -> GkVkBuffer
+/// ### fn as_vk_bytes\<T\>( ... ) -> &[u8]
+/// *Reinterprets a slice of plain data as bytes for uploading.*
+/// <pre>
+/// - Params
+///     data:       &[T]
+/// - Return
+///     &[u8]
+/// </pre>
+pub fn as_vk_bytes<T>(data: &[T]) -> &[u8]
 {
-    let size = std::mem::size_of::<T>() as u64;
-    gk_create_vk_buffer(vk_ctx, "uniform buffer", vk::BufferUsageFlags::UNIFORM_BUFFER | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::CpuToGpu, size)
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
 }
 
-/// ### fn gk_destroy_vk_buffers( ... )
-/// *Traverses a Vec\<\> of GkVkBuffer and frees its resources*
+/// ### fn vk_buffer_usage_access( ... ) -> vk::AccessFlags
+/// *Access flags the graphics queue reads a buffer with, given its usage.*
 /// <pre>
 /// - Params
-///     vk_ctx:     <b>&mut</b> GkVkContext
-///     buffers:    Vec&lt;GkVkBuffer&gt;
+///     usage:      vk::BufferUsageFlags
+/// - Return
+///     vk::AccessFlags
 /// </pre>
-pub fn gk_destroy_vk_buffers(vk_ctx: &mut GkVkContext, buffers: &mut Vec<GkVkBuffer>)
+pub fn vk_buffer_usage_access(usage: vk::BufferUsageFlags) -> vk::AccessFlags
 {
-    for _i in 0..buffers.len()
-    {
-        let buffer = buffers.pop().unwrap();
-        unsafe
-        {
-            vk_ctx.device.destroy_buffer(buffer.handle, None);
-        }
-        vk_check!( vk_ctx.allocator.as_mut().unwrap().free(buffer.allocation) ).unwrap();
-    }
+    let mut access = vk::AccessFlags::empty();
+    if usage.contains(vk::BufferUsageFlags::VERTEX_BUFFER) { access |= vk::AccessFlags::VERTEX_ATTRIBUTE_READ; }
+    if usage.contains(vk::BufferUsageFlags::INDEX_BUFFER) { access |= vk::AccessFlags::INDEX_READ; }
+    if usage.contains(vk::BufferUsageFlags::UNIFORM_BUFFER) { access |= vk::AccessFlags::UNIFORM_READ; }
+    if usage.contains(vk::BufferUsageFlags::STORAGE_BUFFER) { access |= vk::AccessFlags::SHADER_READ; }
+    if usage.contains(vk::BufferUsageFlags::INDIRECT_BUFFER) { access |= vk::AccessFlags::INDIRECT_COMMAND_READ; }
+    access
 }
 
 /// ### fn gk_create_vk_array_buffer<T>( ... ) ###
@@ -303,44 +336,33 @@ pub fn gk_destroy_vk_buffers(vk_ctx: &mut GkVkContext, buffers: &mut Vec<GkVkBuf
 ///     usage:      vk::BufferUsageFlags
 ///     data:       &Vec&lt;T&gt;
 /// - Return
-///     GkVkBuffer
+///     GkResult&lt;GkVkBuffer&gt;
 /// </pre>
 pub fn gk_create_vk_array_buffer<T>(
         vk_ctx: &mut GkVkContext,
         label: &str,
         usage: vk::BufferUsageFlags,
         data: &Vec<T>
-    ) -> GkVkBuffer
+    ) -> GkResult<GkVkBuffer>
 {
     let buffer_size = std::mem::size_of::<T>() * data.len();
-    let staging_label = format!("staging {}", label);
-    let staging = gk_create_vk_buffer(
-        vk_ctx, staging_label.as_str(), 
-        usage | vk::BufferUsageFlags::TRANSFER_SRC, MemoryLocation::CpuToGpu, 
-        buffer_size as vk::DeviceSize
-    );
-
-    unsafe{
-        let mapped_ptr = staging.allocation.mapped_ptr().unwrap().as_ptr() as *mut T;
-        mapped_ptr.copy_from_nonoverlapping(data.as_ptr(), data.len());
-    }
 
     let buffer = gk_create_vk_buffer(
         vk_ctx, label, 
         usage | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::GpuOnly, 
         buffer_size as vk::DeviceSize
-    );
-
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-        copy_vk_buffer(&vk_ctx.device, &cmd_buffer, &staging.handle, &buffer.handle, buffer_size as vk::DeviceSize);
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
+    )?;
 
-    gk_destroy_vk_buffer(vk_ctx, staging);
+    vk_ctx.uploader.upload_buffer(
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(),
+        buffer.handle, as_vk_bytes(data.as_slice()),
+        vk_buffer_usage_access(usage)
+    )?;
 
-    buffer
+    Ok(buffer)
 }
 
-/// ### fn gk_create_vk_vertex_buffer_from_file\<T\>( ... ) -> GkVkBuffer
+/// ### fn gk_create_vk_vertex_buffer_from_file( ... ) -> GkResult\<(GkVkBuffer, GkVkBuffer)\>
 /// *Reads 3d format file and creates a vertex buffer using type T*<br>
 /// *T is Type of Vertex Data*<br>
 /// *Populates vert and index buffer sizes*
@@ -351,106 +373,41 @@ pub fn gk_create_vk_array_buffer<T>(
 ///     usage:              vk::BufferUsageFlags
 ///     file_path:          &path::Path
 /// - Return
-///     (GkVkBuffer, GkVkBuffer) <i>// (vertex_buffer, index_buffer)
+///     GkResult&lt;(GkVkBuffer, GkVkBuffer)&gt; <i>// (vertex_buffer, index_buffer)
 /// </pre>
 pub fn gk_create_vk_vertex_buffer_from_file(
         vk_ctx: &mut GkVkContext, 
         label: &str, 
         file_path: &std::path::Path,
-    ) -> (Option<GkVkBuffer>, Option<GkVkBuffer>)
+    ) -> GkResult<(GkVkBuffer, GkVkBuffer)>
 {
-    let load_options = tobj::LoadOptions { 
-        single_index: true, 
-        triangulate: false, 
-        ignore_points: true, 
-        ignore_lines: true 
-    };
-    let (models, _materials) = match tobj::load_obj(file_path, &load_options)
-    {
-        Ok(data) => 
-        {
-            if let Ok(materials) = data.1 {
-                (data.0, Some(materials))
-            } else {
-                (data.0, None)
-            }
-        },
-        Err(err) => { log_err!(err.to_string()); return (None, None); }
-    };
-
-    let mut vertices: Vec<VertexData> = vec![];
-    let mut indices: Vec<u32> = vec![];
-
-    for m in models.iter()
-    {
-        let mesh = &m.mesh;
+    let mesh = load_obj_mesh(file_path)?;
+    gk_create_vk_vertex_buffer_from_mesh(vk_ctx, label, &mesh)
+}
 
-        if mesh.texcoords.len() == 0
-        {
-            log_err!(format!("Missing texture coordinates for {}", file_path.to_str().unwrap()));
-            return (None, None);
-        }
-        
-        let total_vertices_count = mesh.positions.len() / 3;
-        for i in 0..total_vertices_count
-        {
-            let vertex = VertexData
-            {
-                pos: [
-                    mesh.positions[i * 3],
-                    mesh.positions[i * 3 + 1],
-                    mesh.positions[i * 3 + 2]
-                ],
-                color: [ 0.5, 0.5, 0.5 ],
-                tex_coord: [
-                    mesh.texcoords[ i * 2 ],
-                    -mesh.texcoords[ i * 2 + 1]
-                ]
-            };
-            vertices.push(vertex);
-        }
-        indices = mesh.indices.clone();
-    }
+/// ### fn gk_create_vk_vertex_buffer_from_mesh( ... ) -> GkResult\<(GkVkBuffer, GkVkBuffer)\>
+/// *Creates a vertex and an index buffer from decoded mesh data.*<br>
+/// *The uploads are queued on vk_ctx.uploader and do not block.*
+/// <pre>
+/// - Params
+///     vk_ctx:             <b>&mut</b> GkVkContext
+///     label:              &str                    <i>// Used for debug purposes</i>
+///     mesh:               &GkMeshData
+/// - Return
+///     GkResult&lt;(GkVkBuffer, GkVkBuffer)&gt; <i>// (vertex_buffer, index_buffer)</i>
+/// </pre>
+pub fn gk_create_vk_vertex_buffer_from_mesh(
+        vk_ctx: &mut GkVkContext,
+        label: &str,
+        mesh: &GkMeshData
+    ) -> GkResult<(GkVkBuffer, GkVkBuffer)>
+{
+    let vertices = &mesh.vertices;
+    let indices = &mesh.indices;
 
     let vert_buffer_size = std::mem::size_of::<VertexData>() * vertices.len();
     let index_buffer_size = std::mem::size_of::<u32>() * indices.len();
 
-    // let buffer_size = (vert_buffer_size + index_buffer_size) as vk::DeviceSize;
-    let staging_vert_label = String::from(format!("staging vert{}", label));
-
-    let staging_vertex = gk_create_vk_buffer(
-        vk_ctx, 
-        &staging_vert_label, 
-        vk::BufferUsageFlags::TRANSFER_SRC, 
-        MemoryLocation::CpuToGpu, 
-        vert_buffer_size as vk::DeviceSize
-    );
-
-    unsafe
-    {
-        // let mapped_ptr = vk_check!( vk_ctx.device.map_memory(staging_vertex.allocation.memory(), staging_vertex.allocation.offset(), vert_buffer_size as vk::DeviceSize, MemoryMapFlags::empty()) ).unwrap() as *mut u8;
-        let mapped_ptr = staging_vertex.allocation.mapped_slice().unwrap().as_ptr() as *mut u8;
-            mapped_ptr.copy_from_nonoverlapping(vertices.as_slice().as_ptr() as *const u8, vert_buffer_size);
-        // vk_ctx.device.unmap_memory(staging_vertex.allocation.memory());
-    }
-
-    let staging_index_label = String::from(format!("staging index{}", label));
-    let staging_indices = gk_create_vk_buffer(
-        vk_ctx, 
-        &staging_index_label, 
-        vk::BufferUsageFlags::TRANSFER_SRC,
-        MemoryLocation::CpuToGpu,
-        index_buffer_size as vk::DeviceSize
-    );
-
-    unsafe
-    {
-        // let mapped_ptr = vk_check!( vk_ctx.device.map_memory(staging_indices.allocation.memory(), staging_indices.allocation.offset(), index_buffer_size as vk::DeviceSize, MemoryMapFlags::empty()) ).unwrap() as *mut u8;
-        let mapped_ptr = staging_indices.allocation.mapped_slice().unwrap().as_ptr() as *mut u8;
-            mapped_ptr.copy_from_nonoverlapping(indices.as_slice().as_ptr() as *const u8, index_buffer_size);
-        // vk_ctx.device.unmap_memory(staging_indices.allocation.memory());
-    }
-
     let vert_label = String::from(format!("vertex {}", label));
     let vert_buffer = gk_create_vk_buffer(
         vk_ctx,
@@ -458,7 +415,7 @@ pub fn gk_create_vk_vertex_buffer_from_file(
         vk::BufferUsageFlags::VERTEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
         MemoryLocation::GpuOnly,
         vert_buffer_size as vk::DeviceSize
-    );
+    )?;
 
     let index_label = String::from(format!("index {}", label));
     let index_buffer = gk_create_vk_buffer(
@@ -467,80 +424,21 @@ pub fn gk_create_vk_vertex_buffer_from_file(
         vk::BufferUsageFlags::INDEX_BUFFER | vk::BufferUsageFlags::TRANSFER_DST,
         MemoryLocation::GpuOnly,
         index_buffer_size as vk::DeviceSize
-    );
-
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-        copy_vk_buffer(&vk_ctx.device, &cmd_buffer, &staging_vertex.handle, &vert_buffer.handle, vert_buffer_size as vk::DeviceSize);
-        copy_vk_buffer(&vk_ctx.device, &cmd_buffer, &staging_indices.handle, &index_buffer.handle, index_buffer_size as vk::DeviceSize);
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
-
-    gk_destroy_vk_buffer(vk_ctx, staging_vertex);
-    gk_destroy_vk_buffer(vk_ctx, staging_indices);
-
-    (Some(vert_buffer), Some(index_buffer))
-}
-
-pub struct GkVkVertStorageBuffer
-{
-    pub handle:         vk::Buffer,
-    pub allocation:     Allocation,
-    pub size:           vk::DeviceSize,
-    pub verts_size:      usize,
-    pub indices_size:   usize,
-}
-
-pub fn gk_create_vk_vertex_storage_buffer<T>(
-        vk_ctx: &mut GkVkContext,
-        label: &str,
-        verts: &Vec<T>,
-        indices: Option<&Vec<u32>>
-    ) -> GkVkVertStorageBuffer
-{
-    let verts_size = std::mem::size_of::<T>() * verts.len();
-    let mut indices_size: usize = 0;
-    if indices.is_some()
-    {
-        indices_size = std::mem::size_of::<u32>() * indices.unwrap().len();
-    }
-    let buffer_size = verts_size + indices_size;
-
-    let staging_label = format!("staging {}", label);
-    let staging = gk_create_vk_buffer(
-        vk_ctx, staging_label.as_str(), 
-        vk::BufferUsageFlags::TRANSFER_SRC, 
-        MemoryLocation::CpuToGpu, 
-        buffer_size as vk::DeviceSize
-    );
-
-    unsafe{
-        // let mapped_ptr = staging.allocation.mapped_ptr().unwrap().as_ptr() as *mut T;
-        let mapped_ptr = staging.allocation.mapped_ptr().unwrap().as_ptr() as *mut u8;
-        // mapped_ptr.copy_from_nonoverlapping(verts.as_ptr(), verts.len());
-        std::ptr::copy_nonoverlapping(verts.as_ptr() as * const u8, mapped_ptr, verts_size);
-        std::ptr::copy(indices.unwrap().as_ptr() as * const u8, mapped_ptr.add(verts_size), indices_size);
-    }
-
-    let buffer = gk_create_vk_buffer(
-        vk_ctx, label, 
-        vk::BufferUsageFlags::STORAGE_BUFFER | vk::BufferUsageFlags::TRANSFER_DST, MemoryLocation::GpuOnly, 
-        buffer_size as vk::DeviceSize
-    );
-
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-        copy_vk_buffer(&vk_ctx.device, &cmd_buffer, &staging.handle, &buffer.handle, buffer_size as vk::DeviceSize);
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
-
-    gk_destroy_vk_buffer(vk_ctx, staging);
-
-    // buffer
-    GkVkVertStorageBuffer { handle: buffer.handle, allocation: buffer.allocation, size: buffer.size, verts_size, indices_size }
+    )?;
+
+    let mut allocator = vk_ctx.shared.allocator();
+    vk_ctx.uploader.upload_buffer(
+        &vk_ctx.device, &mut allocator,
+        vert_buffer.handle, as_vk_bytes(vertices.as_slice()),
+        vk::AccessFlags::VERTEX_ATTRIBUTE_READ
+    )?;
+    vk_ctx.uploader.upload_buffer(
+        &vk_ctx.device, &mut allocator,
+        index_buffer.handle, as_vk_bytes(indices.as_slice()),
+        vk::AccessFlags::INDEX_READ
+    )?;
+    drop(allocator);
+
+    Ok((vert_buffer, index_buffer))
 }
 
-pub fn gk_destroy_vk_vertex_storage_buffer(vk_ctx: &mut GkVkContext, buffer: GkVkVertStorageBuffer)
-{
-    unsafe
-    {
-        vk_ctx.device.destroy_buffer(buffer.handle, None);
-    }
-    vk_check!( vk_ctx.allocator.as_mut().unwrap().free(buffer.allocation) ).unwrap();
-}
\ No newline at end of file
@@ -1,13 +1,17 @@
-use ash::{ vk, Device };
-use gpu_allocator::vulkan::Allocator;
+use ash::{ vk, Device, prelude::VkResult };
 
-use crate::{log_info, vk_check};
+use crate::{log_info, log_warn, vk_try};
+use crate::core::gk_error::{ GkError, GkResult };
 
 use super::gunk_vk_render_pass::GkVkRenderPass;
 use super::vk_utils::*;
 use super::gunk_vk_img::create_vk_image_view;
 
 use super::gunk_vk_loader::GkVkLoader;
+use super::gunk_vk_upload::GkVkUploader;
+use super::gunk_vk_device::GkVkSharedDevice;
+use super::gunk_vk_memory::GkVkMemoryReport;
+use super::gunk_vk_profiler::GkVkGpuProfiler;
 
 /// ### GkVkQueue struct
 /// *Contain Vulkan queue family index and a VkQueue handle*
@@ -46,10 +50,12 @@ impl GkVkQueue
 /// <pre>
 /// - Members
 ///     graphics:       GkVkQueue       <i>// GkVkQueue for graphics family of instructrions.</i>
+///     transfer:       GkVkQueue       <i>// Dedicated transfer family. Index is None if the device has none.</i>
 /// </pre>
 pub struct GkVkQueues
 {
     pub graphics:   GkVkQueue,
+    pub transfer:   GkVkQueue,
 }
 
 impl GkVkQueues
@@ -66,7 +72,8 @@ impl GkVkQueues
     { 
         Self
         { 
-            graphics: GkVkQueue::new() 
+            graphics: GkVkQueue::new(),
+            transfer: GkVkQueue::new()
         } 
     } 
 
@@ -91,6 +98,11 @@ impl GkVkQueues
             {
                 self.graphics.index = Some(index);
             }
+            // A transfer family without graphics capabilities runs copies alongside rendering.
+            else if queue_family.queue_flags.contains(vk::QueueFlags::TRANSFER) && self.transfer.index.is_none()
+            {
+                self.transfer.index = Some(index);
+            }
             index += 1;
         }
     }
@@ -104,12 +116,29 @@ impl GkVkQueues
     /// </pre>
     pub fn query_queues(&mut self, device: &ash::Device)
     {
-        if self.graphics.index.is_some()
+        if let Some(graphics_index) = self.graphics.index
+        {
+            self.graphics.handle = unsafe { device.get_device_queue(graphics_index, 0) };
+        }
+        if let Some(transfer_index) = self.transfer.index
         {
-            self.graphics.handle = unsafe { device.get_device_queue(self.graphics.index.clone().unwrap(), 0) };
+            self.transfer.handle = unsafe { device.get_device_queue(transfer_index, 0) };
         }
     }
 
+    /// ### fn GkVkQueues::has_dedicated_transfer( &self ) -> bool
+    /// *True when a transfer only queue family was found.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    /// - Return
+    ///     bool
+    /// </pre>
+    pub fn has_dedicated_transfer(&self) -> bool
+    {
+        self.transfer.index.is_some() && self.transfer.index != self.graphics.index
+    }
+
     /// ### fn get_index_list( &self ) -> Vec\<u32\>
     /// *Returns a list (Vec\<u32\>) of the found VkQueueFamilyIndices*
     /// <pre>
@@ -121,9 +150,13 @@ impl GkVkQueues
     pub fn get_index_list(&self) -> Vec<u32>
     {
         let mut index_list: Vec<u32> = vec![];
-        if self.graphics.index.is_some()
+        if let Some(graphics_index) = self.graphics.index
         {
-            index_list.push(self.graphics.index.clone().unwrap());
+            index_list.push(graphics_index);
+        }
+        if let Some(transfer_index) = self.transfer.index.filter(|_| self.has_dedicated_transfer())
+        {
+            index_list.push(transfer_index);
         }
         index_list
     }
@@ -149,13 +182,14 @@ pub struct GkVkSwapchain
     pub images:     Vec<vk::Image>,
     pub views:      Vec<vk::ImageView>,
     pub format:     vk::Format,
-    pub extent:     vk::Extent2D
+    pub extent:     vk::Extent2D,
+    pub present_mode:   vk::PresentModeKHR,
 }
 
 impl GkVkSwapchain
 {
 
-    /// ### fn GkVkSWapchain::new( ... ) -> GkVkSwapchain
+    /// ### fn GkVkSWapchain::new( ... ) -> GkResult\<GkVkSwapchain\>
     /// *Creates an instance of GkVkSwapchain.*
     /// <pre>
     /// - Param
@@ -165,43 +199,45 @@ impl GkVkSwapchain
     ///     queue_indices:      &Vec&lt;u32&gt;
     ///     width:              u32
     ///     height:             u32
+    ///     b_vsync:            bool
     /// - Return
-    ///     GkVkSwapchain
+    ///     GkResult&lt;GkVkSwapchain&gt;
     /// </pre>
-    pub fn new(loader: &GkVkLoader, device: &ash::Device, physical_device: &vk::PhysicalDevice, queue_indices: &Vec<u32>,  width: u32, height: u32) -> Self
+    pub fn new(loader: &GkVkLoader, device: &ash::Device, physical_device: &vk::PhysicalDevice, queue_indices: &Vec<u32>,  width: u32, height: u32, b_vsync: bool) -> GkResult<Self>
     {
         log_info!("Creating VulkanSwapchain struct...");
-        let details = query_vk_swapchain_details(physical_device, &loader.surface);
+        let details = query_vk_swapchain_details(physical_device, &loader.surface)?;
     
         let format = choose_vk_swap_surface_format(details.formats);
-        let present_mode = choose_vk_swap_present_mode(details.present_modes);
+        let present_mode = choose_vk_swap_present_mode(details.present_modes, b_vsync);
         // let extent = vk::Extent2D{ width: width, height: height };
         let extent = choose_vk_swap_image_extent(&details.capabilities, width, height);
 
-        let (loader, handle) = create_vk_swapchain(&loader.instance, device, &loader.surface, queue_indices, &details.capabilities, &format, &present_mode, &extent);
+        let (loader, handle) = create_vk_swapchain(&loader.instance, device, &loader.surface, queue_indices, &details.capabilities, &format, &present_mode, &extent)?;
         let images = unsafe 
         {
-            vk_check!( loader.get_swapchain_images(handle) ).unwrap()
+            vk_try!( loader.get_swapchain_images(handle) )
         };
 
         let mut views: Vec<vk::ImageView> = vec![];
         for image in images.iter()
         {
-            let view = create_vk_image_view(device, image, &format.format, vk::ImageAspectFlags::COLOR, vk::ImageViewType::TYPE_2D, 1, 1);
+            let view = create_vk_image_view(device, image, &format.format, vk::ImageAspectFlags::COLOR, vk::ImageViewType::TYPE_2D, 1, 1)?;
             views.push(view);
         }
 
         log_info!("VulkanSwapchain struct created");
 
-        Self
+        Ok(Self
         {
             loader,
             handle,
             images,
             views,
             format: format.format,
-            extent
-        }
+            extent,
+            present_mode,
+        })
     }
 
     /// ### fn GkVkSwapchain::destroy( &self, ... )
@@ -241,7 +277,7 @@ pub struct  GkVkCommands
 
 impl GkVkCommands
 {
-    /// ### fn GkVkCommands::new( ... ) -> GkVkCommands
+    /// ### fn GkVkCommands::new( ... ) -> GkResult\<GkVkCommands\>
     /// *Creates an instance of GkVkCommands.*
     /// <pre>
     /// - Params
@@ -249,15 +285,15 @@ impl GkVkCommands
     ///     queue_family_index:     u32
     ///     buffer_count:           u32
     /// - Return
-    ///     GkVkCommands
+    ///     GkResult&lt;GkVkCommands&gt;
     /// </pre>
-    pub fn new(device: &ash::Device, queue_family_index: u32, buffer_count: u32) -> Self
+    pub fn new(device: &ash::Device, queue_family_index: u32, buffer_count: u32) -> GkResult<Self>
     {
-        let pool = create_vk_command_pool(device, queue_family_index);
-        let buffers = allocate_vk_command_buffers(device, &pool, buffer_count);
+        let pool = create_vk_command_pool(device, queue_family_index)?;
+        let buffers = allocate_vk_command_buffers(device, &pool, buffer_count)?;
 
         // Self{ pool, buffers, current_frame_index: 0 }
-        Self{ pool, buffers }
+        Ok(Self{ pool, buffers })
     }
 
     /// ### fn GkVkCommands::destroy( &self, ... )
@@ -293,18 +329,32 @@ impl GkVkCommands
     // }
 }
 
+/// ### GkVkFrameSync struct
+/// *Synchronization objects for each frame in flight.*
+/// <pre>
+/// - Members
+///     wait_semaphores:    Vec&lt;vk::Semaphore&gt;     <i>// Signaled when the swapchain image is acquired.</i>
+///     render_semaphores:  Vec&lt;vk::Semaphore&gt;     <i>// Signaled when rendering is done, waited on by present.</i>
+///     in_flight_fences:   Vec&lt;vk::Fence&gt;         <i>// Signaled when a frame's command buffer completes.</i>
+///     images_in_flight:   Vec&lt;vk::Fence&gt;         <i>// Fence of the frame using each swapchain image, or null.</i>
+///     frames_in_flight:   usize
+///     current_frame_index: usize
+///     frame_number:       u64                     <i>// Total frames started.</i>
+/// </pre>
 pub struct GkVkFrameSync
 {
     pub wait_semaphores:    Vec<vk::Semaphore>,
     pub render_semaphores:  Vec<vk::Semaphore>,
     pub in_flight_fences:   Vec<vk::Fence>,
+    pub images_in_flight:   Vec<vk::Fence>,
     frames_in_flight:       usize,
     current_frame_index:    usize,
+    frame_number:           u64,
 }
 
 impl GkVkFrameSync
 {
-    pub fn new(device: &ash::Device, frames_in_flight: usize) -> Self
+    pub fn new(device: &ash::Device, frames_in_flight: usize) -> GkResult<Self>
     {
         let mut wait_semaphores: Vec<vk::Semaphore> = Vec::new();
         let mut render_semaphores: Vec<vk::Semaphore> = Vec::new();
@@ -312,19 +362,21 @@ impl GkVkFrameSync
 
         for _i in 0..frames_in_flight
         {
-            wait_semaphores.push(create_vk_semaphore(device));
-            render_semaphores.push(create_vk_semaphore(device));
-            in_flight_fences.push(create_vk_fence(device, true));
+            wait_semaphores.push(create_vk_semaphore(device)?);
+            render_semaphores.push(create_vk_semaphore(device)?);
+            in_flight_fences.push(create_vk_fence(device, true)?);
         }
 
-        Self
+        Ok(Self
         {
             wait_semaphores,
             render_semaphores,
             in_flight_fences,
+            images_in_flight: Vec::new(),
             frames_in_flight,
-            current_frame_index: 0
-        }
+            current_frame_index: 0,
+            frame_number: 0
+        })
     }
 
     pub fn destroy(&mut self, device: &ash::Device)
@@ -356,6 +408,32 @@ impl GkVkFrameSync
     pub fn set_next_frame_index(&mut self)
     {
         self.current_frame_index = (self.current_frame_index + 1) % self.frames_in_flight;
+        self.frame_number += 1;
+    }
+
+    pub fn get_frame_number(&self) -> u64
+    {
+        self.frame_number
+    }
+
+    /// ### fn GkVkFrameSync::get_completed_frame_number( &self ) -> Option\<u64\>
+    /// *Newest frame guaranteed to be finished on the gpu,<br>
+    /// once the current in flight fence has been waited on.*
+    pub fn get_completed_frame_number(&self) -> Option<u64>
+    {
+        self.frame_number.checked_sub(self.frames_in_flight as u64)
+    }
+
+    /// ### fn GkVkFrameSync::reset_images_in_flight( &mut self, ... )
+    /// *Forgets which frame uses each swapchain image. Call when the swapchain is (re)created.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     image_count:    usize
+    /// </pre>
+    pub fn reset_images_in_flight(&mut self, image_count: usize)
+    {
+        self.images_in_flight = vec![vk::Fence::null(); image_count];
     }
 
     pub fn get_current_wait_semaphore(&self) -> &vk::Semaphore
@@ -374,92 +452,270 @@ impl GkVkFrameSync
     }
 }
 
+/// Objects of a GkVkContext being created by GkVkContext::new().<br>
+/// Until finish() hands them over, dropping it destroys them and the device in reverse creation order.
+struct GkVkPartialContext
+{
+    device:     Option<Device>,
+    shared:     Option<GkVkSharedDevice>,
+    uploader:   Option<GkVkUploader>,
+    swapchain:  Option<GkVkSwapchain>,
+    frame_sync: Option<GkVkFrameSync>,
+    draw_cmds:  Option<GkVkCommands>,
+}
+
+impl GkVkPartialContext
+{
+    fn new(device: Device) -> Self
+    {
+        Self
+        {
+            device: Some(device),
+            shared: None,
+            uploader: None,
+            swapchain: None,
+            frame_sync: None,
+            draw_cmds: None,
+        }
+    }
+
+    /// Returns the objects once all of them exist. The device is then owned by the context.
+    fn finish(mut self) -> (GkVkSharedDevice, GkVkUploader, GkVkSwapchain, GkVkFrameSync, GkVkCommands)
+    {
+        self.device = None;
+        (
+            self.shared.take().unwrap(),
+            self.uploader.take().unwrap(),
+            self.swapchain.take().unwrap(),
+            self.frame_sync.take().unwrap(),
+            self.draw_cmds.take().unwrap(),
+        )
+    }
+}
+
+impl Drop for GkVkPartialContext
+{
+    fn drop(&mut self)
+    {
+        let Some(device) = self.device.take() else { return; };
+        log_warn!("Destroying a partially created VulkanContext");
+
+        if let Some(draw_cmds) = self.draw_cmds.take() { draw_cmds.destroy(&device); }
+        if let Some(mut frame_sync) = self.frame_sync.take() { frame_sync.destroy(&device); }
+        if let Some(swapchain) = self.swapchain.take() { swapchain.destroy(&device); }
+        if let Some(shared) = self.shared.take()
+        {
+            if let Some(mut uploader) = self.uploader.take() { uploader.destroy(&device, &mut shared.allocator()); }
+            shared.shutdown();
+        }
+        unsafe
+        {
+            device.destroy_device(None);
+        }
+    }
+}
+
 /// ### GkVkContext struct
 /// *Vulkan rendering Context.*<br>
 /// <pre>
 /// - Members
 ///     device:             ash::Device
 ///     physical_device:    vk::PhysicalDevice
-///     allocator:          gpu_allocator::vulkan::Allocator
+///     shared:             GkVkSharedDevice    <i>// Allocator and deferred deletion, shared with resources.</i>
 ///     queues:             GkVkQueues
 ///     swapchain:          GkVkSwapChain
 ///     draw_cmds:          GkVkCommands
-///     render_semaphore:   vk::Semaphore
-///     wait_semaphore:     vk::Semaphore
+///     uploader:           GkVkUploader    <i>// Asynchronous buffer and image uploads.</i>
+///     frame_sync:         GkVkFrameSync
+///     profiler:           GkVkGpuProfiler <i>// Gpu timings of the layers.</i>
+///     b_vsync:            bool    <i>// Used when the swapchain is (re)created.</i>
+///     b_reverse_z:        bool    <i>// Depth is cleared to 0 and tested with GREATER, for reverse-Z projections.</i>
+///     uniform_alignment:  vk::DeviceSize  <i>// Required alignment of dynamic uniform buffer offsets.</i>
 /// </pre>
 pub struct GkVkContext
 {
     pub device:             Device,
     pub physical_device:    vk::PhysicalDevice,
-    pub allocator:          Option<Allocator>,
+    pub shared:             GkVkSharedDevice,
     pub queues:             GkVkQueues,
     pub swapchain:          GkVkSwapchain,
     pub draw_cmds:          GkVkCommands,
+    pub uploader:           GkVkUploader,
     // pub render_semaphore:   vk::Semaphore,
     // pub wait_semaphore:     vk::Semaphore,
     pub frame_sync:         GkVkFrameSync,
+    pub profiler:           GkVkGpuProfiler,
+    pub b_vsync:            bool,
+    pub b_reverse_z:        bool,
+    pub uniform_alignment:  vk::DeviceSize,
 }
 
 impl GkVkContext
 {
-    /// ### fn GkVkContext::new( ... ) -> GkVkContext
+    /// ### fn GkVkContext::new( ... ) -> GkResult\<GkVkContext\>
     /// *Creates an instance of GkVkContext.*
     /// <pre>
     /// - Params
     ///     loader:     &GkVkLoader
     ///     width:      u32
     ///     height:     u32
+    ///     b_vsync:        bool
+    ///     b_reverse_z:    bool
+    /// - Return
+    ///     GkResult&lt;GkVkContext&gt;
     /// </pre>
-    pub fn new(loader: &GkVkLoader, width: u32, height: u32) -> Self
+    pub fn new(loader: &GkVkLoader, width: u32, height: u32, b_vsync: bool, b_reverse_z: bool) -> GkResult<Self>
     {
         log_info!("Creating VulkanContext...");
 
-        let physical_device = find_suitable_vk_physical_device(&loader.instance, &loader.surface);
+        let physical_device = find_suitable_vk_physical_device(&loader.instance, &loader.surface)?;
 
         let mut queues = GkVkQueues::new();
         queues.query_indices(&loader.instance, &physical_device);
+        let graphics_family = queues.graphics.index
+            .ok_or_else(|| GkError::Unsupported(String::from("The physical device has no graphics queue family")))?;
 
         let queue_index_list = queues.get_index_list();
-        let device = create_vk_device(&loader.instance, &physical_device, &queue_index_list);
+        let device = create_vk_device(&loader.instance, &physical_device, &queue_index_list)?;
         queues.query_queues(&device);
 
-        let allocator = create_vk_allocator(&loader.instance, &physical_device, &device);
+        // Every step below may fail. Dropping partial on the way out destroys what was created so far.
+        let mut partial = GkVkPartialContext::new(device.clone());
+
+        let allocator = create_vk_allocator(&loader.instance, &physical_device, &device)?;
+        let debug_utils = loader.debug_layer.as_ref().map(|debug_layer| debug_layer.utils.clone());
+        let shared = partial.shared.insert(GkVkSharedDevice::new(device.clone(), allocator, debug_utils));
+
+        partial.uploader = Some(GkVkUploader::new(&device, &mut shared.allocator(), &queues)?);
+
+        let swapchain = partial.swapchain.insert(GkVkSwapchain::new(loader, &device, &physical_device, &queue_index_list, width, height, b_vsync)?);
+        let num_images = swapchain.images.len();
+
+        let frame_sync = partial.frame_sync.insert(GkVkFrameSync::new(&device, 2)?);
+        frame_sync.reset_images_in_flight(num_images);
+        let num_frames = frame_sync.get_num_frames_in_flight();
 
-        let swapchain = GkVkSwapchain::new(loader, &device, &physical_device, &queue_index_list, width, height);
+        partial.draw_cmds = Some(GkVkCommands::new(&device, graphics_family, num_frames as u32)?);
 
-        let frame_sync = GkVkFrameSync::new(&device, 2);
-        
-        let draw_cmds = GkVkCommands::new(&device, queues.graphics.index.clone().unwrap(), frame_sync.get_num_frames_in_flight() as u32);
+        let profiler = GkVkGpuProfiler::new(&loader.instance, &physical_device, &device, graphics_family, num_frames)?;
+
+        let limits = unsafe { loader.instance.get_physical_device_properties(physical_device).limits };
+        let uniform_alignment = limits.min_uniform_buffer_offset_alignment.max(1);
+
+        let (shared, uploader, swapchain, frame_sync, draw_cmds) = partial.finish();
 
         log_info!("VulkanContext created");
-        Self
+        Ok(Self
         {
             device,
             physical_device,
-            allocator: Some(allocator),
+            shared,
             queues,
             swapchain,
             draw_cmds,
-            frame_sync
-        }
+            uploader,
+            frame_sync,
+            profiler,
+            b_vsync,
+            b_reverse_z,
+            uniform_alignment,
+        })
     }
 
     /// ### fn GkVkContext::destroy( &self )
-    /// *Destroys the instance of GkVkContext.*
+    /// *Destroys the instance of GkVkContext.*<br>
+    /// *Resources still owned elsewhere are listed in a leak report and can no longer be freed,<br>
+    /// so drop them first. Debug builds assert that nothing leaked. The device must be idle.*
     /// <pre>
     /// - Params
     ///     <b>&self</b>
     /// </pre>
     pub fn destroy(&mut self)
     {
+        self.shared.collect_all();
         self.clean_swapchain();
         self.frame_sync.destroy(&self.device);
         self.draw_cmds.destroy(&self.device);
-        drop(self.allocator.take().unwrap());
+        self.profiler.destroy(&self.device);
+        self.uploader.destroy(&self.device, &mut self.shared.allocator());
+        let leaked = self.shared.shutdown();
         unsafe
         {
             self.device.destroy_device(None);
         }
+        debug_assert!(leaked.is_empty() || std::thread::panicking(),
+            "gpu allocations leaked at shutdown: {:?}",
+            leaked.iter().map(|record| record.label.as_str()).collect::<Vec<&str>>());
+    }
+
+    /// ### fn GkVkContext::memory_report( &self ) -> GkVkMemoryReport
+    /// *Gpu memory used by buffers and images: totals per memory location, per label,<br>
+    /// fragmentation of the shared memory blocks and peak usage.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    /// - Return
+    ///     GkVkMemoryReport
+    /// </pre>
+    pub fn memory_report(&self) -> GkVkMemoryReport
+    {
+        self.shared.memory_report()
+    }
+
+    /// ### fn GkVkContext::log_memory_report( &self )
+    /// *Writes memory_report() to the log.*
+    pub fn log_memory_report(&self)
+    {
+        self.memory_report().log();
+    }
+
+    /// ### fn GkVkContext::dump_memory_report( &self, ... ) -> GkResult\<()\>
+    /// *Writes memory_report() as json to file_path.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     file_path:      &std::path::Path
+    /// - Return
+    ///     GkResult&lt;()&gt;
+    /// </pre>
+    pub fn dump_memory_report(&self, file_path: &std::path::Path) -> GkResult<()>
+    {
+        std::fs::write(file_path, self.memory_report().to_json()).map_err(|e| GkError::io(file_path, e))
+    }
+
+    /// ### fn GkVkContext::begin_gpu_scope( &self, ... )
+    /// *Opens a debug label region and a gpu profiler scope named label in cmd_buffer.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     cmd_buffer:         vk::CommandBuffer
+    ///     label:              &str
+    ///     b_pipeline_stats:   bool    <i>// Also gather pipeline statistics for the scope.</i>
+    /// </pre>
+    pub fn begin_gpu_scope(&self, cmd_buffer: vk::CommandBuffer, label: &str, b_pipeline_stats: bool)
+    {
+        self.shared.begin_label(cmd_buffer, label);
+        self.profiler.begin_scope(&self.device, cmd_buffer, label, b_pipeline_stats);
+    }
+
+    pub fn end_gpu_scope(&self, cmd_buffer: vk::CommandBuffer)
+    {
+        self.profiler.end_scope(&self.device, cmd_buffer);
+        self.shared.end_label(cmd_buffer);
+    }
+
+    /// ### fn GkVkContext::flush_uploads( &mut self )
+    /// *Submits pending uploads to the transfer queue without waiting on them.*<br>
+    /// *Call before submitting draw commands that read the uploaded resources.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    /// - Return
+    ///     VkResult&lt;()&gt;
+    /// </pre>
+    pub fn flush_uploads(&mut self) -> VkResult<()>
+    {
+        self.uploader.submit(&self.device, &mut self.shared.allocator()).map(|_| ())
     }
 
     pub fn clean_swapchain(&mut self)
@@ -467,9 +723,48 @@ impl GkVkContext
         self.swapchain.destroy(&self.device);
     }
 
-    pub fn recreate_swapchain(&mut self, loader: &GkVkLoader, width: u32, height: u32)
+    /// ### fn GkVkContext::is_present_paced( &self ) -> bool
+    /// *Whether presenting waits for the display's vertical blank.*
+    pub fn is_present_paced(&self) -> bool
+    {
+        matches!(self.swapchain.present_mode, vk::PresentModeKHR::FIFO | vk::PresentModeKHR::FIFO_RELAXED)
+    }
+
+    pub fn recreate_swapchain(&mut self, loader: &GkVkLoader, width: u32, height: u32) -> GkResult<()>
     {
-        self.swapchain = GkVkSwapchain::new(loader, &self.device, &self.physical_device, &self.queues.get_index_list(), width, height);
+        self.swapchain = GkVkSwapchain::new(loader, &self.device, &self.physical_device, &self.queues.get_index_list(), width, height, self.b_vsync)?;
+        self.frame_sync.reset_images_in_flight(self.swapchain.images.len());
+        Ok(())
+    }
+
+    /// ### fn GkVkContext::collect_deferred( &mut self )
+    /// *Destroys deferred resources whose frames have finished on the gpu.*<br>
+    /// *Call after waiting on the current in flight fence.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    /// </pre>
+    pub fn collect_deferred(&mut self)
+    {
+        let completed = match self.frame_sync.get_completed_frame_number()
+        {
+            Some(frame) => frame,
+            None => return
+        };
+        self.shared.collect(completed);
+    }
+
+    /// ### fn GkVkContext::next_frame( &mut self )
+    /// *Moves on to the next frame in flight. Resources dropped from here on<br>
+    /// are kept alive until that frame has finished on the gpu.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    /// </pre>
+    pub fn next_frame(&mut self)
+    {
+        self.frame_sync.set_next_frame_index();
+        self.shared.set_frame_number(self.frame_sync.get_frame_number());
     }
 
     // pub fn reset_draw_cmd_pool(&self)
@@ -490,15 +785,15 @@ impl GkVkContext
 
 }
 
-/// ### fn gk_begin_single_time_vk_command_buffer( ... ) -> vk::CommandBuffer
+/// ### fn gk_begin_single_time_vk_command_buffer( ... ) -> GkResult\<vk::CommandBuffer\>
 /// *Allocates and sets up a vk::CommandBuffer for temporary use, then returns it.*
 /// <pre>
 /// - Params
 ///     vk_ctx:     &GkVkContext
 /// - Return
-///     vk::CommandBuffer
+///     GkResult&lt;vk::CommandBuffer&gt;
 /// </pre>
-pub fn gk_begin_single_time_vk_command_buffer(vk_ctx: &GkVkContext) -> vk::CommandBuffer
+pub fn gk_begin_single_time_vk_command_buffer(vk_ctx: &GkVkContext) -> GkResult<vk::CommandBuffer>
 {
     let alloc_info = vk::CommandBufferAllocateInfo
     {
@@ -508,7 +803,7 @@ pub fn gk_begin_single_time_vk_command_buffer(vk_ctx: &GkVkContext) -> vk::Comma
         level: vk::CommandBufferLevel::PRIMARY,
         command_buffer_count: 1
     };
-    let cmd_buffer = unsafe{ vk_check!(vk_ctx.device.allocate_command_buffers(&alloc_info)).unwrap()[0] };
+    let cmd_buffer = unsafe{ vk_try!(vk_ctx.device.allocate_command_buffers(&alloc_info))[0] };
 
     let begin_info = vk::CommandBufferBeginInfo
     {
@@ -517,9 +812,9 @@ pub fn gk_begin_single_time_vk_command_buffer(vk_ctx: &GkVkContext) -> vk::Comma
         flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
         p_inheritance_info: std::ptr::null()
     };
-    unsafe{ vk_check!(vk_ctx.device.begin_command_buffer(cmd_buffer, &begin_info)).unwrap() }
+    unsafe{ vk_try!(vk_ctx.device.begin_command_buffer(cmd_buffer, &begin_info)) }
 
-    cmd_buffer
+    Ok(cmd_buffer)
 }
 
 /// ### fn gk_end_single_time_vk_command_buffer( ... )
@@ -528,10 +823,12 @@ pub fn gk_begin_single_time_vk_command_buffer(vk_ctx: &GkVkContext) -> vk::Comma
 /// - Params
 ///     vk_ctx:         &GkVkContext
 ///     cmd_buffer:     &vk::CommandBuffer
+/// - Return
+///     GkResult&lt;()&gt;
 /// </pre>
-pub fn gk_end_single_time_vk_command_buffer(vk_ctx: &GkVkContext, cmd_buffer: vk::CommandBuffer)
+pub fn gk_end_single_time_vk_command_buffer(vk_ctx: &GkVkContext, cmd_buffer: vk::CommandBuffer) -> GkResult<()>
 {
-    unsafe { vk_check!(vk_ctx.device.end_command_buffer(cmd_buffer)).unwrap(); }
+    unsafe { vk_try!(vk_ctx.device.end_command_buffer(cmd_buffer)); }
 
     let submit_info = [vk::SubmitInfo
     {
@@ -548,12 +845,14 @@ pub fn gk_end_single_time_vk_command_buffer(vk_ctx: &GkVkContext, cmd_buffer: vk
 
     unsafe 
     {
-        vk_check!(vk_ctx.device.queue_submit(vk_ctx.queues.graphics.handle, &submit_info, vk::Fence::null())).unwrap();
-
-        vk_check!(vk_ctx.device.queue_wait_idle(vk_ctx.queues.graphics.handle)).unwrap();
+        let result = vk_ctx.device.queue_submit(vk_ctx.queues.graphics.handle, &submit_info, vk::Fence::null())
+            .and_then(|_| vk_ctx.device.queue_wait_idle(vk_ctx.queues.graphics.handle));
 
         vk_ctx.device.free_command_buffers(vk_ctx.draw_cmds.pool, &[cmd_buffer]);
+
+        vk_try!(result);
     }
+    Ok(())
 }
 
 
@@ -561,7 +860,7 @@ pub fn gk_create_vk_color_depth_framebuffers(
         vk_ctx: &GkVkContext, 
         renderpass: &GkVkRenderPass, 
         depth_view: &vk::ImageView
-    ) -> Vec<vk::Framebuffer>
+    ) -> GkResult<Vec<vk::Framebuffer>>
 {
     let mut framebuffers: Vec<vk::Framebuffer> = Vec::new();
 
@@ -586,20 +885,20 @@ pub fn gk_create_vk_color_depth_framebuffers(
         };
 
         let framebuffer = unsafe {
-            vk_check!(vk_ctx.device.create_framebuffer(&create_info, None)).unwrap()
+            vk_try!(vk_ctx.device.create_framebuffer(&create_info, None))
         };
 
         framebuffers.push(framebuffer);
     }
 
-    framebuffers
+    Ok(framebuffers)
 }
 
 
 pub fn gk_create_vk_color_only_framebuffers(
         vk_ctx: &GkVkContext, 
         renderpass: &GkVkRenderPass
-    ) -> Vec<vk::Framebuffer>
+    ) -> GkResult<Vec<vk::Framebuffer>>
 {
     let mut framebuffers: Vec<vk::Framebuffer> = Vec::new();
 
@@ -623,13 +922,13 @@ pub fn gk_create_vk_color_only_framebuffers(
         };
 
         let framebuffer = unsafe {
-            vk_check!(vk_ctx.device.create_framebuffer(&create_info, None)).unwrap()
+            vk_try!(vk_ctx.device.create_framebuffer(&create_info, None))
         };
 
         framebuffers.push(framebuffer);
     }
 
-    framebuffers
+    Ok(framebuffers)
 }
 
 pub fn gk_destroy_vk_framebuffers(device: &ash::Device, framebuffers: &mut Vec<vk::Framebuffer>)
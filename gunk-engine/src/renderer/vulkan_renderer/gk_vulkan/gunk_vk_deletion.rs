@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+
+use ash::vk;
+use gpu_allocator::vulkan::Allocation;
+
+/// ### GkDeletionQueue\<T\> struct
+/// *Holds resources released while frames that may still use them are in flight.*<br>
+/// *Each entry is tagged with the frame number it was released on.*
+/// <pre>
+/// - Members
+///     entries:    VecDeque&lt;(u64, T)&gt;     <i>// Ordered by frame number.</i>
+/// </pre>
+pub struct GkDeletionQueue<T>
+{
+    entries: VecDeque<(u64, T)>,
+}
+
+impl<T> Default for GkDeletionQueue<T>
+{
+    fn default() -> Self
+    {
+        Self { entries: VecDeque::new() }
+    }
+}
+
+impl<T> GkDeletionQueue<T>
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkDeletionQueue::push( &mut self, ... )
+    /// *Queues item for destruction once frame_number has completed on the gpu.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     frame_number:   u64
+    ///     item:           T
+    /// </pre>
+    pub fn push(&mut self, frame_number: u64, item: T)
+    {
+        debug_assert!(self.entries.back().is_none_or(|(last, _)| *last <= frame_number));
+        self.entries.push_back((frame_number, item));
+    }
+
+    /// ### fn GkDeletionQueue::collect( &mut self, ... ) -> Vec\<T\>
+    /// *Removes and returns every item released on or before completed_frame.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     completed_frame:    u64     <i>// Newest frame known to be finished on the gpu.</i>
+    /// - Return
+    ///     Vec&lt;T&gt;
+    /// </pre>
+    pub fn collect(&mut self, completed_frame: u64) -> Vec<T>
+    {
+        let mut ready = Vec::new();
+        while self.entries.front().is_some_and(|(frame, _)| *frame <= completed_frame)
+        {
+            ready.push(self.entries.pop_front().unwrap().1);
+        }
+        ready
+    }
+
+    /// ### fn GkDeletionQueue::drain( &mut self ) -> Vec\<T\>
+    /// *Removes and returns every item. Only call once the device is idle.*
+    pub fn drain(&mut self) -> Vec<T>
+    {
+        self.entries.drain(..).map(|(_, item)| item).collect()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.entries.is_empty()
+    }
+}
+
+/// ### EGkVkDeferred enum
+/// *Raw Vulkan objects GkVkSharedDevice destroys once the gpu is done with them.*<br>
+/// *Buffers and images carry the id their allocation was tracked under.*
+pub enum EGkVkDeferred
+{
+    Buffer { handle: vk::Buffer, allocation: Allocation, id: u64 },
+    Image { handle: vk::Image, view: vk::ImageView, allocation: Allocation, id: u64 },
+    Descriptor { pool: vk::DescriptorPool, layouts: Vec<vk::DescriptorSetLayout> },
+    RenderPass(vk::RenderPass),
+    Framebuffer(vk::Framebuffer),
+    ShaderModule(vk::ShaderModule),
+}
+
+impl EGkVkDeferred
+{
+    /// Short description used when a resource can no longer be destroyed.
+    pub fn describe(&self) -> &'static str
+    {
+        match self
+        {
+            EGkVkDeferred::Buffer { .. } => "VkBuffer",
+            EGkVkDeferred::Image { .. } => "VkImage",
+            EGkVkDeferred::Descriptor { .. } => "VkDescriptorPool",
+            EGkVkDeferred::RenderPass(_) => "VkRenderPass",
+            EGkVkDeferred::Framebuffer(_) => "VkFramebuffer",
+            EGkVkDeferred::ShaderModule(_) => "VkShaderModule",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::GkDeletionQueue;
+
+    #[test]
+    fn collects_only_completed_frames()
+    {
+        let mut queue = GkDeletionQueue::new();
+        queue.push(1, "a");
+        queue.push(2, "b");
+        queue.push(2, "c");
+        queue.push(4, "d");
+
+        assert!(queue.collect(0).is_empty());
+        assert_eq!(queue.collect(2), vec!["a", "b", "c"]);
+        assert_eq!(queue.len(), 1);
+        assert!(queue.collect(3).is_empty());
+        assert_eq!(queue.collect(4), vec!["d"]);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn drain_returns_everything()
+    {
+        let mut queue = GkDeletionQueue::new();
+        queue.push(7, 1);
+        queue.push(9, 2);
+        assert_eq!(queue.drain(), vec![1, 2]);
+        assert!(queue.is_empty());
+    }
+}
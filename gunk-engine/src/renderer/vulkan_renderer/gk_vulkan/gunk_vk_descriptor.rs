@@ -1,8 +1,11 @@
 use ash::{self, vk};
 
 use super::gunk_vk_context::GkVkContext;
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
 
-use crate::vk_check;
+use crate::vk_try;
+use crate::core::gk_error::GkResult;
 
 /// ### fn get_vk_desc_set_layout_binding( ... ) -> vk::DescriptorSetLayoutBinding
 /// *Returns a populated vk::DescriptorLayoutBinding struct*
@@ -94,12 +97,25 @@ pub fn get_vk_image_write_desc_set(
     }
 }
 
+/// ### fn gk_create_vk_desc_pool( ... ) -> GkResult\<vk::DescriptorPool\>
+/// *Creates a descriptor pool sized for one set per frame in flight.*
+/// <pre>
+/// - Params
+///     vk_ctx:             &GkVkContext
+///     uniform_count:      u32
+///     dynamic_count:      u32     <i>// Uniform buffers bound with a dynamic offset.</i>
+///     storage_count:      u32
+///     img_sample_count:   u32
+/// - Return
+///     GkResult&lt;vk::DescriptorPool&gt;
+/// </pre>
 pub fn gk_create_vk_desc_pool(
     vk_ctx: &GkVkContext, 
     uniform_count: u32, 
+    dynamic_count: u32,
     storage_count: u32, 
     img_sample_count: u32
-) -> vk::DescriptorPool
+) -> GkResult<vk::DescriptorPool>
 {
     let img_count = vk_ctx.frame_sync.get_num_frames_in_flight() as u32;
     let mut pool_sizes: Vec<vk::DescriptorPoolSize> = Vec::new();
@@ -115,6 +131,17 @@ pub fn gk_create_vk_desc_pool(
         );
     }
 
+    if dynamic_count > 0
+    {
+        pool_sizes.push(
+            vk::DescriptorPoolSize
+            {
+                ty: vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC,
+                descriptor_count: img_count * dynamic_count
+            }
+        );
+    }
+
     if storage_count > 0
     {
         pool_sizes.push(
@@ -148,28 +175,55 @@ pub fn gk_create_vk_desc_pool(
     };
 
     unsafe{
-        vk_check!(vk_ctx.device.create_descriptor_pool(&create_info, None)).unwrap()
+        Ok(vk_try!(vk_ctx.device.create_descriptor_pool(&create_info, None)))
     }
 }
 
 /// ### GkVkDescriptor struct
+/// *Dropping it destroys the pool and every distinct layout once the frames in flight have finished.*
 /// <pre>
 /// - Members
-///     layout:     Vec&lt;vk::DescriptorSetLayout&gt;
+///     layouts:    Vec&lt;vk::DescriptorSetLayout&gt;     <i>// One per set, may repeat the same layout.</i>
 ///     pool:       vk::DescriptorPool
 ///     sets:       Vec&lt;vk::DescriptorSet&gt;
+///     shared:     GkVkSharedDevice                    <i>// private</i>
 /// </pre>
 pub struct GkVkDescriptor
 {
     pub layouts:    Vec<vk::DescriptorSetLayout>,
     pub pool:       vk::DescriptorPool,
-    pub sets:       Vec<vk::DescriptorSet>
+    pub sets:       Vec<vk::DescriptorSet>,
+    shared:         GkVkSharedDevice,
 }
 
-pub fn gk_destroy_vk_descriptor(vk_ctx: &GkVkContext, descriptor: &GkVkDescriptor)
+impl GkVkDescriptor
 {
-    unsafe{
-        vk_ctx.device.destroy_descriptor_set_layout(*descriptor.layouts.first().unwrap(), None);
-        vk_ctx.device.destroy_descriptor_pool(descriptor.pool, None);
+    /// ### fn GkVkDescriptor::new( ... ) -> GkVkDescriptor
+    /// *Takes ownership of a descriptor pool, its layouts and the sets allocated from it.*
+    /// <pre>
+    /// - Params
+    ///     vk_ctx:     &GkVkContext
+    ///     layouts:    Vec&lt;vk::DescriptorSetLayout&gt;
+    ///     pool:       vk::DescriptorPool
+    ///     sets:       Vec&lt;vk::DescriptorSet&gt;
+    /// - Return
+    ///     GkVkDescriptor
+    /// </pre>
+    pub fn new(vk_ctx: &GkVkContext, layouts: Vec<vk::DescriptorSetLayout>, pool: vk::DescriptorPool, sets: Vec<vk::DescriptorSet>) -> Self
+    {
+        Self { layouts, pool, sets, shared: vk_ctx.shared.clone() }
+    }
+}
+
+impl Drop for GkVkDescriptor
+{
+    fn drop(&mut self)
+    {
+        let mut layouts: Vec<vk::DescriptorSetLayout> = Vec::new();
+        for layout in self.layouts.iter()
+        {
+            if !layouts.contains(layout) { layouts.push(*layout); }
+        }
+        self.shared.defer(EGkVkDeferred::Descriptor { pool: self.pool, layouts });
     }
-}
\ No newline at end of file
+}
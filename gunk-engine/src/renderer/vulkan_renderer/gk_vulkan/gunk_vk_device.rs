@@ -0,0 +1,322 @@
+use std::ops::{ Deref, DerefMut };
+use std::sync::{ Arc, Mutex, MutexGuard };
+use std::sync::atomic::{ AtomicBool, AtomicU64, Ordering };
+
+use std::ffi::CString;
+
+use ash::{ self, vk, extensions::ext::DebugUtils };
+use gpu_allocator::{ vulkan::{ Allocation, Allocator }, MemoryLocation };
+
+use crate::{ log_info, log_warn, vk_check };
+
+use super::gunk_vk_deletion::{ GkDeletionQueue, EGkVkDeferred };
+use super::gunk_vk_memory::{ GkVkAllocationRecord, GkVkAllocationRegistry, GkVkMemoryReport };
+
+/// ### GkVkAllocatorGuard struct
+/// *Locked access to the shared gpu_allocator::vulkan::Allocator.*<br>
+/// *Derefs to Allocator, so `&mut guard` can be passed where `&mut Allocator` is expected.*
+pub struct GkVkAllocatorGuard<'a>
+{
+    guard: MutexGuard<'a, Option<Allocator>>,
+}
+
+impl<'a> Deref for GkVkAllocatorGuard<'a>
+{
+    type Target = Allocator;
+
+    fn deref(&self) -> &Allocator
+    {
+        self.guard.as_ref().expect("Allocator used after the device was destroyed")
+    }
+}
+
+impl<'a> DerefMut for GkVkAllocatorGuard<'a>
+{
+    fn deref_mut(&mut self) -> &mut Allocator
+    {
+        self.guard.as_mut().expect("Allocator used after the device was destroyed")
+    }
+}
+
+/// Shared state behind GkVkSharedDevice.
+struct GkVkSharedInner
+{
+    device:         ash::Device,
+    debug_utils:    Option<DebugUtils>,
+    allocator:      Mutex<Option<Allocator>>,
+    deferred:       Mutex<GkDeletionQueue<EGkVkDeferred>>,
+    allocations:    Mutex<GkVkAllocationRegistry>,
+    frame_number:   AtomicU64,
+    shut_down:      AtomicBool,
+}
+
+/// ### GkVkSharedDevice struct
+/// *Cheap to clone handle to the logical device, its allocator and the deferred deletion queue.*<br>
+/// *Also names objects and labels command buffers for debuggers while validation is on.*<br>
+/// *GkVkBuffer, GkVkImage, GkVkDescriptor, GkVkRenderPass and GkVkShaderModule keep one<br>
+/// and hand their Vulkan objects back to it when dropped.*
+#[derive(Clone)]
+pub struct GkVkSharedDevice
+{
+    inner: Arc<GkVkSharedInner>,
+}
+
+/// Debug names can't hold interior nul bytes, those are dropped.
+fn debug_cstring(name: &str) -> CString
+{
+    CString::new(name.replace('\0', "")).unwrap_or_default()
+}
+
+/// Locks a mutex, carrying on with the data if another thread panicked while holding it.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T>
+{
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+impl GkVkSharedDevice
+{
+    /// ### fn GkVkSharedDevice::new( ... ) -> GkVkSharedDevice
+    /// <pre>
+    /// - Params
+    ///     device:         ash::Device
+    ///     allocator:      gpu_allocator::vulkan::Allocator
+    ///     debug_utils:    Option&lt;ext::DebugUtils&gt;    <i>// None turns debug names and labels into no-ops.</i>
+    /// - Return
+    ///     GkVkSharedDevice
+    /// </pre>
+    pub fn new(device: ash::Device, allocator: Allocator, debug_utils: Option<DebugUtils>) -> Self
+    {
+        Self
+        {
+            inner: Arc::new(GkVkSharedInner
+            {
+                device,
+                debug_utils,
+                allocator: Mutex::new(Some(allocator)),
+                deferred: Mutex::new(GkDeletionQueue::new()),
+                allocations: Mutex::new(GkVkAllocationRegistry::new()),
+                frame_number: AtomicU64::new(0),
+                shut_down: AtomicBool::new(false),
+            })
+        }
+    }
+
+    pub fn device(&self) -> &ash::Device
+    {
+        &self.inner.device
+    }
+
+    /// ### fn GkVkSharedDevice::set_object_name( &self, ... )
+    /// *Names a Vulkan object for validation messages and captures. Does nothing without validation.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     handle:     impl vk::Handle     <i>// vk::Buffer, vk::Image, vk::Pipeline, ...</i>
+    ///     name:       &str
+    /// </pre>
+    pub fn set_object_name<H: vk::Handle>(&self, handle: H, name: &str)
+    {
+        let utils = match self.inner.debug_utils.as_ref()
+        {
+            Some(utils) => utils,
+            None => return
+        };
+        let name = debug_cstring(name);
+        let name_info = vk::DebugUtilsObjectNameInfoEXT::builder()
+            .object_type(H::TYPE)
+            .object_handle(handle.as_raw())
+            .object_name(&name);
+        vk_check!( unsafe { utils.set_debug_utils_object_name(self.inner.device.handle(), &name_info) } );
+    }
+
+    /// ### fn GkVkSharedDevice::begin_label( &self, ... )
+    /// *Opens a named region in cmd_buffer. Close it with end_label(). Does nothing without validation.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     cmd_buffer:     vk::CommandBuffer
+    ///     name:           &str
+    /// </pre>
+    pub fn begin_label(&self, cmd_buffer: vk::CommandBuffer, name: &str)
+    {
+        if let Some(utils) = self.inner.debug_utils.as_ref()
+        {
+            let name = debug_cstring(name);
+            let label = vk::DebugUtilsLabelEXT::builder().label_name(&name);
+            unsafe { utils.cmd_begin_debug_utils_label(cmd_buffer, &label); }
+        }
+    }
+
+    pub fn end_label(&self, cmd_buffer: vk::CommandBuffer)
+    {
+        if let Some(utils) = self.inner.debug_utils.as_ref()
+        {
+            unsafe { utils.cmd_end_debug_utils_label(cmd_buffer); }
+        }
+    }
+
+    /// ### fn GkVkSharedDevice::allocator( &self ) -> GkVkAllocatorGuard
+    /// *Locks the allocator. Drop the guard before anything else needs it.*
+    pub fn allocator(&self) -> GkVkAllocatorGuard<'_>
+    {
+        GkVkAllocatorGuard { guard: lock(&self.inner.allocator) }
+    }
+
+    /// ### fn GkVkSharedDevice::set_frame_number( &self, ... )
+    /// *Frame number resources dropped from now on are tagged with.*
+    pub fn set_frame_number(&self, frame_number: u64)
+    {
+        self.inner.frame_number.store(frame_number, Ordering::Release);
+    }
+
+    /// ### fn GkVkSharedDevice::track_allocation( &self, ... ) -> u64
+    /// *Registers an allocation for memory reports and the leak check. Returns the id it is released with.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     label:          &str
+    ///     location:       MemoryLocation
+    ///     allocation:     &vulkan::Allocation
+    /// - Return
+    ///     u64
+    /// </pre>
+    pub fn track_allocation(&self, label: &str, location: MemoryLocation, allocation: &Allocation) -> u64
+    {
+        lock(&self.inner.allocations).track(GkVkAllocationRecord::new(label, location, allocation))
+    }
+
+    /// ### fn GkVkSharedDevice::live_allocations( &self ) -> Vec\<GkVkAllocationRecord\>
+    /// *Allocations that have not been freed yet, oldest first.*
+    pub fn live_allocations(&self) -> Vec<GkVkAllocationRecord>
+    {
+        lock(&self.inner.allocations).live()
+    }
+
+    /// ### fn GkVkSharedDevice::memory_report( &self ) -> GkVkMemoryReport
+    /// *Current gpu memory usage of tracked buffers and images.*
+    pub fn memory_report(&self) -> GkVkMemoryReport
+    {
+        lock(&self.inner.allocations).report()
+    }
+
+    /// ### fn GkVkSharedDevice::defer( &self, ... )
+    /// *Queues a resource for destruction once the current frame has finished on the gpu.*<br>
+    /// *After shutdown() there is no device left to destroy it with, so it is reported and leaked.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     resource:   EGkVkDeferred
+    /// </pre>
+    pub fn defer(&self, resource: EGkVkDeferred)
+    {
+        if self.inner.shut_down.load(Ordering::Acquire)
+        {
+            log_warn!(format!("{} dropped after the device was destroyed, leaking it.", resource.describe()));
+            std::mem::forget(resource);
+            return;
+        }
+        let frame_number = self.inner.frame_number.load(Ordering::Acquire);
+        lock(&self.inner.deferred).push(frame_number, resource);
+    }
+
+    /// ### fn GkVkSharedDevice::collect( &self, ... )
+    /// *Destroys deferred resources released on or before completed_frame.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     completed_frame:    u64
+    /// </pre>
+    pub fn collect(&self, completed_frame: u64)
+    {
+        let ready = lock(&self.inner.deferred).collect(completed_frame);
+        for resource in ready
+        {
+            self.destroy_resource(resource);
+        }
+    }
+
+    /// ### fn GkVkSharedDevice::collect_all( &self )
+    /// *Destroys every deferred resource. Only call once the device is idle.*
+    pub fn collect_all(&self)
+    {
+        let ready = lock(&self.inner.deferred).drain();
+        for resource in ready
+        {
+            self.destroy_resource(resource);
+        }
+    }
+
+    pub fn pending_count(&self) -> usize
+    {
+        lock(&self.inner.deferred).len()
+    }
+
+    /// ### fn GkVkSharedDevice::shutdown( &self ) -> Vec\<GkVkAllocationRecord\>
+    /// *Destroys what is left in the deletion queue, logs a leak report and drops the allocator.*<br>
+    /// *The device must be idle. The caller still owns destroying the VkDevice itself.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    /// - Return
+    ///     Vec&lt;GkVkAllocationRecord&gt;    <i>// Allocations still alive, ie. leaked.</i>
+    /// </pre>
+    pub fn shutdown(&self) -> Vec<GkVkAllocationRecord>
+    {
+        self.collect_all();
+
+        let leaked = self.live_allocations();
+        if leaked.is_empty()
+        {
+            log_info!("No gpu allocations leaked.");
+        }
+        else
+        {
+            let total: vk::DeviceSize = leaked.iter().map(|record| record.size).sum();
+            let mut report = format!("{} gpu allocations ({} bytes) still alive at shutdown:", leaked.len(), total);
+            for record in leaked.iter()
+            {
+                report.push_str(&format!("\n\t{} ({} bytes)", record.label, record.size));
+            }
+            log_warn!(report);
+        }
+
+        self.inner.shut_down.store(true, Ordering::Release);
+        drop(lock(&self.inner.allocator).take());
+        leaked
+    }
+
+    fn destroy_resource(&self, resource: EGkVkDeferred)
+    {
+        let device = &self.inner.device;
+        unsafe
+        {
+            match resource
+            {
+                EGkVkDeferred::Buffer { handle, allocation, id } =>
+                {
+                    device.destroy_buffer(handle, None);
+                    vk_check!( self.allocator().free(allocation) );
+                    lock(&self.inner.allocations).release(id);
+                },
+                EGkVkDeferred::Image { handle, view, allocation, id } =>
+                {
+                    device.destroy_image_view(view, None);
+                    device.destroy_image(handle, None);
+                    vk_check!( self.allocator().free(allocation) );
+                    lock(&self.inner.allocations).release(id);
+                },
+                EGkVkDeferred::Descriptor { pool, layouts } =>
+                {
+                    for layout in layouts
+                    {
+                        device.destroy_descriptor_set_layout(layout, None);
+                    }
+                    device.destroy_descriptor_pool(pool, None);
+                },
+                EGkVkDeferred::RenderPass(handle) => device.destroy_render_pass(handle, None),
+                EGkVkDeferred::Framebuffer(handle) => device.destroy_framebuffer(handle, None),
+                EGkVkDeferred::ShaderModule(handle) => device.destroy_shader_module(handle, None),
+            }
+        }
+    }
+}
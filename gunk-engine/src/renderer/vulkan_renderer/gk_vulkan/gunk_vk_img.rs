@@ -9,10 +9,11 @@ use gpu_allocator::{
     }, 
 };
 
-use image::{self, EncodableLayout};
 
 
-use super::gunk_vk_buffer::create_vk_buffer;
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
+use super::gunk_vk_upload::GkVkImageUploadInfo;
 use super::gunk_vk_context::{
     GkVkContext, 
     gk_begin_single_time_vk_command_buffer, 
@@ -20,23 +21,18 @@ use super::gunk_vk_context::{
 };
 
 use crate::renderer::{
-    cubemap_utils::{
-        convert_equirectangle_to_vertical_cross, 
-        convert_multi_file_to_cubemap_faces, 
-        convert_vertical_cross_to_cubemap_faces
-    }, 
-    gk_bitmap::{self, EBitMapFormat, GkBitMap},
+    gk_asset_loader::{ load_cubemap_bitmap, load_image_bitmap },
+    gk_bitmap::{self, GkBitMap},
 };
 
-use crate::{ log_err, vk_check };
-
+use crate::{ log_err, vk_check, vk_try };
+use crate::core::gk_error::{ GkError, GkResult };
 
-use core::panic;
 // use std::{ffi::c_char, os::raw::c_int};
 
 
 
-/// ### fn create_vk_image( ... ) -> (vk::Image, vulkan::Allocation)
+/// ### fn create_vk_image( ... ) -> GkResult\<(vk::Image, vulkan::Allocation)\>
 /// *Creates a vk::Image and an Allocation for memory)
 /// <pre>
 /// - Param
@@ -49,7 +45,7 @@ use core::panic;
 ///     create_flags:
 ///     mip_levels:
 /// - Return
-///     (vk::Image, vulkan::Allocation)
+///     GkResult&lt;(vk::Image, vulkan::Allocation)&gt;
 /// </pre>
 pub fn create_vk_image(
         device: &ash::Device, allocator: &mut Allocator, label: &str, 
@@ -57,7 +53,7 @@ pub fn create_vk_image(
         format: vk::Format, tiling: vk::ImageTiling, 
         usage: vk::ImageUsageFlags, mip_levels: u32,
         create_flags: vk::ImageCreateFlags
-    ) -> (vk::Image, Allocation)
+    ) -> GkResult<(vk::Image, Allocation)>
 {
     let create_info = vk::ImageCreateInfo
     {
@@ -77,7 +73,7 @@ pub fn create_vk_image(
         p_queue_family_indices: std::ptr::null(),
         initial_layout: vk::ImageLayout::UNDEFINED
     };
-    let img = unsafe { vk_check!( device.create_image(&create_info, None) ).unwrap() };
+    let img = unsafe { vk_try!( device.create_image(&create_info, None) ) };
 
     let mem_requirements = unsafe{ device.get_image_memory_requirements(img) };
 
@@ -89,14 +85,31 @@ pub fn create_vk_image(
         linear: true,
         allocation_scheme: AllocationScheme::DedicatedImage(img)
     };
-    let allocation = vk_check!(allocator.allocate(&alloc_info)).unwrap();
-    unsafe { vk_check!(device.bind_image_memory(img, allocation.memory(), allocation.offset())); }
+    let allocation = match allocator.allocate(&alloc_info)
+    {
+        Ok(allocation) => allocation,
+        Err(e) =>
+        {
+            unsafe { device.destroy_image(img, None); }
+            let err = GkError::from(e);
+            log_err!(err);
+            return Err(err);
+        }
+    };
+    if let Err(e) = unsafe { device.bind_image_memory(img, allocation.memory(), allocation.offset()) }
+    {
+        unsafe { device.destroy_image(img, None); }
+        vk_check!(allocator.free(allocation));
+        let err = GkError::from(e);
+        log_err!(err);
+        return Err(err);
+    }
 
-    (img, allocation)
+    Ok((img, allocation))
 }
 
 
-/// ### fn create_vk_image_view( ... ) -> vk::ImageView
+/// ### fn create_vk_image_view( ... ) -> GkResult\<vk::ImageView\>
 /// *Creates a vk::ImageView*
 /// <pre>
 /// - Params
@@ -108,13 +121,13 @@ pub fn create_vk_image(
 ///     layer_count:        u32
 ///     mip_levels:         u32
 /// - Return
-///     vk::ImageView
+///     GkResult&lt;vk::ImageView&gt;
 /// </pre>
 pub fn create_vk_image_view(
         device: &ash::Device, image: &vk::Image, 
         format: &vk::Format, aspect_flags: vk::ImageAspectFlags, 
         view_type: vk::ImageViewType, layer_count: u32, mip_levels: u32
-    ) -> vk::ImageView
+    ) -> GkResult<vk::ImageView>
 {
 let create_info = vk::ImageViewCreateInfo
 {
@@ -135,18 +148,18 @@ let create_info = vk::ImageViewCreateInfo
     ..Default::default()
 };
 
-unsafe { vk_check!( device.create_image_view(&create_info, None) ).unwrap() }
+unsafe { Ok(vk_try!( device.create_image_view(&create_info, None) )) }
 }
 
-/// ### fn create_vk_sampler( ... ) -> vk::Sampler
+/// ### fn create_vk_sampler( ... ) -> GkResult\<vk::Sampler\>
 /// *Creates a vk::Sampler*
 /// <pre>
 /// - Params
 ///     device:     &ash::Device
 /// - Return
-///     vk::Sampler
+///     GkResult&lt;vk::Sampler&gt;
 /// </pre>
-pub fn create_vk_sampler(device: &ash::Device) -> vk::Sampler
+pub fn create_vk_sampler(device: &ash::Device) -> GkResult<vk::Sampler>
 {
     let create_info = vk::SamplerCreateInfo
     {
@@ -170,24 +183,24 @@ pub fn create_vk_sampler(device: &ash::Device) -> vk::Sampler
         ..Default::default()
     };
 
-    unsafe{ vk_check!(device.create_sampler(&create_info, None)).unwrap() }
+    unsafe{ Ok(vk_try!(device.create_sampler(&create_info, None))) }
 }
 
 
-/// ### fn find_supported_vk_format( ... ) -> vk::Format
+/// ### fn find_supported_vk_format( ... ) -> GkResult\<vk::Format\>
 /// *Loops through supplied candidate formats. <br>
 /// Determines best one based on Tiling and and format features.*
 /// <pre>
 /// - Params
 ///     instance:           &ash::Instance
 ///     phys_device:        &vk::PhysicalDevice
-///     candidates:         &Vec&lt;vk::Format&gt;          <i>// Candidate formats to loop through.</i>
+///     candidates:         &[vk::Format] <i>// Candidate formats to loop through.</i>
 ///     tiling:             vk::ImageTiling
 ///     features:           vk::FormatFeatureFlags
 /// - Return
-///     vk::Format
+///     GkResult&lt;vk::Format&gt;
 /// </pre>
-pub fn find_supported_vk_format(instance: &ash::Instance, phys_device: &vk::PhysicalDevice, candidates: &Vec<vk::Format>, tiling: vk::ImageTiling, features: vk::FormatFeatureFlags) -> vk::Format
+pub fn find_supported_vk_format(instance: &ash::Instance, phys_device: &vk::PhysicalDevice, candidates: &[vk::Format], tiling: vk::ImageTiling, features: vk::FormatFeatureFlags) -> GkResult<vk::Format>
 {
     for format in candidates.iter()
     {
@@ -195,16 +208,16 @@ pub fn find_supported_vk_format(instance: &ash::Instance, phys_device: &vk::Phys
 
         if tiling == vk::ImageTiling::LINEAR && (props.linear_tiling_features & features) == features
         {
-            return *format;
+            return Ok(*format);
         }
         else if tiling == vk::ImageTiling::OPTIMAL && (props.optimal_tiling_features & features) == features
         {
-            return *format;
+            return Ok(*format);
         }
     }
 
     log_err!("Fn 'find_supported_vk_format()' Failed to find supported format!");
-    panic!("Fn 'find_supported_vk_format()' Failed to find supported format!");
+    Err(GkError::Unsupported(String::from("Fn 'find_supported_vk_format()' Failed to find supported format!")))
 }
 
 pub fn get_bytes_per_pixel_vk_format(fmt: vk::Format) -> u32
@@ -455,16 +468,16 @@ pub fn copy_vk_buffer_to_img(
     }
 }
 
-/// ### fn find_vk_format_depth_img( ... ) -> vk::Format
+/// ### fn find_vk_format_depth_img( ... ) -> GkResult\<vk::Format\>
 /// *Finds a suitable format for a depth image texture*
 /// <pre>
 /// - Params
 ///     instance:       &ash::Instance
 ///     phys_device:    &vk::PhysicalDevice
 /// - Return
-///     vk::Format      <i>// A format suitable for a depth image texture.*
+///     GkResult&lt;vk::Format&gt;      <i>// A format suitable for a depth image texture.*
 /// </pre>
-pub fn find_vk_format_depth_img(instance: &ash::Instance, phys_device: &vk::PhysicalDevice) -> vk::Format
+pub fn find_vk_format_depth_img(instance: &ash::Instance, phys_device: &vk::PhysicalDevice) -> GkResult<vk::Format>
 {
     find_supported_vk_format(
         instance, phys_device, 
@@ -476,12 +489,16 @@ pub fn find_vk_format_depth_img(instance: &ash::Instance, phys_device: &vk::Phys
 
 
 /// ### struct GkVkImage
-/// *A convenience struct. has the image, memory allocation, and view*
+/// *A convenience struct. has the image, memory allocation, and view*<br>
+/// *Dropping it queues all three for destruction once the frames in flight that may use them have finished.*
 /// <pre>
 /// - Members
 ///     handle:     vk::Image
 ///     alloc:      vulkan::Allocation
 ///     view:       vk::ImageView
+///     size:       vk::DeviceSize
+///     id:         u64                 <i>// private, id in the allocation registry</i>
+///     shared:     GkVkSharedDevice    <i>// private</i>
 /// </pre>
 pub struct GkVkImage
 {
@@ -489,88 +506,94 @@ pub struct GkVkImage
     pub alloc:      Allocation,
     pub view:       vk::ImageView,
     pub size:       vk::DeviceSize,
+    id:             u64,
+    shared:         GkVkSharedDevice,
+}
+
+impl Drop for GkVkImage
+{
+    fn drop(&mut self)
+    {
+        self.shared.defer(EGkVkDeferred::Image
+        {
+            handle: self.handle,
+            view: self.view,
+            allocation: std::mem::take(&mut self.alloc),
+            id: self.id
+        });
+    }
 }
 
-/// ### gk_create_vk_image( ... ) -> GkVkImage
+/// ### gk_create_vk_image( ... ) -> GkResult\<GkVkImage\>
 /// *Creates a generic GkVkImage from a given file_name.*
 /// <pre>
 /// - Params
 ///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
 ///     file_name:      &str
 /// - Return
-///     GkVkImage
+///     GkResult&lt;GkVkImage&gt;
 /// </pre>
-pub fn gk_create_vk_image(vk_ctx: &mut GkVkContext, file_name: &str) -> GkVkImage
+pub fn gk_create_vk_image(vk_ctx: &mut GkVkContext, file_name: &str) -> GkResult<GkVkImage>
 {  
+    let bitmap = load_image_bitmap(std::path::Path::new(file_name))?;
+    gk_create_vk_image_from_bitmap(vk_ctx, file_name, &bitmap)
+}
 
-    let img = image::open(std::path::Path::new(file_name)).map_err( |e| { log_err!(e); } ).unwrap();
-    let pixels = img.to_rgba8().into_raw();
-    
-    let img_size : vk::DeviceSize = (std::mem::size_of::<u8>() as u32 * img.width() * img.height() * 4) as vk::DeviceSize;
-    let staging_buffer: vk::Buffer;
-    let staging_allocation: Allocation;
-
-    let label = String::from(format!("staging_allocation: {}", file_name));
-    (staging_buffer, staging_allocation) = create_vk_buffer(
-        &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), label.as_str(), 
-        img_size, 
-        vk::BufferUsageFlags::TRANSFER_SRC, 
-        MemoryLocation::CpuToGpu,
-    );
-
-    // map_vk_allocation_data(&staging_allocation, pixels.as_slice(), pixels.len());
-    unsafe
-    {
-        let mapped_ptr = staging_allocation.mapped_slice().unwrap().as_ptr() as *mut u8;
-            mapped_ptr.copy_from_nonoverlapping(pixels.as_slice().as_ptr() as *const u8, pixels.len());
-        // vk_ctx.device.unmap_memory(staging_allocation.memory());
-    }
+/// ### gk_create_vk_image_from_bitmap( ... ) -> GkResult\<GkVkImage\>
+/// *Creates a 2d GkVkImage from decoded RGBA pixels.*<br>
+/// *The upload is queued on vk_ctx.uploader and does not block.*
+/// <pre>
+/// - Params
+///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
+///     label:          &str                    <i>// Used for debug purposes.</i>
+///     bitmap:         &GkBitMap
+/// - Return
+///     GkResult&lt;GkVkImage&gt;
+/// </pre>
+pub fn gk_create_vk_image_from_bitmap(vk_ctx: &mut GkVkContext, label: &str, bitmap: &GkBitMap) -> GkResult<GkVkImage>
+{
+    let img_format = get_vk_format_bitmap(bitmap);
+    let img_size : vk::DeviceSize = bitmap.data.len() as vk::DeviceSize;
 
-    let img_format = vk::Format::R8G8B8A8_SRGB;
     let (handle, alloc) = create_vk_image(
-        &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), file_name, 
-        img.width(), img.height(), img_format, 
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(), label, 
+        bitmap.width, bitmap.height, img_format, 
         vk::ImageTiling::OPTIMAL, vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED, 
-        1, vk::ImageCreateFlags::empty());
-
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-
-        transition_vk_image_layout(
-            &vk_ctx.device, &cmd_buffer, 
-            handle, img_format,
-            vk::ImageLayout::UNDEFINED, vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            1, 1);
-
-        copy_vk_buffer_to_img(
-            &vk_ctx.device, &cmd_buffer, 
-            &staging_buffer, &handle, 
-            img.width(), img.height(),
-            1);
-
-        transition_vk_image_layout(
-            &vk_ctx.device, &cmd_buffer, 
-            handle, img_format, 
-            vk::ImageLayout::TRANSFER_DST_OPTIMAL, vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL, 
-            1, 1);
-
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
-
-    unsafe
-    {
-        vk_ctx.device.destroy_buffer(staging_buffer, None);
-        vk_check!( vk_ctx.allocator.as_mut().unwrap().free(staging_allocation) ).unwrap()
-    }
+        1, vk::ImageCreateFlags::empty())?;
 
     let view = create_vk_image_view(
         &vk_ctx.device, &handle, 
         &img_format, vk::ImageAspectFlags::COLOR, 
         vk::ImageViewType::TYPE_2D, 
         1, 1);
+    let img = finish_vk_image(vk_ctx, label, handle, alloc, view, img_size)?;
+
+    vk_ctx.uploader.upload_image(
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(),
+        &GkVkImageUploadInfo { image: img.handle, width: bitmap.width, height: bitmap.height, layer_count: 1 },
+        bitmap.data.as_slice())?;
 
-    GkVkImage { handle, alloc, view, size: img_size }
+    Ok(img)
+}
+
+/// ### fn get_vk_format_bitmap( ... ) -> vk::Format
+/// *Vulkan format matching a 4 channel GkBitMap.*
+/// <pre>
+/// - Params
+///     bitmap:     &GkBitMap
+/// - Return
+///     vk::Format
+/// </pre>
+pub fn get_vk_format_bitmap(bitmap: &GkBitMap) -> vk::Format
+{
+    match bitmap.format
+    {
+        gk_bitmap::EBitMapFormat::UByte => vk::Format::R8G8B8A8_SRGB,
+        gk_bitmap::EBitMapFormat::Float => vk::Format::R32G32B32A32_SFLOAT
+    }
 }
 
-/// ### fn gk_create_vk_depth_img( ... ) -> GkVkImage
+/// ### fn gk_create_vk_depth_img( ... ) -> GkResult\<GkVkImage\>
 /// *Creates an GkVkImage used for depth textures.*
 /// <pre>
 /// - Params
@@ -578,16 +601,18 @@ pub fn gk_create_vk_image(vk_ctx: &mut GkVkContext, file_name: &str) -> GkVkImag
 ///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
 ///     width:          u32
 ///     height:         u32
+/// - Return
+///     GkResult&lt;GkVkImage&gt;
 /// </pre>
-pub fn gk_create_vk_depth_img(instance: &ash::Instance, vk_ctx: &mut GkVkContext, width: u32, height: u32) -> GkVkImage
+pub fn gk_create_vk_depth_img(instance: &ash::Instance, vk_ctx: &mut GkVkContext, width: u32, height: u32) -> GkResult<GkVkImage>
 {
-    let format = find_vk_format_depth_img(instance, &vk_ctx.physical_device);
+    let format = find_vk_format_depth_img(instance, &vk_ctx.physical_device)?;
     let (img, alloc) = create_vk_image(
-        &vk_ctx.device, &mut vk_ctx.allocator.as_mut().unwrap(), "depth image",
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(), "depth image",
         width, height, 
         format, vk::ImageTiling::OPTIMAL, 
         vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT, 
-        1, vk::ImageCreateFlags::empty());
+        1, vk::ImageCreateFlags::empty())?;
 
     let view = create_vk_image_view(
         &vk_ctx.device, &img, &format, 
@@ -595,18 +620,19 @@ pub fn gk_create_vk_depth_img(instance: &ash::Instance, vk_ctx: &mut GkVkContext
         vk::ImageViewType::TYPE_2D,
         1, 1);
 
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-        transition_vk_image_layout(
-            &vk_ctx.device, &cmd_buffer, 
-            img, format, 
-            vk::ImageLayout::UNDEFINED, 
-            vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL, 
-            1, 1);
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
-
     let size : vk::DeviceSize = (std::mem::size_of::<u8>() as u32 * width * height) as vk::DeviceSize;
+    let depth_img = finish_vk_image(vk_ctx, "depth image", img, alloc, view, size)?;
+
+    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx)?;
+    transition_vk_image_layout(
+        &vk_ctx.device, &cmd_buffer, 
+        depth_img.handle, format, 
+        vk::ImageLayout::UNDEFINED, 
+        vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL, 
+        1, 1);
+    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer)?;
 
-    GkVkImage { handle: img, alloc, view, size }
+    Ok(depth_img)
 }
 
 #[allow(dead_code)]
@@ -660,124 +686,76 @@ fn img_f3_to_f4(w: usize, h: usize, input_data: &Vec<f32>) -> Vec<f32>
     output
 }
 
-pub fn gk_create_vk_cubemap_image(vk_ctx: &mut GkVkContext, file_names: Vec<std::path::PathBuf>) -> Result<GkVkImage, String>
+/// ### fn gk_create_vk_cubemap_image( ... ) -> GkResult\<GkVkImage\>
+/// *Loads 6 face images, or 1 equirectangular image, into a cube GkVkImage.*
+/// <pre>
+/// - Params
+///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
+///     file_names:     Vec&lt;PathBuf&gt;
+/// - Return
+///     GkResult&lt;GkVkImage&gt;
+/// </pre>
+pub fn gk_create_vk_cubemap_image(vk_ctx: &mut GkVkContext, file_names: Vec<std::path::PathBuf>) -> GkResult<GkVkImage>
 {
-    let mut correct_file_count = false;
-    if file_names.len() == 1 { correct_file_count = true; }
-    if file_names.len() == 6 { correct_file_count = true; }
-    if !correct_file_count 
-    {
-        log_err!("Cubemap file_names count must be either 1 or 6");
-        return  Err(String::from("Cubemap file_names count must be either 1 or 6"));
-    }
-
-    // let mut width = 0;
-    // let mut height = 0;
-
-    let cube: GkBitMap = if file_names.len() == 1
-    {
-        let img = image::open(std::path::Path::new(&file_names[0])).map_err( |e| { log_err!(e); } ).unwrap();
-        let img = img.flipv();
-        // let pixels = img_f3_to_f4(img.width() as usize, img.height() as usize, &img.to_rgb32f().into_raw());
-        let pixels = img.to_rgba32f().into_raw();
-    
-        let bitmap_in = GkBitMap::new(img.width(), img.height(), 1, 4, EBitMapFormat::Float, pixels.as_bytes().to_vec());
-        let bitmap_out = convert_equirectangle_to_vertical_cross(&bitmap_in);
-
-        convert_vertical_cross_to_cubemap_faces(&bitmap_out)
-    } else {
-        let result = convert_multi_file_to_cubemap_faces(&file_names);
-        match result
-        {
-            Ok(cube_map) => { cube_map },
-            Err(e) => { return Err(e); }
-        }
-    };
+    let cube = load_cubemap_bitmap(&file_names)?;
+    gk_create_vk_cubemap_image_from_bitmap(vk_ctx, &file_names[0].to_string_lossy(), &cube)
+}
 
-    let img_format = match cube.format
-    {
-        gk_bitmap::EBitMapFormat::UByte => vk::Format::R8G8B8A8_SRGB,
-        gk_bitmap::EBitMapFormat::Float => vk::Format::R32G32B32A32_SFLOAT
-    };
+/// ### fn gk_create_vk_cubemap_image_from_bitmap( ... ) -> GkResult\<GkVkImage\>
+/// *Creates a cube GkVkImage from a 6 layer GkBitMap.*<br>
+/// *The upload is queued on vk_ctx.uploader and does not block.*
+/// <pre>
+/// - Params
+///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
+///     label:          &str                    <i>// Used for debug purposes.</i>
+///     cube:           &GkBitMap               <i>// Faces stored as consecutive layers.</i>
+/// - Return
+///     GkResult&lt;GkVkImage&gt;
+/// </pre>
+pub fn gk_create_vk_cubemap_image_from_bitmap(vk_ctx: &mut GkVkContext, label: &str, cube: &GkBitMap) -> GkResult<GkVkImage>
+{
+    let img_format = get_vk_format_bitmap(cube);
     let bytes_per_pixel = get_bytes_per_pixel_vk_format(img_format);
     let img_size : vk::DeviceSize = (cube.width * cube.height * bytes_per_pixel) as vk::DeviceSize * 6;
 
-    let staging_buffer: vk::Buffer;
-    let staging_allocation: Allocation;
-
-    let label = String::from(format!("staging_allocation: {}", file_names[0].to_str().unwrap()));
-    (staging_buffer, staging_allocation) = create_vk_buffer(
-        &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), label.as_str(), 
-        img_size, 
-        vk::BufferUsageFlags::TRANSFER_SRC, 
-        MemoryLocation::CpuToGpu,
-    );
-
-    unsafe {
-        let mapped_ptr = staging_allocation.mapped_slice().unwrap().as_ptr() as * mut u8;
-        mapped_ptr.copy_from_nonoverlapping(cube.data.as_ptr(), cube.data.len());
-    }
-
     let (handle, alloc) = create_vk_image(
-        &vk_ctx.device, vk_ctx.allocator.as_mut().unwrap(), file_names[0].to_str().unwrap(),
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(), label,
         cube.width, cube.height, img_format, 
         vk::ImageTiling::OPTIMAL, vk::ImageUsageFlags::TRANSFER_DST | vk::ImageUsageFlags::SAMPLED,
-        1, vk::ImageCreateFlags::CUBE_COMPATIBLE);
-
-    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx);
-
-        transition_vk_image_layout(
-            &vk_ctx.device, &cmd_buffer, 
-            handle, img_format,
-            vk::ImageLayout::UNDEFINED,
-            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            6, 1);
-
-        copy_vk_buffer_to_img(
-            &vk_ctx.device, &cmd_buffer, 
-            &staging_buffer, &handle, 
-            cube.width, cube.height,
-            6);
-
-        transition_vk_image_layout(
-            &vk_ctx.device, &cmd_buffer, 
-            handle, img_format, 
-            vk::ImageLayout::TRANSFER_DST_OPTIMAL,
-            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
-            6, 1);
-
-    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer);
-
-    unsafe
-    {
-        vk_ctx.device.destroy_buffer(staging_buffer, None);
-        vk_check!( vk_ctx.allocator.as_mut().unwrap().free(staging_allocation) ).unwrap()
-    }
+        1, vk::ImageCreateFlags::CUBE_COMPATIBLE)?;
 
     let view = create_vk_image_view(
         &vk_ctx.device, &handle, 
         &img_format, vk::ImageAspectFlags::COLOR, 
         vk::ImageViewType::CUBE, 
         6, 1);
+    let img = finish_vk_image(vk_ctx, label, handle, alloc, view, img_size)?;
+
+    vk_ctx.uploader.upload_image(
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(),
+        &GkVkImageUploadInfo { image: img.handle, width: cube.width, height: cube.height, layer_count: 6 },
+        cube.data.as_slice())?;
 
-    Ok(GkVkImage { handle, alloc, view, size: img_size })
+    Ok(img)
 }
 
-/// ### fn gk_destroy_vk_img( ... )
-/// *Destroys the given instance of GkVkImage*
-/// <pre>
-/// - Param
-///     vk_ctx:     &mut GkVkContext
-///     img:        GkVkImage           <i>// GkVkImage to be destroyed.</i>
-/// </pre>
-pub fn gk_destroy_vk_img(vk_ctx: &mut GkVkContext, img: GkVkImage)
+/// Wraps a created image into a GkVkImage, freeing the image and its memory if the view failed.
+pub(crate) fn finish_vk_image(vk_ctx: &GkVkContext, label: &str, handle: vk::Image, alloc: Allocation, view: GkResult<vk::ImageView>, size: vk::DeviceSize) -> GkResult<GkVkImage>
 {
-    unsafe
+    match view
     {
-        vk_ctx.device.destroy_image(img.handle, None);
-        // device.free_memory(img.memory, None);
-        vk_ctx.allocator.as_mut().unwrap().free(img.alloc).map_err(|e| { log_err!(e); } ).unwrap();
-        vk_ctx.device.destroy_image_view(img.view, None);
+        Ok(view) =>
+        {
+            let id = vk_ctx.shared.track_allocation(label, MemoryLocation::GpuOnly, &alloc);
+            vk_ctx.shared.set_object_name(handle, label);
+            vk_ctx.shared.set_object_name(view, &format!("{} view", label));
+            Ok(GkVkImage { handle, alloc, view, size, id, shared: vk_ctx.shared.clone() })
+        },
+        Err(e) =>
+        {
+            unsafe { vk_ctx.device.destroy_image(handle, None); }
+            vk_check!(vk_ctx.shared.allocator().free(alloc));
+            Err(e)
+        }
     }
 }
-
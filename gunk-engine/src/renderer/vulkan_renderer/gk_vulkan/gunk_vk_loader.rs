@@ -1,5 +1,5 @@
 use ash::{
-    vk::{self, DebugUtilsMessengerEXT},
+    vk,
     Instance, Entry,
     extensions::{
         khr::Surface,
@@ -11,15 +11,16 @@ pub use winit::raw_window_handle as raw_window_handle;
 
 use winit::window::Window;
 
-use crate::{ vk_check, vk_validate_info, vk_validate_warn, vk_validate_err, log_err, log_info };
+use crate::{ vk_try, vk_validate_info, vk_validate_warn, vk_validate_err, log_info, log_warn };
+use crate::core::gk_error::GkResult;
 
 use std::ffi::{ CString, CStr };
 use std::os::raw::c_void;
+use std::sync::Arc;
 
-#[cfg(debug_assertions)]
-pub const VALIDATION: bool = true;
-#[cfg(not(debug_assertions))]
-pub const VALIDATION: bool = false;
+use super::gunk_vk_validation::{ GkVkValidationConfig, GkVkValidationState };
+
+const GK_VK_VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation";
 
 /// ### GkVkDebugLayers
 /// *Used for validation layer messages*
@@ -27,55 +28,58 @@ pub const VALIDATION: bool = false;
 /// - Members
 ///     utils:          ext::DebugUtils             <i>// Creates and destroys messenger</i>
 ///     messenger:      vk::DebugUtilsMessengerEXT  <i>// Responsible for validation layer messages</i>
+///     validation:     Arc&lt;GkVkValidationState&gt;   <i>// Message filter and counters used by the messenger</i>
 /// </pre>
 pub struct GkVkDebugLayers
 {
     pub utils: DebugUtils,
     pub messenger: vk::DebugUtilsMessengerEXT,
+    pub validation: Arc<GkVkValidationState>,
 }
 
 impl GkVkDebugLayers
 {
-    /// ### fn GkVkDebugLayers::new( ... ) -> GkVkDebugLayers
+    /// ### fn GkVkDebugLayers::new( ... ) -> GkResult\<GkVkDebugLayers\>
     /// *Creates an instance of GkVkDebugLayers*
     /// <pre>
     /// - Params
     ///     entry:      &ash::Entry
     ///     instance:   &ash::Instance
+    ///     config:     GkVkValidationConfig
     /// - Return
-    ///     GkVkDebuLayers
+    ///     GkResult&lt;GkVkDebuLayers&gt;
     /// </pre>
-    pub fn new(entry: &Entry, instance: &Instance) -> Self
+    pub fn new(entry: &Entry, instance: &Instance, config: GkVkValidationConfig) -> GkResult<Self>
     {
         let utils = ash::extensions::ext::DebugUtils::new(entry, instance);
+        let validation = Arc::new(GkVkValidationState::new(config));
 
         let messenger_ci = vk::DebugUtilsMessengerCreateInfoEXT
         {
             s_type: vk::StructureType::DEBUG_UTILS_MESSENGER_CREATE_INFO_EXT,
             p_next: std::ptr::null(),
             flags: vk::DebugUtilsMessengerCreateFlagsEXT::empty(),
-            message_severity:
-                vk::DebugUtilsMessageSeverityFlagsEXT::INFO |
-                vk::DebugUtilsMessageSeverityFlagsEXT::WARNING |
-                vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+            message_severity: validation.config.severity_flags(),
             message_type:
                 vk::DebugUtilsMessageTypeFlagsEXT::GENERAL |
                 vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE |
                 vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION,
             pfn_user_callback: Some(vulkan_debug_callback),
-            p_user_data: std::ptr::null_mut(),
+            // The Arc is kept alive in Self for as long as the messenger exists.
+            p_user_data: Arc::as_ptr(&validation) as *mut c_void,
         };
 
         let messenger = unsafe 
         {  
-            vk_check!( utils.create_debug_utils_messenger(&messenger_ci, None) ).unwrap() 
+            vk_try!( utils.create_debug_utils_messenger(&messenger_ci, None) )
         };
         
-        Self
+        Ok(Self
         {
             utils,
-            messenger
-        }
+            messenger,
+            validation
+        })
     }
 
     /// ### fn GkVkDebugLayers::destroy(&self)
@@ -107,7 +111,7 @@ pub struct GkVkSurface
 
 impl GkVkSurface
 {
-    /// ### fn GkVkSurface::new( ... ) -> GkVkSurface
+    /// ### fn GkVkSurface::new( ... ) -> GkResult\<GkVkSurface\>
     /// *Creates an instance of GkVkSurface*
     /// <pre>
     /// - Params
@@ -115,15 +119,15 @@ impl GkVkSurface
     ///     entry:          &ash::Entry
     ///     instance:       &ash::Instance
     /// - Return
-    ///     GkVkSurface
+    ///     GkResult&lt;GkVkSurface&gt;
     /// </pre>
-    pub fn new(window: &Window, entry: &Entry, instance: &Instance) -> Self
+    pub fn new(window: &Window, entry: &Entry, instance: &Instance) -> GkResult<Self>
     {
         let loader = Surface::new(&entry, &instance);
 
-        let handle  = unsafe{  vk_check!( ash_window::create_surface(&entry, &instance, window, None) ).unwrap() };
+        let handle  = unsafe{  vk_try!( ash_window::create_surface(&entry, &instance, window, None) ) };
     
-        Self{ loader, handle }
+        Ok(Self{ loader, handle })
     }
 
     /// ### fn GkVkSurface::destroy(&self)
@@ -158,39 +162,80 @@ pub struct GkVkLoader
 impl GkVkLoader
 {
     
-    /// ### fn GkVkLoader::new( ... ) -> GkVkLoader
+    /// ### fn GkVkLoader::new( ... ) -> GkResult\<GkVkLoader\>
     /// *Creates an instance GkVkLoader struct. Loads vulkan and debuggers.*
     /// <pre>
     /// - Param
     ///     window:         &winit::window::Window
     ///     app_name:       CString
     ///     app_version:    u32
+    ///     validation:     GkVkValidationConfig
     /// - Return
-    ///     GkVkLoader
+    ///     GkResult&lt;GkVkLoader&gt;
     /// </pre>
-    pub fn new(window: &Window, app_name: CString, app_version: u32) -> Self
+    pub fn new(window: &Window, app_name: CString, app_version: u32, mut validation: GkVkValidationConfig) -> GkResult<Self>
     {
-        let entry = unsafe { Entry::load().map_err(|e| { log_err!(e); } ).unwrap() };
+        let entry = unsafe { vk_try!(Entry::load()) };
+
+        if validation.b_enabled && !has_vk_validation_layer(&entry)
+        {
+            log_warn!("VK_LAYER_KHRONOS_validation is not installed, running without validation.");
+            validation.b_enabled = false;
+        }
 
         let engine_name = CString::new("Gunk Engine").unwrap();
         let engine_version = vk::make_api_version(0, 0, 1, 0);
-        let instance = create_vk_instance(window, &entry, app_name, app_version, engine_name, engine_version);
+        let instance = create_vk_instance(window, &entry, app_name, app_version, engine_name, engine_version, &validation)?;
 
         let mut debug_layer: Option<GkVkDebugLayers> = None;
-        if VALIDATION
+        if validation.b_enabled
         {
-            debug_layer = Some(GkVkDebugLayers::new(&entry, &instance));
+            debug_layer = Some(GkVkDebugLayers::new(&entry, &instance, validation)?);
         }
 
-        let surface = GkVkSurface::new(&window, &entry, &instance);
+        let surface = GkVkSurface::new(window, &entry, &instance)?;
 
-        Self
+        Ok(Self
         {
             entry,
             instance,
             debug_layer,
             surface
-        }
+        })
+    }
+
+    /// ### GkVkLoader::recreate_surface(&mut self, ... )
+    /// *Replaces a lost surface with a new one for the same window.*<br>
+    /// *The swapchain created from the old surface must be destroyed first.*
+    /// <pre>
+    /// - Param
+    ///     <b>&mut self</b>
+    ///     window:     &winit::window::Window
+    /// - Return
+    ///     GkResult&lt;()&gt;
+    /// </pre>
+    pub fn recreate_surface(&mut self, window: &Window) -> GkResult<()>
+    {
+        log_info!("Recreating VkSurfaceKHR...");
+        self.surface.destroy();
+        self.surface.handle = vk::SurfaceKHR::null();
+        self.surface = GkVkSurface::new(window, &self.entry, &self.instance)?;
+        log_info!("VkSurfaceKHR recreated.");
+        Ok(())
+    }
+
+    /// ### GkVkLoader::validation( &self ) -> Option\<&GkVkValidationState\>
+    /// *Validation message counters, None when validation is off.*
+    pub fn validation(&self) -> Option<&GkVkValidationState>
+    {
+        self.debug_layer.as_ref().map(|debug_layer| debug_layer.validation.as_ref())
+    }
+
+    /// ### GkVkLoader::validation_error_count( &self ) -> u64
+    /// *Validation errors reported so far. Always 0 when validation is off.*
+    pub fn validation_error_count(&self) -> u64
+    {
+        self.validation().map_or(0, |validation| validation.error_count())
     }
 
     /// ### GkVkLoader::destroy(&self)
@@ -202,16 +247,16 @@ impl GkVkLoader
     pub fn destroy(&self)
     {
         self.surface.destroy();
-        if VALIDATION
+        if let Some(debug_layer) = self.debug_layer.as_ref()
         {
-            self.debug_layer.as_ref().unwrap().destroy();
+            debug_layer.destroy();
         }
         unsafe { self.instance.destroy_instance(None); }
     }
 }
 
 
-/// ### fn create_vk_instance( ... ) -> ash::Instance
+/// ### fn create_vk_instance( ... ) -> GkResult\<ash::Instance\>
 /// *Creates a VkInstance handle*
 /// <pre>
 /// - Params
@@ -221,10 +266,11 @@ impl GkVkLoader
 ///     app_version:        u32
 ///     engine_name:        CString
 ///     engine_version:     u32
+///     validation:         &GkVkValidationConfig  <i>// Layers and extensions are only requested when enabled.</i>
 /// - Return
-///     ash::Instance
+///     GkResult&lt;ash::Instance&gt;
 /// </pre>
-pub fn create_vk_instance(window: &Window, entry: &Entry, app_name: CString, app_version: u32, engine_name: CString, engine_version: u32) -> Instance
+pub fn create_vk_instance(window: &Window, entry: &Entry, app_name: CString, app_version: u32, engine_name: CString, engine_version: u32, validation: &GkVkValidationConfig) -> GkResult<Instance>
 {
     log_info!("Creating VkInstance handle...");
 
@@ -239,21 +285,45 @@ pub fn create_vk_instance(window: &Window, entry: &Entry, app_name: CString, app
         api_version: ash::vk::API_VERSION_1_3
     };
 
-    let extension_names = vk_check!( ash_window::enumerate_required_extensions(window) ).unwrap();
-    let debug_utils_name = &[DebugUtils::name().as_ptr()];
-    let extension_names = [ extension_names,  debug_utils_name ].concat();
+    let mut extension_names = vk_try!( ash_window::enumerate_required_extensions(window) ).to_vec();
 
-    let validation_layer = CString::new("VK_LAYER_KHRONOS_validation").unwrap();
+    let validation_layer = CString::new(GK_VK_VALIDATION_LAYER).unwrap();
     let mut layers: Vec<*const i8> = vec![];
-    if VALIDATION
+    let mut enabled_features: Vec<vk::ValidationFeatureEnableEXT> = vec![];
+    if validation.b_enabled
     {
         layers.push(validation_layer.as_ptr());
+        extension_names.push(DebugUtils::name().as_ptr());
+
+        if validation.b_sync_validation
+        {
+            enabled_features.push(vk::ValidationFeatureEnableEXT::SYNCHRONIZATION_VALIDATION);
+        }
+        if validation.b_gpu_assisted
+        {
+            enabled_features.push(vk::ValidationFeatureEnableEXT::GPU_ASSISTED);
+            enabled_features.push(vk::ValidationFeatureEnableEXT::GPU_ASSISTED_RESERVE_BINDING_SLOT);
+        }
+        if !enabled_features.is_empty()
+        {
+            extension_names.push(vk::ExtValidationFeaturesFn::name().as_ptr());
+        }
     }
 
+    let validation_features = vk::ValidationFeaturesEXT
+    {
+        s_type: vk::StructureType::VALIDATION_FEATURES_EXT,
+        p_next: std::ptr::null(),
+        enabled_validation_feature_count: enabled_features.len() as u32,
+        p_enabled_validation_features: enabled_features.as_ptr(),
+        disabled_validation_feature_count: 0,
+        p_disabled_validation_features: std::ptr::null(),
+    };
+
     let create_info = vk::InstanceCreateInfo
     {
         s_type: vk::StructureType::INSTANCE_CREATE_INFO,
-        p_next: std::ptr::null(),
+        p_next: if enabled_features.is_empty() { std::ptr::null() } else { &validation_features as *const _ as *const c_void },
         flags: vk::InstanceCreateFlags::empty(),
         p_application_info: &app_info,
         enabled_extension_count: extension_names.len() as u32,
@@ -262,11 +332,11 @@ pub fn create_vk_instance(window: &Window, entry: &Entry, app_name: CString, app
         pp_enabled_layer_names: layers.as_ptr(),
     };
 
-    let instance = unsafe { vk_check!( entry.create_instance(&create_info, None)).unwrap() };
+    let instance = unsafe { vk_try!( entry.create_instance(&create_info, None)) };
 
     log_info!("VkInstance handle created.");
 
-    return instance;
+    Ok(instance)
 }
 
 
@@ -274,14 +344,22 @@ unsafe extern "system" fn vulkan_debug_callback(
         message_severity: vk::DebugUtilsMessageSeverityFlagsEXT,
         message_type: vk::DebugUtilsMessageTypeFlagsEXT,
         p_callback_data: *const vk::DebugUtilsMessengerCallbackDataEXT,
-        _p_user_data: *mut c_void
+        p_user_data: *mut c_void
     ) -> vk::Bool32
 {
-    if message_severity == vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE
+    let data = &*p_callback_data;
+    let message = CStr::from_ptr(data.p_message);
+    let id_name = if data.p_message_id_name.is_null() { "" } else { CStr::from_ptr(data.p_message_id_name).to_str().unwrap_or("") };
+
+    if !p_user_data.is_null()
     {
-        return vk::FALSE;
+        let validation = &*(p_user_data as *const GkVkValidationState);
+        if !validation.accept(message_severity, id_name, data.message_id_number, &message.to_string_lossy())
+        {
+            return vk::FALSE;
+        }
     }
-    let message = CStr::from_ptr((*p_callback_data).p_message);
+
     let types = match message_type
     {
         vk::DebugUtilsMessageTypeFlagsEXT::GENERAL => "[General]",
@@ -291,66 +369,49 @@ unsafe extern "system" fn vulkan_debug_callback(
     };
     match message_severity
     {
+        vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE =>
+        {
+            let severity = "[Verbose]";
+            vk_validate_info!("Validation: {} {} {}", severity, types, message.to_string_lossy());
+        }
         vk::DebugUtilsMessageSeverityFlagsEXT::INFO =>
         {
             let severity = "[Info]";
-            vk_validate_info!("Validation:", severity, types, message.to_str().unwrap());
+            vk_validate_info!("Validation: {} {} {}", severity, types, message.to_string_lossy());
         }
         vk::DebugUtilsMessageSeverityFlagsEXT::WARNING =>
         {
             let severity = "[Warning]";
-            vk_validate_warn!("Validation:", severity, types, message.to_str().unwrap());
+            vk_validate_warn!("Validation: {} {} {}", severity, types, message.to_string_lossy());
         }
         vk::DebugUtilsMessageSeverityFlagsEXT::ERROR => 
         {
             let severity = "[Error]";
-            vk_validate_err!("Validation:", severity, types, message.to_str().unwrap());
+            vk_validate_err!("Validation: {} {} {}", severity, types, message.to_string_lossy());
         }
         _ => {}
     };
     return vk::FALSE;
 }
 
-
-/// ### fn create_debug_callback( ... ) -> (ext::DebugUtils, vk::definitions::DebugUtilsMessengerEXT)
-/// *Creates debug utils for validation layer callbacks*
+/// ### fn has_vk_validation_layer( ... ) -> bool
+/// *Whether VK_LAYER_KHRONOS_validation is installed.*
 /// <pre>
 /// - Param
-///     entry: &ash::Entry
-///     instance: &ash::Instance
+///     entry:      &ash::Entry
 /// - Return
-///     (ash::extensions::ext::DebugUtils, ash::vk::definitions::DebugUtilsMessengerEXT)
+///     bool
 /// </pre>
-pub fn create_debug_callback(entry: &Entry, instance: &Instance) -> (DebugUtils, DebugUtilsMessengerEXT)
+pub fn has_vk_validation_layer(entry: &Entry) -> bool
 {
-    let debut_utils_loader = ash::extensions::ext::DebugUtils::new(entry, instance);
-
-    if !VALIDATION
-    {
-        return  (debut_utils_loader, ash::vk::DebugUtilsMessengerEXT::null());
-    }
-
-    let messenger_ci = vk::DebugUtilsMessengerCreateInfoEXT
+    let layers = match entry.enumerate_instance_layer_properties()
     {
-        s_type: vk::StructureType::DEBUG_UTILS_MESSENGER_CREATE_INFO_EXT,
-        p_next: std::ptr::null(),
-        flags: vk::DebugUtilsMessengerCreateFlagsEXT::empty(),
-        message_severity:
-            vk::DebugUtilsMessageSeverityFlagsEXT::INFO |
-            vk::DebugUtilsMessageSeverityFlagsEXT::WARNING |
-            vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
-        message_type:
-            vk::DebugUtilsMessageTypeFlagsEXT::GENERAL |
-            vk::DebugUtilsMessageTypeFlagsEXT::PERFORMANCE |
-            vk::DebugUtilsMessageTypeFlagsEXT::VALIDATION,
-        pfn_user_callback: Some(vulkan_debug_callback),
-        p_user_data: std::ptr::null_mut(),
-    };
-
-    let utils_messenger = unsafe 
-    {  
-        vk_check!( debut_utils_loader.create_debug_utils_messenger(&messenger_ci, None) ).unwrap() 
+        Ok(layers) => layers,
+        Err(_) => return false
     };
-
-    (debut_utils_loader, utils_messenger)
+    layers.iter().any(|layer|
+    {
+        let name = unsafe { CStr::from_ptr(layer.layer_name.as_ptr()) };
+        name.to_bytes() == GK_VK_VALIDATION_LAYER.as_bytes()
+    })
 }
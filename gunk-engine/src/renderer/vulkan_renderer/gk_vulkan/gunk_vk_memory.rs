@@ -0,0 +1,438 @@
+use std::collections::BTreeMap;
+
+use ash::vk::{ self, Handle };
+use gpu_allocator::{ vulkan::Allocation, MemoryLocation };
+
+use crate::log_info;
+
+/// Memory locations the report breaks usage down by, in the order they are listed.
+const GK_VK_MEMORY_LOCATIONS: [MemoryLocation; 4] =
+[
+    MemoryLocation::GpuOnly,
+    MemoryLocation::CpuToGpu,
+    MemoryLocation::GpuToCpu,
+    MemoryLocation::Unknown,
+];
+
+fn location_index(location: MemoryLocation) -> usize
+{
+    match location
+    {
+        MemoryLocation::GpuOnly => 0,
+        MemoryLocation::CpuToGpu => 1,
+        MemoryLocation::GpuToCpu => 2,
+        MemoryLocation::Unknown => 3,
+    }
+}
+
+/// ### fn gk_vk_memory_location_name( ... ) -> &'static str
+/// *Name of a gpu_allocator::MemoryLocation as printed in memory reports.*
+pub fn gk_vk_memory_location_name(location: MemoryLocation) -> &'static str
+{
+    match location
+    {
+        MemoryLocation::GpuOnly => "GpuOnly",
+        MemoryLocation::CpuToGpu => "CpuToGpu",
+        MemoryLocation::GpuToCpu => "GpuToCpu",
+        MemoryLocation::Unknown => "Unknown",
+    }
+}
+
+/// ### GkVkAllocationRecord struct
+/// *A live gpu allocation, as tracked for memory reports and the leak check.*
+/// <pre>
+/// - Members
+///     label:      String
+///     location:   MemoryLocation
+///     size:       vk::DeviceSize
+///     memory:     u64             <i>// Raw VkDeviceMemory the allocation lives in.</i>
+///     offset:     vk::DeviceSize  <i>// Offset into memory.</i>
+///     dedicated:  bool            <i>// True if memory holds only this allocation.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkAllocationRecord
+{
+    pub label:      String,
+    pub location:   MemoryLocation,
+    pub size:       vk::DeviceSize,
+    pub memory:     u64,
+    pub offset:     vk::DeviceSize,
+    pub dedicated:  bool,
+}
+
+impl GkVkAllocationRecord
+{
+    /// ### fn GkVkAllocationRecord::new( ... ) -> GkVkAllocationRecord
+    /// <pre>
+    /// - Params
+    ///     label:          &str
+    ///     location:       MemoryLocation  <i>// The location the allocation was requested with.</i>
+    ///     allocation:     &vulkan::Allocation
+    /// - Return
+    ///     GkVkAllocationRecord
+    /// </pre>
+    pub fn new(label: &str, location: MemoryLocation, allocation: &Allocation) -> Self
+    {
+        Self
+        {
+            label: String::from(label),
+            location,
+            size: allocation.size(),
+            memory: unsafe { allocation.memory() }.as_raw(),
+            offset: allocation.offset(),
+            dedicated: allocation.is_dedicated(),
+        }
+    }
+}
+
+/// ### GkVkAllocationRegistry struct
+/// *Keeps a record for every allocation owned by a GkVkBuffer or GkVkImage.*<br>
+/// *Entries are removed once the allocation is actually freed.*
+/// <pre>
+/// - Members
+///     records:    BTreeMap&lt;u64, GkVkAllocationRecord&gt;   <i>// Ordered by creation.</i>
+///     next_id:    u64
+///     bytes:      [vk::DeviceSize; 4]     <i>// Live bytes per memory location.</i>
+///     peak_bytes: [vk::DeviceSize; 4]     <i>// Highest live bytes per memory location.</i>
+///     peak_total: vk::DeviceSize          <i>// Highest live bytes over all locations.</i>
+/// </pre>
+#[derive(Default)]
+pub struct GkVkAllocationRegistry
+{
+    records:    BTreeMap<u64, GkVkAllocationRecord>,
+    next_id:    u64,
+    bytes:      [vk::DeviceSize; 4],
+    peak_bytes: [vk::DeviceSize; 4],
+    peak_total: vk::DeviceSize,
+}
+
+impl GkVkAllocationRegistry
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkVkAllocationRegistry::track( &mut self, ... ) -> u64
+    /// *Records a new allocation and returns the id to release it with.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     record:     GkVkAllocationRecord
+    /// - Return
+    ///     u64
+    /// </pre>
+    pub fn track(&mut self, record: GkVkAllocationRecord) -> u64
+    {
+        let index = location_index(record.location);
+        self.bytes[index] += record.size;
+        self.peak_bytes[index] = self.peak_bytes[index].max(self.bytes[index]);
+        self.peak_total = self.peak_total.max(self.bytes.iter().sum());
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.insert(id, record);
+        id
+    }
+
+    pub fn release(&mut self, id: u64) -> Option<GkVkAllocationRecord>
+    {
+        let record = self.records.remove(&id)?;
+        self.bytes[location_index(record.location)] -= record.size;
+        Some(record)
+    }
+
+    /// ### fn GkVkAllocationRegistry::live( &self ) -> Vec\<GkVkAllocationRecord\>
+    /// *Every allocation not yet freed, oldest first.*
+    pub fn live(&self) -> Vec<GkVkAllocationRecord>
+    {
+        self.records.values().cloned().collect()
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.records.is_empty()
+    }
+
+    /// ### fn GkVkAllocationRegistry::report( &self ) -> GkVkMemoryReport
+    /// *Snapshot of the current usage, see GkVkMemoryReport.*
+    pub fn report(&self) -> GkVkMemoryReport
+    {
+        let mut locations: Vec<GkVkLocationUsage> = GK_VK_MEMORY_LOCATIONS.iter()
+            .map(|&location| GkVkLocationUsage
+            {
+                location,
+                allocations: 0,
+                bytes: self.bytes[location_index(location)],
+                peak_bytes: self.peak_bytes[location_index(location)],
+            })
+            .collect();
+
+        let mut labels: BTreeMap<&str, GkVkLabelUsage> = BTreeMap::new();
+        let mut blocks: BTreeMap<u64, GkVkBlockUsage> = BTreeMap::new();
+        for record in self.records.values()
+        {
+            locations[location_index(record.location)].allocations += 1;
+
+            let label = labels.entry(record.label.as_str())
+                .or_insert_with(|| GkVkLabelUsage { label: record.label.clone(), allocations: 0, bytes: 0 });
+            label.allocations += 1;
+            label.bytes += record.size;
+
+            if !record.dedicated
+            {
+                let block = blocks.entry(record.memory)
+                    .or_insert_with(|| GkVkBlockUsage { memory: record.memory, location: record.location, allocations: 0, used_bytes: 0, span_bytes: 0 });
+                block.allocations += 1;
+                block.used_bytes += record.size;
+                block.span_bytes = block.span_bytes.max(record.offset + record.size);
+            }
+        }
+
+        let mut labels: Vec<GkVkLabelUsage> = labels.into_values().collect();
+        labels.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.label.cmp(&b.label)));
+
+        GkVkMemoryReport
+        {
+            allocations: self.records.len(),
+            bytes: self.bytes.iter().sum(),
+            peak_bytes: self.peak_total,
+            locations,
+            labels,
+            blocks: blocks.into_values().collect(),
+        }
+    }
+}
+
+/// ### GkVkLocationUsage struct
+/// *Usage of one gpu_allocator::MemoryLocation.*
+/// <pre>
+/// - Members
+///     location:       MemoryLocation
+///     allocations:    usize
+///     bytes:          vk::DeviceSize
+///     peak_bytes:     vk::DeviceSize
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkLocationUsage
+{
+    pub location:       MemoryLocation,
+    pub allocations:    usize,
+    pub bytes:          vk::DeviceSize,
+    pub peak_bytes:     vk::DeviceSize,
+}
+
+/// ### GkVkLabelUsage struct
+/// *Live allocations sharing a debug label.*
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkLabelUsage
+{
+    pub label:          String,
+    pub allocations:    usize,
+    pub bytes:          vk::DeviceSize,
+}
+
+/// ### GkVkBlockUsage struct
+/// *Sub allocations living in one shared VkDeviceMemory block.*
+/// <pre>
+/// - Members
+///     memory:         u64                 <i>// Raw VkDeviceMemory handle.</i>
+///     location:       MemoryLocation
+///     allocations:    usize
+///     used_bytes:     vk::DeviceSize
+///     span_bytes:     vk::DeviceSize      <i>// End of the highest live allocation in the block.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkBlockUsage
+{
+    pub memory:         u64,
+    pub location:       MemoryLocation,
+    pub allocations:    usize,
+    pub used_bytes:     vk::DeviceSize,
+    pub span_bytes:     vk::DeviceSize,
+}
+
+impl GkVkBlockUsage
+{
+    /// ### fn GkVkBlockUsage::fragmentation( &self ) -> f32
+    /// *Share of the block's used range lost to holes between allocations. 0.0 is tightly packed.*
+    pub fn fragmentation(&self) -> f32
+    {
+        if self.span_bytes == 0 { return 0.0; }
+        1.0 - self.used_bytes as f32 / self.span_bytes as f32
+    }
+}
+
+/// ### GkVkMemoryReport struct
+/// *Snapshot of the gpu memory owned by GkVkBuffer and GkVkImage.*<br>
+/// *Staging memory inside the upload manager is not included.*
+/// <pre>
+/// - Members
+///     allocations:    usize
+///     bytes:          vk::DeviceSize
+///     peak_bytes:     vk::DeviceSize              <i>// Highest bytes live at once since startup.</i>
+///     locations:      Vec&lt;GkVkLocationUsage&gt;
+///     labels:         Vec&lt;GkVkLabelUsage&gt;      <i>// Largest first.</i>
+///     blocks:         Vec&lt;GkVkBlockUsage&gt;      <i>// Shared blocks only, dedicated allocations are left out.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkMemoryReport
+{
+    pub allocations:    usize,
+    pub bytes:          vk::DeviceSize,
+    pub peak_bytes:     vk::DeviceSize,
+    pub locations:      Vec<GkVkLocationUsage>,
+    pub labels:         Vec<GkVkLabelUsage>,
+    pub blocks:         Vec<GkVkBlockUsage>,
+}
+
+impl GkVkMemoryReport
+{
+    /// ### fn GkVkMemoryReport::fragmentation( &self ) -> f32
+    /// *Fragmentation over all shared blocks, weighted by their used range.*
+    pub fn fragmentation(&self) -> f32
+    {
+        let span: vk::DeviceSize = self.blocks.iter().map(|block| block.span_bytes).sum();
+        if span == 0 { return 0.0; }
+        let used: vk::DeviceSize = self.blocks.iter().map(|block| block.used_bytes).sum();
+        1.0 - used as f32 / span as f32
+    }
+
+    /// ### fn GkVkMemoryReport::log( &self )
+    /// *Writes the report to the engine log.*
+    pub fn log(&self)
+    {
+        log_info!(self.to_string());
+    }
+
+    /// ### fn GkVkMemoryReport::to_json( &self ) -> String
+    /// *Serializes the report as a json object.*
+    pub fn to_json(&self) -> String
+    {
+        let locations: Vec<String> = self.locations.iter()
+            .map(|usage| format!(
+                "{{\"location\":\"{}\",\"allocations\":{},\"bytes\":{},\"peak_bytes\":{}}}",
+                gk_vk_memory_location_name(usage.location), usage.allocations, usage.bytes, usage.peak_bytes))
+            .collect();
+        let labels: Vec<String> = self.labels.iter()
+            .map(|usage| format!(
+                "{{\"label\":\"{}\",\"allocations\":{},\"bytes\":{}}}",
+                json_escape(&usage.label), usage.allocations, usage.bytes))
+            .collect();
+        let blocks: Vec<String> = self.blocks.iter()
+            .map(|block| format!(
+                "{{\"memory\":{},\"location\":\"{}\",\"allocations\":{},\"used_bytes\":{},\"span_bytes\":{},\"fragmentation\":{}}}",
+                block.memory, gk_vk_memory_location_name(block.location), block.allocations,
+                block.used_bytes, block.span_bytes, block.fragmentation()))
+            .collect();
+
+        format!(
+            "{{\"allocations\":{},\"bytes\":{},\"peak_bytes\":{},\"fragmentation\":{},\"locations\":[{}],\"labels\":[{}],\"blocks\":[{}]}}",
+            self.allocations, self.bytes, self.peak_bytes, self.fragmentation(),
+            locations.join(","), labels.join(","), blocks.join(","))
+    }
+}
+
+impl std::fmt::Display for GkVkMemoryReport
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "gpu memory: {} allocations, {} bytes (peak {} bytes), {:.1}% fragmented",
+            self.allocations, self.bytes, self.peak_bytes, self.fragmentation() * 100.0)?;
+        for usage in self.locations.iter()
+        {
+            write!(f, "\n\t{:<10} {:>5} allocations {:>12} bytes (peak {} bytes)",
+                gk_vk_memory_location_name(usage.location), usage.allocations, usage.bytes, usage.peak_bytes)?;
+        }
+        for usage in self.labels.iter()
+        {
+            write!(f, "\n\t{:<24} {:>5} allocations {:>12} bytes", usage.label, usage.allocations, usage.bytes)?;
+        }
+        Ok(())
+    }
+}
+
+fn json_escape(text: &str) -> String
+{
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars()
+    {
+        match c
+        {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests
+{
+    use gpu_allocator::MemoryLocation;
+
+    use super::{ GkVkAllocationRecord, GkVkAllocationRegistry };
+
+    fn record(label: &str, location: MemoryLocation, size: u64, memory: u64, offset: u64) -> GkVkAllocationRecord
+    {
+        GkVkAllocationRecord { label: String::from(label), location, size, memory, offset, dedicated: false }
+    }
+
+    #[test]
+    fn registry_lists_unreleased_allocations_in_order()
+    {
+        let mut registry = GkVkAllocationRegistry::new();
+        let a = registry.track(record("vertex mesh", MemoryLocation::GpuOnly, 64, 1, 0));
+        let b = registry.track(record("depth image", MemoryLocation::GpuOnly, 128, 1, 64));
+        let c = registry.track(record("uniform buffer", MemoryLocation::CpuToGpu, 16, 2, 0));
+
+        assert_eq!(registry.release(b).map(|record| record.label), Some(String::from("depth image")));
+        assert!(registry.release(b).is_none());
+
+        let labels: Vec<String> = registry.live().into_iter().map(|record| record.label).collect();
+        assert_eq!(labels, vec![String::from("vertex mesh"), String::from("uniform buffer")]);
+
+        registry.release(a);
+        registry.release(c);
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn report_tracks_location_totals_peak_and_fragmentation()
+    {
+        let mut registry = GkVkAllocationRegistry::new();
+        let a = registry.track(record("vertex mesh", MemoryLocation::GpuOnly, 64, 1, 0));
+        registry.track(record("vertex mesh", MemoryLocation::GpuOnly, 64, 1, 64));
+        registry.track(record("uniform buffer", MemoryLocation::CpuToGpu, 32, 2, 0));
+        registry.release(a);
+
+        let report = registry.report();
+        assert_eq!(report.allocations, 2);
+        assert_eq!(report.bytes, 96);
+        assert_eq!(report.peak_bytes, 160);
+
+        let gpu_only = &report.locations[0];
+        assert_eq!(gpu_only.location, MemoryLocation::GpuOnly);
+        assert_eq!((gpu_only.allocations, gpu_only.bytes, gpu_only.peak_bytes), (1, 64, 128));
+
+        assert_eq!(report.labels[0].label, "vertex mesh");
+        assert_eq!(report.labels[0].bytes, 64);
+
+        // Block 1 only uses 64..128 now, so half of its range is a hole.
+        assert_eq!(report.blocks[0].fragmentation(), 0.5);
+        assert_eq!(report.fragmentation(), 1.0 - 96.0 / 160.0);
+
+        let json = report.to_json();
+        assert!(json.starts_with("{\"allocations\":2,\"bytes\":96,\"peak_bytes\":160,"));
+        assert!(json.contains("{\"label\":\"uniform buffer\",\"allocations\":1,\"bytes\":32}"));
+    }
+}
@@ -0,0 +1,646 @@
+use std::cell::{ Cell, RefCell };
+
+use ash::vk;
+
+use crate::{ log_info, log_warn, vk_try };
+use crate::core::gk_error::{ GkError, GkResult };
+
+/// Most scopes recorded in one frame. Scopes past this are not timed.
+const GK_GPU_MAX_SCOPES: u32 = 64;
+
+/// Pipeline statistics gathered by scopes that ask for them. Results come back in bit order.
+const GK_GPU_PIPELINE_STATS: vk::QueryPipelineStatisticFlags = vk::QueryPipelineStatisticFlags::from_raw(
+    vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES.as_raw() |
+    vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES.as_raw() |
+    vk::QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS.as_raw() |
+    vk::QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES.as_raw() |
+    vk::QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS.as_raw()
+);
+
+/// ### GkGpuPipelineStats struct
+/// *Pipeline statistics of one profiler scope.*
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GkGpuPipelineStats
+{
+    pub input_vertices:         u64,
+    pub input_primitives:       u64,
+    pub vertex_invocations:     u64,
+    pub clipped_primitives:     u64,
+    pub fragment_invocations:   u64,
+}
+
+impl GkGpuPipelineStats
+{
+    fn from_results(values: &[u64]) -> Self
+    {
+        Self
+        {
+            input_vertices: values[0],
+            input_primitives: values[1],
+            vertex_invocations: values[2],
+            clipped_primitives: values[3],
+            fragment_invocations: values[4],
+        }
+    }
+}
+
+/// ### GkGpuTimingNode struct
+/// *Gpu time spent in one scope, with the scopes opened inside it.*
+/// <pre>
+/// - Members
+///     label:              String
+///     start_ms:           f64     <i>// Relative to the start of the frame.</i>
+///     duration_ms:        f64
+///     pipeline_stats:     Option&lt;GkGpuPipelineStats&gt;
+///     children:           Vec&lt;GkGpuTimingNode&gt;
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkGpuTimingNode
+{
+    pub label:          String,
+    pub start_ms:       f64,
+    pub duration_ms:    f64,
+    pub pipeline_stats: Option<GkGpuPipelineStats>,
+    pub children:       Vec<GkGpuTimingNode>,
+}
+
+/// ### GkGpuFrameTimings struct
+/// *Gpu timings of a finished frame.*
+/// <pre>
+/// - Members
+///     frame_number:   u64
+///     gpu_start_ns:   f64                     <i>// Gpu clock at the start of the frame, for lining frames up.</i>
+///     scopes:         Vec&lt;GkGpuTimingNode&gt;   <i>// Top level scopes, normally the single "Frame" scope.</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkGpuFrameTimings
+{
+    pub frame_number:   u64,
+    pub gpu_start_ns:   f64,
+    pub scopes:         Vec<GkGpuTimingNode>,
+}
+
+impl GkGpuFrameTimings
+{
+    /// ### fn GkGpuFrameTimings::total_ms( &self ) -> f64
+    /// *Gpu time from the start of the first top level scope to the end of the last.*
+    pub fn total_ms(&self) -> f64
+    {
+        self.scopes.iter().map(|scope| scope.start_ms + scope.duration_ms).fold(0.0, f64::max)
+    }
+
+    /// ### fn GkGpuFrameTimings::flatten( &self ) -> Vec\<(usize, &GkGpuTimingNode)\>
+    /// *Every scope depth first, paired with its depth in the tree.*
+    pub fn flatten(&self) -> Vec<(usize, &GkGpuTimingNode)>
+    {
+        fn visit<'a>(node: &'a GkGpuTimingNode, depth: usize, out: &mut Vec<(usize, &'a GkGpuTimingNode)>)
+        {
+            out.push((depth, node));
+            for child in node.children.iter() { visit(child, depth + 1, out); }
+        }
+        let mut out = Vec::new();
+        for scope in self.scopes.iter() { visit(scope, 0, &mut out); }
+        out
+    }
+}
+
+impl std::fmt::Display for GkGpuFrameTimings
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "gpu frame {}: {:.3} ms", self.frame_number, self.total_ms())?;
+        for (depth, node) in self.flatten()
+        {
+            write!(f, "\n{:indent$}{:<24} {:>8.3} ms", "", node.label, node.duration_ms, indent = 2 + depth * 2)?;
+            if let Some(stats) = node.pipeline_stats
+            {
+                write!(f, "  {} verts, {} prims, {} fragments", stats.input_vertices, stats.input_primitives, stats.fragment_invocations)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A scope as recorded into a command buffer, waiting for its queries to come back.
+#[derive(Clone, Debug)]
+struct GkGpuRecordedScope
+{
+    label:          String,
+    parent:         Option<usize>,
+    begin_query:    u32,
+    end_query:      Option<u32>,
+    stats_query:    Option<u32>,
+}
+
+/// Query pools and the scopes recorded into them for one frame in flight.
+struct GkGpuProfilerSlot
+{
+    timestamp_pool:     vk::QueryPool,
+    stats_pool:         vk::QueryPool,
+    frame_number:       Option<u64>,
+    scopes:             Vec<GkGpuRecordedScope>,
+    timestamps_used:    u32,
+    stats_used:         u32,
+}
+
+/// Recording state, mutated while layers draw with a shared GkVkContext.
+struct GkGpuProfilerState
+{
+    slots:          Vec<GkGpuProfilerSlot>,
+    current:        usize,
+    open:           Vec<Option<usize>>, // Indices of the scopes not yet ended, innermost last. None for scopes past the limit.
+    stats_open:     bool,               // Pipeline statistics queries can't nest.
+    last_frame:     Option<GkGpuFrameTimings>,
+    capture:        Option<Vec<GkGpuFrameTimings>>,
+}
+
+/// ### GkVkGpuProfiler struct
+/// *Times command buffer scopes with timestamp queries and gathers pipeline statistics.*<br>
+/// *Results are read back once the frame's slot comes around again, one frame in flight later.*
+/// <pre>
+/// - Members
+///     b_enabled:          bool    <i>// False if the graphics queue has no timestamps.</i>
+///     b_pipeline_stats:   bool    <i>// False if pipelineStatisticsQuery is not supported.</i>
+///     b_overlay:          Cell&lt;bool&gt;  <i>// Draw the timings on screen.</i>
+///     ns_per_tick:        f64
+///     timestamp_mask:     u64
+/// </pre>
+pub struct GkVkGpuProfiler
+{
+    pub b_enabled:          bool,
+    pub b_pipeline_stats:   bool,
+    pub b_overlay:          Cell<bool>,
+    ns_per_tick:            f64,
+    timestamp_mask:         u64,
+    state:                  RefCell<GkGpuProfilerState>,
+}
+
+impl GkVkGpuProfiler
+{
+    /// ### fn GkVkGpuProfiler::new( ... ) -> GkResult\<GkVkGpuProfiler\>
+    /// *Creates query pools for every frame in flight, if the device can time the graphics queue.*
+    /// <pre>
+    /// - Params
+    ///     instance:           &ash::Instance
+    ///     physical_device:    &vk::PhysicalDevice
+    ///     device:             &ash::Device
+    ///     queue_family:       u32         <i>// Family of the queue draw commands are submitted to.</i>
+    ///     frames_in_flight:   usize
+    /// - Return
+    ///     GkResult&lt;GkVkGpuProfiler&gt;
+    /// </pre>
+    pub fn new(instance: &ash::Instance, physical_device: &vk::PhysicalDevice, device: &ash::Device, queue_family: u32, frames_in_flight: usize) -> GkResult<Self>
+    {
+        let properties = unsafe { instance.get_physical_device_properties(*physical_device) };
+        let families = unsafe { instance.get_physical_device_queue_family_properties(*physical_device) };
+        let valid_bits = families.get(queue_family as usize).map_or(0, |family| family.timestamp_valid_bits);
+        let b_enabled = valid_bits > 0 && properties.limits.timestamp_period > 0.0;
+        let b_pipeline_stats = b_enabled && gk_vk_supports_pipeline_stats(instance, physical_device);
+
+        let mut profiler = Self
+        {
+            b_enabled,
+            b_pipeline_stats,
+            b_overlay: Cell::new(false),
+            ns_per_tick: properties.limits.timestamp_period as f64,
+            timestamp_mask: if valid_bits >= 64 { u64::MAX } else { (1u64 << valid_bits) - 1 },
+            state: RefCell::new(GkGpuProfilerState
+            {
+                slots: Vec::new(),
+                current: 0,
+                open: Vec::new(),
+                stats_open: false,
+                last_frame: None,
+                capture: None,
+            }),
+        };
+
+        if !b_enabled
+        {
+            log_warn!("Graphics queue does not support timestamps, gpu profiling is disabled.");
+            return Ok(profiler);
+        }
+
+        let mut slots = Vec::with_capacity(frames_in_flight);
+        for _ in 0..frames_in_flight
+        {
+            match create_profiler_slot(device, b_pipeline_stats)
+            {
+                Ok(slot) => slots.push(slot),
+                Err(e) =>
+                {
+                    profiler.state.get_mut().slots = slots;
+                    profiler.destroy(device);
+                    return Err(e);
+                }
+            }
+        }
+        profiler.state.get_mut().slots = slots;
+        log_info!("Gpu profiler created.");
+        Ok(profiler)
+    }
+
+    /// ### fn GkVkGpuProfiler::destroy( &mut self, ... )
+    /// *Destroys the query pools. The device must be idle.*
+    pub fn destroy(&mut self, device: &ash::Device)
+    {
+        for slot in self.state.get_mut().slots.drain(..)
+        {
+            unsafe
+            {
+                device.destroy_query_pool(slot.timestamp_pool, None);
+                if slot.stats_pool != vk::QueryPool::null() { device.destroy_query_pool(slot.stats_pool, None); }
+            }
+        }
+    }
+
+    /// ### fn GkVkGpuProfiler::begin_frame( &self, ... )
+    /// *Reads back the results last recorded in frame_index's slot, resets its queries and opens the "Frame" scope.*<br>
+    /// *Call right after beginning the frame's command buffer, once its in flight fence has been waited on.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     device:         &ash::Device
+    ///     cmd_buffer:     vk::CommandBuffer
+    ///     frame_index:    usize
+    ///     frame_number:   u64
+    /// </pre>
+    pub fn begin_frame(&self, device: &ash::Device, cmd_buffer: vk::CommandBuffer, frame_index: usize, frame_number: u64)
+    {
+        if !self.b_enabled { return; }
+        {
+            let mut state = self.state.borrow_mut();
+            if let Some(timings) = self.read_slot(device, &state.slots[frame_index])
+            {
+                if let Some(capture) = state.capture.as_mut() { capture.push(timings.clone()); }
+                state.last_frame = Some(timings);
+            }
+
+            state.current = frame_index;
+            state.open.clear();
+            state.stats_open = false;
+
+            let slot = &mut state.slots[frame_index];
+            slot.frame_number = Some(frame_number);
+            slot.scopes.clear();
+            slot.timestamps_used = 0;
+            slot.stats_used = 0;
+            unsafe
+            {
+                device.cmd_reset_query_pool(cmd_buffer, slot.timestamp_pool, 0, GK_GPU_MAX_SCOPES * 2);
+                if slot.stats_pool != vk::QueryPool::null()
+                {
+                    device.cmd_reset_query_pool(cmd_buffer, slot.stats_pool, 0, GK_GPU_MAX_SCOPES);
+                }
+            }
+        }
+        self.begin_scope(device, cmd_buffer, "Frame", false);
+    }
+
+    /// ### fn GkVkGpuProfiler::end_frame( &self, ... )
+    /// *Closes the "Frame" scope and any scope left open. Call before ending the command buffer.*
+    pub fn end_frame(&self, device: &ash::Device, cmd_buffer: vk::CommandBuffer)
+    {
+        if !self.b_enabled { return; }
+        while !self.state.borrow().open.is_empty()
+        {
+            self.end_scope(device, cmd_buffer);
+        }
+    }
+
+    /// ### fn GkVkGpuProfiler::begin_scope( &self, ... )
+    /// *Opens a timed scope. Pipeline statistics are gathered if asked for and no enclosing scope gathers them.*<br>
+    /// *Must be outside of a render pass, or begin and end inside the same subpass.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     device:             &ash::Device
+    ///     cmd_buffer:         vk::CommandBuffer
+    ///     label:              &str
+    ///     b_pipeline_stats:   bool
+    /// </pre>
+    pub fn begin_scope(&self, device: &ash::Device, cmd_buffer: vk::CommandBuffer, label: &str, b_pipeline_stats: bool)
+    {
+        if !self.b_enabled { return; }
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let slot = &mut state.slots[state.current];
+        let open_timed = state.open.iter().flatten().count();
+        if slot.frame_number.is_none() || !gpu_scope_fits(slot.timestamps_used, open_timed)
+        {
+            // Still pushed, so the matching end_scope() doesn't close the enclosing scope.
+            state.open.push(None);
+            return;
+        }
+
+        let begin_query = slot.timestamps_used;
+        slot.timestamps_used += 1;
+        unsafe { device.cmd_write_timestamp(cmd_buffer, vk::PipelineStageFlags::TOP_OF_PIPE, slot.timestamp_pool, begin_query); }
+
+        let mut stats_query = None;
+        if b_pipeline_stats && self.b_pipeline_stats && !state.stats_open
+        {
+            stats_query = Some(slot.stats_used);
+            unsafe { device.cmd_begin_query(cmd_buffer, slot.stats_pool, slot.stats_used, vk::QueryControlFlags::empty()); }
+            slot.stats_used += 1;
+            state.stats_open = true;
+        }
+
+        slot.scopes.push(GkGpuRecordedScope
+        {
+            label: String::from(label),
+            parent: state.open.iter().rev().find_map(|open| *open),
+            begin_query,
+            end_query: None,
+            stats_query,
+        });
+        state.open.push(Some(slot.scopes.len() - 1));
+    }
+
+    /// ### fn GkVkGpuProfiler::end_scope( &self, ... )
+    /// *Closes the innermost open scope.*
+    pub fn end_scope(&self, device: &ash::Device, cmd_buffer: vk::CommandBuffer)
+    {
+        if !self.b_enabled { return; }
+        let mut state = self.state.borrow_mut();
+        let state = &mut *state;
+        let index = match state.open.pop()
+        {
+            Some(Some(index)) => index,
+            _ => return
+        };
+        let slot = &mut state.slots[state.current];
+
+        let end_query = slot.timestamps_used;
+        slot.timestamps_used += 1;
+        unsafe { device.cmd_write_timestamp(cmd_buffer, vk::PipelineStageFlags::BOTTOM_OF_PIPE, slot.timestamp_pool, end_query); }
+
+        let scope = &mut slot.scopes[index];
+        scope.end_query = Some(end_query);
+        if let Some(stats_query) = scope.stats_query
+        {
+            unsafe { device.cmd_end_query(cmd_buffer, slot.stats_pool, stats_query); }
+            state.stats_open = false;
+        }
+    }
+
+    /// ### fn GkVkGpuProfiler::last_frame( &self ) -> Option\<GkGpuFrameTimings\>
+    /// *Timings of the most recent frame that finished on the gpu.*
+    pub fn last_frame(&self) -> Option<GkGpuFrameTimings>
+    {
+        self.state.borrow().last_frame.clone()
+    }
+
+    /// ### fn GkVkGpuProfiler::start_capture( &self )
+    /// *Keeps the timings of every frame read back from now on, for a Chrome trace.*
+    pub fn start_capture(&self)
+    {
+        self.state.borrow_mut().capture = Some(Vec::new());
+    }
+
+    /// ### fn GkVkGpuProfiler::stop_capture( &self ) -> Vec\<GkGpuFrameTimings\>
+    /// *Stops capturing and returns the captured frames, oldest first.*
+    pub fn stop_capture(&self) -> Vec<GkGpuFrameTimings>
+    {
+        self.state.borrow_mut().capture.take().unwrap_or_default()
+    }
+
+    /// Reads a slot's queries. None if nothing was recorded or the gpu has not written every query.
+    fn read_slot(&self, device: &ash::Device, slot: &GkGpuProfilerSlot) -> Option<GkGpuFrameTimings>
+    {
+        let frame_number = slot.frame_number?;
+        if slot.scopes.is_empty() || slot.scopes.iter().any(|scope| scope.end_query.is_none()) { return None; }
+
+        let flags = vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WITH_AVAILABILITY;
+        let mut timestamps: Vec<[u64; 2]> = vec![[0; 2]; slot.timestamps_used as usize];
+        unsafe { device.get_query_pool_results(slot.timestamp_pool, 0, slot.timestamps_used, &mut timestamps, flags) }.ok()?;
+        if timestamps.iter().any(|result| result[1] == 0) { return None; }
+
+        let mut stats: Vec<[u64; 6]> = vec![[0; 6]; slot.stats_used as usize];
+        if slot.stats_used > 0
+        {
+            unsafe { device.get_query_pool_results(slot.stats_pool, 0, slot.stats_used, &mut stats, flags) }.ok()?;
+            if stats.iter().any(|result| result[5] == 0) { return None; }
+        }
+
+        let ticks: Vec<u64> = timestamps.iter().map(|result| result[0] & self.timestamp_mask).collect();
+        let stats: Vec<GkGpuPipelineStats> = stats.iter().map(|result| GkGpuPipelineStats::from_results(&result[..5])).collect();
+        Some(build_gpu_frame_timings(frame_number, &slot.scopes, &ticks, &stats, self.ns_per_tick, self.timestamp_mask))
+    }
+}
+
+/// ### fn gk_vk_supports_pipeline_stats( ... ) -> bool
+/// *Whether the physical device supports the pipelineStatisticsQuery feature.*
+pub fn gk_vk_supports_pipeline_stats(instance: &ash::Instance, physical_device: &vk::PhysicalDevice) -> bool
+{
+    let features = unsafe { instance.get_physical_device_features(*physical_device) };
+    features.pipeline_statistics_query == vk::TRUE
+}
+
+/// Whether a new scope can be timed with timestamps_used queries already written and open_timed scopes still to end.<br>
+/// Leaves room for the end query of every open scope, so ending them never writes past the pool.
+fn gpu_scope_fits(timestamps_used: u32, open_timed: usize) -> bool
+{
+    // Its own begin and end, plus the ends still owed.
+    timestamps_used as usize + 2 + open_timed <= (GK_GPU_MAX_SCOPES * 2) as usize
+}
+
+fn create_profiler_slot(device: &ash::Device, b_pipeline_stats: bool) -> GkResult<GkGpuProfilerSlot>
+{
+    let timestamp_info = vk::QueryPoolCreateInfo::builder()
+        .query_type(vk::QueryType::TIMESTAMP)
+        .query_count(GK_GPU_MAX_SCOPES * 2);
+    let timestamp_pool = unsafe { vk_try!( device.create_query_pool(&timestamp_info, None) ) };
+
+    let mut stats_pool = vk::QueryPool::null();
+    if b_pipeline_stats
+    {
+        let stats_info = vk::QueryPoolCreateInfo::builder()
+            .query_type(vk::QueryType::PIPELINE_STATISTICS)
+            .query_count(GK_GPU_MAX_SCOPES)
+            .pipeline_statistics(GK_GPU_PIPELINE_STATS);
+        stats_pool = match unsafe { device.create_query_pool(&stats_info, None) }
+        {
+            Ok(pool) => pool,
+            Err(e) =>
+            {
+                unsafe { device.destroy_query_pool(timestamp_pool, None); }
+                return Err(GkError::from(e));
+            }
+        };
+    }
+
+    Ok(GkGpuProfilerSlot
+    {
+        timestamp_pool,
+        stats_pool,
+        frame_number: None,
+        scopes: Vec::new(),
+        timestamps_used: 0,
+        stats_used: 0,
+    })
+}
+
+/// Turns the raw query results of a frame into its timing tree.
+fn build_gpu_frame_timings(frame_number: u64, scopes: &[GkGpuRecordedScope], ticks: &[u64], stats: &[GkGpuPipelineStats], ns_per_tick: f64, mask: u64) -> GkGpuFrameTimings
+{
+    let frame_start = scopes.iter().map(|scope| ticks[scope.begin_query as usize]).min().unwrap_or(0);
+    let to_ms = |from: u64, to: u64| (to.wrapping_sub(from) & mask) as f64 * ns_per_tick / 1_000_000.0;
+
+    fn node(index: usize, scopes: &[GkGpuRecordedScope], make: &dyn Fn(&GkGpuRecordedScope) -> GkGpuTimingNode) -> GkGpuTimingNode
+    {
+        let mut timing = make(&scopes[index]);
+        timing.children = (0..scopes.len())
+            .filter(|&child| scopes[child].parent == Some(index))
+            .map(|child| node(child, scopes, make))
+            .collect();
+        timing
+    }
+
+    let make = |scope: &GkGpuRecordedScope| GkGpuTimingNode
+    {
+        label: scope.label.clone(),
+        start_ms: to_ms(frame_start, ticks[scope.begin_query as usize]),
+        duration_ms: to_ms(ticks[scope.begin_query as usize], ticks[scope.end_query.unwrap_or(scope.begin_query) as usize]),
+        pipeline_stats: scope.stats_query.map(|query| stats[query as usize]),
+        children: Vec::new(),
+    };
+
+    GkGpuFrameTimings
+    {
+        frame_number,
+        gpu_start_ns: frame_start as f64 * ns_per_tick,
+        scopes: (0..scopes.len())
+            .filter(|&index| scopes[index].parent.is_none())
+            .map(|index| node(index, scopes, &make))
+            .collect(),
+    }
+}
+
+/// ### fn gk_gpu_chrome_trace( ... ) -> String
+/// *Chrome trace event json (chrome://tracing, Perfetto) of captured frames.*<br>
+/// *Times are relative to the first frame's start.*
+/// <pre>
+/// - Params
+///     frames:     &[GkGpuFrameTimings]
+/// - Return
+///     String
+/// </pre>
+pub fn gk_gpu_chrome_trace(frames: &[GkGpuFrameTimings]) -> String
+{
+    let origin_ns = frames.first().map_or(0.0, |frame| frame.gpu_start_ns);
+    let mut events: Vec<String> = vec![
+        String::from("{\"name\":\"thread_name\",\"ph\":\"M\",\"pid\":0,\"tid\":0,\"args\":{\"name\":\"GPU\"}}")
+    ];
+    for frame in frames.iter()
+    {
+        let frame_offset_us = (frame.gpu_start_ns - origin_ns) / 1000.0;
+        for (_, node) in frame.flatten()
+        {
+            let mut args = format!("\"frame\":{}", frame.frame_number);
+            if let Some(stats) = node.pipeline_stats
+            {
+                args.push_str(&format!(
+                    ",\"input_vertices\":{},\"input_primitives\":{},\"vertex_invocations\":{},\"clipped_primitives\":{},\"fragment_invocations\":{}",
+                    stats.input_vertices, stats.input_primitives, stats.vertex_invocations, stats.clipped_primitives, stats.fragment_invocations));
+            }
+            events.push(format!(
+                "{{\"name\":\"{}\",\"cat\":\"gpu\",\"ph\":\"X\",\"pid\":0,\"tid\":0,\"ts\":{:.3},\"dur\":{:.3},\"args\":{{{}}}}}",
+                node.label.replace('\\', "\\\\").replace('"', "\\\""),
+                frame_offset_us + node.start_ms * 1000.0, node.duration_ms * 1000.0, args));
+        }
+    }
+    format!("{{\"displayTimeUnit\":\"ms\",\"traceEvents\":[{}]}}", events.join(","))
+}
+
+/// ### fn gk_gpu_write_chrome_trace( ... ) -> GkResult\<()\>
+/// *Writes gk_gpu_chrome_trace() of frames to file_path.*
+/// <pre>
+/// - Params
+///     file_path:  &std::path::Path
+///     frames:     &[GkGpuFrameTimings]
+/// - Return
+///     GkResult&lt;()&gt;
+/// </pre>
+pub fn gk_gpu_write_chrome_trace(file_path: &std::path::Path, frames: &[GkGpuFrameTimings]) -> GkResult<()>
+{
+    std::fs::write(file_path, gk_gpu_chrome_trace(frames)).map_err(|e| GkError::io(file_path, e))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{ GkGpuRecordedScope, GkGpuPipelineStats, GK_GPU_MAX_SCOPES, build_gpu_frame_timings, gk_gpu_chrome_trace, gpu_scope_fits };
+
+    fn scope(label: &str, parent: Option<usize>, begin_query: u32, end_query: u32, stats_query: Option<u32>) -> GkGpuRecordedScope
+    {
+        GkGpuRecordedScope { label: String::from(label), parent, begin_query, end_query: Some(end_query), stats_query }
+    }
+
+    #[test]
+    fn timings_nest_scopes_under_their_parent()
+    {
+        // Frame [0, 5], 3d layers [1, 4], Simple3d [2, 3]. One tick is 1000 ns.
+        let scopes = vec![
+            scope("Frame", None, 0, 5, None),
+            scope("3d layers", Some(0), 1, 4, None),
+            scope("Simple3d", Some(1), 2, 3, Some(0)),
+        ];
+        let ticks = vec![100, 110, 120, 620, 630, 700];
+        let stats = vec![GkGpuPipelineStats { input_vertices: 36, fragment_invocations: 1000, ..Default::default() }];
+
+        let frame = build_gpu_frame_timings(7, &scopes, &ticks, &stats, 1000.0, u64::MAX);
+
+        assert_eq!(frame.scopes.len(), 1);
+        assert_eq!(frame.total_ms(), 0.6);
+        let layers = &frame.scopes[0].children[0];
+        assert_eq!(layers.label, "3d layers");
+        assert_eq!(layers.start_ms, 0.01);
+        let simple3d = &layers.children[0];
+        assert_eq!(simple3d.duration_ms, 0.5);
+        assert_eq!(simple3d.pipeline_stats.map(|stats| stats.input_vertices), Some(36));
+
+        let labels: Vec<(usize, &str)> = frame.flatten().iter().map(|(depth, node)| (*depth, node.label.as_str())).collect();
+        assert_eq!(labels, vec![(0, "Frame"), (1, "3d layers"), (2, "Simple3d")]);
+    }
+
+    #[test]
+    fn timings_survive_timestamp_wraparound()
+    {
+        let scopes = vec![scope("Frame", None, 0, 1, None)];
+        let frame = build_gpu_frame_timings(0, &scopes, &[0xFFFF_FFF0, 0x10], &[], 1.0, 0xFFFF_FFFF);
+        assert_eq!(frame.scopes[0].duration_ms, 32.0 / 1_000_000.0);
+    }
+
+    #[test]
+    fn scopes_past_the_limit_leave_room_to_end_the_open_ones()
+    {
+        // Nest as deep as possible, then end everything, like end_frame() does.
+        let (mut used, mut open) = (0u32, 0usize);
+        while gpu_scope_fits(used, open)
+        {
+            used += 1;
+            open += 1;
+        }
+        used += open as u32;
+        assert_eq!(used, GK_GPU_MAX_SCOPES * 2);
+
+        // Siblings under a few open parents fill the pool without overflowing it either.
+        let (mut used, open) = (3u32, 3usize);
+        while gpu_scope_fits(used, open) { used += 2; }
+        assert!(used + open as u32 <= GK_GPU_MAX_SCOPES * 2);
+    }
+
+    #[test]
+    fn chrome_trace_lists_complete_events()
+    {
+        let scopes = vec![scope("Frame", None, 0, 1, None)];
+        let first = build_gpu_frame_timings(1, &scopes, &[1000, 3000], &[], 1.0, u64::MAX);
+        let second = build_gpu_frame_timings(2, &scopes, &[21000, 22000], &[], 1.0, u64::MAX);
+
+        let trace = gk_gpu_chrome_trace(&[first, second]);
+        assert!(trace.starts_with("{\"displayTimeUnit\":\"ms\",\"traceEvents\":["));
+        assert!(trace.contains("{\"name\":\"Frame\",\"cat\":\"gpu\",\"ph\":\"X\",\"pid\":0,\"tid\":0,\"ts\":0.000,\"dur\":2.000,\"args\":{\"frame\":1}}"));
+        assert!(trace.contains("\"ts\":20.000,\"dur\":1.000,\"args\":{\"frame\":2}"));
+    }
+}
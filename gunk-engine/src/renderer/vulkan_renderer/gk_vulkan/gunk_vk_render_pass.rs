@@ -2,9 +2,12 @@ use ash::{self, vk};
 
 use super::gunk_vk_img::find_vk_format_depth_img;
 
-use crate::vk_check;
+use crate::vk_try;
+use crate::core::gk_error::GkResult;
 
 use super::gunk_vk_context::GkVkContext;
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
 
 use bitflags::bitflags;
 
@@ -87,29 +90,41 @@ pub struct GkVkRenderPassInfo
 }
 
 /// ### GkVkRenderPass struct
-/// *Contains VkRenderPass handle and details describing it.*
+/// *Contains VkRenderPass handle and details describing it.*<br>
+/// *The render pass is destroyed once dropped and the frames in flight have finished.*
 /// <pre>
 /// - Members
 ///     info:       GkVkRenderPassInfo
 ///     handle:     vk::RenderPass
+///     shared:     GkVkSharedDevice    <i>// private</i>
 /// </pre>
 pub struct GkVkRenderPass
 {
     pub info:       GkVkRenderPassInfo,
-    pub handle:     vk::RenderPass
+    pub handle:     vk::RenderPass,
+    shared:         GkVkSharedDevice,
 }
 
-/// ### fn gk_create_vk_renderpass( ... ) -> GkVkRenderPass
+impl Drop for GkVkRenderPass
+{
+    fn drop(&mut self)
+    {
+        self.shared.defer(EGkVkDeferred::RenderPass(self.handle));
+    }
+}
+
+/// ### fn gk_create_vk_renderpass( ... ) -> GkResult\<GkVkRenderPass\>
 /// *Creates an instance of GkVkRenderPass.*
 /// <pre>
 /// - Params
 ///     instance:       &ash::Instance
 ///     vk_ctx:         &GkVkContext
+///     label:          &str                    <i>// Debug name of the render pass.</i>
 ///     info:           GkVkRenderPassInfo
 /// - Return
-///     GkVkRenderpass
+///     GkResult&lt;GkVkRenderpass&gt;
 /// </pre>
-pub fn gk_create_vk_renderpass(instance: &ash::Instance, vk_ctx: &GkVkContext, info: GkVkRenderPassInfo) -> GkVkRenderPass
+pub fn gk_create_vk_renderpass(instance: &ash::Instance, vk_ctx: &GkVkContext, label: &str, info: GkVkRenderPassInfo) -> GkResult<GkVkRenderPass>
 {
     let offscreen_internal: bool = (info.flags & ERenderPassBit::OFFSCREEN_INTERNAL) != ERenderPassBit::NONE;
     let first: bool = (info.flags & ERenderPassBit::FIRST) != ERenderPassBit::NONE;
@@ -158,7 +173,7 @@ pub fn gk_create_vk_renderpass(instance: &ash::Instance, vk_ctx: &GkVkContext, i
         depth_attachmment = vk::AttachmentDescription
         {
             flags: vk::AttachmentDescriptionFlags::empty(),
-            format: find_vk_format_depth_img(instance, &vk_ctx.physical_device),
+            format: find_vk_format_depth_img(instance, &vk_ctx.physical_device)?,
             samples: info.samples,
             load_op: if offscreen_internal { vk::AttachmentLoadOp::LOAD} else { if info.b_clear_depth { vk::AttachmentLoadOp::CLEAR } else { vk::AttachmentLoadOp::LOAD }},
             store_op: vk::AttachmentStoreOp::STORE,
@@ -252,18 +267,13 @@ pub fn gk_create_vk_renderpass(instance: &ash::Instance, vk_ctx: &GkVkContext, i
         p_dependencies: dependencies.as_ptr()
     };
 
-    let handle = unsafe { vk_check!( vk_ctx.device.create_render_pass(&create_info, None) ).unwrap() };
+    let handle = unsafe { vk_try!( vk_ctx.device.create_render_pass(&create_info, None) ) };
+    vk_ctx.shared.set_object_name(handle, label);
 
-    GkVkRenderPass
+    Ok(GkVkRenderPass
     {
         info,
-        handle
-    }
+        handle,
+        shared: vk_ctx.shared.clone()
+    })
 }
-
-pub fn gk_destroy_vk_renderpass(vk_ctx: &GkVkContext, renderpass: &GkVkRenderPass)
-{
-    unsafe{
-        vk_ctx.device.destroy_render_pass(renderpass.handle, None);
-    }
-}
\ No newline at end of file
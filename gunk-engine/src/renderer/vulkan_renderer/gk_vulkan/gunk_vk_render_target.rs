@@ -0,0 +1,162 @@
+use ash::{self, vk};
+
+use super::gunk_vk_context::{
+    GkVkContext,
+    gk_begin_single_time_vk_command_buffer,
+    gk_end_single_time_vk_command_buffer
+};
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
+use super::gunk_vk_img::{ GkVkImage, create_vk_image, create_vk_image_view, finish_vk_image, gk_create_vk_depth_img, transition_vk_image_layout };
+use super::gunk_vk_render_pass::{ GkVkRenderPass, GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass };
+
+use crate::vk_try;
+use crate::core::gk_error::GkResult;
+
+/// ### GkVkRenderTarget struct
+/// *Offscreen color and depth images a camera draws into instead of the swapchain.*<br>
+/// *The images use the swapchain's color format and the depth format, so the 3d layers' render passes can draw into its framebuffer.
+/// begin() clears them and end() leaves color in SHADER_READ_ONLY_OPTIMAL, to be sampled later in the frame.*
+/// <pre>
+/// - Members
+///     color:          GkVkImage
+///     depth:          GkVkImage
+///     extent:         vk::Extent2D
+///     renderpass:     GkVkRenderPass      <i>// Clears both images, used by begin().</i>
+///     framebuffer:    vk::Framebuffer
+///     shared:         GkVkSharedDevice    <i>// private</i>
+/// </pre>
+pub struct GkVkRenderTarget
+{
+    pub color:          GkVkImage,
+    pub depth:          GkVkImage,
+    pub extent:         vk::Extent2D,
+    pub renderpass:     GkVkRenderPass,
+    pub framebuffer:    vk::Framebuffer,
+    shared:             GkVkSharedDevice,
+}
+
+impl Drop for GkVkRenderTarget
+{
+    fn drop(&mut self)
+    {
+        self.shared.defer(EGkVkDeferred::Framebuffer(self.framebuffer));
+    }
+}
+
+impl GkVkRenderTarget
+{
+    /// ### fn GkVkRenderTarget::begin( &self, ... )
+    /// *Clears color to black and depth to far. Records nothing else, layers then draw with their own render passes.*
+    pub fn begin(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
+    {
+        let clear_values = [
+            vk::ClearValue { color: vk::ClearColorValue { float32: [ 0.0, 0.0, 0.0, 1.0 ] } },
+            // Far is depth 0 with a reverse-Z depth buffer.
+            vk::ClearValue { depth_stencil: vk::ClearDepthStencilValue { depth: if vk_ctx.b_reverse_z { 0.0 } else { 1.0 }, stencil: 0 } },
+        ];
+        let render_begin_info = vk::RenderPassBeginInfo
+        {
+            s_type: vk::StructureType::RENDER_PASS_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            render_pass: self.renderpass.handle,
+            framebuffer: self.framebuffer,
+            render_area: vk::Rect2D { offset: vk::Offset2D { x: 0, y: 0 }, extent: self.extent },
+            clear_value_count: clear_values.len() as u32,
+            p_clear_values: clear_values.as_ptr()
+        };
+        unsafe
+        {
+            vk_ctx.device.cmd_begin_render_pass(*cmd_buffer, &render_begin_info, vk::SubpassContents::INLINE);
+            vk_ctx.device.cmd_end_render_pass(*cmd_buffer);
+        }
+    }
+
+    /// ### fn GkVkRenderTarget::end( &self, ... )
+    /// *Makes the color image readable by fragment shaders recorded after it.*
+    pub fn end(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
+    {
+        transition_vk_image_layout(
+            &vk_ctx.device, cmd_buffer,
+            self.color.handle, vk_ctx.swapchain.format,
+            vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+            vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            1, 1);
+    }
+}
+
+/// ### fn gk_create_vk_render_target( ... ) -> GkResult\<GkVkRenderTarget\>
+/// *Creates a GkVkRenderTarget. Its color image starts out in SHADER_READ_ONLY_OPTIMAL, so it can be sampled before anything is drawn to it.*
+/// <pre>
+/// - Params
+///     instance:       &ash::Instance
+///     vk_ctx:         &mut GkVkContext        <i>// mutable because of allocator</i>
+///     label:          &str                    <i>// Debug name of the images and framebuffer.</i>
+///     width:          u32
+///     height:         u32
+/// - Return
+///     GkResult&lt;GkVkRenderTarget&gt;
+/// </pre>
+pub fn gk_create_vk_render_target(instance: &ash::Instance, vk_ctx: &mut GkVkContext, label: &str, width: u32, height: u32) -> GkResult<GkVkRenderTarget>
+{
+    let format = vk_ctx.swapchain.format;
+    let (handle, alloc) = create_vk_image(
+        &vk_ctx.device, &mut vk_ctx.shared.allocator(), label,
+        width, height, format,
+        vk::ImageTiling::OPTIMAL, vk::ImageUsageFlags::COLOR_ATTACHMENT | vk::ImageUsageFlags::SAMPLED,
+        1, vk::ImageCreateFlags::empty())?;
+    let view = create_vk_image_view(
+        &vk_ctx.device, &handle,
+        &format, vk::ImageAspectFlags::COLOR,
+        vk::ImageViewType::TYPE_2D,
+        1, 1);
+    let size = (width * height * 4) as vk::DeviceSize;
+    let color = finish_vk_image(vk_ctx, label, handle, alloc, view, size)?;
+    let depth = gk_create_vk_depth_img(instance, vk_ctx, width, height)?;
+
+    let cmd_buffer = gk_begin_single_time_vk_command_buffer(vk_ctx)?;
+    transition_vk_image_layout(
+        &vk_ctx.device, &cmd_buffer,
+        color.handle, format,
+        vk::ImageLayout::UNDEFINED,
+        vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+        1, 1);
+    gk_end_single_time_vk_command_buffer(vk_ctx, cmd_buffer)?;
+
+    let renderpass_info = GkVkRenderPassInfo{
+        b_use_color: true,
+        b_clear_color: true,
+        b_use_depth: true,
+        b_clear_depth: true,
+        color_format: format,
+        flags: ERenderPassBit::FIRST,
+        samples: vk::SampleCountFlags::TYPE_1
+    };
+    let renderpass = gk_create_vk_renderpass(instance, vk_ctx, &format!("{} renderpass", label), renderpass_info)?;
+
+    let attachments = [ color.view, depth.view ];
+    let create_info = vk::FramebufferCreateInfo
+    {
+        s_type: vk::StructureType::FRAMEBUFFER_CREATE_INFO,
+        p_next: std::ptr::null(),
+        flags: vk::FramebufferCreateFlags::empty(),
+        render_pass: renderpass.handle,
+        attachment_count: attachments.len() as u32,
+        p_attachments: attachments.as_ptr(),
+        width,
+        height,
+        layers: 1
+    };
+    let framebuffer = unsafe { vk_try!(vk_ctx.device.create_framebuffer(&create_info, None)) };
+    vk_ctx.shared.set_object_name(framebuffer, &format!("{} framebuffer", label));
+
+    Ok(GkVkRenderTarget
+    {
+        color,
+        depth,
+        extent: vk::Extent2D { width, height },
+        renderpass,
+        framebuffer,
+        shared: vk_ctx.shared.clone(),
+    })
+}
@@ -0,0 +1,815 @@
+use ash::{self, vk, prelude::VkResult};
+
+use gpu_allocator::{
+    MemoryLocation,
+    vulkan::{ Allocation, Allocator }
+};
+
+use std::collections::VecDeque;
+
+use crate::{ log_info, log_warn, log_err, vk_check, vk_try };
+use crate::core::gk_error::{ GkError, GkResult };
+
+use super::gunk_vk_buffer::create_vk_buffer;
+use super::gunk_vk_context::GkVkQueues;
+use super::vk_utils::{ create_vk_command_pool, allocate_vk_command_buffers, create_vk_fence, create_vk_semaphore };
+
+/// Size in bytes of the persistent staging ring.
+pub const GK_VK_STAGING_RING_SIZE: vk::DeviceSize = 64 * 1024 * 1024;
+
+/// Offsets handed out by the staging ring are aligned to this value.<br>
+/// Large enough for any texel block used by the renderer (RGBA32F).
+const STAGING_ALIGNMENT: vk::DeviceSize = 16;
+
+/// ### GkVkUploadTicket struct
+/// *Handle to an upload batch. Poll or wait on it through GkVkUploader.*
+/// <pre>
+/// - Members
+///     batch_id:   u64     <i>// Id of the batch the upload was recorded into.</i>
+/// </pre>
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GkVkUploadTicket
+{
+    pub batch_id: u64,
+}
+
+/// ### GkVkImageUploadInfo struct
+/// *Destination of GkVkUploader::upload_image().*
+/// <pre>
+/// - Members
+///     image:          vk::Image   <i>// Must be in UNDEFINED layout.</i>
+///     width:          u32
+///     height:         u32
+///     layer_count:    u32         <i>// Every layer is written, 6 for a cubemap.</i>
+/// </pre>
+#[derive(Debug, Clone, Copy)]
+pub struct GkVkImageUploadInfo
+{
+    pub image:          vk::Image,
+    pub width:          u32,
+    pub height:         u32,
+    pub layer_count:    u32,
+}
+
+/// ### GkVkUploadBatch struct
+/// *A set of copies recorded together and submitted with a single fence.*
+struct GkVkUploadBatch
+{
+    id:             u64,
+    transfer_cmd:   vk::CommandBuffer,
+    acquire_cmd:    vk::CommandBuffer,      // null when transfer and graphics share a family
+    semaphore:      vk::Semaphore,          // transfer -> graphics, null when sharing a family
+    fence:          vk::Fence,
+    ring_end:       u64,                    // ring head when the batch was closed
+    oversized:      Vec<(vk::Buffer, Allocation)>, // staging too big for the ring
+}
+
+/// ### GkVkUploader struct
+/// *Streams data into gpu only buffers and images.*<br>
+/// *Copies are staged through a persistent ring buffer, recorded into batches<br>
+/// and submitted on the dedicated transfer queue when the device has one.<br>
+/// Ownership of the destination is then released to the graphics queue family.*
+/// <pre>
+/// - Members
+///     transfer_family:    u32
+///     graphics_family:    u32
+///     transfer_queue:     vk::Queue
+///     graphics_queue:     vk::Queue
+///     transfer_pool:      vk::CommandPool
+///     acquire_pool:       vk::CommandPool         <i>// Graphics family pool for acquire barriers.</i>
+///     ring:               vk::Buffer
+///     ring_alloc:         Allocation
+///     ring_head:          u64                     <i>// Monotonic write position.</i>
+///     ring_tail:          u64                     <i>// Monotonic position of the oldest byte still in use.</i>
+///     recording:          Option&lt;GkVkUploadBatch&gt;
+///     in_flight:          VecDeque&lt;GkVkUploadBatch&gt;
+///     next_batch_id:      u64
+///     completed_batch_id: u64                     <i>// Every batch with an id below this is complete.</i>
+/// </pre>
+pub struct GkVkUploader
+{
+    transfer_family:    u32,
+    graphics_family:    u32,
+    transfer_queue:     vk::Queue,
+    graphics_queue:     vk::Queue,
+    transfer_pool:      vk::CommandPool,
+    acquire_pool:       vk::CommandPool,
+    ring:               vk::Buffer,
+    ring_alloc:         Option<Allocation>,
+    ring_head:          u64,
+    ring_tail:          u64,
+    recording:          Option<GkVkUploadBatch>,
+    in_flight:          VecDeque<GkVkUploadBatch>,
+    next_batch_id:      u64,
+    completed_batch_id: u64,
+}
+
+impl GkVkUploader
+{
+    /// ### fn GkVkUploader::new( ... ) -> GkResult\<GkVkUploader\>
+    /// *Creates an instance of GkVkUploader and its staging ring.*
+    /// <pre>
+    /// - Params
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    ///     queues:         &GkVkQueues
+    /// - Return
+    ///     GkResult&lt;GkVkUploader&gt;
+    /// </pre>
+    pub fn new(device: &ash::Device, allocator: &mut Allocator, queues: &GkVkQueues) -> GkResult<Self>
+    {
+        log_info!("Creating GkVkUploader...");
+
+        let graphics_family = queues.graphics.index
+            .ok_or_else(|| GkError::Unsupported(String::from("No graphics queue family to upload for")))?;
+        let (transfer_family, transfer_queue) = match queues.transfer.index.filter(|_| queues.has_dedicated_transfer())
+        {
+            Some(transfer_family) => (transfer_family, queues.transfer.handle),
+            None =>
+            {
+                log_warn!("No dedicated transfer queue found. Uploads will use the graphics queue.");
+                (graphics_family, queues.graphics.handle)
+            },
+        };
+
+        let transfer_pool = create_vk_command_pool(device, transfer_family)?;
+        let acquire_pool = match create_vk_command_pool(device, graphics_family)
+        {
+            Ok(pool) => pool,
+            Err(e) =>
+            {
+                unsafe { device.destroy_command_pool(transfer_pool, None); }
+                return Err(e);
+            }
+        };
+
+        let (ring, ring_alloc) = match create_vk_buffer(
+            device, allocator,
+            "upload staging ring", GK_VK_STAGING_RING_SIZE,
+            vk::BufferUsageFlags::TRANSFER_SRC,
+            MemoryLocation::CpuToGpu
+        )
+        {
+            Ok(ring) => ring,
+            Err(e) =>
+            {
+                unsafe
+                {
+                    device.destroy_command_pool(transfer_pool, None);
+                    device.destroy_command_pool(acquire_pool, None);
+                }
+                return Err(e);
+            }
+        };
+
+        log_info!("GkVkUploader created.");
+
+        Ok(Self
+        {
+            transfer_family,
+            graphics_family,
+            transfer_queue,
+            graphics_queue: queues.graphics.handle,
+            transfer_pool,
+            acquire_pool,
+            ring,
+            ring_alloc: Some(ring_alloc),
+            ring_head: 0,
+            ring_tail: 0,
+            recording: None,
+            in_flight: VecDeque::new(),
+            next_batch_id: 1,
+            completed_batch_id: 1,
+        })
+    }
+
+    /// ### fn GkVkUploader::destroy( &mut self, ... )
+    /// *Waits for all uploads to finish and frees the uploader resources.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    /// </pre>
+    pub fn destroy(&mut self, device: &ash::Device, allocator: &mut Allocator)
+    {
+        let _ = self.submit(device, allocator);
+        while !self.in_flight.is_empty()
+        {
+            self.retire_oldest(device, allocator, true);
+        }
+        unsafe
+        {
+            device.destroy_command_pool(self.transfer_pool, None);
+            device.destroy_command_pool(self.acquire_pool, None);
+            device.destroy_buffer(self.ring, None);
+        }
+        if let Some(ring_alloc) = self.ring_alloc.take()
+        {
+            vk_check!( allocator.free(ring_alloc) );
+        }
+    }
+
+    /// ### fn GkVkUploader::is_cross_family( &self ) -> bool
+    /// *True when copies run on a different queue family than rendering.*
+    pub fn is_cross_family(&self) -> bool
+    {
+        self.transfer_family != self.graphics_family
+    }
+
+    /// ### fn GkVkUploader::current_ticket( &self ) -> GkVkUploadTicket
+    /// *Ticket of the batch currently being recorded.*<br>
+    /// *Uploads queued before the next submit() complete with this ticket.<br>
+    /// When nothing is being recorded it refers to the last submitted batch.*
+    pub fn current_ticket(&self) -> GkVkUploadTicket
+    {
+        match self.recording.as_ref()
+        {
+            Some(batch) => GkVkUploadTicket { batch_id: batch.id },
+            None => GkVkUploadTicket { batch_id: self.next_batch_id - 1 }
+        }
+    }
+
+    /// ### fn GkVkUploader::is_complete( &mut self, ... ) -> bool
+    /// *Polls whether the uploads of a ticket are visible to the graphics queue.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:     &ash::Device
+    ///     ticket:     GkVkUploadTicket
+    /// - Return
+    ///     bool
+    /// </pre>
+    pub fn is_complete(&mut self, device: &ash::Device, ticket: GkVkUploadTicket) -> bool
+    {
+        self.poll(device);
+        ticket.batch_id < self.completed_batch_id
+    }
+
+    /// ### fn GkVkUploader::wait( &mut self, ... )
+    /// *Blocks until the uploads of a ticket are done. Submits the batch if still recording.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:     &ash::Device
+    ///     allocator:  &mut Allocator
+    ///     ticket:     GkVkUploadTicket
+    /// </pre>
+    pub fn wait(&mut self, device: &ash::Device, allocator: &mut Allocator, ticket: GkVkUploadTicket)
+    {
+        if self.recording.as_ref().is_some_and(|batch| batch.id <= ticket.batch_id)
+        {
+            let _ = self.submit(device, allocator);
+        }
+        while ticket.batch_id >= self.completed_batch_id && !self.in_flight.is_empty()
+        {
+            self.retire_oldest(device, allocator, true);
+        }
+    }
+
+    /// ### fn GkVkUploader::upload_buffer( ... ) -> GkResult\<GkVkUploadTicket\>
+    /// *Queues a copy of data into dst_buffer.*<br>
+    /// *dst_buffer must have been created with TRANSFER_DST usage.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    ///     dst_buffer:     vk::Buffer
+    ///     data:           &[u8]
+    ///     dst_access:     vk::AccessFlags         <i>// How the graphics queue will read the buffer.</i>
+    /// - Return
+    ///     GkResult&lt;GkVkUploadTicket&gt;
+    /// </pre>
+    pub fn upload_buffer(
+            &mut self, device: &ash::Device, allocator: &mut Allocator,
+            dst_buffer: vk::Buffer, data: &[u8],
+            dst_access: vk::AccessFlags
+        ) -> GkResult<GkVkUploadTicket>
+    {
+        let size = data.len() as vk::DeviceSize;
+        let (src_buffer, src_offset) = self.stage(device, allocator, data)?;
+        let cross_family = self.is_cross_family();
+        let (src_family, dst_family) = self.ownership_families();
+        let batch = self.recording.as_ref().unwrap();
+
+        let region = vk::BufferCopy { src_offset, dst_offset: 0, size };
+
+        // Release on the transfer family, acquire on the graphics family.
+        // When both are the same family a single barrier makes the copy visible.
+        let release = vk::BufferMemoryBarrier
+        {
+            s_type: vk::StructureType::BUFFER_MEMORY_BARRIER,
+            p_next: std::ptr::null(),
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: if cross_family { vk::AccessFlags::empty() } else { dst_access },
+            src_queue_family_index: src_family,
+            dst_queue_family_index: dst_family,
+            buffer: dst_buffer,
+            offset: 0,
+            size
+        };
+        let acquire = vk::BufferMemoryBarrier
+        {
+            src_access_mask: vk::AccessFlags::empty(),
+            dst_access_mask: dst_access,
+            ..release
+        };
+
+        unsafe
+        {
+            device.cmd_copy_buffer(batch.transfer_cmd, src_buffer, dst_buffer, &[region]);
+            device.cmd_pipeline_barrier(
+                batch.transfer_cmd,
+                vk::PipelineStageFlags::TRANSFER,
+                if cross_family { vk::PipelineStageFlags::BOTTOM_OF_PIPE } else { vk::PipelineStageFlags::ALL_GRAPHICS },
+                vk::DependencyFlags::empty(),
+                &[], &[release], &[]
+            );
+            if cross_family
+            {
+                device.cmd_pipeline_barrier(
+                    batch.acquire_cmd,
+                    vk::PipelineStageFlags::TOP_OF_PIPE,
+                    vk::PipelineStageFlags::ALL_GRAPHICS,
+                    vk::DependencyFlags::empty(),
+                    &[], &[acquire], &[]
+                );
+            }
+        }
+
+        Ok(GkVkUploadTicket { batch_id: batch.id })
+    }
+
+    /// ### fn GkVkUploader::upload_image( ... ) -> GkResult\<GkVkUploadTicket\>
+    /// *Queues a copy of tightly packed pixel data into every layer of dst.image.*<br>
+    /// *The image ends up in SHADER_READ_ONLY_OPTIMAL layout, owned by the graphics family.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    ///     dst:            &GkVkImageUploadInfo
+    ///     data:           &[u8]
+    /// - Return
+    ///     GkResult&lt;GkVkUploadTicket&gt;
+    /// </pre>
+    pub fn upload_image(
+            &mut self, device: &ash::Device, allocator: &mut Allocator,
+            dst: &GkVkImageUploadInfo, data: &[u8]
+        ) -> GkResult<GkVkUploadTicket>
+    {
+        let GkVkImageUploadInfo { image: dst_img, width, height, layer_count } = *dst;
+        let (src_buffer, src_offset) = self.stage(device, allocator, data)?;
+        let cross_family = self.is_cross_family();
+        let (src_family, dst_family) = self.ownership_families();
+        let batch = self.recording.as_ref().unwrap();
+
+        let subresource_range = vk::ImageSubresourceRange
+        {
+            aspect_mask: vk::ImageAspectFlags::COLOR,
+            base_mip_level: 0,
+            level_count: 1,
+            base_array_layer: 0,
+            layer_count
+        };
+
+        let to_transfer_dst = vk::ImageMemoryBarrier
+        {
+            s_type: vk::StructureType::IMAGE_MEMORY_BARRIER,
+            p_next: std::ptr::null(),
+            src_access_mask: vk::AccessFlags::empty(),
+            dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            old_layout: vk::ImageLayout::UNDEFINED,
+            new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            src_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            dst_queue_family_index: vk::QUEUE_FAMILY_IGNORED,
+            image: dst_img,
+            subresource_range
+        };
+
+        let copy_region = vk::BufferImageCopy
+        {
+            buffer_offset: src_offset,
+            buffer_row_length: 0,
+            buffer_image_height: 0,
+            image_subresource: vk::ImageSubresourceLayers
+            {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count,
+            },
+            image_offset: vk::Offset3D{ x: 0, y: 0, z: 0 },
+            image_extent: vk::Extent3D{ width, height, depth: 1 }
+        };
+
+        // The layout transition is part of the ownership transfer and must match on both sides.
+        let release = vk::ImageMemoryBarrier
+        {
+            src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+            dst_access_mask: if cross_family { vk::AccessFlags::empty() } else { vk::AccessFlags::SHADER_READ },
+            old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+            new_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+            src_queue_family_index: src_family,
+            dst_queue_family_index: dst_family,
+            ..to_transfer_dst
+        };
+        let acquire = vk::ImageMemoryBarrier
+        {
+            src_access_mask: vk::AccessFlags::empty(),
+            dst_access_mask: vk::AccessFlags::SHADER_READ,
+            ..release
+        };
+
+        unsafe
+        {
+            device.cmd_pipeline_barrier(
+                batch.transfer_cmd,
+                vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[], &[], &[to_transfer_dst]
+            );
+            device.cmd_copy_buffer_to_image(
+                batch.transfer_cmd,
+                src_buffer, dst_img,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL, &[copy_region]
+            );
+            device.cmd_pipeline_barrier(
+                batch.transfer_cmd,
+                vk::PipelineStageFlags::TRANSFER,
+                if cross_family { vk::PipelineStageFlags::BOTTOM_OF_PIPE } else { vk::PipelineStageFlags::FRAGMENT_SHADER },
+                vk::DependencyFlags::empty(),
+                &[], &[], &[release]
+            );
+            if cross_family
+            {
+                device.cmd_pipeline_barrier(
+                    batch.acquire_cmd,
+                    vk::PipelineStageFlags::TOP_OF_PIPE, vk::PipelineStageFlags::FRAGMENT_SHADER,
+                    vk::DependencyFlags::empty(),
+                    &[], &[], &[acquire]
+                );
+            }
+        }
+
+        Ok(GkVkUploadTicket { batch_id: batch.id })
+    }
+
+    /// ### fn GkVkUploader::submit( &mut self, ... ) -> VkResult\<Option\<GkVkUploadTicket\>\>
+    /// *Submits the batch being recorded, if any, without waiting for it.*<br>
+    /// *Also retires batches that have completed since the last call.<br>
+    /// A batch that fails to submit is dropped and its uploads are lost.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    /// - Return
+    ///     VkResult&lt;Option&lt;GkVkUploadTicket&gt;&gt;      <i>// Ticket of the submitted batch.</i>
+    /// </pre>
+    pub fn submit(&mut self, device: &ash::Device, allocator: &mut Allocator) -> VkResult<Option<GkVkUploadTicket>>
+    {
+        self.collect(device, allocator);
+
+        let mut batch = match self.recording.take()
+        {
+            Some(batch) => batch,
+            None => return Ok(None)
+        };
+        batch.ring_end = self.ring_head;
+
+        if let Err(e) = self.submit_batch(device, &batch)
+        {
+            log_err!(format!("Failed to submit upload batch {}: {}", batch.id, e));
+            // Nothing was queued, so the staging memory can be reused once older batches retire.
+            if self.in_flight.is_empty()
+            {
+                self.ring_tail = batch.ring_end;
+                self.completed_batch_id = batch.id + 1;
+            }
+            free_batch(device, allocator, self.transfer_pool, self.acquire_pool, batch);
+            return Err(e);
+        }
+
+        let ticket = GkVkUploadTicket { batch_id: batch.id };
+        self.in_flight.push_back(batch);
+        Ok(Some(ticket))
+    }
+
+    fn submit_batch(&self, device: &ash::Device, batch: &GkVkUploadBatch) -> VkResult<()>
+    {
+        unsafe
+        {
+            device.end_command_buffer(batch.transfer_cmd)?;
+
+            let transfer_submit = vk::SubmitInfo
+            {
+                s_type: vk::StructureType::SUBMIT_INFO,
+                p_next: std::ptr::null(),
+                wait_semaphore_count: 0,
+                p_wait_semaphores: std::ptr::null(),
+                p_wait_dst_stage_mask: std::ptr::null(),
+                command_buffer_count: 1,
+                p_command_buffers: &batch.transfer_cmd,
+                signal_semaphore_count: if batch.semaphore != vk::Semaphore::null() { 1 } else { 0 },
+                p_signal_semaphores: &batch.semaphore
+            };
+
+            if batch.acquire_cmd == vk::CommandBuffer::null()
+            {
+                device.queue_submit(self.transfer_queue, &[transfer_submit], batch.fence)?;
+            } else {
+                device.end_command_buffer(batch.acquire_cmd)?;
+                device.queue_submit(self.transfer_queue, &[transfer_submit], vk::Fence::null())?;
+
+                let wait_stage = vk::PipelineStageFlags::ALL_COMMANDS;
+                let acquire_submit = vk::SubmitInfo
+                {
+                    s_type: vk::StructureType::SUBMIT_INFO,
+                    p_next: std::ptr::null(),
+                    wait_semaphore_count: 1,
+                    p_wait_semaphores: &batch.semaphore,
+                    p_wait_dst_stage_mask: &wait_stage,
+                    command_buffer_count: 1,
+                    p_command_buffers: &batch.acquire_cmd,
+                    signal_semaphore_count: 0,
+                    p_signal_semaphores: std::ptr::null()
+                };
+                device.queue_submit(self.graphics_queue, &[acquire_submit], batch.fence)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// ### fn GkVkUploader::collect( &mut self, ... )
+    /// *Retires every completed batch, freeing ring space and oversized staging buffers.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     device:         &ash::Device
+    ///     allocator:      &mut Allocator
+    /// </pre>
+    pub fn collect(&mut self, device: &ash::Device, allocator: &mut Allocator)
+    {
+        while !self.in_flight.is_empty()
+        {
+            if !self.retire_oldest(device, allocator, false) { break; }
+        }
+    }
+
+    /// Updates completed_batch_id without freeing anything.
+    fn poll(&mut self, device: &ash::Device)
+    {
+        for batch in self.in_flight.iter()
+        {
+            let signaled = unsafe { device.get_fence_status(batch.fence).unwrap_or(false) };
+            if !signaled { break; }
+            self.completed_batch_id = batch.id + 1;
+        }
+    }
+
+    /// Retires the oldest in flight batch. Returns false if it is still running and block is false.
+    fn retire_oldest(&mut self, device: &ash::Device, allocator: &mut Allocator, block: bool) -> bool
+    {
+        let fence = self.in_flight.front().unwrap().fence;
+        unsafe
+        {
+            if block
+            {
+                // On a lost device the fence never signals, the batch is retired regardless.
+                vk_check!( device.wait_for_fences(&[fence], true, u64::MAX) );
+            } else if !device.get_fence_status(fence).unwrap_or(false) {
+                return false;
+            }
+        }
+
+        let batch = self.in_flight.pop_front().unwrap();
+        let (ring_end, id) = (batch.ring_end, batch.id);
+        free_batch(device, allocator, self.transfer_pool, self.acquire_pool, batch);
+        self.ring_tail = ring_end;
+        self.completed_batch_id = id + 1;
+        true
+    }
+
+    /// (src, dst) queue family indices for ownership transfer barriers.
+    fn ownership_families(&self) -> (u32, u32)
+    {
+        if self.is_cross_family()
+        {
+            (self.transfer_family, self.graphics_family)
+        } else {
+            (vk::QUEUE_FAMILY_IGNORED, vk::QUEUE_FAMILY_IGNORED)
+        }
+    }
+
+    /// Opens a new batch if none is being recorded.
+    fn begin_batch(&mut self, device: &ash::Device) -> GkResult<()>
+    {
+        if self.recording.is_some() { return Ok(()); }
+
+        let begin_info = vk::CommandBufferBeginInfo
+        {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT,
+            p_inheritance_info: std::ptr::null()
+        };
+
+        let mut batch = GkVkUploadBatch
+        {
+            id: self.next_batch_id,
+            transfer_cmd: vk::CommandBuffer::null(),
+            acquire_cmd: vk::CommandBuffer::null(),
+            semaphore: vk::Semaphore::null(),
+            fence: vk::Fence::null(),
+            ring_end: self.ring_head,
+            oversized: Vec::new()
+        };
+        if let Err(e) = self.record_batch(device, &mut batch, &begin_info)
+        {
+            // Handles that were never created are still null, destroying a null handle is a no-op.
+            unsafe
+            {
+                if batch.transfer_cmd != vk::CommandBuffer::null() { device.free_command_buffers(self.transfer_pool, &[batch.transfer_cmd]); }
+                if batch.acquire_cmd != vk::CommandBuffer::null() { device.free_command_buffers(self.acquire_pool, &[batch.acquire_cmd]); }
+                device.destroy_semaphore(batch.semaphore, None);
+                device.destroy_fence(batch.fence, None);
+            }
+            return Err(e);
+        }
+
+        self.recording = Some(batch);
+        self.next_batch_id += 1;
+        Ok(())
+    }
+
+    /// Creates the command buffers and sync objects of a new batch and starts recording.
+    fn record_batch(&self, device: &ash::Device, batch: &mut GkVkUploadBatch, begin_info: &vk::CommandBufferBeginInfo) -> GkResult<()>
+    {
+        batch.transfer_cmd = allocate_vk_command_buffers(device, &self.transfer_pool, 1)?[0];
+        unsafe { vk_try!( device.begin_command_buffer(batch.transfer_cmd, begin_info) ); }
+
+        if self.is_cross_family()
+        {
+            batch.acquire_cmd = allocate_vk_command_buffers(device, &self.acquire_pool, 1)?[0];
+            unsafe { vk_try!( device.begin_command_buffer(batch.acquire_cmd, begin_info) ); }
+            batch.semaphore = create_vk_semaphore(device)?;
+        }
+
+        batch.fence = create_vk_fence(device, false)?;
+        Ok(())
+    }
+
+    /// Copies data into staging memory and returns the (buffer, offset) to copy from.
+    fn stage(&mut self, device: &ash::Device, allocator: &mut Allocator, data: &[u8]) -> GkResult<(vk::Buffer, vk::DeviceSize)>
+    {
+        let size = data.len() as u64;
+
+        if size > GK_VK_STAGING_RING_SIZE
+        {
+            self.begin_batch(device)?;
+            let (buffer, allocation) = create_vk_buffer(
+                device, allocator,
+                "upload oversized staging", size,
+                vk::BufferUsageFlags::TRANSFER_SRC,
+                MemoryLocation::CpuToGpu
+            )?;
+            unsafe
+            {
+                let mapped_ptr = allocation.mapped_ptr().unwrap().as_ptr() as *mut u8;
+                mapped_ptr.copy_from_nonoverlapping(data.as_ptr(), data.len());
+            }
+            self.recording.as_mut().unwrap().oversized.push((buffer, allocation));
+            return Ok((buffer, 0));
+        }
+
+        let prev_head = self.ring_head;
+        let offset = loop
+        {
+            if let Some(offset) = self.try_reserve(size) { break offset; }
+
+            // Ring is full. Flush what is being recorded and wait for the oldest batch.
+            if self.in_flight.is_empty()
+            {
+                // A failed submit frees its staging space directly.
+                let _ = self.submit(device, allocator);
+                if self.in_flight.is_empty()
+                {
+                    // Nothing uses the ring, but the data fits neither before nor after the head. Restart at the ring's start.
+                    self.ring_head = ring_restart(self.ring_head);
+                    self.ring_tail = self.ring_head;
+                    continue;
+                }
+            }
+            self.retire_oldest(device, allocator, true);
+        };
+
+        // begin_batch() may only run after reserving, a flush above would close it.
+        if let Err(e) = self.begin_batch(device)
+        {
+            // The tail may have moved past prev_head when the ring restarted.
+            self.ring_head = prev_head.max(self.ring_tail);
+            return Err(e);
+        }
+
+        unsafe
+        {
+            let mapped_ptr = self.ring_alloc.as_ref().unwrap().mapped_ptr().unwrap().as_ptr() as *mut u8;
+            mapped_ptr.add(offset as usize).copy_from_nonoverlapping(data.as_ptr(), data.len());
+        }
+
+        Ok((self.ring, offset))
+    }
+
+    /// Reserves size bytes in the ring. Returns the offset or None if it does not fit yet.
+    fn try_reserve(&mut self, size: u64) -> Option<vk::DeviceSize>
+    {
+        let (head, offset) = reserve_ring_span(self.ring_head, self.ring_tail, size)?;
+        self.ring_head = head;
+        Some(offset)
+    }
+}
+
+/// Places size bytes after head in a ring whose oldest used byte is tail.<br>
+/// Returns the new head and the offset in the ring, or None if the span would overwrite bytes still in use.
+fn reserve_ring_span(head: u64, tail: u64, size: u64) -> Option<(u64, vk::DeviceSize)>
+{
+    let mut head = (head + STAGING_ALIGNMENT - 1) & !(STAGING_ALIGNMENT - 1);
+    let mut offset = head % GK_VK_STAGING_RING_SIZE;
+    // Allocations never wrap around the end of the ring.
+    if offset + size > GK_VK_STAGING_RING_SIZE
+    {
+        head += GK_VK_STAGING_RING_SIZE - offset;
+        offset = 0;
+    }
+    if head + size - tail > GK_VK_STAGING_RING_SIZE
+    {
+        return None;
+    }
+    Some((head + size, offset))
+}
+
+/// Monotonic position of the next start of the ring at or after head.
+fn ring_restart(head: u64) -> u64
+{
+    head.div_ceil(GK_VK_STAGING_RING_SIZE) * GK_VK_STAGING_RING_SIZE
+}
+
+/// Frees the command buffers, sync objects and oversized staging of a batch.
+fn free_batch(device: &ash::Device, allocator: &mut Allocator, transfer_pool: vk::CommandPool, acquire_pool: vk::CommandPool, batch: GkVkUploadBatch)
+{
+    unsafe
+    {
+        device.free_command_buffers(transfer_pool, &[batch.transfer_cmd]);
+        if batch.acquire_cmd != vk::CommandBuffer::null()
+        {
+            device.free_command_buffers(acquire_pool, &[batch.acquire_cmd]);
+        }
+        if batch.semaphore != vk::Semaphore::null()
+        {
+            device.destroy_semaphore(batch.semaphore, None);
+        }
+        device.destroy_fence(batch.fence, None);
+        for (buffer, allocation) in batch.oversized.into_iter()
+        {
+            device.destroy_buffer(buffer, None);
+            vk_check!( allocator.free(allocation) );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn spans_never_wrap_around_the_ring_end()
+    {
+        let (head, offset) = reserve_ring_span(60 * MB, 60 * MB, 8 * MB).unwrap();
+        assert_eq!(offset, 0);
+        assert_eq!(head, GK_VK_STAGING_RING_SIZE + 8 * MB);
+    }
+
+    #[test]
+    fn spans_do_not_overwrite_bytes_in_use()
+    {
+        assert_eq!(reserve_ring_span(40 * MB, 10 * MB, 30 * MB), None);
+        assert_eq!(reserve_ring_span(40 * MB, 10 * MB, 10 * MB), Some((50 * MB, 40 * MB)));
+    }
+
+    #[test]
+    fn an_idle_ring_restarts_for_spans_larger_than_either_side_of_the_head()
+    {
+        // Idle, but 38 MB fits neither the 31 MB after the head nor the 33 MB before it.
+        let idle = 33 * MB;
+        assert_eq!(reserve_ring_span(idle, idle, 38 * MB), None);
+
+        let restart = ring_restart(idle);
+        assert_eq!(reserve_ring_span(restart, restart, 38 * MB), Some((restart + 38 * MB, 0)));
+        assert_eq!(ring_restart(GK_VK_STAGING_RING_SIZE), GK_VK_STAGING_RING_SIZE);
+    }
+}
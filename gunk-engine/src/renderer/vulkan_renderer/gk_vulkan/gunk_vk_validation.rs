@@ -0,0 +1,286 @@
+use std::sync::Mutex;
+use std::sync::atomic::{ AtomicU64, Ordering };
+
+use ash::vk;
+
+/// Most validation error messages kept around for assert_no_validation_errors().
+const GK_VK_MAX_KEPT_ERRORS: usize = 16;
+
+/// ### GkVkValidationConfig struct
+/// *Controls the Vulkan validation layer and which of its messages are reported.*<br>
+/// *Defaults to validation in debug builds only. Each member can be overridden with an environment variable,<br>
+/// see GkVkValidationConfig::with_env().*
+/// <pre>
+/// - Members
+///     b_enabled:          bool    <i>// Load VK_LAYER_KHRONOS_validation and the debug messenger.     GUNK_VK_VALIDATION</i>
+///     b_sync_validation:  bool    <i>// Synchronization validation.                                  GUNK_VK_SYNC_VALIDATION</i>
+///     b_gpu_assisted:     bool    <i>// Gpu assisted validation.                                     GUNK_VK_GPU_VALIDATION</i>
+///     min_severity:       vk::DebugUtilsMessageSeverityFlagsEXT   <i>// Lower severities are dropped.  GUNK_VK_VALIDATION_SEVERITY</i>
+///     suppressed_ids:     Vec&lt;String&gt;     <i>// Message id names or numbers to ignore.       GUNK_VK_VALIDATION_SUPPRESS</i>
+/// </pre>
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkVkValidationConfig
+{
+    pub b_enabled:          bool,
+    pub b_sync_validation:  bool,
+    pub b_gpu_assisted:     bool,
+    pub min_severity:       vk::DebugUtilsMessageSeverityFlagsEXT,
+    pub suppressed_ids:     Vec<String>,
+}
+
+impl Default for GkVkValidationConfig
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            b_enabled: cfg!(debug_assertions),
+            b_sync_validation: false,
+            b_gpu_assisted: false,
+            min_severity: vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
+            suppressed_ids: Vec::new(),
+        }
+    }
+}
+
+impl GkVkValidationConfig
+{
+    /// ### fn GkVkValidationConfig::with_env( self ) -> GkVkValidationConfig
+    /// *Overrides members with the GUNK_VK_* environment variables that are set.*
+    /// <pre>
+    /// - Params
+    ///     <b>self</b>
+    /// - Return
+    ///     GkVkValidationConfig
+    /// </pre>
+    pub fn with_env(self) -> Self
+    {
+        self.with_vars(|name| std::env::var(name).ok())
+    }
+
+    /// ### fn GkVkValidationConfig::with_vars( self, ... ) -> GkVkValidationConfig
+    /// *Same as with_env(), reading variables through lookup.*<br>
+    /// *Booleans accept 1/0, true/false, on/off. Severity is one of verbose, info, warning, error.<br>
+    /// Suppressed ids are comma separated and added to the ones already configured.*
+    /// <pre>
+    /// - Params
+    ///     <b>self</b>
+    ///     lookup:     impl Fn(&str) -> Option&lt;String&gt;
+    /// - Return
+    ///     GkVkValidationConfig
+    /// </pre>
+    pub fn with_vars(mut self, lookup: impl Fn(&str) -> Option<String>) -> Self
+    {
+        if let Some(value) = lookup("GUNK_VK_VALIDATION").and_then(|value| parse_flag(&value))
+        {
+            self.b_enabled = value;
+        }
+        if let Some(value) = lookup("GUNK_VK_SYNC_VALIDATION").and_then(|value| parse_flag(&value))
+        {
+            self.b_sync_validation = value;
+        }
+        if let Some(value) = lookup("GUNK_VK_GPU_VALIDATION").and_then(|value| parse_flag(&value))
+        {
+            self.b_gpu_assisted = value;
+        }
+        if let Some(value) = lookup("GUNK_VK_VALIDATION_SEVERITY").and_then(|value| parse_severity(&value))
+        {
+            self.min_severity = value;
+        }
+        if let Some(value) = lookup("GUNK_VK_VALIDATION_SUPPRESS")
+        {
+            self.suppressed_ids.extend(
+                value.split(',').map(|id| id.trim()).filter(|id| !id.is_empty()).map(String::from)
+            );
+        }
+        self
+    }
+
+    /// ### fn GkVkValidationConfig::severity_flags( &self ) -> vk::DebugUtilsMessageSeverityFlagsEXT
+    /// *min_severity and every severity above it, as the debug messenger expects them.*
+    pub fn severity_flags(&self) -> vk::DebugUtilsMessageSeverityFlagsEXT
+    {
+        [
+            vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE,
+            vk::DebugUtilsMessageSeverityFlagsEXT::INFO,
+            vk::DebugUtilsMessageSeverityFlagsEXT::WARNING,
+            vk::DebugUtilsMessageSeverityFlagsEXT::ERROR,
+        ]
+        .into_iter()
+        .filter(|&severity| severity.as_raw() >= self.min_severity.as_raw())
+        .fold(vk::DebugUtilsMessageSeverityFlagsEXT::empty(), |flags, severity| flags | severity)
+    }
+
+    /// ### fn GkVkValidationConfig::is_suppressed( &self, ... ) -> bool
+    /// *True if a message matches one of suppressed_ids, by id name, decimal or hex id number.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     id_name:        &str
+    ///     id_number:      i32
+    /// - Return
+    ///     bool
+    /// </pre>
+    pub fn is_suppressed(&self, id_name: &str, id_number: i32) -> bool
+    {
+        self.suppressed_ids.iter().any(|id|
+        {
+            id == id_name
+                || id.parse::<i32>().is_ok_and(|number| number == id_number)
+                || id.strip_prefix("0x").and_then(|hex| u32::from_str_radix(hex, 16).ok()).is_some_and(|number| number == id_number as u32)
+        })
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool>
+{
+    match value.trim().to_ascii_lowercase().as_str()
+    {
+        "1" | "true" | "on" | "yes" => Some(true),
+        "0" | "false" | "off" | "no" => Some(false),
+        _ => None
+    }
+}
+
+fn parse_severity(value: &str) -> Option<vk::DebugUtilsMessageSeverityFlagsEXT>
+{
+    match value.trim().to_ascii_lowercase().as_str()
+    {
+        "verbose" => Some(vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE),
+        "info" => Some(vk::DebugUtilsMessageSeverityFlagsEXT::INFO),
+        "warning" | "warn" => Some(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING),
+        "error" => Some(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR),
+        _ => None
+    }
+}
+
+/// ### GkVkValidationState struct
+/// *Shared with the debug messenger callback. Filters messages and counts them.*
+/// <pre>
+/// - Members
+///     config:         GkVkValidationConfig
+///     errors:         AtomicU64
+///     warnings:       AtomicU64
+///     kept_errors:    Mutex&lt;Vec&lt;String&gt;&gt;    <i>// The first few error messages.</i>
+/// </pre>
+pub struct GkVkValidationState
+{
+    pub config:     GkVkValidationConfig,
+    errors:         AtomicU64,
+    warnings:       AtomicU64,
+    kept_errors:    Mutex<Vec<String>>,
+}
+
+impl GkVkValidationState
+{
+    pub fn new(config: GkVkValidationConfig) -> Self
+    {
+        Self { config, errors: AtomicU64::new(0), warnings: AtomicU64::new(0), kept_errors: Mutex::new(Vec::new()) }
+    }
+
+    /// ### fn GkVkValidationState::accept( &self, ... ) -> bool
+    /// *Counts a message and returns whether it should be logged. Suppressed messages are not counted.*
+    /// <pre>
+    /// - Params
+    ///     <b>&self</b>
+    ///     severity:       vk::DebugUtilsMessageSeverityFlagsEXT
+    ///     id_name:        &str
+    ///     id_number:      i32
+    ///     message:        &str
+    /// - Return
+    ///     bool
+    /// </pre>
+    pub fn accept(&self, severity: vk::DebugUtilsMessageSeverityFlagsEXT, id_name: &str, id_number: i32, message: &str) -> bool
+    {
+        if severity.as_raw() < self.config.min_severity.as_raw() || self.config.is_suppressed(id_name, id_number)
+        {
+            return false;
+        }
+        if severity == vk::DebugUtilsMessageSeverityFlagsEXT::ERROR
+        {
+            self.errors.fetch_add(1, Ordering::Relaxed);
+            let mut kept = self.kept_errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if kept.len() < GK_VK_MAX_KEPT_ERRORS
+            {
+                kept.push(String::from(message));
+            }
+        }
+        else if severity == vk::DebugUtilsMessageSeverityFlagsEXT::WARNING
+        {
+            self.warnings.fetch_add(1, Ordering::Relaxed);
+        }
+        true
+    }
+
+    pub fn error_count(&self) -> u64
+    {
+        self.errors.load(Ordering::Relaxed)
+    }
+
+    pub fn warning_count(&self) -> u64
+    {
+        self.warnings.load(Ordering::Relaxed)
+    }
+
+    /// ### fn GkVkValidationState::errors( &self ) -> Vec\<String\>
+    /// *The first error messages received, at most 16.*
+    pub fn errors(&self) -> Vec<String>
+    {
+        self.kept_errors.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    /// ### fn GkVkValidationState::assert_no_errors( &self )
+    /// *Panics listing the errors received so far, if any. Meant for tests.*
+    pub fn assert_no_errors(&self)
+    {
+        let count = self.error_count();
+        assert!(count == 0, "{} Vulkan validation errors:\n{}", count, self.errors().join("\n"));
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use ash::vk;
+
+    use super::{ GkVkValidationConfig, GkVkValidationState };
+
+    #[test]
+    fn env_vars_override_config()
+    {
+        let config = GkVkValidationConfig { b_enabled: false, ..Default::default() }.with_vars(|name| match name
+        {
+            "GUNK_VK_VALIDATION" => Some(String::from("1")),
+            "GUNK_VK_SYNC_VALIDATION" => Some(String::from("on")),
+            "GUNK_VK_GPU_VALIDATION" => Some(String::from("maybe")),
+            "GUNK_VK_VALIDATION_SEVERITY" => Some(String::from("warning")),
+            "GUNK_VK_VALIDATION_SUPPRESS" => Some(String::from("VUID-vkCmdDraw-None-02859, 0x609a13b,")),
+            _ => None
+        });
+
+        assert!(config.b_enabled);
+        assert!(config.b_sync_validation);
+        assert!(!config.b_gpu_assisted);
+        assert_eq!(config.severity_flags(),
+            vk::DebugUtilsMessageSeverityFlagsEXT::WARNING | vk::DebugUtilsMessageSeverityFlagsEXT::ERROR);
+        assert!(config.is_suppressed("VUID-vkCmdDraw-None-02859", 1));
+        assert!(config.is_suppressed("UNASSIGNED", 0x609a13b));
+        assert!(!config.is_suppressed("UNASSIGNED", 7));
+    }
+
+    #[test]
+    fn state_counts_reported_errors_only()
+    {
+        let config = GkVkValidationConfig { suppressed_ids: vec![String::from("VUID-ignored")], ..Default::default() };
+        let state = GkVkValidationState::new(config);
+
+        assert!(!state.accept(vk::DebugUtilsMessageSeverityFlagsEXT::VERBOSE, "", 0, "chatter"));
+        assert!(!state.accept(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR, "VUID-ignored", 0, "known issue"));
+        assert!(state.accept(vk::DebugUtilsMessageSeverityFlagsEXT::WARNING, "", 0, "careful"));
+        assert!(state.accept(vk::DebugUtilsMessageSeverityFlagsEXT::ERROR, "VUID-real", 0, "broken"));
+
+        assert_eq!(state.error_count(), 1);
+        assert_eq!(state.warning_count(), 1);
+        assert_eq!(state.errors(), vec![String::from("broken")]);
+    }
+}
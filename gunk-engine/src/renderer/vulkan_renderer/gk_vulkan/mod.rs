@@ -1,8 +1,15 @@
 pub mod gunk_vk_context;
 pub mod gunk_vk_loader;
+pub mod gunk_vk_validation;
+pub mod gunk_vk_device;
+pub mod gunk_vk_memory;
+pub mod gunk_vk_profiler;
 pub mod gunk_vk_buffer;
+pub mod gunk_vk_upload;
+pub mod gunk_vk_deletion;
 pub mod gunk_vk_img;
 pub mod gunk_vk_render_pass;
+pub mod gunk_vk_render_target;
 pub mod gunk_vk_descriptor;
 pub mod vk_macros;
 pub mod vk_utils;
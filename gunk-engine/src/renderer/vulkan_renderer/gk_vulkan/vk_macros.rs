@@ -13,77 +13,43 @@ macro_rules! vk_check {
     };
 }
 
+/// ### vk_try!( ... )
+/// *Like vk_check!, but returns the error from the enclosing function as a GkError.*<br>
+/// *Works on any Result whose error converts into GkError.*
 #[macro_export]
-macro_rules! vk_validate_info {
-    () => {};
-    ( $x:expr ) => 
+macro_rules! vk_try {
+    ( $result:expr ) => 
     {
-        use colored::Colorize;
-
-        let label: &str = "Info: ";
-        let data = format!("{}", $x );
-        
-        $crate::logger!(label.blue().bold(), data.blue());
+        match $result
+        {
+            Ok(obj) => { obj },
+            Err(e) =>
+            {
+                let err: $crate::core::gk_error::GkError = e.into();
+                $crate::log_err!(err);
+                return Err(err);
+            }
+        }
     };
-    ( $( $x:expr ),* ) =>
-    {
-        use colored::Colorize;
-
-        let mut t = std::string::String::from("");
+}
 
-        let label: &str = "Info: ";
-        $( t.push_str(format!("{}", $x ).as_str()); )*
-        
-        $crate::logger!(label.blue().bold(), t.blue());
-    };
+/// ### vk_validate_info!( ... )
+/// *Logs a validation layer message as information, under the vulkan::validation target.*
+#[macro_export]
+macro_rules! vk_validate_info {
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Info, "vulkan::validation", $( $x )*) };
 }
 
+/// ### vk_validate_warn!( ... )
+/// *Logs a validation layer message as a warning, under the vulkan::validation target.*
 #[macro_export]
 macro_rules! vk_validate_warn {
-    () => {};
-    ( $x:expr ) => 
-    {
-        use colored::Colorize;
-
-        let label: &str = "Warning: ";
-        let data = format!("{}", $x );
-        
-        $crate::logger!(label.yellow().bold(), data.yellow());
-    };
-    ( $( $x:expr ),* ) =>
-    {
-        use colored::Colorize;
-
-        let mut t = std::string::String::from("");
-
-        let label: &str = "Warning: ";
-        $( t.push_str(format!("{}", $x ).as_str()); )*
-        
-        $crate::logger!(label.yellow().bold(), t.yellow());
-    };
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Warn, "vulkan::validation", $( $x )*) };
 }
 
+/// ### vk_validate_err!( ... )
+/// *Logs a validation layer message as an error, under the vulkan::validation target.*
 #[macro_export]
 macro_rules! vk_validate_err {
-    () => {};
-    ( $x:expr ) => 
-    {
-        use colored::Colorize;
-        
-        let label: &str = "Error: ";
-        let data = format!("{}", $x );
-
-        $crate::logger!(label.red().bold(), data.red());
-    };
-    ( $( $x:expr ),* ) =>
-    {
-        use colored::Colorize;
-
-        let mut t = std::string::String::from("");
-
-        let label: &str = "Error: ";
-        $( t.push_str(format!("{}", $x ).as_str()); )*
-
-        $crate::logger!(label.red().bold(), t.red());
-    };
-}
\ No newline at end of file
+    ( $( $x:tt )* ) => { $crate::gk_log!($crate::core::logger::ELogLevel::Error, "vulkan::validation", $( $x )*) };
+}
@@ -5,7 +5,11 @@ use ash::{ self, vk };
 
 use shaderc::ShaderKind;
 
-use crate::{log_err, vk_check, check_err};
+use crate::{log_err, vk_try, profile_fn};
+use crate::core::gk_error::{ GkError, GkResult };
+
+use super::gunk_vk_deletion::EGkVkDeferred;
+use super::gunk_vk_device::GkVkSharedDevice;
 
 /// ### fn is_extension( ... ) -> bool
 /// *Compares a files extension to the string provided.*
@@ -18,7 +22,7 @@ use crate::{log_err, vk_check, check_err};
 /// </pre>
 fn is_extension(file_path: &std::path::Path, file_ext: &str) -> bool
 {
-    let fp_ext = file_path.extension().and_then(OsStr::to_str).unwrap();
+    let fp_ext = file_path.extension().and_then(OsStr::to_str).unwrap_or("");
     if fp_ext == file_ext 
     {
         return true;
@@ -27,120 +31,117 @@ fn is_extension(file_path: &std::path::Path, file_ext: &str) -> bool
     }
 }
 
-/// ### fn read_file_to_string( ... ) -> String
+/// ### fn read_file_to_string( ... ) -> GkResult\<String\>
 /// *Reads and returns the contents of a file to type String.*
 /// <pre>
 /// - Params
 ///     file_path:      &std::path::Path
 /// - Return
-///     String
+///     GkResult&lt;String&gt;
 /// </pre>
-fn read_file_to_string(file_path: &std::path::Path) -> String
+fn read_file_to_string(file_path: &std::path::Path) -> GkResult<String>
 {
-    let mut file = check_err!( std::fs::File::open(file_path) )
-        .expect(format!("Unable to open file {}", file_path.to_str().unwrap()).as_str());
+    let mut file = std::fs::File::open(file_path).map_err(|e| GkError::io(file_path, e))?;
     
     let mut code = String::new();
-    let _buff_size = check_err!( file.read_to_string(&mut code) )
-        .expect(format!("Unable to read file {}", file_path.to_str().unwrap()).as_str());
+    let _buff_size = file.read_to_string(&mut code).map_err(|e| GkError::io(file_path, e))?;
 
-    code
+    Ok(code)
 }
 
-/// ### fn get_shaderc_shaderkind_from_filename( ... ) -> shaderc::ShaderKind
+/// ### fn get_shaderc_shaderkind_from_filename( ... ) -> GkResult\<shaderc::ShaderKind\>
 /// *Get the shaderc::ShaderKind from file name.*
 /// <pre>
 /// - Param
 ///     file_path:      &std::path::Path
 /// - Return
-///     shaderc::ShaderKind
+///     GkResult&lt;shaderc::ShaderKind&gt;
 /// </pre>
-pub fn get_shaderc_shaderkind_from_filename(file_path: &std::path::Path) -> ShaderKind
+pub fn get_shaderc_shaderkind_from_filename(file_path: &std::path::Path) -> GkResult<ShaderKind>
 {
-    if is_extension(&file_path, "vert") { return ShaderKind::Vertex; }
-    if is_extension(&file_path, "frag") { return ShaderKind::Fragment; }
-    if is_extension(&file_path, "comp") { return ShaderKind::Compute; }
-    if is_extension(&file_path, "geom") { return ShaderKind::Geometry; }
-    if is_extension(&file_path, "tesc") { return ShaderKind::TessControl; }
-    if is_extension(&file_path, "tese") { return ShaderKind::TessEvaluation; }
-    log_err!("\nShader file extension for file {} is not supported. Please be sure the following extensions are used: \n\t'.vert' '.frag' '.comp' '.geom' '.tesc' '.tese'", file_path.to_str().unwrap());
-
-    panic!("Shader file extension not supported!");
+    if is_extension(file_path, "vert") { return Ok(ShaderKind::Vertex); }
+    if is_extension(file_path, "frag") { return Ok(ShaderKind::Fragment); }
+    if is_extension(file_path, "comp") { return Ok(ShaderKind::Compute); }
+    if is_extension(file_path, "geom") { return Ok(ShaderKind::Geometry); }
+    if is_extension(file_path, "tesc") { return Ok(ShaderKind::TessControl); }
+    if is_extension(file_path, "tese") { return Ok(ShaderKind::TessEvaluation); }
+    log_err!("\nShader file extension for file {} is not supported. Please be sure the following extensions are used: \n\t'.vert' '.frag' '.comp' '.geom' '.tesc' '.tese'", file_path.display());
+
+    Err(GkError::ShaderCompile(format!("{}: shader file extension not supported", file_path.display())))
 }
 
-/// ### fn get_vk_shader_stage_from_shaderc_shaderkind( ... ) -> vk::ShaderStageFlags
+/// ### fn get_vk_shader_stage_from_shaderc_shaderkind( ... ) -> GkResult\<vk::ShaderStageFlags\>
 /// *Get the vk::ShaderStageFlags from shaderc::ShaderKind*
 /// <pre>
 /// - Param
 ///     shader_kind:     shaderc::ShaderKind
 /// - Return
-///     vk::ShaderStageFlags
+///     GkResult&lt;vk::ShaderStageFlags&gt;
 /// </pre>
-pub fn get_vk_shader_stage_from_shaderc_shaderkind(shader_kind: ShaderKind) -> vk::ShaderStageFlags
+pub fn get_vk_shader_stage_from_shaderc_shaderkind(shader_kind: ShaderKind) -> GkResult<vk::ShaderStageFlags>
 {
     match shader_kind
     {
-        ShaderKind::Vertex => { return vk::ShaderStageFlags::VERTEX; },
-        ShaderKind::Fragment => { return vk::ShaderStageFlags::FRAGMENT; },
-        ShaderKind::Compute => { return vk::ShaderStageFlags::COMPUTE; },
-        ShaderKind::Geometry => { return vk::ShaderStageFlags::GEOMETRY; },
-        ShaderKind::TessControl => { return vk::ShaderStageFlags::TESSELLATION_CONTROL; },
-        ShaderKind::TessEvaluation => { return vk::ShaderStageFlags::TESSELLATION_EVALUATION; }
-        _ => 
-        {
-            panic!("Shader stage not supported not supported!");
-        }   
+        ShaderKind::Vertex => Ok(vk::ShaderStageFlags::VERTEX),
+        ShaderKind::Fragment => Ok(vk::ShaderStageFlags::FRAGMENT),
+        ShaderKind::Compute => Ok(vk::ShaderStageFlags::COMPUTE),
+        ShaderKind::Geometry => Ok(vk::ShaderStageFlags::GEOMETRY),
+        ShaderKind::TessControl => Ok(vk::ShaderStageFlags::TESSELLATION_CONTROL),
+        ShaderKind::TessEvaluation => Ok(vk::ShaderStageFlags::TESSELLATION_EVALUATION),
+        _ => Err(GkError::Unsupported(format!("Shader stage {:?} not supported", shader_kind))),
     }
 }
 
-/// ### fn get_vk_shader_stage_from_filename( ... ) -> vk::ShaderStageFlags
+/// ### fn get_vk_shader_stage_from_filename( ... ) -> GkResult\<vk::ShaderStageFlags\>
 /// *Get vk::ShaderStageFlags from file_name*
 /// <pre>
 /// - Params
 ///     file_path:      &std::path::Path
 /// - Return
-///     vk::ShaderStageFlags
+///     GkResult&lt;vk::ShaderStageFlags&gt;
 /// </pre>
-pub fn get_vk_shader_stage_from_filename(file_path: &std::path::Path) -> vk::ShaderStageFlags
+pub fn get_vk_shader_stage_from_filename(file_path: &std::path::Path) -> GkResult<vk::ShaderStageFlags>
 {
-    get_vk_shader_stage_from_shaderc_shaderkind(get_shaderc_shaderkind_from_filename(file_path))
+    get_vk_shader_stage_from_shaderc_shaderkind(get_shaderc_shaderkind_from_filename(file_path)?)
 }
 
-/// ### fn compile_shader_to_spirv( ... ) -> Vec\<u32\>
+/// ### fn compile_shader_to_spirv( ... ) -> GkResult\<shaderc::CompilationArtifact\>
 /// *Compiles a shader source file into a binary in format Vec\<u32\>*
 /// <pre>
 /// - Params
 ///     file_path:      &std::path::Path
 /// - Return
-///     shaderc::CompilationArtifact      // Spirv binary
+///     GkResult&lt;shaderc::CompilationArtifact&gt;      // Spirv binary
 /// </pre>
-pub fn compile_shader_to_spirv(file_path: &std::path::Path) -> shaderc::CompilationArtifact
+pub fn compile_shader_to_spirv(file_path: &std::path::Path) -> GkResult<shaderc::CompilationArtifact>
 {
-    let source = read_file_to_string(file_path);
-    let shader_kind = get_shaderc_shaderkind_from_filename(file_path);
-
-    let compiler = shaderc::Compiler::new().unwrap();
-
-    check_err!( 
-        compiler.compile_into_spirv(
-            &source.as_str(), 
-            shader_kind, 
-            file_path.to_str().unwrap(),
-            "main", 
-            None
-        )
-    ).unwrap()
+    profile_fn!();
+    let source = read_file_to_string(file_path)?;
+    let shader_kind = get_shaderc_shaderkind_from_filename(file_path)?;
+
+    let compiler = shaderc::Compiler::new()
+        .ok_or_else(|| GkError::ShaderCompile(String::from("Failed to initialize shaderc compiler")))?;
+
+    let spirv = compiler.compile_into_spirv(
+        source.as_str(), 
+        shader_kind, 
+        &file_path.to_string_lossy(),
+        "main", 
+        None
+    ).map_err(|e| { log_err!(e); GkError::from(e) })?;
+
+    Ok(spirv)
 }
 
-/// ### fn create_vk_shader_modue( ... ) -> vk::ShaderModule
+/// ### fn create_vk_shader_modue( ... ) -> GkResult\<vk::ShaderModule\>
 /// <pre>
 /// - Params
 ///     device:     &ash::Device
 ///     spirv:      &shaderc::CompilationArtifact
 /// - Return
-///     vk::ShaderModule
+///     GkResult&lt;vk::ShaderModule&gt;
 /// </pre>
-pub fn create_vk_shader_module(device: &ash::Device, spirv: &shaderc::CompilationArtifact) -> vk::ShaderModule
+pub fn create_vk_shader_module(device: &ash::Device, spirv: &shaderc::CompilationArtifact) -> GkResult<vk::ShaderModule>
 {
     let create_info = vk::ShaderModuleCreateInfo
     {
@@ -150,57 +151,52 @@ pub fn create_vk_shader_module(device: &ash::Device, spirv: &shaderc::Compilatio
         ..Default::default()
     };
 
-    unsafe { vk_check!(device.create_shader_module(&create_info, None)).unwrap() }
+    Ok(unsafe { vk_try!(device.create_shader_module(&create_info, None)) })
 }
 
 /// ### GkVkShaderModule
+/// *The module is destroyed when dropped, usually right after the pipelines using it are built.*
 /// <pre>
 /// - Members
 ///     handle:     vk::ShaderModule,
 ///     spirv:      shaderc::CompilationArtifact
 ///     stage:      vk::ShaderStageFlags
+///     shared:     GkVkSharedDevice    <i>// private</i>
 /// </pre>
 pub struct GkVkShaderModule
 {
     pub handle:     vk::ShaderModule,
     pub spirv:      shaderc::CompilationArtifact,
-    pub stage:      vk::ShaderStageFlags
+    pub stage:      vk::ShaderStageFlags,
+    shared:         GkVkSharedDevice,
+}
+
+impl Drop for GkVkShaderModule
+{
+    fn drop(&mut self)
+    {
+        self.shared.defer(EGkVkDeferred::ShaderModule(self.handle));
+    }
 }
 
 impl GkVkShaderModule
 {
-    /// ### fn GkVkShaderModule::new(...) -> GkVkShaderModule
+    /// ### fn GkVkShaderModule::new(...) -> GkResult\<GkVkShaderModule\>
     /// *Creates an instance of GkVkShaderModule*
     /// <pre>
     /// - Params
-    ///     device:         &ash::Device
+    ///     shared:         &GkVkSharedDevice
     ///     file_path:      &std::path::Path
     /// - Return
-    ///     GkVkShaderModule
+    ///     GkResult&lt;GkVkShaderModule&gt;
     /// </pre>
-    pub fn new(device: &ash::Device, file_path: &std::path::Path) -> Self
+    pub fn new(shared: &GkVkSharedDevice, file_path: &std::path::Path) -> GkResult<Self>
     {
-        let spirv = compile_shader_to_spirv(file_path);
-        let handle = create_vk_shader_module(device, &spirv);
-        let stage = get_vk_shader_stage_from_filename(file_path);
+        let spirv = compile_shader_to_spirv(file_path)?;
+        let stage = get_vk_shader_stage_from_filename(file_path)?;
+        let handle = create_vk_shader_module(shared.device(), &spirv)?;
 
-        Self{ handle, spirv, stage }
-    }
-
-    /// ### fn GkVkShaderModule::destroy(&mut self, ...)
-    /// *Destroys the Vulkan resources contained in an instance of GkVkShaderModule*
-    /// <pre>
-    /// - Param
-    ///     <b>&mut self</b>
-    ///     device:     &ash::Device
-    /// </pre>
-    pub fn destroy(&mut self, device: &ash::Device)
-    {
-        // self.spirv.clear();
-        unsafe
-        {
-            device.destroy_shader_module(self.handle, None);
-        }
+        Ok(Self{ handle, spirv, stage, shared: shared.clone() })
     }
 
     /// ### fn get_vk_pipeline_info_shader_stage (&self) -> vk::PipelineShaderStageCreateInfo
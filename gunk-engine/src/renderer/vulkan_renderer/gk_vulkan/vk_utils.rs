@@ -5,7 +5,9 @@ use ash::{
 };
 
 use super::gunk_vk_loader::GkVkSurface;
-use crate::{ vk_check, log_info, log_err };
+use super::gunk_vk_profiler::gk_vk_supports_pipeline_stats;
+use crate::{ vk_try, log_info, log_err, log_warn };
+use crate::core::gk_error::{ GkError, GkResult };
 
 use std::ffi::CString;
 
@@ -34,21 +36,25 @@ pub fn is_vk_physical_device_suitable(instance: &ash::Instance, surface: &GkVkSu
     // Geometry shader capable
     let geometry_shader_capabale = device_features.geometry_shader == 1;
 
-    let graphics_family_index = find_vk_queue_family_index(instance, &physical_device, vk::QueueFlags::GRAPHICS);
+    let graphics_family_index = match find_vk_queue_family_index(instance, &physical_device, vk::QueueFlags::GRAPHICS)
+    {
+        Some(index) => index,
+        None => return false
+    };
 
     let present_supported = unsafe 
     { 
         surface.loader.get_physical_device_surface_support(
             physical_device, 
-            graphics_family_index.clone().unwrap(), 
+            graphics_family_index, 
             surface.handle
-        ).unwrap() 
+        ).unwrap_or(false)
     };
 
-    is_gpu && geometry_shader_capabale && graphics_family_index.is_some() && present_supported
+    is_gpu && geometry_shader_capabale && present_supported
 }
 
-/// ### fn find_suitable_vk_physical_device( ... ) -> vk::PhysicalDevice
+/// ### fn find_suitable_vk_physical_device( ... ) -> GkResult\<vk::PhysicalDevice\>
 /// *Queueies available devices, then picks the most suitable.*<br>
 /// *Intrinsically uses "is_vk_physical_device_suitable()"*
 /// <pre>
@@ -56,25 +62,25 @@ pub fn is_vk_physical_device_suitable(instance: &ash::Instance, surface: &GkVkSu
 ///     instance:       &ash::Instance
 ///     surface:        &GkVkSurface
 /// - Return
-///     vk::PhsyicalDevice
+///     GkResult&lt;vk::PhsyicalDevice&gt;
 /// </pre>
-pub fn find_suitable_vk_physical_device(instance: &ash::Instance, surface: &GkVkSurface) -> vk::PhysicalDevice
+pub fn find_suitable_vk_physical_device(instance: &ash::Instance, surface: &GkVkSurface) -> GkResult<vk::PhysicalDevice>
 {
     log_info!("Finding suitable VkPhysicalDevice...");
 
-    let physical_devices = unsafe { vk_check!(instance.enumerate_physical_devices()).unwrap() };
+    let physical_devices = unsafe { vk_try!(instance.enumerate_physical_devices()) };
 
     for &physical_device in physical_devices.iter()
     {
         if is_vk_physical_device_suitable(instance, surface, physical_device)
         {
             log_info!("VkPhysicalDevice found.");
-            return physical_device;
+            return Ok(physical_device);
         }
     }
 
     log_err!("VkPhysical Device not found!");
-    panic!("Failed to find a suitable GPU (VkPhysicalDevice)");
+    Err(GkError::Unsupported(String::from("Failed to find a suitable GPU (VkPhysicalDevice)")))
 }
 
 /// ### fn find_vk_queue_family_index( ... ) -> Option\<u32\>
@@ -106,18 +112,18 @@ pub fn find_vk_queue_family_index(instance: &ash::Instance, physical_device: &vk
     result
 }
 
-/// ### fn create_vk_device( ... ) -> ash::Device
+/// ### fn create_vk_device( ... ) -> GkResult\<ash::Device\>
 /// *Creates an ash::Device (VkDevice) struct.*<br>
 /// *Used to execute vulkan gpu functions.*
 /// <pre>
 /// - Params
 ///     instance:           &ash::Instance
 ///     physical_device:    &vk::PhysicalDevice
-///     queue_indices:      &Vec&lt;u32&gt;
+///     queue_indices:      &[u32]
 /// - Return
-///     ash::Device
+///     GkResult&lt;ash::Device&gt;
 /// </pre>
-pub fn create_vk_device(instance: &ash::Instance, physical_device: &vk::PhysicalDevice, queue_indices: &Vec<u32>) -> ash::Device
+pub fn create_vk_device(instance: &ash::Instance, physical_device: &vk::PhysicalDevice, queue_indices: &[u32]) -> GkResult<ash::Device>
 {
     log_info!("Creating VkDevice handle...");
 
@@ -144,6 +150,13 @@ pub fn create_vk_device(instance: &ash::Instance, physical_device: &vk::Physical
         vk_khr_shader_draw_parameters.as_ptr()
     ];
 
+    // Pipeline statistics are only used by the gpu profiler, so they are optional.
+    let features = vk::PhysicalDeviceFeatures
+    {
+        pipeline_statistics_query: if gk_vk_supports_pipeline_stats(instance, physical_device) { vk::TRUE } else { vk::FALSE },
+        ..Default::default()
+    };
+
     let create_info = vk::DeviceCreateInfo
     {
         s_type: vk::StructureType::DEVICE_CREATE_INFO,
@@ -153,17 +166,18 @@ pub fn create_vk_device(instance: &ash::Instance, physical_device: &vk::Physical
         p_queue_create_infos: queue_create_infos.as_ptr(),
         enabled_extension_count: extensions.len() as u32,
         pp_enabled_extension_names: extensions.as_ptr(),
+        p_enabled_features: &features,
         ..Default::default()
     };
 
-    let device = unsafe { vk_check!( instance.create_device(*physical_device, &create_info, None)).unwrap() };
+    let device = unsafe { vk_try!( instance.create_device(*physical_device, &create_info, None)) };
 
     log_info!("VkDevice created.");
 
-    device
+    Ok(device)
 }
 
-/// ### fn create_vk_allocator( ... ) -> gpu_allocator::vulkan::Allocator
+/// ### fn create_vk_allocator( ... ) -> GkResult\<gpu_allocator::vulkan::Allocator\>
 /// *Creates a vulkan gpu_allocator::Allocator struct.*<br>
 /// *Used to allocate gpu memory from a pool.*
 /// <pre>
@@ -172,9 +186,9 @@ pub fn create_vk_device(instance: &ash::Instance, physical_device: &vk::Physical
 ///     physical_device:    &vk::PhysicalDevice
 ///     device:             &ash::Device
 /// -Return
-///     gpu_allocator::vulkan::Allocator
+///     GkResult&lt;gpu_allocator::vulkan::Allocator&gt;
 /// </pre>
-pub fn create_vk_allocator(instance: &ash::Instance, physical_device: &vk::PhysicalDevice, device: &ash::Device) -> gpu_allocator::vulkan::Allocator
+pub fn create_vk_allocator(instance: &ash::Instance, physical_device: &vk::PhysicalDevice, device: &ash::Device) -> GkResult<gpu_allocator::vulkan::Allocator>
 {
     log_info!("Creating Memory Allocator...");
 
@@ -188,11 +202,11 @@ pub fn create_vk_allocator(instance: &ash::Instance, physical_device: &vk::Physi
         allocation_sizes: Default::default(),
     };
 
-    let allocator = gpu_allocator::vulkan::Allocator::new(&alloc_desc).map_err(|e| { log_err!(e); } ).unwrap();
+    let allocator = vk_try!(gpu_allocator::vulkan::Allocator::new(&alloc_desc));
  
     log_info!("Memory Allocator created");
 
-    allocator
+    Ok(allocator)
 }
 
 /// ### VkSwapchainDetails struct
@@ -210,31 +224,31 @@ pub struct VkSwapchainDetails
     pub present_modes: Vec<vk::PresentModeKHR>
 }
 
-/// ### fn query_vk_swapchain_details( ... ) -> VkSwapchainDetails
+/// ### fn query_vk_swapchain_details( ... ) -> GkResult\<VkSwapchainDetails\>
 /// *Queuries the vk::PhysicalDevice and Surface for VkSwapchainDetails*
 /// <pre>
 /// - Params
 ///     physical_device:    &vk::PhysicalDevice
 ///     surface:            &GkVkSurface
 /// - Return
-///     VkSwapchainDetails
+///     GkResult&lt;VkSwapchainDetails&gt;
 /// </pre>
-pub fn query_vk_swapchain_details(physical_device: &vk::PhysicalDevice, surface: &GkVkSurface) -> VkSwapchainDetails
+pub fn query_vk_swapchain_details(physical_device: &vk::PhysicalDevice, surface: &GkVkSurface) -> GkResult<VkSwapchainDetails>
 {
     let capabilities = unsafe
     {
-        surface.loader.get_physical_device_surface_capabilities(*physical_device, surface.handle).map_err( |e| { log_err!(e); } ).unwrap()
+        vk_try!(surface.loader.get_physical_device_surface_capabilities(*physical_device, surface.handle))
     };
     let formats = unsafe
     {
-        surface.loader.get_physical_device_surface_formats(*physical_device, surface.handle).map_err( |e| { log_err!(e); } ).unwrap()
+        vk_try!(surface.loader.get_physical_device_surface_formats(*physical_device, surface.handle))
     };
     let present_modes = unsafe
     {
-        surface.loader.get_physical_device_surface_present_modes(*physical_device, surface.handle).map_err( |e| { log_err!(e); } ).unwrap()    
+        vk_try!(surface.loader.get_physical_device_surface_present_modes(*physical_device, surface.handle))
     };
 
-    VkSwapchainDetails { capabilities, formats, present_modes }
+    Ok(VkSwapchainDetails { capabilities, formats, present_modes })
 }
 
 /// ### fn choose_vk_swap_surface_format( ... ) -> vk::SurfaceFormatKHR
@@ -259,22 +273,28 @@ pub fn choose_vk_swap_surface_format(formats: Vec<vk::SurfaceFormatKHR>) -> vk::
 
 /// ### fn choose_vk_swap_present_mode( ... ) -> vk::PresentModeKHR
 /// *Chooses a vk::PresentMode for the vk::Swapchain*<br>
-/// *Choice is made by traversing a Vec<> of vk::PresentMode*
+/// *With vsync FIFO is used, which is always supported. Without it MAILBOX is preferred, then IMMEDIATE.*
 /// <pre>
 /// - Params
 ///     present_modes:  Vec&lt;vk::PresentMode&gt;    <i>// vk::PresentMode(s) to choose from</i>
+///     b_vsync:        bool
 /// - Return
 ///     vk::PresentMode                         <i>// Chosen vk::PresentMode</i>
 /// </pre>
-pub fn choose_vk_swap_present_mode(present_modes: Vec<vk::PresentModeKHR>) -> vk::PresentModeKHR
+pub fn choose_vk_swap_present_mode(present_modes: Vec<vk::PresentModeKHR>, b_vsync: bool) -> vk::PresentModeKHR
 {
-    for mode in present_modes.iter()
+    if b_vsync
+    {
+        return vk::PresentModeKHR::FIFO;
+    }
+    for preferred in [vk::PresentModeKHR::MAILBOX, vk::PresentModeKHR::IMMEDIATE]
     {
-        if *mode == vk::PresentModeKHR::MAILBOX
+        if present_modes.contains(&preferred)
         {
-            return mode.clone();
+            return preferred;
         }
     }
+    log_warn!("Surface only supports vsync'd present modes, using FIFO.");
     vk::PresentModeKHR::FIFO
 }
 
@@ -311,7 +331,7 @@ pub fn choose_vk_swap_image_extent(capabilities: &vk::SurfaceCapabilitiesKHR, wi
     }
 }
 
-/// ### fn create_vk_swapchain( ... ) -> (khr::Swapchain, vk::SwapchainKHR)
+/// ### fn create_vk_swapchain( ... ) -> GkResult\<(khr::Swapchain, vk::SwapchainKHR)\>
 /// *Creates a tuple of khr::Swapchain and vk::SwapchainKHR* <br>
 /// *khr::Swapchain loads and destroys an instance of vk::SwapchainKHR*
 /// <pre>
@@ -325,7 +345,7 @@ pub fn choose_vk_swap_image_extent(capabilities: &vk::SurfaceCapabilitiesKHR, wi
 ///     present_mode:       &vk::PresentModeKHR
 ///     extent:             &vk::Extent2D
 /// - Returns
-///     (khr::Swapchain, vk::SwapchainKHR)
+///     GkResult&lt;(khr::Swapchain, vk::SwapchainKHR)&gt;
 /// </pre>
 pub fn create_vk_swapchain(
         instance: &ash::Instance,
@@ -336,7 +356,7 @@ pub fn create_vk_swapchain(
         surface_format: &vk::SurfaceFormatKHR,
         present_mode: &vk::PresentModeKHR,
         extent: &vk::Extent2D,
-    ) -> (khr::Swapchain, vk::SwapchainKHR)
+    ) -> GkResult<(khr::Swapchain, vk::SwapchainKHR)>
 {
     log_info!("Createing VkSwaphainKHR handle...");
 
@@ -365,24 +385,24 @@ pub fn create_vk_swapchain(
     let swapchain_loader = ash::extensions::khr::Swapchain::new(instance, device);
     let swapchain_handle = unsafe 
     {
-        swapchain_loader.create_swapchain(&create_info, None).map_err( |e| { log_err!(e); } ).unwrap()
+        vk_try!(swapchain_loader.create_swapchain(&create_info, None))
     };
 
     log_info!("VkSwapchainKHR created");
 
-    (swapchain_loader, swapchain_handle)
+    Ok((swapchain_loader, swapchain_handle))
 }
 
-/// ### fn create_vk_command_pool( ... ) -> vk::CommandPool
+/// ### fn create_vk_command_pool( ... ) -> GkResult\<vk::CommandPool\>
 /// *Creates a vk::CommandPool*
 /// <pre>
 /// - Params
 ///     device:                 &ash::Device
 ///     queue_family_index:     u32     <i>// Queue index of the command family</i>
 /// - Return
-///     vk::CommandPool
+///     GkResult&lt;vk::CommandPool&gt;
 /// </pre>
-pub fn create_vk_command_pool(device: &ash::Device, queue_family_index: u32) -> vk::CommandPool
+pub fn create_vk_command_pool(device: &ash::Device, queue_family_index: u32) -> GkResult<vk::CommandPool>
 {
     let create_info = vk::CommandPoolCreateInfo
     {
@@ -393,11 +413,11 @@ pub fn create_vk_command_pool(device: &ash::Device, queue_family_index: u32) ->
     };
 
     unsafe{
-        vk_check!(device.create_command_pool(&create_info, None)).unwrap()
+        Ok(vk_try!(device.create_command_pool(&create_info, None)))
     }
 }
 
-/// ### fn allocate_vk_command_buffers() -> Vec\<vk::CommandBuffer\>
+/// ### fn allocate_vk_command_buffers() -> GkResult\<Vec\<vk::CommandBuffer\>\>
 /// *Allocates a Vec\<vk::CommandBuffer\>*
 /// <pre>
 /// - Params
@@ -405,9 +425,9 @@ pub fn create_vk_command_pool(device: &ash::Device, queue_family_index: u32) ->
 ///     cmd_pool:      &vk::CommandPool
 ///     buffer_count:  u32                 <i>// Amount to allocate.</i>
 /// - Return
-///     Vec&lt;vk::CommandBuffer&gt;
+///     GkResult&lt;Vec&lt;vk::CommandBuffer&gt;&gt;
 /// </pre>
-pub fn allocate_vk_command_buffers(device: &ash::Device, cmd_pool: &vk::CommandPool, buffer_count: u32 ) -> Vec<vk::CommandBuffer>
+pub fn allocate_vk_command_buffers(device: &ash::Device, cmd_pool: &vk::CommandPool, buffer_count: u32 ) -> GkResult<Vec<vk::CommandBuffer>>
 {
     let alloc_info = vk::CommandBufferAllocateInfo
     {
@@ -419,19 +439,19 @@ pub fn allocate_vk_command_buffers(device: &ash::Device, cmd_pool: &vk::CommandP
     };
 
     unsafe{
-        vk_check!(device.allocate_command_buffers(&alloc_info)).unwrap()
+        Ok(vk_try!(device.allocate_command_buffers(&alloc_info)))
     }
 }
 
-/// ### fn create_vk_semaphore( ... ) -> vk::Semaphore
+/// ### fn create_vk_semaphore( ... ) -> GkResult\<vk::Semaphore\>
 /// *Creates a vk::Semaphore*
 /// <pre>
 /// - Params
 ///      device:     &ash::Device
 /// - Return
-///       vk::Semaphore
+///       GkResult&lt;vk::Semaphore&gt;
 /// </pre>
-pub fn create_vk_semaphore(device: &ash::Device) -> vk::Semaphore
+pub fn create_vk_semaphore(device: &ash::Device) -> GkResult<vk::Semaphore>
 {
     let create_info = vk::SemaphoreCreateInfo
     {
@@ -440,20 +460,20 @@ pub fn create_vk_semaphore(device: &ash::Device) -> vk::Semaphore
         flags: vk::SemaphoreCreateFlags::empty()
     };
     unsafe {
-        vk_check!(device.create_semaphore(&create_info, None)).unwrap()
+        Ok(vk_try!(device.create_semaphore(&create_info, None)))
     }
 }
 
-/// ### fn create_vk_fence( ... ) -> vk::Fence
+/// ### fn create_vk_fence( ... ) -> GkResult\<vk::Fence\>
 /// *Creates a vk::Fence*
 /// <pre>
 /// - Params
 ///     device:         &ash::Device
 ///     is_signaled:    bool
 /// - Return
-///       vk::Fence
+///       GkResult&lt;vk::Fence&gt;
 /// </pre>
-pub fn create_vk_fence(device: &ash::Device, is_signaled: bool) -> vk::Fence
+pub fn create_vk_fence(device: &ash::Device, is_signaled: bool) -> GkResult<vk::Fence>
 {
     let create_info = vk::FenceCreateInfo
     {
@@ -462,11 +482,11 @@ pub fn create_vk_fence(device: &ash::Device, is_signaled: bool) -> vk::Fence
         flags: if is_signaled { vk::FenceCreateFlags::SIGNALED } else { vk::FenceCreateFlags::empty() }
     };
     unsafe{
-        vk_check!(device.create_fence(&create_info, None)).unwrap()
+        Ok(vk_try!(device.create_fence(&create_info, None)))
     }
 }
 
-/// ### fn create_vk_pipeline_layout( ... ) -> vk::PipelineLayout
+/// ### fn create_vk_pipeline_layout( ... ) -> GkResult\<vk::PipelineLayout\>
 /// *Creates a vk::PipelineLayout*
 /// <pre>
 /// - Params
@@ -474,13 +494,13 @@ pub fn create_vk_fence(device: &ash::Device, is_signaled: bool) -> vk::Fence
 ///     desc_set_layouts:   &Vec&lt;vk::DescriptorSetLayout&gt;
 ///     push_const_ranges:  &Vec&lt;vk::PushConstantRange&gt;
 /// - Return
-///     vk::PipelineLayout
+///     GkResult&lt;vk::PipelineLayout&gt;
 /// </pre>
 pub fn create_vk_pipeline_layout(
         device: &ash::Device, 
         desc_set_layouts: &Vec<vk::DescriptorSetLayout>, 
         push_const_ranges: &Vec<vk::PushConstantRange>
-    ) -> vk::PipelineLayout
+    ) -> GkResult<vk::PipelineLayout>
 {
     let create_info = vk::PipelineLayoutCreateInfo
     {
@@ -495,7 +515,7 @@ pub fn create_vk_pipeline_layout(
 
     unsafe
     {
-        vk_check!(device.create_pipeline_layout(&create_info, None)).unwrap()
+        Ok(vk_try!(device.create_pipeline_layout(&create_info, None)))
     }
 }
 
@@ -659,20 +679,22 @@ pub fn create_vk_pipeline_info_color_blend(attachments: &Vec<vk::PipelineColorBl
     }   
 }
 
-/// ### fn create_vk_pipeline_info_depth_stencil() -> vk::PipelineDepthStencilStateCreateInfo
+/// ### fn create_vk_pipeline_info_depth_stencil( ... ) -> vk::PipelineDepthStencilStateCreateInfo
 /// *Creates a vk::PipelineDepthStencilStateCreateInfo struct*
 /// <pre>
+/// - Params
+///     b_reverse_z:    bool    <i>// Nearer fragments have greater depth.</i>
 /// - Return
 ///     vk::PipelineDepthStencilStateCreateInfo
 /// </pre>
-pub fn create_vk_pipeline_info_depth_stencil() -> vk::PipelineDepthStencilStateCreateInfo
+pub fn create_vk_pipeline_info_depth_stencil(b_reverse_z: bool) -> vk::PipelineDepthStencilStateCreateInfo
 {
     vk::PipelineDepthStencilStateCreateInfo
     {
         s_type: vk::StructureType::PIPELINE_DEPTH_STENCIL_STATE_CREATE_INFO,
         depth_test_enable: vk::TRUE,
         depth_write_enable: vk::TRUE,
-        depth_compare_op: vk::CompareOp::LESS,
+        depth_compare_op: if b_reverse_z { vk::CompareOp::GREATER } else { vk::CompareOp::LESS },
         depth_bounds_test_enable: vk::FALSE,
         min_depth_bounds: 0.0,
         max_depth_bounds: 1.0,
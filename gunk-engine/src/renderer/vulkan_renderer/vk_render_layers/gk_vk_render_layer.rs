@@ -4,19 +4,79 @@ use crate::renderer::vulkan_renderer::gk_vulkan::{
     gunk_vk_context::GkVkContext, 
     gunk_vk_buffer::GkVkBuffer, 
     gunk_vk_img::GkVkImage,
-    gunk_vk_render_pass::GkVkRenderPass};
+    gunk_vk_render_pass::GkVkRenderPass,
+    gunk_vk_render_target::GkVkRenderTarget};
+use crate::renderer::gk_asset_loader::{ GkAssetHandle, EAssetData };
+use crate::core::gk_error::GkResult;
+use crate::scene::GkSceneGraph;
+
+/// ### GkVkView struct
+/// *Where one camera draws this frame and where its data sits in the transform uniform.*
+/// <pre>
+/// - Members
+///     viewport:           vk::Viewport
+///     scissor:            vk::Rect2D
+///     uniform_offset:     u32             <i>// Dynamic offset of the camera's GkCameraUniformData, in bytes.</i>
+///     target:             Option&lt;usize&gt;   <i>// Camera slot of the GkVkRenderTarget drawn to, None for the swapchain.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug)]
+pub struct GkVkView
+{
+    pub viewport:       vk::Viewport,
+    pub scissor:        vk::Rect2D,
+    pub uniform_offset: u32,
+    pub target:         Option<usize>,
+}
 
 pub trait GkVkLayerDraw
 {
     fn draw_frame(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, current_img: usize);
 
+    // Name of the layer, used for its command buffer region in debuggers.
+    fn label(&self) -> &str;
+
+    // Whether the gpu profiler gathers pipeline statistics for this layer's scope.
+    // Statistics queries can't nest, so containers of layers leave them to their children.
+    fn collects_pipeline_stats(&self) -> bool
+    {
+        true
+    }
+
+    // draw_frame() wrapped in a debug label region and gpu profiler scope named after the layer.
+    fn draw_frame_labelled(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, current_img: usize)
+    {
+        vk_ctx.begin_gpu_scope(*cmd_buffer, self.label(), self.collects_pipeline_stats());
+        self.draw_frame(vk_ctx, cmd_buffer, current_img);
+        vk_ctx.end_gpu_scope(*cmd_buffer);
+    }
+
+    // Draws the layer's views of the camera in slot into its offscreen target, between GkVkRenderTarget::begin() and end().
+    // Only 3d layers draw cameras, the others leave it empty.
+    fn draw_offscreen(&self, _vk_ctx: &GkVkContext, _cmd_buffer: &vk::CommandBuffer, _slot: usize, _target: &GkVkRenderTarget)
+    {
+    }
+
+    // Destroys the raw handles the layer owns, pipelines, samplers and framebuffers.
+    // Buffers, images, descriptors and render passes are released when the layer is dropped.
     fn destroy(&mut self, vk_ctx: &mut GkVkContext);
 
     fn cleanup_framebuffers(&mut self, device: &ash::Device);
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>);
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>;
+
+    // Offers a streamed asset to the layer. Returns true if the layer requested it and swapped it in.
+    fn on_asset_loaded(&mut self, _vk_ctx: &mut GkVkContext, _handle: GkAssetHandle, _asset: &EAssetData) -> bool
+    {
+        false
+    }
 
     fn begin_renderpass(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, renderpass: &GkVkRenderPass, pipeline: vk::Pipeline, framebuffer: vk::Framebuffer)
+    {
+        self.begin_renderpass_sized(vk_ctx, cmd_buffer, renderpass, pipeline, framebuffer, vk_ctx.swapchain.extent);
+    }
+
+    // begin_renderpass() for a framebuffer that isn't swapchain sized, such as a GkVkRenderTarget's.
+    fn begin_renderpass_sized(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, renderpass: &GkVkRenderPass, pipeline: vk::Pipeline, framebuffer: vk::Framebuffer, extent: vk::Extent2D)
     {
         let mut clear_values: Vec<vk::ClearValue> = Vec::new();
         if renderpass.info.b_clear_color
@@ -33,7 +93,8 @@ pub trait GkVkLayerDraw
             clear_values.push(
                 vk::ClearValue
                 {
-                    depth_stencil: vk::ClearDepthStencilValue { depth: 1.0, stencil: 0 }
+                    // Far is depth 0 with a reverse-Z depth buffer.
+                    depth_stencil: vk::ClearDepthStencilValue { depth: if vk_ctx.b_reverse_z { 0.0 } else { 1.0 }, stencil: 0 }
                 }
             );
         }
@@ -41,7 +102,7 @@ pub trait GkVkLayerDraw
         let screen_rect = vk::Rect2D
         {
             offset: vk::Offset2D{ x: 0, y: 0 },
-            extent: vk_ctx.swapchain.extent
+            extent
         };
 
         let render_begin_info = vk::RenderPassBeginInfo
@@ -62,8 +123,8 @@ pub trait GkVkLayerDraw
                 y: 0.0,
                 // width: if custom_width > 0 { custom_width as f32 } else { vk_ctx.swapchain.extent.width as f32 },
                 // height: if custom_height > 0 { custom_height as f32 } else { vk_ctx.swapchain.extent.height as f32 },
-                width: vk_ctx.swapchain.extent.width as f32,
-                height: vk_ctx.swapchain.extent.height as f32,
+                width: extent.width as f32,
+                height: extent.height as f32,
                 min_depth: 0.0,
                 max_depth: 1.0
             }
@@ -77,8 +138,8 @@ pub trait GkVkLayerDraw
                     { 
                         // width: if custom_width > 0 { custom_width } else { vk_ctx.swapchain.extent.width },
                         // height: if custom_height > 0 { custom_height } else { vk_ctx.swapchain.extent.height }
-                        width: vk_ctx.swapchain.extent.width,
-                        height: vk_ctx.swapchain.extent.height
+                        width: extent.width,
+                        height: extent.height
                     }
             }
         ];
@@ -98,6 +159,16 @@ pub trait GkVkLayerDraw
         }
     }
 
+    // Narrows drawing to a camera's viewport, inside a render pass started by begin_renderpass().
+    fn set_view(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, view: &GkVkView)
+    {
+        unsafe
+        {
+            vk_ctx.device.cmd_set_viewport(*cmd_buffer, 0, &[view.viewport]);
+            vk_ctx.device.cmd_set_scissor(*cmd_buffer, 0, &[view.scissor]);
+        }
+    }
+
     fn end_renderpass(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
     {
         unsafe{ vk_ctx.device.cmd_end_render_pass(*cmd_buffer); }
@@ -107,7 +178,9 @@ pub trait GkVkLayerDraw
 
 pub trait GkVk3dLayerUpdate
 {
-    fn update(&mut self, vk_ctx: &GkVkContext, transform_uniform: &GkVkBuffer, delta_time: f32);
+    // views are the cameras to draw this frame, each reading its slot of transform_uniform.
+    // scene has up to date world matrices, layers draw the mesh nodes they own with them.
+    fn update(&mut self, vk_ctx: &GkVkContext, transform_uniform: &GkVkBuffer, views: &[GkVkView], scene: &GkSceneGraph, delta_time: f32);
 
     // fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: &GkVkImage);
 }
@@ -172,13 +245,32 @@ impl GkVkLayerDraw for Vk3dLayerList
     {
         for layer in self.list.iter()
         {
-            layer.draw_frame(vk_ctx, cmd_buffer, current_img);
+            layer.draw_frame_labelled(vk_ctx, cmd_buffer, current_img);
+        }
+    }
+
+    fn label(&self) -> &str
+    {
+        "3d layers"
+    }
+
+    fn collects_pipeline_stats(&self) -> bool
+    {
+        false
+    }
+
+    fn draw_offscreen(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, slot: usize, target: &GkVkRenderTarget)
+    {
+        for layer in self.list.iter()
+        {
+            layer.draw_offscreen(vk_ctx, cmd_buffer, slot, target);
         }
     }
 
     fn destroy(&mut self, vk_ctx: &mut GkVkContext)
     {
-        for layer in self.list.iter_mut()
+        // Dropping the layers hands their buffers, images and descriptors back to the device.
+        for mut layer in self.list.drain(..)
         {
             layer.destroy(vk_ctx);
         }
@@ -192,22 +284,32 @@ impl GkVkLayerDraw for Vk3dLayerList
         }
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>)
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>
+    {
+        for layer in self.list.iter_mut()
+        {
+            layer.recreate_framebuffers(vk_ctx, depth_img)?;
+        }
+        Ok(())
+    }
+
+    fn on_asset_loaded(&mut self, vk_ctx: &mut GkVkContext, handle: GkAssetHandle, asset: &EAssetData) -> bool
     {
         for layer in self.list.iter_mut()
         {
-            layer.recreate_framebuffers(vk_ctx, depth_img);
+            if layer.on_asset_loaded(vk_ctx, handle, asset) { return true; }
         }
+        false
     }
 }
 
 impl GkVk3dLayerUpdate for Vk3dLayerList
 {
-    fn update(&mut self, vk_ctx: &GkVkContext, transform_uniform: &GkVkBuffer, delta_time: f32)
+    fn update(&mut self, vk_ctx: &GkVkContext, transform_uniform: &GkVkBuffer, views: &[GkVkView], scene: &GkSceneGraph, delta_time: f32)
     {
         for layer in self.list.iter_mut()
         {
-            layer.update(vk_ctx, transform_uniform, delta_time);
+            layer.update(vk_ctx, transform_uniform, views, scene, delta_time);
         }
     }
 
@@ -261,13 +363,24 @@ impl GkVkLayerDraw for Vk2dLayerList
     {
         for layer in self.list.iter()
         {
-            layer.draw_frame(vk_ctx, cmd_buffer, current_img);
+            layer.draw_frame_labelled(vk_ctx, cmd_buffer, current_img);
         }
     }
 
+    fn label(&self) -> &str
+    {
+        "2d layers"
+    }
+
+    fn collects_pipeline_stats(&self) -> bool
+    {
+        false
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext)
     {
-        for layer in self.list.iter_mut()
+        // Dropping the layers hands their buffers, images and descriptors back to the device.
+        for mut layer in self.list.drain(..)
         {
             layer.destroy(vk_ctx);
         }
@@ -281,12 +394,22 @@ impl GkVkLayerDraw for Vk2dLayerList
         }
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, _depth_img: Option<&GkVkImage>)
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, _depth_img: Option<&GkVkImage>) -> GkResult<()>
+    {
+        for layer in self.list.iter_mut()
+        {
+            layer.recreate_framebuffers(vk_ctx, None)?;
+        }
+        Ok(())
+    }
+
+    fn on_asset_loaded(&mut self, vk_ctx: &mut GkVkContext, handle: GkAssetHandle, asset: &EAssetData) -> bool
     {
         for layer in self.list.iter_mut()
         {
-            layer.recreate_framebuffers(vk_ctx, None);
+            if layer.on_asset_loaded(vk_ctx, handle, asset) { return true; }
         }
+        false
     }
 }
 
@@ -5,4 +5,5 @@ pub mod vk_simple3d_layer;
 pub mod vk_simple_skybox_layer;
 pub mod vk_sprite_layer;
 pub mod vk_simple2d_layer;
+pub mod vk_profiler_overlay_layer;
 pub mod vk_end_layer;
\ No newline at end of file
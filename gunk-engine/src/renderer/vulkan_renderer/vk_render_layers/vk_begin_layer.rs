@@ -3,9 +3,11 @@ use ash::{self, vk};
 use crate::renderer::vulkan_renderer::gk_vulkan::{
     gunk_vk_context::{GkVkContext, gk_create_vk_color_depth_framebuffers, gk_create_vk_color_only_framebuffers, gk_destroy_vk_framebuffers},
     gunk_vk_img::GkVkImage,
-    gunk_vk_render_pass::{ GkVkRenderPass, GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass, gk_destroy_vk_renderpass }
+    gunk_vk_render_pass::{ GkVkRenderPass, GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass }
 };
 
+use crate::core::gk_error::GkResult;
+
 use super::gk_vk_render_layer::GkVkLayerDraw;
 
 pub struct VkBeginLayer
@@ -20,7 +22,7 @@ impl VkBeginLayer
             instance: &ash::Instance,
             vk_ctx: &mut GkVkContext,
             depth_img: Option<&GkVkImage>
-        ) -> Self
+        ) -> GkResult<Self>
     {
         let renderpass_info = GkVkRenderPassInfo{
             b_use_color: true,
@@ -31,20 +33,19 @@ impl VkBeginLayer
             flags: ERenderPassBit::FIRST,
             samples: vk::SampleCountFlags::TYPE_1,
         };
-        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, renderpass_info);
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "Begin renderpass", renderpass_info)?;
 
-        let framebuffers = if depth_img.is_some()
+        let framebuffers = match depth_img
         {
-            gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.unwrap().view)
-        } else {
-            gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)
-        };
+            Some(depth_img) => gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view),
+            None => gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)
+        }?;
 
-        Self
+        Ok(Self
         { 
             renderpass,
             framebuffers
-        }
+        })
     }
 }
 
@@ -56,10 +57,14 @@ impl GkVkLayerDraw for VkBeginLayer
         self.end_renderpass(vk_ctx, cmd_buffer);
     }
 
+    fn label(&self) -> &str
+    {
+        "Begin"
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext)
     {
         self.cleanup_framebuffers(&vk_ctx.device);
-        gk_destroy_vk_renderpass(&vk_ctx, &self.renderpass);
     }
 
     fn cleanup_framebuffers(&mut self, device: &ash::Device)
@@ -67,13 +72,13 @@ impl GkVkLayerDraw for VkBeginLayer
         gk_destroy_vk_framebuffers(device, &mut self.framebuffers);
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>)
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>
     {
-        self.framebuffers = if depth_img.is_some()
+        self.framebuffers = match depth_img
         {
-            gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view)
-        } else {
-            gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)
+            Some(depth_img) => gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.view)?,
+            None => gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)?
         };
+        Ok(())
     }
 }
\ No newline at end of file
@@ -3,11 +3,13 @@ use ash::{self, vk};
 use crate::renderer::vulkan_renderer::gk_vulkan::{
     gunk_vk_context::{GkVkContext, gk_create_vk_color_depth_framebuffers, gk_create_vk_color_only_framebuffers, gk_destroy_vk_framebuffers}, 
     gunk_vk_render_pass::{
-        GkVkRenderPass, ERenderPassBit, GkVkRenderPassInfo, gk_create_vk_renderpass, gk_destroy_vk_renderpass
+        GkVkRenderPass, ERenderPassBit, GkVkRenderPassInfo, gk_create_vk_renderpass
     }, 
     gunk_vk_img::GkVkImage
 };
 
+use crate::core::gk_error::GkResult;
+
 use super::gk_vk_render_layer::GkVkLayerDraw;
 
 pub struct VkEndLayer
@@ -18,7 +20,7 @@ pub struct VkEndLayer
 
 impl VkEndLayer
 {
-    pub fn new(instance: &ash::Instance, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> Self
+    pub fn new(instance: &ash::Instance, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<Self>
     {
         let renderpass_info = GkVkRenderPassInfo{
             b_use_color: true,
@@ -29,16 +31,15 @@ impl VkEndLayer
             flags: ERenderPassBit::LAST,
             samples: vk::SampleCountFlags::TYPE_1
         };
-        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, renderpass_info);
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "End renderpass", renderpass_info)?;
 
-        let framebuffers = if depth_img.is_some()
+        let framebuffers = match depth_img
         {
-            gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.unwrap().view)
-        } else {
-            gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)
-        };
+            Some(depth_img) => gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view),
+            None => gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)
+        }?;
 
-        Self{ renderpass, framebuffers }
+        Ok(Self{ renderpass, framebuffers })
     }
 }
 
@@ -50,6 +51,11 @@ impl GkVkLayerDraw for VkEndLayer
         self.end_renderpass(vk_ctx, cmd_buffer);
     }
 
+    fn label(&self) -> &str
+    {
+        "End"
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext) 
     {
         unsafe{
@@ -58,7 +64,6 @@ impl GkVkLayerDraw for VkEndLayer
                 vk_ctx.device.destroy_framebuffer(*framebuffer, None);
             }
         }
-        gk_destroy_vk_renderpass(&vk_ctx, &self.renderpass);
     }
 
     fn cleanup_framebuffers(&mut self, device: &ash::Device)
@@ -66,13 +71,13 @@ impl GkVkLayerDraw for VkEndLayer
         gk_destroy_vk_framebuffers(device, &mut self.framebuffers);
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>)
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>
     {
-        self.framebuffers = if depth_img.is_some()
+        self.framebuffers = match depth_img
         {
-            gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view)
-        } else {
-            gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)
+            Some(depth_img) => gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.view)?,
+            None => gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)?
         };
+        Ok(())
     }
 }
\ No newline at end of file
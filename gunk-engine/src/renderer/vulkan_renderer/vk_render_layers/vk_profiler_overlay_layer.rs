@@ -0,0 +1,292 @@
+use std::ffi::CString;
+
+use ash::{self, vk};
+
+use crate::renderer::renderer_utils::to_shader_path;
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_context::{gk_create_vk_color_only_framebuffers, gk_destroy_vk_framebuffers};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_img::GkVkImage;
+use crate::renderer::vulkan_renderer::gk_vulkan::vk_utils::{
+    create_vk_pipeline_info_vertex_input, create_vk_pipeline_info_assembly,
+    create_vk_pipeline_info_dynamic_states, create_vk_pipeline_info_viewport,
+    create_vk_pipeline_info_rasterization, create_vk_pipeline_info_multisample,
+    create_vk_pipeline_info_color_blend_attachment, create_vk_pipeline_info_color_blend,
+    create_vk_pipeline_info_tessellation, create_vk_pipeline_layout
+};
+use crate::renderer::vulkan_renderer::gk_vulkan::{
+    gunk_vk_context::GkVkContext,
+    gunk_vk_render_pass::GkVkRenderPass,
+    gunk_vk_render_pass::{GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass},
+    vk_shader_utils::GkVkShaderModule
+};
+use crate::{log_info, log_err};
+use crate::core::gk_error::{GkError, GkResult};
+
+use super::gk_vk_render_layer::{GkVkLayerDraw, GkVk2dLayerUpdate};
+
+/// Frame time the bars are scaled against, 60 fps.
+const OVERLAY_BUDGET_MS: f64 = 1000.0 / 60.0;
+/// Width in normalized device coordinates of a bar that takes the whole budget.
+const OVERLAY_BUDGET_WIDTH: f32 = 0.6;
+const OVERLAY_LEFT: f32 = -0.98;
+const OVERLAY_TOP: f32 = -0.96;
+const OVERLAY_ROW_HEIGHT: f32 = 0.04;
+const OVERLAY_INDENT: f32 = 0.02;
+
+const OVERLAY_COLORS: [[f32; 4]; 6] =
+[
+    [0.90, 0.35, 0.25, 0.9],
+    [0.30, 0.75, 0.40, 0.9],
+    [0.30, 0.55, 0.90, 0.9],
+    [0.95, 0.75, 0.25, 0.9],
+    [0.70, 0.40, 0.85, 0.9],
+    [0.30, 0.80, 0.80, 0.9],
+];
+
+/// Push constants of one bar, matching GpuProfilerOverlay.vert.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct OverlayBar
+{
+    rect:   [f32; 4],   // x0, y0, x1, y1
+    color:  [f32; 4],
+}
+
+/// Lays out one bar per scope of the frame, with a background panel and a budget marker underneath.
+fn overlay_bars(rows: &[(usize, f64)]) -> Vec<OverlayBar>
+{
+    let bottom = OVERLAY_TOP + OVERLAY_ROW_HEIGHT * rows.len().max(1) as f32 + 0.01;
+    let budget_x = OVERLAY_LEFT + OVERLAY_BUDGET_WIDTH;
+    let mut bars = vec![
+        OverlayBar { rect: [OVERLAY_LEFT - 0.01, OVERLAY_TOP - 0.01, budget_x + 0.05, bottom], color: [0.0, 0.0, 0.0, 0.6] },
+        OverlayBar { rect: [budget_x, OVERLAY_TOP - 0.01, budget_x + 0.004, bottom], color: [1.0, 1.0, 1.0, 0.8] },
+    ];
+    for (row, (depth, duration_ms)) in rows.iter().enumerate()
+    {
+        let x0 = OVERLAY_LEFT + OVERLAY_INDENT * *depth as f32;
+        let width = (*duration_ms / OVERLAY_BUDGET_MS) as f32 * OVERLAY_BUDGET_WIDTH;
+        let y0 = OVERLAY_TOP + OVERLAY_ROW_HEIGHT * row as f32;
+        bars.push(OverlayBar
+        {
+            rect: [x0, y0, x0 + width.clamp(0.002, 0.98 - x0), y0 + OVERLAY_ROW_HEIGHT * 0.75],
+            color: OVERLAY_COLORS[row % OVERLAY_COLORS.len()],
+        });
+    }
+    bars
+}
+
+/// ### VkProfilerOverlayLayer struct
+/// *Draws the gpu profiler's last frame as bars in the top left corner, one row per scope.*<br>
+/// *Bars are scaled against a 16.6 ms budget, marked by the white line. Toggled with vk_ctx.profiler.b_overlay.*
+pub struct VkProfilerOverlayLayer
+{
+    renderpass:         GkVkRenderPass,
+    framebuffers:       Vec<vk::Framebuffer>,
+    pipeline_layout:    vk::PipelineLayout,
+    pipeline:           vk::Pipeline,
+}
+
+impl VkProfilerOverlayLayer
+{
+    pub fn new(instance: &ash::Instance, vk_ctx: &mut GkVkContext) -> GkResult<Self>
+    {
+        log_info!("Creating ProfilerOverlayLayer...");
+        let renderpass_info = GkVkRenderPassInfo{
+            b_use_color: true,
+            b_clear_color: false,
+            b_use_depth: false,
+            b_clear_depth: false,
+            color_format: vk_ctx.swapchain.format,
+            flags: ERenderPassBit::NONE,
+            samples: vk::SampleCountFlags::TYPE_1
+        };
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "ProfilerOverlay renderpass", renderpass_info)?;
+
+        let framebuffers = gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)?;
+
+        let push_ranges = vec![
+            vk::PushConstantRange
+            {
+                stage_flags: vk::ShaderStageFlags::VERTEX,
+                offset: 0,
+                size: std::mem::size_of::<OverlayBar>() as u32
+            }
+        ];
+        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &Vec::new(), &push_ranges)?;
+
+        let shader_modules: Vec<GkVkShaderModule> = vec![
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("GpuProfilerOverlay.vert").as_path())?,
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("GpuProfilerOverlay.frag").as_path())?
+        ];
+
+        let pipeline = Self::create_pipeline(vk_ctx, &shader_modules, &renderpass, &pipeline_layout);
+
+        drop(shader_modules);
+        let pipeline = pipeline?;
+        vk_ctx.shared.set_object_name(pipeline_layout, "ProfilerOverlay pipeline layout");
+        vk_ctx.shared.set_object_name(pipeline, "ProfilerOverlay pipeline");
+
+        log_info!("ProfilerOverlayLayer created.");
+        Ok(Self
+        {
+            renderpass,
+            framebuffers,
+            pipeline_layout,
+            pipeline,
+        })
+    }
+
+    fn create_pipeline(
+            vk_ctx: &GkVkContext,
+            shader_modules: &[GkVkShaderModule],
+            renderpass: &GkVkRenderPass,
+            layout: &vk::PipelineLayout
+        ) -> GkResult<vk::Pipeline>
+    {
+        log_info!("creating VkProfilerOverlayLayer pipeline... ");
+
+        let mut shader_stage_infos: Vec<vk::PipelineShaderStageCreateInfo> = Vec::new();
+        let entry_point = CString::new("main").unwrap();
+        for shader in shader_modules.iter()
+        {
+            shader_stage_infos.push(shader.get_vk_pipeline_info_shader_stage(&entry_point));
+        }
+
+        // Corners come from gl_VertexIndex, there are no vertex buffers.
+        let vertex_input_info = create_vk_pipeline_info_vertex_input();
+        let assembly_info = create_vk_pipeline_info_assembly(vk::PrimitiveTopology::TRIANGLE_LIST, vk::FALSE);
+
+        let viewports: Vec<vk::Viewport> = vec![
+            vk::Viewport
+            {
+                x: 0.0,
+                y: 0.0,
+                width: vk_ctx.swapchain.extent.width as f32,
+                height: vk_ctx.swapchain.extent.height as f32,
+                min_depth: 0.0,
+                max_depth: 1.0
+            }
+        ];
+        let scissors: Vec<vk::Rect2D> = vec![
+            vk::Rect2D
+            {
+                offset: vk::Offset2D{ x: 0, y: 0 },
+                extent: vk_ctx.swapchain.extent
+            }
+        ];
+        let viewport_info = create_vk_pipeline_info_viewport(viewports, scissors);
+
+        let rasterizer_info = create_vk_pipeline_info_rasterization(vk::PolygonMode::FILL, vk::CullModeFlags::NONE, vk::FrontFace::CLOCKWISE, 1.0);
+        let multisampling_info = create_vk_pipeline_info_multisample(vk::SampleCountFlags::TYPE_1, vk::FALSE, 1.0);
+
+        let color_attachments: Vec<vk::PipelineColorBlendAttachmentState> = vec![
+            create_vk_pipeline_info_color_blend_attachment(true)
+        ];
+        let color_blending_info = create_vk_pipeline_info_color_blend(&color_attachments);
+
+        let dynamic_states: Vec<vk::DynamicState> = vec![
+            vk::DynamicState::VIEWPORT,
+            vk::DynamicState::SCISSOR
+        ];
+        let dynamic_info = create_vk_pipeline_info_dynamic_states(&dynamic_states);
+
+        let tessellation_info = create_vk_pipeline_info_tessellation(0);
+
+        let create_info = vk::GraphicsPipelineCreateInfo
+        {
+            s_type: vk::StructureType::GRAPHICS_PIPELINE_CREATE_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::PipelineCreateFlags::empty(),
+            stage_count: shader_stage_infos.len() as u32,
+            p_stages: shader_stage_infos.as_ptr(),
+            p_vertex_input_state: &vertex_input_info,
+            p_input_assembly_state: &assembly_info,
+            p_viewport_state: &viewport_info,
+            p_rasterization_state: &rasterizer_info,
+            p_multisample_state: &multisampling_info,
+            p_color_blend_state: &color_blending_info,
+            p_depth_stencil_state: std::ptr::null(),
+            p_dynamic_state: &dynamic_info,
+            p_tessellation_state: &tessellation_info,
+            layout: *layout,
+            render_pass: renderpass.handle,
+            subpass: 0,
+            base_pipeline_handle: vk::Pipeline::null(),
+            base_pipeline_index: -1
+        };
+
+        let pipeline = unsafe {
+            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); GkError::from(e.1) })?[0]
+        };
+        log_info!("VkProfilerOverlayLayer pipeline created.");
+
+        Ok(pipeline)
+    }
+
+    fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, bars: &[OverlayBar])
+    {
+        for bar in bars.iter()
+        {
+            let bytes = unsafe { std::slice::from_raw_parts(bar as *const OverlayBar as *const u8, std::mem::size_of::<OverlayBar>()) };
+            unsafe
+            {
+                vk_ctx.device.cmd_push_constants(*cmd_buffer, self.pipeline_layout, vk::ShaderStageFlags::VERTEX, 0, bytes);
+                vk_ctx.device.cmd_draw(*cmd_buffer, 6, 1, 0, 0);
+            }
+        }
+    }
+}
+
+impl GkVkLayerDraw for VkProfilerOverlayLayer
+{
+    fn draw_frame(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, current_image: usize)
+    {
+        if !vk_ctx.profiler.b_overlay.get() { return; }
+        let timings = match vk_ctx.profiler.last_frame()
+        {
+            Some(timings) => timings,
+            None => return
+        };
+        let rows: Vec<(usize, f64)> = timings.flatten().iter().map(|(depth, node)| (*depth, node.duration_ms)).collect();
+
+        self.begin_renderpass(vk_ctx, cmd_buffer, &self.renderpass, self.pipeline, self.framebuffers[current_image]);
+        self.draw(vk_ctx, cmd_buffer, &overlay_bars(&rows));
+        self.end_renderpass(vk_ctx, cmd_buffer);
+    }
+
+    fn label(&self) -> &str
+    {
+        "ProfilerOverlay"
+    }
+
+    fn collects_pipeline_stats(&self) -> bool
+    {
+        false
+    }
+
+    fn destroy(&mut self, vk_ctx: &mut GkVkContext)
+    {
+        self.cleanup_framebuffers(&vk_ctx.device);
+        unsafe {
+            vk_ctx.device.destroy_pipeline_layout(self.pipeline_layout, None);
+            vk_ctx.device.destroy_pipeline(self.pipeline, None);
+        }
+    }
+
+    fn cleanup_framebuffers(&mut self, device: &ash::Device)
+    {
+        gk_destroy_vk_framebuffers(device, &mut self.framebuffers);
+    }
+
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, _depth_img: Option<&GkVkImage>) -> GkResult<()>
+    {
+        self.framebuffers = gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)?;
+        Ok(())
+    }
+}
+
+impl GkVk2dLayerUpdate for VkProfilerOverlayLayer
+{
+    fn update(&mut self, _vk_ctx: &GkVkContext)
+    {
+    }
+}
@@ -5,10 +5,10 @@ use ash::{self, vk};
 use nalgebra_glm as glm;
 
 use crate::renderer::renderer_utils::to_shader_path;
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_buffer::{GkVkBuffer, gk_destroy_vk_buffer, gk_create_vk_array_buffer};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_buffer::{GkVkBuffer, gk_create_vk_array_buffer};
 use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_context::{gk_create_vk_color_only_framebuffers, gk_destroy_vk_framebuffers};
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_descriptor::{GkVkDescriptor, gk_create_vk_desc_pool, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set, gk_destroy_vk_descriptor};
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_img::{GkVkImage, gk_create_vk_image, create_vk_sampler, gk_destroy_vk_img};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_descriptor::{GkVkDescriptor, gk_create_vk_desc_pool, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_img::{GkVkImage, gk_create_vk_image, gk_create_vk_image_from_bitmap, create_vk_sampler};
 use crate::renderer::vulkan_renderer::gk_vulkan::vk_utils::{
     create_vk_pipeline_info_vertex_input, create_vk_pipeline_info_assembly,
     create_vk_pipeline_info_dynamic_states, create_vk_pipeline_info_viewport, 
@@ -19,10 +19,12 @@ use crate::renderer::vulkan_renderer::gk_vulkan::vk_utils::{
 use crate::renderer::vulkan_renderer::gk_vulkan::{
     gunk_vk_context::GkVkContext,
     gunk_vk_render_pass::GkVkRenderPass,
-    gunk_vk_render_pass::{GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass, gk_destroy_vk_renderpass},
+    gunk_vk_render_pass::{GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass},
     vk_shader_utils::GkVkShaderModule
 };
-use crate::{log_info, log_err, vk_check};
+use crate::{log_info, log_warn, log_err, vk_try};
+use crate::core::gk_error::{GkError, GkResult};
+use crate::renderer::gk_asset_loader::gk_placeholder_bitmap;
 
 use super::gk_vk_render_layer::{GkVkLayerDraw, GkVk2dLayerUpdate};
 
@@ -100,9 +102,10 @@ pub struct VkSimple2dLayer
     descriptor:         GkVkDescriptor,
     pipeline_layout:    vk::PipelineLayout,
     pipeline:           vk::Pipeline,
-    triangle_verts:     Option<GkVkBuffer>,
-    triangle_indices:   Option<GkVkBuffer>,
-    texture:            Option<GkVkImage>,
+    triangle_verts:     GkVkBuffer,
+    triangle_indices:   GkVkBuffer,
+    #[allow(dead_code)] // Only read through the descriptor sets, owned here to keep it alive.
+    texture:            GkVkImage,
     sampler:            vk::Sampler
 }
 
@@ -112,11 +115,21 @@ impl VkSimple2dLayer
             instance: &ash::Instance,
             vk_ctx: &mut GkVkContext,
             texture_file: &std::path::Path
-        ) -> Self
+        ) -> GkResult<Self>
     {
         log_info!("Creating Simple2dLayer...");
-        let texture = gk_create_vk_image(vk_ctx, texture_file.to_str().unwrap());
-        let sampler = create_vk_sampler(&vk_ctx.device);
+        // A missing or broken texture file falls back to the placeholder instead of failing the layer.
+        let texture = match gk_create_vk_image(vk_ctx, &texture_file.to_string_lossy())
+        {
+            Ok(texture) => texture,
+            Err(e @ (GkError::Io(_) | GkError::ImageDecode(_))) =>
+            {
+                log_warn!(format!("Simple2dLayer using placeholder texture: {}", e));
+                gk_create_vk_image_from_bitmap(vk_ctx, "Simple2d placeholder texture", &gk_placeholder_bitmap(1))?
+            },
+            Err(e) => return Err(e)
+        };
+        let sampler = create_vk_sampler(&vk_ctx.device)?;
         
         let renderpass_info = GkVkRenderPassInfo{
             b_use_color: true,
@@ -127,16 +140,16 @@ impl VkSimple2dLayer
             flags: ERenderPassBit::NONE,
             samples: vk::SampleCountFlags::TYPE_1
         };
-        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, renderpass_info);
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "Simple2d renderpass", renderpass_info)?;
 
-        let descriptor = Self::create_desc_sets(vk_ctx, &texture, &sampler);
+        let descriptor = Self::create_desc_sets(vk_ctx, &texture, &sampler)?;
 
-        let framebuffers = gk_create_vk_color_only_framebuffers(&vk_ctx, &renderpass);
-        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &Vec::new());
+        let framebuffers = gk_create_vk_color_only_framebuffers(vk_ctx, &renderpass)?;
+        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &Vec::new())?;
 
         let mut shader_modules: Vec<GkVkShaderModule> = vec![
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("Simple2dLayer.vert").as_path()),
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("Simple2dLayer.frag").as_path())
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("Simple2dLayer.vert").as_path())?,
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("Simple2dLayer.frag").as_path())?
         ];
 
         let pipeline = Self::create_pipeline(
@@ -146,37 +159,37 @@ impl VkSimple2dLayer
             &pipeline_layout, None
         );
 
-        for shader in shader_modules.iter_mut()
-        {
-            shader.destroy(&vk_ctx.device);
-        }
+        drop(shader_modules);
+        let pipeline = pipeline?;
+        vk_ctx.shared.set_object_name(pipeline_layout, "Simple2d pipeline layout");
+        vk_ctx.shared.set_object_name(pipeline, "Simple2d pipeline");
 
         // create buffers
-        let triangle_verts = gk_create_vk_array_buffer::<Simple2dVertex>(vk_ctx, "Triangle", vk::BufferUsageFlags::VERTEX_BUFFER, &VERTICES_DATA.to_vec());
-        let triangle_indices = gk_create_vk_array_buffer::<u32>(vk_ctx, "Triangle Indices", vk::BufferUsageFlags::INDEX_BUFFER, &INDICES_DATA.to_vec());
+        let triangle_verts = gk_create_vk_array_buffer::<Simple2dVertex>(vk_ctx, "Triangle", vk::BufferUsageFlags::VERTEX_BUFFER, &VERTICES_DATA.to_vec())?;
+        let triangle_indices = gk_create_vk_array_buffer::<u32>(vk_ctx, "Triangle Indices", vk::BufferUsageFlags::INDEX_BUFFER, &INDICES_DATA.to_vec())?;
 
         log_info!("Simple2dLayer created.");
-        Self
+        Ok(Self
         {
             renderpass,
             framebuffers,
             descriptor,
             pipeline_layout,
             pipeline,
-            triangle_verts: Some(triangle_verts),
-            triangle_indices: Some(triangle_indices),
-            texture: Some(texture),
+            triangle_verts,
+            triangle_indices,
+            texture,
             sampler,
-        }
+        })
     }
 
     fn create_desc_sets(
             vk_ctx: &GkVkContext,
             texture: &GkVkImage,
             sampler: &vk::Sampler
-        ) -> GkVkDescriptor
+        ) -> GkResult<GkVkDescriptor>
     {
-        let pool = gk_create_vk_desc_pool(vk_ctx, 0, 0, 1);
+        let pool = gk_create_vk_desc_pool(vk_ctx, 0, 0, 0, 1)?;
 
         let bindings: Vec<vk::DescriptorSetLayoutBinding> = vec![
             get_vk_desc_set_layout_binding(0, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 1, vk::ShaderStageFlags::FRAGMENT)
@@ -191,7 +204,7 @@ impl VkSimple2dLayer
             p_bindings: bindings.as_ptr()
         };
         let layout = unsafe{
-            vk_check!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None)).unwrap()
+            vk_try!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None))
         };
 
         let layouts: Vec<vk::DescriptorSetLayout> = vec![layout; vk_ctx.frame_sync.get_num_frames_in_flight()];
@@ -205,7 +218,7 @@ impl VkSimple2dLayer
         };
 
         let sets = unsafe {
-            vk_check!(vk_ctx.device.allocate_descriptor_sets(&alloc_info)).unwrap()
+            vk_try!(vk_ctx.device.allocate_descriptor_sets(&alloc_info))
         };
 
         for i in 0..vk_ctx.frame_sync.get_num_frames_in_flight()
@@ -221,12 +234,7 @@ impl VkSimple2dLayer
             }
         }   
 
-        GkVkDescriptor
-        { 
-            layouts, 
-            pool, 
-            sets 
-        }
+        Ok(GkVkDescriptor::new(vk_ctx, layouts, pool, sets))
     }
 
     fn create_pipeline(
@@ -235,7 +243,7 @@ impl VkSimple2dLayer
             renderpass: &GkVkRenderPass,
             layout: &vk::PipelineLayout,
             custom_extent: Option<vk::Extent2D>
-        ) -> vk::Pipeline    
+        ) -> GkResult<vk::Pipeline>
     {
         log_info!("creating VkSimple2dLayer pipeline... ");
 
@@ -326,18 +334,18 @@ impl VkSimple2dLayer
         };
 
         let pipeline = unsafe {
-            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); }).unwrap()[0]
+            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); GkError::from(e.1) })?[0]
         };
         log_info!("VkSimple2dLayer pipeline created.");
 
-        pipeline
+        Ok(pipeline)
     }
 
     fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
     {
         unsafe{
-            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.triangle_verts.as_ref().unwrap().handle], &[0 as vk::DeviceSize]);
-            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.triangle_indices.as_ref().unwrap().handle, 0, vk::IndexType::UINT32);
+            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.triangle_verts.handle], &[0 as vk::DeviceSize]);
+            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.triangle_indices.handle, 0, vk::IndexType::UINT32);
 
             let desc_set = [self.descriptor.sets[vk_ctx.frame_sync.get_current_frame_index()]];
             vk_ctx.device.cmd_bind_descriptor_sets(*cmd_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline_layout, 0, &desc_set, &[]);
@@ -358,17 +366,16 @@ impl GkVkLayerDraw for VkSimple2dLayer
         self.end_renderpass(vk_ctx, cmd_buffer);
     }
 
+    fn label(&self) -> &str
+    {
+        "Simple2d"
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext) 
     {
-        gk_destroy_vk_buffer(vk_ctx, self.triangle_verts.take().unwrap());
-        gk_destroy_vk_buffer(vk_ctx, self.triangle_indices.take().unwrap());
-        gk_destroy_vk_img(vk_ctx, self.texture.take().unwrap());
         unsafe { vk_ctx.device.destroy_sampler(self.sampler, None); }
-
-        gk_destroy_vk_descriptor(vk_ctx, &self.descriptor);
         
         self.cleanup_framebuffers(&vk_ctx.device);
-        gk_destroy_vk_renderpass(vk_ctx, &self.renderpass);
         unsafe {
             vk_ctx.device.destroy_pipeline_layout(self.pipeline_layout, None);
             vk_ctx.device.destroy_pipeline(self.pipeline, None);
@@ -380,9 +387,10 @@ impl GkVkLayerDraw for VkSimple2dLayer
         gk_destroy_vk_framebuffers(device, &mut self.framebuffers);   
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, _depth_img: Option<&GkVkImage>)
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, _depth_img: Option<&GkVkImage>) -> GkResult<()>
     {
-        self.framebuffers = gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass);
+        self.framebuffers = gk_create_vk_color_only_framebuffers(vk_ctx, &self.renderpass)?;
+        Ok(())
     }
 
 }
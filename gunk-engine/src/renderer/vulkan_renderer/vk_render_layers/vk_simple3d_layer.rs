@@ -1,16 +1,16 @@
 use std::ffi::CString;
+use std::path::PathBuf;
 
 use ash::{self, vk};
 
-
-use gpu_allocator::MemoryLocation;
 use nalgebra_glm as glm;
 
 use crate::renderer::renderer_utils::to_shader_path;
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_buffer::{gk_create_vk_buffer, gk_create_vk_vertex_buffer_from_file, gk_destroy_vk_buffer, map_vk_allocation_data, GkVkBuffer};
+use crate::renderer::gk_asset_loader::{GkAssetLoader, GkAssetHandle, EAssetRequest, EAssetData, gk_placeholder_bitmap, gk_placeholder_mesh};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_buffer::{gk_create_vk_vertex_buffer_from_mesh, GkVkBuffer};
 use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_context::{gk_destroy_vk_framebuffers, gk_create_vk_color_depth_framebuffers};
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_descriptor::{GkVkDescriptor, gk_create_vk_desc_pool, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set, get_vk_buffer_write_desc_set, gk_destroy_vk_descriptor};
-use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_img::{GkVkImage, gk_create_vk_image, create_vk_sampler, gk_destroy_vk_img};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_descriptor::{GkVkDescriptor, gk_create_vk_desc_pool, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set, get_vk_buffer_write_desc_set};
+use crate::renderer::vulkan_renderer::gk_vulkan::gunk_vk_img::{GkVkImage, gk_create_vk_image_from_bitmap, create_vk_sampler};
 use crate::renderer::vulkan_renderer::gk_vulkan::vk_utils::{
     create_vk_pipeline_info_vertex_input, create_vk_pipeline_info_assembly,
     create_vk_pipeline_info_dynamic_states, create_vk_pipeline_info_viewport, 
@@ -21,12 +21,17 @@ use crate::renderer::vulkan_renderer::gk_vulkan::vk_utils::{
 use crate::renderer::vulkan_renderer::gk_vulkan::{
     gunk_vk_context::GkVkContext,
     gunk_vk_render_pass::GkVkRenderPass,
-    gunk_vk_render_pass::{GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass, gk_destroy_vk_renderpass},
+    gunk_vk_render_pass::{GkVkRenderPassInfo, ERenderPassBit, gk_create_vk_renderpass},
+    gunk_vk_render_target::GkVkRenderTarget,
     vk_shader_utils::GkVkShaderModule
 };
-use crate::{log_info, log_err, vk_check};
+use crate::{log_info, log_err, vk_try};
+use crate::core::gk_error::{GkError, GkResult};
+
+use crate::renderer::vulkan_renderer::gk_vk_camera::GkCameraUniformData;
+use crate::scene::GkSceneGraph;
 
-use super::gk_vk_render_layer::{GkVkLayerDraw, GkVk3dLayerUpdate};
+use super::gk_vk_render_layer::{GkVkLayerDraw, GkVk3dLayerUpdate, GkVkView};
 
 #[repr(C)]
 #[derive(Clone, Copy)]
@@ -110,12 +115,16 @@ pub struct VkSimple3dLayer
     descriptor:         GkVkDescriptor,
     pipeline_layout:    vk::PipelineLayout,
     pipeline:           vk::Pipeline,
-    mesh_verts:         Option<GkVkBuffer>,
-    mesh_indices:       Option<GkVkBuffer>,
-    texture:            Option<GkVkImage>,
+    mesh_verts:         GkVkBuffer,
+    mesh_indices:       GkVkBuffer,
+    texture:            GkVkImage,
     sampler:            vk::Sampler,
-    model_space:        glm::Mat4,
-    model_space_buffer: Option<GkVkBuffer>,
+    mesh_file:          PathBuf, // Scene nodes with a mesh attachment for this file are drawn by the layer
+    instances:          Vec<glm::Mat4>, // World matrices of those nodes, from update()
+    mesh_request:       Option<GkAssetHandle>,
+    texture_request:    Option<GkAssetHandle>,
+    desc_sets_dirty:    Vec<bool>,
+    views:              Vec<GkVkView>, // Cameras to draw this frame, from update()
 }
 
 impl VkSimple3dLayer
@@ -123,15 +132,19 @@ impl VkSimple3dLayer
     pub fn new(
             instance: &ash::Instance,
             vk_ctx: &mut GkVkContext,
+            asset_loader: &mut GkAssetLoader,
             camera_uniforms: &Vec<GkVkBuffer>,
             depth_img: &GkVkImage,
             mesh_file: &std::path::Path,
             texture_file: &std::path::Path
-        ) -> Self
+        ) -> GkResult<Self>
     {
         log_info!("Creating Simple3dLayer...");
-        let texture = gk_create_vk_image(vk_ctx, texture_file.to_str().unwrap());
-        let sampler = create_vk_sampler(&vk_ctx.device);
+        // Draw with placeholders until the real mesh and texture are streamed in.
+        let mesh_request = Some(asset_loader.request(EAssetRequest::Mesh(mesh_file.to_path_buf())));
+        let texture_request = Some(asset_loader.request(EAssetRequest::Texture2d(texture_file.to_path_buf())));
+        let texture = gk_create_vk_image_from_bitmap(vk_ctx, "Simple3d placeholder texture", &gk_placeholder_bitmap(1))?;
+        let sampler = create_vk_sampler(&vk_ctx.device)?;
         
         let renderpass_info = GkVkRenderPassInfo{
             b_use_color: true,
@@ -142,25 +155,26 @@ impl VkSimple3dLayer
             flags: ERenderPassBit::NONE,
             samples: vk::SampleCountFlags::TYPE_1
         };
-        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, renderpass_info);
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "Simple3d renderpass", renderpass_info)?;
         
-        let model_space = glm::Mat4::identity();
-        let model_space_buffer = Some(gk_create_vk_buffer(
-            vk_ctx, "Simple3d_model_space", 
-            vk::BufferUsageFlags::UNIFORM_BUFFER,
-            MemoryLocation::CpuToGpu, 
-            std::mem::size_of::<glm::Mat4>() as vk::DeviceSize
-        ));
-
-        let descriptor = Self::create_desc_sets(vk_ctx, camera_uniforms, &texture, &sampler, model_space_buffer.as_ref().unwrap());
+        let descriptor = Self::create_desc_sets(vk_ctx, camera_uniforms, &texture, &sampler)?;
 
-        let framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view);
+        let framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view)?;
 
-        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &Vec::new());
+        // Each instance's model matrix is pushed right before its draw.
+        let push_ranges = vec![
+            vk::PushConstantRange
+            {
+                stage_flags: vk::ShaderStageFlags::VERTEX,
+                offset: 0,
+                size: std::mem::size_of::<glm::Mat4>() as u32
+            }
+        ];
+        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &push_ranges)?;
 
         let mut shader_modules: Vec<GkVkShaderModule> = vec![
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("Simple3dLayer.vert").as_path()),
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("Simple3dLayer.frag").as_path())
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("Simple3dLayer.vert").as_path())?,
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("Simple3dLayer.frag").as_path())?
         ];
 
         let pipeline = Self::create_pipeline(
@@ -170,17 +184,17 @@ impl VkSimple3dLayer
             &pipeline_layout, None
         );
 
-        for shader in shader_modules.iter_mut()
-        {
-            shader.destroy(&vk_ctx.device);
-        }
+        drop(shader_modules);
+        let pipeline = pipeline?;
+        vk_ctx.shared.set_object_name(pipeline_layout, "Simple3d pipeline layout");
+        vk_ctx.shared.set_object_name(pipeline, "Simple3d pipeline");
 
         // let triangle_verts = gk_create_vk_array_buffer::<Simple3dVertex>(vk_ctx, "Triangle", vk::BufferUsageFlags::VERTEX_BUFFER, &VERTICES_DATA.to_vec());
         // let triangle_indices = gk_create_vk_array_buffer::<u32>(vk_ctx, "Triangle Indices", vk::BufferUsageFlags::INDEX_BUFFER, &INDICES_DATA.to_vec());
-        let (mesh_verts, mesh_indices) = gk_create_vk_vertex_buffer_from_file(vk_ctx, "mesh", mesh_file);
+        let (mesh_verts, mesh_indices) = gk_create_vk_vertex_buffer_from_mesh(vk_ctx, "mesh placeholder", &gk_placeholder_mesh())?;
 
         log_info!("Simple3dLayer created.");
-        Self
+        Ok(Self
         {
             renderpass,
             framebuffers,
@@ -191,26 +205,41 @@ impl VkSimple3dLayer
             mesh_indices,
             // mesh_verts: None,
             // mesh_indices: None,
-            texture: Some(texture),
+            texture,
             sampler,
-            model_space,
-            model_space_buffer
-        }
+            mesh_file: mesh_file.to_path_buf(),
+            instances: Vec::new(),
+            mesh_request,
+            texture_request,
+            desc_sets_dirty: vec![false; vk_ctx.frame_sync.get_num_frames_in_flight()],
+            views: Vec::new(),
+        })
+    }
+
+    // Only the set of the current frame is rewritten, the others may still be in use by the gpu.
+    fn refresh_texture_desc_set(&mut self, vk_ctx: &GkVkContext)
+    {
+        let frame = vk_ctx.frame_sync.get_current_frame_index();
+        if !self.desc_sets_dirty[frame] { return; }
+        self.desc_sets_dirty[frame] = false;
+
+        let texture = &self.texture;
+        let image_info = [vk::DescriptorImageInfo{ sampler: self.sampler, image_view: texture.view, image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL }];
+        let desc_writes = [ get_vk_image_write_desc_set(&self.descriptor.sets[frame], &image_info, 2) ];
+        unsafe { vk_ctx.device.update_descriptor_sets(&desc_writes, &[]) }
     }
 
     fn create_desc_sets(
             vk_ctx: &GkVkContext,
             camera_uniforms: &Vec<GkVkBuffer>,
             texture: &GkVkImage,
-            sampler: &vk::Sampler,
-            model_space_buffer: &GkVkBuffer
-        ) -> GkVkDescriptor
+            sampler: &vk::Sampler
+        ) -> GkResult<GkVkDescriptor>
     {
-        let pool = gk_create_vk_desc_pool(vk_ctx, 2, 0, 1);
+        let pool = gk_create_vk_desc_pool(vk_ctx, 0, 1, 0, 1)?;
 
         let bindings: Vec<vk::DescriptorSetLayoutBinding> = vec![
-            get_vk_desc_set_layout_binding(0, vk::DescriptorType::UNIFORM_BUFFER, 1, vk::ShaderStageFlags::VERTEX),
-            get_vk_desc_set_layout_binding(1, vk::DescriptorType::UNIFORM_BUFFER, 1, vk::ShaderStageFlags::VERTEX),
+            get_vk_desc_set_layout_binding(0, vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC, 1, vk::ShaderStageFlags::VERTEX),
             get_vk_desc_set_layout_binding(2, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 1, vk::ShaderStageFlags::FRAGMENT)
         ];
 
@@ -223,7 +252,7 @@ impl VkSimple3dLayer
             p_bindings: bindings.as_ptr()
         };
         let layout = unsafe{
-            vk_check!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None)).unwrap()
+            vk_try!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None))
         };
 
         let layouts: Vec<vk::DescriptorSetLayout> = vec![layout; vk_ctx.frame_sync.get_num_frames_in_flight()];
@@ -237,18 +266,16 @@ impl VkSimple3dLayer
         };
 
         let sets = unsafe {
-            vk_check!(vk_ctx.device.allocate_descriptor_sets(&alloc_info)).unwrap()
+            vk_try!(vk_ctx.device.allocate_descriptor_sets(&alloc_info))
         };
 
         for i in 0..vk_ctx.frame_sync.get_num_frames_in_flight()
         {
-            let buffer_info1 = vk::DescriptorBufferInfo{ buffer: camera_uniforms[i].handle, offset: 0, range: camera_uniforms[i].size };
-            let buffer_info2 = vk::DescriptorBufferInfo{ buffer: model_space_buffer.handle, offset: 0, range: model_space_buffer.size };
+            let buffer_info1 = vk::DescriptorBufferInfo{ buffer: camera_uniforms[i].handle, offset: 0, range: std::mem::size_of::<GkCameraUniformData>() as vk::DeviceSize };
             let image_info1 = vk::DescriptorImageInfo{ sampler: *sampler, image_view: texture.view, image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL};
 
             let desc_writes: Vec<vk::WriteDescriptorSet> = vec![
-                get_vk_buffer_write_desc_set(&sets[i], &[buffer_info1], 0, vk::DescriptorType::UNIFORM_BUFFER),
-                get_vk_buffer_write_desc_set(&sets[i], &[buffer_info2], 1, vk::DescriptorType::UNIFORM_BUFFER),
+                get_vk_buffer_write_desc_set(&sets[i], &[buffer_info1], 0, vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC),
                 get_vk_image_write_desc_set(&sets[i], &[image_info1], 2) 
             ];
 
@@ -257,12 +284,7 @@ impl VkSimple3dLayer
             }
         }   
 
-        GkVkDescriptor
-        { 
-            layouts, 
-            pool, 
-            sets 
-        }
+        Ok(GkVkDescriptor::new(vk_ctx, layouts, pool, sets))
     }
 
     fn create_pipeline(
@@ -271,7 +293,7 @@ impl VkSimple3dLayer
             renderpass: &GkVkRenderPass,
             layout: &vk::PipelineLayout,
             custom_extent: Option<vk::Extent2D>
-        ) -> vk::Pipeline    
+        ) -> GkResult<vk::Pipeline>
     {
         log_info!("creating VkSimple3dLayer pipeline... ");
 
@@ -330,7 +352,7 @@ impl VkSimple3dLayer
         ];
         let color_blending_info = create_vk_pipeline_info_color_blend(&color_attachments);
         
-        let depth_stencil_info = create_vk_pipeline_info_depth_stencil();
+        let depth_stencil_info = create_vk_pipeline_info_depth_stencil(vk_ctx.b_reverse_z);
 
         let dynamic_states: Vec<vk::DynamicState> = vec![
             vk::DynamicState::VIEWPORT,
@@ -364,24 +386,34 @@ impl VkSimple3dLayer
         };
 
         let pipeline = unsafe {
-            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); }).unwrap()[0]
+            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); GkError::from(e.1) })?[0]
         };
         log_info!("VkSimple3dLayer pipeline created.");
 
-        pipeline
+        Ok(pipeline)
     }
 
-    fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
+    // Draws the views going to target, None for the swapchain.
+    fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, target: Option<usize>)
     {
         unsafe{
-            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.mesh_verts.as_ref().unwrap().handle], &[0 as vk::DeviceSize]);
-            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.mesh_indices.as_ref().unwrap().handle, 0, vk::IndexType::UINT32);
+            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.mesh_verts.handle], &[0 as vk::DeviceSize]);
+            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.mesh_indices.handle, 0, vk::IndexType::UINT32);
 
             let desc_set = [self.descriptor.sets[vk_ctx.frame_sync.get_current_frame_index()]];
-            vk_ctx.device.cmd_bind_descriptor_sets(*cmd_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline_layout, 0, &desc_set, &[]);
-
-            // vk_ctx.device.cmd_draw(*cmd_buffer, VERTICES_DATA.len() as u32, 1, 0, 0);
-            vk_ctx.device.cmd_draw_indexed(*cmd_buffer, self.mesh_indices.as_ref().unwrap().size as u32 / std::mem::size_of::<u32>() as u32, 1, 0, 0, 0);
+            for view in self.views.iter().filter(|view| view.target == target)
+            {
+                self.set_view(vk_ctx, cmd_buffer, view);
+                vk_ctx.device.cmd_bind_descriptor_sets(*cmd_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline_layout, 0, &desc_set, &[view.uniform_offset]);
+
+                for model in self.instances.iter()
+                {
+                    let bytes = std::slice::from_raw_parts(model.as_ptr() as *const u8, std::mem::size_of::<glm::Mat4>());
+                    vk_ctx.device.cmd_push_constants(*cmd_buffer, self.pipeline_layout, vk::ShaderStageFlags::VERTEX, 0, bytes);
+                    // vk_ctx.device.cmd_draw(*cmd_buffer, VERTICES_DATA.len() as u32, 1, 0, 0);
+                    vk_ctx.device.cmd_draw_indexed(*cmd_buffer, self.mesh_indices.size as u32 / std::mem::size_of::<u32>() as u32, 1, 0, 0, 0);
+                }
+            }
         }
     }
 
@@ -392,22 +424,29 @@ impl GkVkLayerDraw for VkSimple3dLayer
     fn draw_frame(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, current_image: usize)
     {
         self.begin_renderpass(vk_ctx, cmd_buffer, &self.renderpass, self.pipeline, self.framebuffers[current_image]);
-        self.draw(vk_ctx, cmd_buffer);
+        self.draw(vk_ctx, cmd_buffer, None);
         self.end_renderpass(vk_ctx, cmd_buffer);
     }
 
+    fn draw_offscreen(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, slot: usize, target: &GkVkRenderTarget)
+    {
+        if !self.views.iter().any(|view| view.target == Some(slot)) { return; }
+        // The target's attachments have the formats of the swapchain and depth image, so this layer's render pass fits them.
+        self.begin_renderpass_sized(vk_ctx, cmd_buffer, &self.renderpass, self.pipeline, target.framebuffer, target.extent);
+        self.draw(vk_ctx, cmd_buffer, Some(slot));
+        self.end_renderpass(vk_ctx, cmd_buffer);
+    }
+
+    fn label(&self) -> &str
+    {
+        "Simple3d"
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext) 
     {
-        gk_destroy_vk_buffer(vk_ctx, self.mesh_verts.take().unwrap());
-        gk_destroy_vk_buffer(vk_ctx, self.mesh_indices.take().unwrap());
-        gk_destroy_vk_buffer(vk_ctx, self.model_space_buffer.take().unwrap());
-        gk_destroy_vk_img(vk_ctx, self.texture.take().unwrap());
         unsafe { vk_ctx.device.destroy_sampler(self.sampler, None); }
-
-        gk_destroy_vk_descriptor(vk_ctx, &self.descriptor);
         
         self.cleanup_framebuffers(&vk_ctx.device);
-        gk_destroy_vk_renderpass(vk_ctx, &self.renderpass);
         unsafe {
             vk_ctx.device.destroy_pipeline_layout(self.pipeline_layout, None);
             vk_ctx.device.destroy_pipeline(self.pipeline, None);
@@ -419,21 +458,55 @@ impl GkVkLayerDraw for VkSimple3dLayer
         gk_destroy_vk_framebuffers(device, &mut self.framebuffers);   
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>)
+    fn on_asset_loaded(&mut self, vk_ctx: &mut GkVkContext, handle: GkAssetHandle, asset: &EAssetData) -> bool
+    {
+        match asset
+        {
+            EAssetData::Mesh(mesh) if self.mesh_request == Some(handle) =>
+            {
+                self.mesh_request = None;
+                // On failure the placeholder mesh is kept.
+                if let Ok((mesh_verts, mesh_indices)) = gk_create_vk_vertex_buffer_from_mesh(vk_ctx, "mesh", mesh)
+                {
+                    self.mesh_verts = mesh_verts;
+                    self.mesh_indices = mesh_indices;
+                }
+                true
+            },
+            EAssetData::Texture2d(bitmap) if self.texture_request == Some(handle) =>
+            {
+                self.texture_request = None;
+                // On failure the placeholder texture is kept.
+                if let Ok(texture) = gk_create_vk_image_from_bitmap(vk_ctx, "Simple3d texture", bitmap)
+                {
+                    self.texture = texture;
+                    self.desc_sets_dirty.iter_mut().for_each(|dirty| *dirty = true);
+                }
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>
     {
-        self.framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view);
+        self.framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view)?;
+        Ok(())
     }
 
 }
 
 impl GkVk3dLayerUpdate for VkSimple3dLayer
 {
-    fn update(&mut self, _vk_ctx: &GkVkContext, _transform_uniform: &GkVkBuffer, delta_time: f32)
+    fn update(&mut self, vk_ctx: &GkVkContext, _transform_uniform: &GkVkBuffer, views: &[GkVkView], scene: &GkSceneGraph, _delta_time: f32)
     {
         // update
-        self.model_space = glm::rotate(&self.model_space, glm::pi::<f32>() * delta_time, &glm::vec3(0.0, 0.0, 1.0));
+        self.views.clear();
+        self.views.extend_from_slice(views);
+        self.instances.clear();
+        self.instances.extend(scene.meshes().filter(|(mesh, _)| mesh.mesh == self.mesh_file).map(|(_, world)| *world));
 
-        map_vk_allocation_data::<glm::Mat4>(&self.model_space_buffer.as_ref().unwrap().allocation, &[self.model_space], 1);
+        self.refresh_texture_desc_set(vk_ctx);
     }
 
 }
\ No newline at end of file
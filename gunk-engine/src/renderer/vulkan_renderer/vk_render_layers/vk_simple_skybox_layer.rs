@@ -6,22 +6,27 @@ use nalgebra_glm as glm;
 use memoffset;
 
 use crate::renderer::{
+    gk_asset_loader::{ GkAssetLoader, GkAssetHandle, EAssetRequest, EAssetData, gk_placeholder_bitmap },
     renderer_utils::to_shader_path, vulkan_renderer::gk_vulkan::{
-        gunk_vk_buffer::{gk_create_vk_array_buffer, gk_create_vk_buffer, gk_destroy_vk_buffer, map_vk_allocation_data, GkVkBuffer}, gunk_vk_context::{gk_create_vk_color_depth_framebuffers, gk_destroy_vk_framebuffers, GkVkContext}, gunk_vk_descriptor::{
-            get_vk_buffer_write_desc_set, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set, gk_create_vk_desc_pool, gk_destroy_vk_descriptor, GkVkDescriptor
+        gunk_vk_buffer::{gk_create_vk_array_buffer, gk_create_vk_buffer, map_vk_allocation_data, GkVkBuffer}, gunk_vk_context::{gk_create_vk_color_depth_framebuffers, gk_destroy_vk_framebuffers, GkVkContext}, gunk_vk_descriptor::{
+            get_vk_buffer_write_desc_set, get_vk_desc_set_layout_binding, get_vk_image_write_desc_set, gk_create_vk_desc_pool, GkVkDescriptor
         }, gunk_vk_img::{
-            create_vk_sampler, gk_create_vk_cubemap_image, gk_destroy_vk_img, GkVkImage
+            create_vk_sampler, gk_create_vk_cubemap_image_from_bitmap, GkVkImage
         }, gunk_vk_render_pass::{
-            gk_create_vk_renderpass, gk_destroy_vk_renderpass, ERenderPassBit, GkVkRenderPass, GkVkRenderPassInfo
-        }, vk_shader_utils::GkVkShaderModule, vk_utils::{
+            gk_create_vk_renderpass, ERenderPassBit, GkVkRenderPass, GkVkRenderPassInfo
+        }, gunk_vk_render_target::GkVkRenderTarget, vk_shader_utils::GkVkShaderModule, vk_utils::{
             create_vk_pipeline_info_assembly, create_vk_pipeline_info_color_blend, create_vk_pipeline_info_color_blend_attachment, create_vk_pipeline_info_depth_stencil, create_vk_pipeline_info_dynamic_states, create_vk_pipeline_info_multisample, create_vk_pipeline_info_rasterization, create_vk_pipeline_info_tessellation, create_vk_pipeline_info_vertex_input, create_vk_pipeline_info_viewport, create_vk_pipeline_layout
         }
     }
 };
 
-use crate::{ vk_check, log_info, log_err };
+use crate::{ vk_try, log_info, log_err };
+use crate::core::gk_error::{ GkError, GkResult };
 
-use super::gk_vk_render_layer::{ GkVkLayerDraw, GkVk3dLayerUpdate };
+use crate::renderer::vulkan_renderer::gk_vk_camera::GkCameraUniformData;
+use crate::scene::GkSceneGraph;
+
+use super::gk_vk_render_layer::{ GkVkLayerDraw, GkVk3dLayerUpdate, GkVkView };
 
 #[derive(Clone)]
 pub struct SkyBoxVertex
@@ -110,12 +115,15 @@ pub struct VkSimpleSkyBoxLayer
     descriptor:         GkVkDescriptor,
     pipeline_layout:    vk::PipelineLayout,
     pipeline:           vk::Pipeline,
-    triangle_verts:     Option<GkVkBuffer>,
-    triangle_indices:   Option<GkVkBuffer>,
-    texture:            Option<GkVkImage>,
+    triangle_verts:     GkVkBuffer,
+    triangle_indices:   GkVkBuffer,
+    texture:            GkVkImage,
     sampler:            vk::Sampler,
-    model_space:        glm::Mat4,
-    model_space_buffer: Option<GkVkBuffer>,
+    #[allow(dead_code)] // Only read through the descriptor sets, owned here to keep it alive.
+    model_space_buffer: GkVkBuffer,
+    texture_request:    Option<GkAssetHandle>,
+    desc_sets_dirty:    Vec<bool>,
+    views:              Vec<GkVkView>, // Cameras to draw this frame, from update()
 }
 
 impl VkSimpleSkyBoxLayer
@@ -123,15 +131,18 @@ impl VkSimpleSkyBoxLayer
     pub fn new(
             instance: &ash::Instance,
             vk_ctx: &mut GkVkContext,
+            asset_loader: &mut GkAssetLoader,
             camera_uniforms: &Vec<GkVkBuffer>,
             depth_img: &GkVkImage,
             texture_files: Vec<std::path::PathBuf>
-        ) -> Self
+        ) -> GkResult<Self>
     {
         log_info!("Creating SimpleSkyBoxLayer...");
         // let texture = gk_create_vk_image(vk_ctx, texture_file.to_str().unwrap());
-        let texture = gk_create_vk_cubemap_image(vk_ctx, texture_files).map_err(|err| { log_err!(err); panic!("Error creating vk cubemap image\n") }).unwrap();
-        let sampler = create_vk_sampler(&vk_ctx.device);
+        // Equirect to cube conversion is slow, draw a flat placeholder until the worker is done.
+        let texture_request = Some(asset_loader.request(EAssetRequest::Cubemap(texture_files)));
+        let texture = gk_create_vk_cubemap_image_from_bitmap(vk_ctx, "SkyBox placeholder cubemap", &gk_placeholder_bitmap(6))?;
+        let sampler = create_vk_sampler(&vk_ctx.device)?;
 
         let renderpass_info = GkVkRenderPassInfo{
             b_use_color: true,
@@ -142,24 +153,24 @@ impl VkSimpleSkyBoxLayer
             flags: ERenderPassBit::NONE,
             samples: vk::SampleCountFlags::TYPE_1
         };
-        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, renderpass_info);
-        let framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view);
+        let renderpass = gk_create_vk_renderpass(instance, vk_ctx, "Simple_SkyBox renderpass", renderpass_info)?;
+        let framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &renderpass, &depth_img.view)?;
         
         let model_space = get_z_up_matrix();
-        let model_space_buffer = Some(gk_create_vk_buffer(
+        let model_space_buffer = gk_create_vk_buffer(
             vk_ctx, "Simple_SkyBox_model_space", 
             vk::BufferUsageFlags::UNIFORM_BUFFER,
             MemoryLocation::CpuToGpu, 
             std::mem::size_of::<glm::Mat4>() as vk::DeviceSize
-        ));
+        )?;
 
-        let descriptor = Self::create_desc_sets(vk_ctx, camera_uniforms, &texture, &sampler, model_space_buffer.as_ref().unwrap());
+        let descriptor = Self::create_desc_sets(vk_ctx, camera_uniforms, &texture, &sampler, &model_space_buffer)?;
 
-        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &Vec::new());
+        let pipeline_layout = create_vk_pipeline_layout(&vk_ctx.device, &descriptor.layouts, &Vec::new())?;
 
         let mut shader_modules: Vec<GkVkShaderModule> = vec![
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("SimpleSkyBox.vert").as_path()),
-            GkVkShaderModule::new(&vk_ctx.device, to_shader_path("SimpleSkyBox.frag").as_path())
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("SimpleSkyBox.vert").as_path())?,
+            GkVkShaderModule::new(&vk_ctx.shared, to_shader_path("SimpleSkyBox.frag").as_path())?
         ];
 
         let pipeline = Self::create_pipeline(
@@ -169,32 +180,47 @@ impl VkSimpleSkyBoxLayer
             &pipeline_layout, None
         );
 
-        for shader in shader_modules.iter_mut()
-        {
-            shader.destroy(&vk_ctx.device);
-        }
+        drop(shader_modules);
+        let pipeline = pipeline?;
+        vk_ctx.shared.set_object_name(pipeline_layout, "Simple_SkyBox pipeline layout");
+        vk_ctx.shared.set_object_name(pipeline, "Simple_SkyBox pipeline");
 
-        let triangle_verts = gk_create_vk_array_buffer::<SkyBoxVertex>(vk_ctx, "Sky Verts", vk::BufferUsageFlags::VERTEX_BUFFER, &SKYBOX_VERTICES_DATA.to_vec());
-        let triangle_indices = gk_create_vk_array_buffer::<u32>(vk_ctx, "Sky Indices", vk::BufferUsageFlags::INDEX_BUFFER, &SKYBOX_INDICES_DATA.to_vec());
+        let triangle_verts = gk_create_vk_array_buffer::<SkyBoxVertex>(vk_ctx, "Sky Verts", vk::BufferUsageFlags::VERTEX_BUFFER, &SKYBOX_VERTICES_DATA.to_vec())?;
+        let triangle_indices = gk_create_vk_array_buffer::<u32>(vk_ctx, "Sky Indices", vk::BufferUsageFlags::INDEX_BUFFER, &SKYBOX_INDICES_DATA.to_vec())?;
 
-        map_vk_allocation_data::<glm::Mat4>(&model_space_buffer.as_ref().unwrap().allocation, &[model_space], 1);
+        map_vk_allocation_data::<glm::Mat4>(&model_space_buffer.allocation, &[model_space], 1);
 
         log_info!("SimpleSkyBoxLayer created.");
     
-        Self
+        Ok(Self
         {
             renderpass,
             framebuffers,
             descriptor,
             pipeline_layout,
             pipeline,
-            triangle_verts: Some(triangle_verts),
-            triangle_indices: Some(triangle_indices),
-            texture: Some(texture),
+            triangle_verts,
+            triangle_indices,
+            texture,
             sampler,
-            model_space,
-            model_space_buffer
-        }
+            model_space_buffer,
+            texture_request,
+            desc_sets_dirty: vec![false; vk_ctx.frame_sync.get_num_frames_in_flight()],
+            views: Vec::new(),
+        })
+    }
+
+    // Only the set of the current frame is rewritten, the others may still be in use by the gpu.
+    fn refresh_texture_desc_set(&mut self, vk_ctx: &GkVkContext)
+    {
+        let frame = vk_ctx.frame_sync.get_current_frame_index();
+        if !self.desc_sets_dirty[frame] { return; }
+        self.desc_sets_dirty[frame] = false;
+
+        let texture = &self.texture;
+        let image_info = [vk::DescriptorImageInfo{ sampler: self.sampler, image_view: texture.view, image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL }];
+        let desc_writes = [ get_vk_image_write_desc_set(&self.descriptor.sets[frame], &image_info, 2) ];
+        unsafe { vk_ctx.device.update_descriptor_sets(&desc_writes, &[]) }
     }
 
     fn create_desc_sets(
@@ -203,13 +229,13 @@ impl VkSimpleSkyBoxLayer
             texture: &GkVkImage,
             sampler: &vk::Sampler,
             model_space_buffer: &GkVkBuffer
-        ) -> GkVkDescriptor
+        ) -> GkResult<GkVkDescriptor>
     {
         let num_frames = vk_ctx.frame_sync.get_num_frames_in_flight() as u32;
-        let pool = gk_create_vk_desc_pool(vk_ctx, 2 * num_frames, 0, 1 * num_frames);
+        let pool = gk_create_vk_desc_pool(vk_ctx, num_frames, num_frames, 0, num_frames)?;
 
         let bindings: Vec<vk::DescriptorSetLayoutBinding> = vec![
-            get_vk_desc_set_layout_binding(0, vk::DescriptorType::UNIFORM_BUFFER, 1, vk::ShaderStageFlags::VERTEX),
+            get_vk_desc_set_layout_binding(0, vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC, 1, vk::ShaderStageFlags::VERTEX),
             get_vk_desc_set_layout_binding(1, vk::DescriptorType::UNIFORM_BUFFER, 1, vk::ShaderStageFlags::VERTEX),
             get_vk_desc_set_layout_binding(2, vk::DescriptorType::COMBINED_IMAGE_SAMPLER, 1, vk::ShaderStageFlags::FRAGMENT)
         ];
@@ -223,7 +249,7 @@ impl VkSimpleSkyBoxLayer
             p_bindings: bindings.as_ptr()
         };
         let layout = unsafe{
-            vk_check!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None)).unwrap()
+            vk_try!(vk_ctx.device.create_descriptor_set_layout(&layout_info, None))
         };
 
         let layouts: Vec<vk::DescriptorSetLayout> = vec![layout; vk_ctx.frame_sync.get_num_frames_in_flight()];
@@ -237,17 +263,17 @@ impl VkSimpleSkyBoxLayer
         };
 
         let sets = unsafe {
-            vk_check!(vk_ctx.device.allocate_descriptor_sets(&alloc_info)).unwrap()
+            vk_try!(vk_ctx.device.allocate_descriptor_sets(&alloc_info))
         };
 
         for i in 0..vk_ctx.frame_sync.get_num_frames_in_flight()
         {
-            let buffer_info1 = vk::DescriptorBufferInfo{ buffer: camera_uniforms[i].handle, offset: 0, range: camera_uniforms[i].size };
+            let buffer_info1 = vk::DescriptorBufferInfo{ buffer: camera_uniforms[i].handle, offset: 0, range: std::mem::size_of::<GkCameraUniformData>() as vk::DeviceSize };
             let buffer_info2 = vk::DescriptorBufferInfo{ buffer: model_space_buffer.handle, offset: 0, range: model_space_buffer.size };
             let image_info1 = vk::DescriptorImageInfo{ sampler: *sampler, image_view: texture.view, image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL};
 
             let desc_writes: Vec<vk::WriteDescriptorSet> = vec![
-                get_vk_buffer_write_desc_set(&sets[i], &[buffer_info1], 0, vk::DescriptorType::UNIFORM_BUFFER),
+                get_vk_buffer_write_desc_set(&sets[i], &[buffer_info1], 0, vk::DescriptorType::UNIFORM_BUFFER_DYNAMIC),
                 get_vk_buffer_write_desc_set(&sets[i], &[buffer_info2], 1, vk::DescriptorType::UNIFORM_BUFFER),
                 get_vk_image_write_desc_set(&sets[i], &[image_info1], 2) 
             ];
@@ -257,12 +283,7 @@ impl VkSimpleSkyBoxLayer
             }
         }
 
-        GkVkDescriptor
-        { 
-            layouts, 
-            pool, 
-            sets 
-        }
+        Ok(GkVkDescriptor::new(vk_ctx, layouts, pool, sets))
     }
 
     fn create_pipeline(
@@ -271,7 +292,7 @@ impl VkSimpleSkyBoxLayer
             renderpass: &GkVkRenderPass,
             layout: &vk::PipelineLayout,
             custom_extent: Option<vk::Extent2D>
-        ) -> vk::Pipeline    
+        ) -> GkResult<vk::Pipeline>
     {
         log_info!("creating VkSimpleSkyBoxLayer pipeline... ");
 
@@ -330,7 +351,7 @@ impl VkSimpleSkyBoxLayer
         ];
         let color_blending_info = create_vk_pipeline_info_color_blend(&color_attachments);
         
-        let depth_stencil_info = create_vk_pipeline_info_depth_stencil();
+        let depth_stencil_info = create_vk_pipeline_info_depth_stencil(vk_ctx.b_reverse_z);
         // depth_stencil_info.depth_test_enable = vk::FALSE;
         // depth_stencil_info.depth_write_enable = vk::FALSE;
     
@@ -366,25 +387,30 @@ impl VkSimpleSkyBoxLayer
         };
 
         let pipeline = unsafe {
-            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); }).unwrap()[0]
+            vk_ctx.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], None).map_err(|e| { log_err!("{}", e.1); GkError::from(e.1) })?[0]
         };
         log_info!("VkSimpleSkyBoxLayer pipeline created.");
 
-        pipeline
+        Ok(pipeline)
     }
 
-    fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer)
+    // Draws the views going to target, None for the swapchain.
+    fn draw(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, target: Option<usize>)
     {
         unsafe{
-            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.triangle_verts.as_ref().unwrap().handle], &[0 as vk::DeviceSize]);
-            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.triangle_indices.as_ref().unwrap().handle, 0, vk::IndexType::UINT32);
+            vk_ctx.device.cmd_bind_vertex_buffers(*cmd_buffer, 0, &[self.triangle_verts.handle], &[0 as vk::DeviceSize]);
+            vk_ctx.device.cmd_bind_index_buffer(*cmd_buffer, self.triangle_indices.handle, 0, vk::IndexType::UINT32);
 
             let desc_set = [self.descriptor.sets[vk_ctx.frame_sync.get_current_frame_index()]];
-            vk_ctx.device.cmd_bind_descriptor_sets(*cmd_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline_layout, 0, &desc_set, &[]);
+            for view in self.views.iter().filter(|view| view.target == target)
+            {
+                self.set_view(vk_ctx, cmd_buffer, view);
+                vk_ctx.device.cmd_bind_descriptor_sets(*cmd_buffer, vk::PipelineBindPoint::GRAPHICS, self.pipeline_layout, 0, &desc_set, &[view.uniform_offset]);
 
-            // vk_ctx.device.cmd_draw(*cmd_buffer, VERTICES_DATA.len() as u32, 1, 0, 0);
-            // vk_ctx.device.cmd_draw_indexed(*cmd_buffer, SKYBOX_INDICES_DATA.len() as u32, 1, 0, 0, 0);
-            vk_ctx.device.cmd_draw_indexed(*cmd_buffer, self.triangle_indices.as_ref().unwrap().size as u32 / std::mem::size_of::<u32>() as u32, 1, 0, 0, 0);
+                // vk_ctx.device.cmd_draw(*cmd_buffer, VERTICES_DATA.len() as u32, 1, 0, 0);
+                // vk_ctx.device.cmd_draw_indexed(*cmd_buffer, SKYBOX_INDICES_DATA.len() as u32, 1, 0, 0, 0);
+                vk_ctx.device.cmd_draw_indexed(*cmd_buffer, self.triangle_indices.size as u32 / std::mem::size_of::<u32>() as u32, 1, 0, 0, 0);
+            }
         }
     }
 
@@ -395,22 +421,29 @@ impl GkVkLayerDraw for VkSimpleSkyBoxLayer
     fn draw_frame(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, current_image: usize)
     {
         self.begin_renderpass(vk_ctx, cmd_buffer, &self.renderpass, self.pipeline, self.framebuffers[current_image]);
-        self.draw(vk_ctx, cmd_buffer);
+        self.draw(vk_ctx, cmd_buffer, None);
         self.end_renderpass(vk_ctx, cmd_buffer);
     }
 
+    fn draw_offscreen(&self, vk_ctx: &GkVkContext, cmd_buffer: &vk::CommandBuffer, slot: usize, target: &GkVkRenderTarget)
+    {
+        if !self.views.iter().any(|view| view.target == Some(slot)) { return; }
+        // The target's attachments have the formats of the swapchain and depth image, so this layer's render pass fits them.
+        self.begin_renderpass_sized(vk_ctx, cmd_buffer, &self.renderpass, self.pipeline, target.framebuffer, target.extent);
+        self.draw(vk_ctx, cmd_buffer, Some(slot));
+        self.end_renderpass(vk_ctx, cmd_buffer);
+    }
+
+    fn label(&self) -> &str
+    {
+        "Simple_SkyBox"
+    }
+
     fn destroy(&mut self, vk_ctx: &mut GkVkContext) 
     {
-        gk_destroy_vk_buffer(vk_ctx, self.triangle_verts.take().unwrap());
-        gk_destroy_vk_buffer(vk_ctx, self.triangle_indices.take().unwrap());
-        gk_destroy_vk_buffer(vk_ctx, self.model_space_buffer.take().unwrap());
-        gk_destroy_vk_img(vk_ctx, self.texture.take().unwrap());
         unsafe { vk_ctx.device.destroy_sampler(self.sampler, None); }
-
-        gk_destroy_vk_descriptor(vk_ctx, &self.descriptor);
         
         self.cleanup_framebuffers(&vk_ctx.device);
-        gk_destroy_vk_renderpass(vk_ctx, &self.renderpass);
         unsafe {
             vk_ctx.device.destroy_pipeline_layout(self.pipeline_layout, None);
             vk_ctx.device.destroy_pipeline(self.pipeline, None);
@@ -422,18 +455,41 @@ impl GkVkLayerDraw for VkSimpleSkyBoxLayer
         gk_destroy_vk_framebuffers(device, &mut self.framebuffers);   
     }
 
-    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>)
+    fn on_asset_loaded(&mut self, vk_ctx: &mut GkVkContext, handle: GkAssetHandle, asset: &EAssetData) -> bool
+    {
+        match asset
+        {
+            EAssetData::Cubemap(cube) if self.texture_request == Some(handle) =>
+            {
+                self.texture_request = None;
+                // On failure the placeholder cubemap is kept.
+                if let Ok(texture) = gk_create_vk_cubemap_image_from_bitmap(vk_ctx, "SkyBox cubemap", cube)
+                {
+                    self.texture = texture;
+                    self.desc_sets_dirty.iter_mut().for_each(|dirty| *dirty = true);
+                }
+                true
+            },
+            _ => false
+        }
+    }
+
+    fn recreate_framebuffers(&mut self, vk_ctx: &GkVkContext, depth_img: Option<&GkVkImage>) -> GkResult<()>
     {
-        self.framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view);
+        self.framebuffers = gk_create_vk_color_depth_framebuffers(vk_ctx, &self.renderpass, &depth_img.unwrap().view)?;
+        Ok(())
     }
 
 }
 
 impl GkVk3dLayerUpdate for VkSimpleSkyBoxLayer
 {
-    fn update(&mut self, _vk_ctx: &GkVkContext, _transform_uniform: &GkVkBuffer, _delta_time: f32)
+    fn update(&mut self, vk_ctx: &GkVkContext, _transform_uniform: &GkVkBuffer, views: &[GkVkView], _scene: &GkSceneGraph, _delta_time: f32)
     {
         // update
-        map_vk_allocation_data::<glm::Mat4>(&self.model_space_buffer.as_ref().unwrap().allocation, &[self.model_space], 1);
+        self.views.clear();
+        self.views.extend_from_slice(views);
+        // The model matrix never changes and was mapped in new(), writing it here would race frames in flight.
+        self.refresh_texture_desc_set(vk_ctx);
     }
 }
\ No newline at end of file
@@ -1,18 +1,24 @@
-use crate::renderer::renderer_utils::{self, to_asset_path};
-use crate::{log_err, vk_check, log_info, log_warn};
+use crate::renderer::renderer_utils::{self, to_asset_path, ERenderError, GfxRenderer};
+use crate::renderer::gk_asset_loader::GkAssetLoader;
+use crate::{log_err, vk_check, log_info, log_warn, profile_scope};
+use crate::core::gk_error::{GkError, GkResult};
+use crate::math::GkTransform;
+use crate::scene::{GkSceneGraph, GkMeshAttachment};
 
 
 use ash::vk::BufferUsageFlags;
 use ash::{self, vk};
 
-use super::gk_vk_camera::{GkCamera, GkCameraUniformData, CamView, CamProjection};
-use super::gk_vulkan::gunk_vk_buffer::{GkVkBuffer, gk_create_vk_buffers, gk_destroy_vk_buffers, map_vk_allocation_data};
-use super::gk_vulkan::gunk_vk_img::{gk_create_vk_depth_img, gk_destroy_vk_img, GkVkImage};
+use super::gk_vk_camera::{GkCamera, GkCameraUniformData, CamView, CamProjection, ECameraTarget, MAX_CAMERAS};
+use super::gk_vulkan::gunk_vk_buffer::{GkVkBuffer, gk_create_vk_buffers, map_vk_allocation_data};
+use super::gk_vulkan::gunk_vk_img::{gk_create_vk_depth_img, GkVkImage};
+use super::gk_vulkan::gunk_vk_render_target::{gk_create_vk_render_target, GkVkRenderTarget};
 use super::gk_vulkan::{
     gunk_vk_loader::GkVkLoader, 
-    gunk_vk_context::GkVkContext
+    gunk_vk_context::GkVkContext,
+    gunk_vk_validation::GkVkValidationConfig
 };
-use super::vk_render_layers::gk_vk_render_layer::{Vk2dLayerList, Vk3dLayerList, GkVk2dLayerUpdate, GkVk3dLayerUpdate};
+use super::vk_render_layers::gk_vk_render_layer::{Vk2dLayerList, Vk3dLayerList, GkVk2dLayerUpdate, GkVk3dLayerUpdate, GkVkView};
 
 use super::vk_render_layers::vk_simple3d_layer::VkSimple3dLayer;
 use super::vk_render_layers::vk_simple_skybox_layer::VkSimpleSkyBoxLayer;
@@ -20,6 +26,7 @@ use super::vk_render_layers::{
     gk_vk_render_layer::GkVkLayerDraw,
     vk_begin_layer::VkBeginLayer,
     vk_end_layer::VkEndLayer,
+    vk_profiler_overlay_layer::VkProfilerOverlayLayer,
     // vk_simple2d_layer::VkSimple2dLayer
 };
 
@@ -33,58 +40,175 @@ pub struct VulkanRenderer
 {
     pub loader:             GkVkLoader,
     pub vk_ctx:             GkVkContext,
-    pub transform_uniforms: Vec<GkVkBuffer>, // Uniform buffers
-    pub camera:             GkCamera,
+    pub transform_uniforms: Vec<GkVkBuffer>, // Uniform buffers, one slot per camera
+    cameras:                Vec<GkCamera>, // Never empty, the first is the main camera
+    camera_targets:         Vec<Option<GkVkRenderTarget>>, // Offscreen targets of cameras drawing to textures, by camera slot
+    scene:                  GkSceneGraph, // Outlives the device objects, so it survives a device loss
     pub depth_img:          Option<GkVkImage>,
-    vk_begin_layer:         VkBeginLayer,
-    vk_end_layer:           VkEndLayer,
+    vk_begin_layer:         Option<VkBeginLayer>, // None once the device objects are destroyed
+    vk_end_layer:           Option<VkEndLayer>,
     pub layers3d:           Vk3dLayerList,
     pub layers2d:           Vk2dLayerList,
+    pub asset_loader:       GkAssetLoader,
+    pub fence_wait_time:    std::time::Duration, // Time the last frame spent waiting on in flight fences
     has_resized:            bool,
+    device_lost_count:      u32,  // Consecutive frames that ended in VK_ERROR_DEVICE_LOST
+    surface_lost:           bool, // The surface was recreated on the previous frame
+    device_objects_alive:   bool, // False once destroy_device_objects() ran, until they are created again
     // model_matrix:           glm::Mat4,
 }
 
+/// Bytes between cameras in the transform uniform, GkCameraUniformData padded to the device's offset alignment.
+fn camera_uniform_stride(vk_ctx: &GkVkContext) -> vk::DeviceSize
+{
+    let size = std::mem::size_of::<GkCameraUniformData>() as vk::DeviceSize;
+    size.div_ceil(vk_ctx.uniform_alignment) * vk_ctx.uniform_alignment
+}
+
+/// Mesh loaded by the Simple3d layer, placed in the scene at startup.
+const SIMPLE3D_MESH: &str = "viking_room/viking_room.obj";
+
+/// Consecutive device losses tolerated before render() gives up.
+const MAX_DEVICE_RECREATIONS: u32 = 3;
+
+/// Everything that lives on the logical device and is rebuilt after a device loss.
+type VkDeviceObjects = (GkVkContext, Vec<GkVkBuffer>, GkVkImage, VkBeginLayer, VkEndLayer, Vk3dLayerList, Vk2dLayerList);
+
 impl VulkanRenderer
 {
-    pub fn new(window: &Window, app_name: CString, app_version: u32) -> Self
+    /// ### fn VulkanRenderer::new( ... ) -> GkResult\<VulkanRenderer\>
+    /// *Creates the Vulkan instance, device and the default layers.*
+    /// <pre>
+    /// - Params
+    ///     window:         &winit::window::Window
+    ///     app_name:       CString
+    ///     app_version:    u32
+    ///     validation:     GkVkValidationConfig
+    ///     b_vsync:        bool
+    ///     b_reverse_z:    bool    <i>// Depth buffer convention, every camera's projection follows it.</i>
+    /// - Return
+    ///     GkResult&lt;VulkanRenderer&gt;
+    /// </pre>
+    pub fn new(window: &Window, app_name: CString, app_version: u32, validation: GkVkValidationConfig, b_vsync: bool, b_reverse_z: bool) -> GkResult<Self>
     {
-        let loader = GkVkLoader::new(window, app_name, app_version);
-
-        let inner_size = window.inner_size();
-        let mut vk_ctx = GkVkContext::new(&loader, inner_size.width, inner_size.height);
-        let num_frames = vk_ctx.frame_sync.get_num_frames_in_flight();
+        let loader = GkVkLoader::new(window, app_name, app_version, validation)?;
 
-        let depth_img = gk_create_vk_depth_img(&loader.instance, &mut vk_ctx, inner_size.width, inner_size.height);
+        let num_workers = std::thread::available_parallelism().map(|n| n.get() - 1).unwrap_or(1).min(4);
+        let mut asset_loader = GkAssetLoader::new(num_workers);
 
-        let transform_uniforms = gk_create_vk_buffers(
-            &mut vk_ctx,
-            "transform uniform",
-            BufferUsageFlags::UNIFORM_BUFFER,
-            MemoryLocation::CpuToGpu,
-            std::mem::size_of::<GkCameraUniformData>() as vk::DeviceSize,
-            num_frames
-        );
+        let (vk_ctx, transform_uniforms, depth_img, vk_begin_layer, vk_end_layer, layers3d, layers2d) =
+            match Self::create_device_objects(&loader, &mut asset_loader, window, b_vsync, b_reverse_z)
+            {
+                Ok(objects) => objects,
+                Err(e) =>
+                {
+                    asset_loader.destroy();
+                    loader.destroy();
+                    return Err(e);
+                }
+            };
 
         let view = CamView
         {
             pos: glm::Vec3::new(0.0, -2.0, 1.0),
-            front: glm::Vec3::new(0.0, 1.0, 0.0),
+            front: glm::Vec3::new(0.0, 3.0, -1.0).normalize(),
             up: glm::Vec3::new(0.0, 0.0, 1.0)
         };
-        let projection = CamProjection
+        let aspect = vk_ctx.swapchain.extent.width as f32 / vk_ctx.swapchain.extent.height as f32;
+        let projection = CamProjection::perspective(45f32.to_radians(), aspect, 0.1, 100.0).with_reverse_z(b_reverse_z);
+        let camera = GkCamera::new(view, projection);
+
+        let mut scene = GkSceneGraph::new();
+        let room = scene.add_node("viking_room", GkTransform::identity());
+        if let Some(node) = scene.node_mut(room) { node.mesh = Some(GkMeshAttachment::new(to_asset_path(SIMPLE3D_MESH))); }
+
+        // let model_matrix = glm::Mat4::identity();
+
+        Ok(Self
         {
-            aspect: vk_ctx.swapchain.extent.width as f32 / vk_ctx.swapchain.extent.height as f32,
-            fov: 45.0,
-            near: 0.1,
-            far: 100.0
-        };
-        let camera = GkCamera{ view, projection };
+            loader,
+            vk_ctx,
+            transform_uniforms,
+            cameras: vec![camera],
+            camera_targets: Vec::new(),
+            scene,
+            depth_img: Some(depth_img),
+            vk_begin_layer: Some(vk_begin_layer),
+            vk_end_layer: Some(vk_end_layer),
+            layers3d,
+            layers2d,
+            asset_loader,
+            fence_wait_time: std::time::Duration::ZERO,
+            has_resized: false,
+            device_lost_count: 0,
+            surface_lost: false,
+            device_objects_alive: true,
+            // model_matrix
+        })
+    }
 
-        let vk_begin_layer = VkBeginLayer::new(&loader.instance, &mut vk_ctx, Some(&depth_img));
-        let vk_end_layer = VkEndLayer::new(&loader.instance, &mut vk_ctx, Some(&depth_img));
+    /// Creates the device context, uniforms, depth image and layers.<br>
+    /// Used on startup and again after the device is lost. If a step fails, everything created before it is destroyed.
+    fn create_device_objects(loader: &GkVkLoader, asset_loader: &mut GkAssetLoader, window: &Window, b_vsync: bool, b_reverse_z: bool) -> GkResult<VkDeviceObjects>
+    {
+        let inner_size = window.inner_size();
+        let mut vk_ctx = GkVkContext::new(loader, inner_size.width, inner_size.height, b_vsync, b_reverse_z)?;
 
+        let mut vk_begin_layer = None;
+        let mut vk_end_layer = None;
         let mut layers3d = Vk3dLayerList::new();
-        layers3d.push( Box::new(VkSimple3dLayer::new(&loader.instance, &mut vk_ctx, &transform_uniforms, &depth_img, &to_asset_path("viking_room/viking_room.obj"), &to_asset_path("viking_room/viking_room.png"))) );
+        let mut layers2d = Vk2dLayerList::new();
+        match Self::create_device_resources(loader, asset_loader, &mut vk_ctx, &mut vk_begin_layer, &mut vk_end_layer, &mut layers3d, &mut layers2d)
+        {
+            // Both layers exist once create_device_resources() succeeded.
+            Ok((transform_uniforms, depth_img)) =>
+                Ok((vk_ctx, transform_uniforms, depth_img, vk_begin_layer.unwrap(), vk_end_layer.unwrap(), layers3d, layers2d)),
+            Err(e) =>
+            {
+                // The uniforms and depth image were dropped into the deletion queue on the way out.
+                if let Some(mut layer) = vk_begin_layer { layer.destroy(&mut vk_ctx); }
+                layers3d.destroy(&mut vk_ctx);
+                layers2d.destroy(&mut vk_ctx);
+                if let Some(mut layer) = vk_end_layer { layer.destroy(&mut vk_ctx); }
+                // Uploads recorded by the layers may still be running.
+                unsafe { vk_check!(vk_ctx.device.device_wait_idle()); }
+                vk_ctx.destroy();
+                Err(e)
+            }
+        }
+    }
+
+    /// Creates what create_device_objects() needs besides the context. The layers are written to the out parameters
+    /// as soon as they exist, so the caller can destroy them when a later one fails.
+    fn create_device_resources(
+        loader: &GkVkLoader,
+        asset_loader: &mut GkAssetLoader,
+        vk_ctx: &mut GkVkContext,
+        vk_begin_layer: &mut Option<VkBeginLayer>,
+        vk_end_layer: &mut Option<VkEndLayer>,
+        layers3d: &mut Vk3dLayerList,
+        layers2d: &mut Vk2dLayerList
+    ) -> GkResult<(Vec<GkVkBuffer>, GkVkImage)>
+    {
+        let num_frames = vk_ctx.frame_sync.get_num_frames_in_flight();
+
+        let extent = vk_ctx.swapchain.extent;
+        let depth_img = gk_create_vk_depth_img(&loader.instance, vk_ctx, extent.width, extent.height)?;
+
+        let uniform_size = camera_uniform_stride(vk_ctx) * MAX_CAMERAS as vk::DeviceSize;
+        let transform_uniforms = gk_create_vk_buffers(
+            vk_ctx,
+            "transform uniform",
+            BufferUsageFlags::UNIFORM_BUFFER,
+            MemoryLocation::CpuToGpu,
+            uniform_size,
+            num_frames
+        )?;
+
+        *vk_begin_layer = Some(VkBeginLayer::new(&loader.instance, vk_ctx, Some(&depth_img))?);
+        *vk_end_layer = Some(VkEndLayer::new(&loader.instance, vk_ctx, Some(&depth_img))?);
+
+        layers3d.push( Box::new(VkSimple3dLayer::new(&loader.instance, vk_ctx, asset_loader, &transform_uniforms, &depth_img, &to_asset_path(SIMPLE3D_MESH), &to_asset_path("viking_room/viking_room.png"))?) );
 
         // let sky_textures = vec![ 
         //     to_asset_path("textures/skyboxes/default/left.jpg"),
@@ -97,155 +221,309 @@ impl VulkanRenderer
         let sky_textures = vec![ 
             to_asset_path("textures/skyboxes/piazza_bologni/piazza_bologni_1k.hdr")
         ];
-        layers3d.push( Box::new( VkSimpleSkyBoxLayer::new(&loader.instance, &mut vk_ctx, &transform_uniforms, &depth_img, sky_textures) ));
+        layers3d.push( Box::new( VkSimpleSkyBoxLayer::new(&loader.instance, vk_ctx, asset_loader, &transform_uniforms, &depth_img, sky_textures)? ));
         // layers3d.push(Box::new( VkModelLayer::new(&loader.instance, &mut vk_ctx, &transform_uniforms, &depth_img, &to_asset_path("rubber_duck/scene.gltf").as_path(), &to_asset_path("rubber_duck/textures/Duck_baseColor.png").as_path())) );
 
-        let layers2d = Vk2dLayerList::new();
         // layers2d.push( Box::new(VkSimple2dLayer::new(&loader.instance, &mut vk_ctx, &to_asset_path("textures/statue.jpg"))) );
         // layers2d.push( Box::new(VkSimple2dLayer::new(&loader.instance, &mut vk_ctx, &to_asset_path("textures/skyboxes/piazza_bologni/piazza_bologni_1k.hdr"))) );
-        
-        // let model_matrix = glm::Mat4::identity();
+        layers2d.push( Box::new(VkProfilerOverlayLayer::new(&loader.instance, vk_ctx)?) );
 
-        Self
-        {
-            loader,
-            vk_ctx,
-            transform_uniforms,
-            camera,
-            depth_img: Some(depth_img),
-            vk_begin_layer,
-            vk_end_layer,
-            layers3d,
-            layers2d,
-            has_resized: false,
-            // model_matrix
-        }
+        Ok((transform_uniforms, depth_img))
+    }
+
+    /// Destroys everything made by create_device_objects(). The device must be idle or lost.<br>
+    /// Resources are dropped before the context, anything still alive after that shows up in its leak report.<br>
+    /// Does nothing if they are already gone, e.g. after recreate_device() failed.
+    fn destroy_device_objects(&mut self)
+    {
+        if !self.device_objects_alive { return; }
+        self.device_objects_alive = false;
+
+        self.depth_img = None;
+        self.transform_uniforms.clear();
+        // Created again by the first frame after a device recreation.
+        self.camera_targets.clear();
+
+        if let Some(mut layer) = self.vk_begin_layer.take() { layer.destroy(&mut self.vk_ctx); }
+        self.layers3d.destroy(&mut self.vk_ctx);
+        self.layers2d.destroy(&mut self.vk_ctx);
+        if let Some(mut layer) = self.vk_end_layer.take() { layer.destroy(&mut self.vk_ctx); }
+
+        self.vk_ctx.destroy();
     }
 
     pub fn cleanup_swapchain(&mut self)
     {
         log_info!("Cleaning VkSwapchain and VkFramebuffers...");
-        self.vk_begin_layer.cleanup_framebuffers(&self.vk_ctx.device);
+        if let Some(layer) = self.vk_begin_layer.as_mut() { layer.cleanup_framebuffers(&self.vk_ctx.device); }
         self.layers2d.cleanup_framebuffers(&self.vk_ctx.device);
         self.layers3d.cleanup_framebuffers(&self.vk_ctx.device);
-        self.vk_end_layer.cleanup_framebuffers(&self.vk_ctx.device);
+        if let Some(layer) = self.vk_end_layer.as_mut() { layer.cleanup_framebuffers(&self.vk_ctx.device); }
 
         self.vk_ctx.clean_swapchain();
         
         log_info!("VkSwapchain and VkFramebuffers cleaned.");
     }
 
-    pub fn recreate_swapchain(&mut self, window: &Window)
+    pub fn recreate_swapchain(&mut self, window: &Window) -> GkResult<()>
     {
         log_info!("Recreating VkSwapchain and Framebuffers...");
 
-        unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()).unwrap(); }
+        unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()); }
 
         self.cleanup_swapchain();
+        self.rebuild_swapchain(window)?;
 
-        let inner_size = window.inner_size();
-        self.vk_ctx.recreate_swapchain(&self.loader, inner_size.width, inner_size.height);
+        log_info!("VkSwapchain and VkFramebuffers recreated.");
+        Ok(())
+    }
 
-        self.vk_begin_layer.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref());
-        self.layers3d.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref());
-        self.layers2d.recreate_framebuffers(&self.vk_ctx, None);
-        self.vk_end_layer.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref());
+    /// ### fn VulkanRenderer::recreate_surface( &mut self, ... )
+    /// *Replaces a lost VkSurfaceKHR and rebuilds the swapchain on top of it.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     window:     &winit::window::Window
+    /// - Return
+    ///     GkResult&lt;()&gt;
+    /// </pre>
+    pub fn recreate_surface(&mut self, window: &Window) -> GkResult<()>
+    {
+        log_warn!("VkSurfaceKHR lost, recreating surface and swapchain...");
 
-        log_info!("VkSwapchain and VkFramebuffers recreated.");
+        unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()); }
+
+        self.cleanup_swapchain();
+        self.loader.recreate_surface(window)?;
+        self.rebuild_swapchain(window)?;
+
+        log_info!("VkSurfaceKHR and VkSwapchain recreated.");
+        Ok(())
     }
 
+    /// ### fn VulkanRenderer::recreate_device( &mut self, ... )
+    /// *Rebuilds the device and every resource on it after VK_ERROR_DEVICE_LOST.*<br>
+    /// *Streamed assets are requested again by the new layers.<br>
+    /// The lost device is gone even if this fails, the renderer then refuses to render and destroy() skips it.*
+    /// <pre>
+    /// - Params
+    ///     <b>&mut self</b>
+    ///     window:     &winit::window::Window
+    /// - Return
+    ///     GkResult&lt;()&gt;
+    /// </pre>
+    pub fn recreate_device(&mut self, window: &Window) -> GkResult<()>
+    {
+        log_warn!("VkDevice lost, recreating device and resources...");
 
-    fn draw_frame(&mut self, _window: &Window, draw_buffer: &vk::CommandBuffer, current_img: usize) 
+        // Wait errors are expected here, a lost device fails every wait immediately.
+        unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()); }
+        let (b_vsync, b_reverse_z) = (self.vk_ctx.b_vsync, self.vk_ctx.b_reverse_z);
+        self.destroy_device_objects();
+        // Results of requests made by the destroyed layers have nobody to go to.
+        let _ = self.asset_loader.poll();
+
+        let (vk_ctx, transform_uniforms, depth_img, vk_begin_layer, vk_end_layer, layers3d, layers2d) =
+            Self::create_device_objects(&self.loader, &mut self.asset_loader, window, b_vsync, b_reverse_z)?;
+        self.vk_ctx = vk_ctx;
+        self.transform_uniforms = transform_uniforms;
+        self.depth_img = Some(depth_img);
+        self.vk_begin_layer = Some(vk_begin_layer);
+        self.vk_end_layer = Some(vk_end_layer);
+        self.layers3d = layers3d;
+        self.layers2d = layers2d;
+        self.device_objects_alive = true;
+        self.has_resized = false;
+
+        log_info!("VkDevice recreated.");
+        Ok(())
+    }
+
+    /// Creates the swapchain, depth image and framebuffers after cleanup_swapchain().
+    fn rebuild_swapchain(&mut self, window: &Window) -> GkResult<()>
     {
-        // let draw_buffer = *self.vk_ctx.draw_cmds.get_current_buffer();
+        let inner_size = window.inner_size();
+        self.vk_ctx.recreate_swapchain(&self.loader, inner_size.width, inner_size.height)?;
+
+        // The depth attachment has to match the new swapchain extent.
+        let extent = self.vk_ctx.swapchain.extent;
+        let depth_img = gk_create_vk_depth_img(&self.loader.instance, &mut self.vk_ctx, extent.width, extent.height)?;
+        self.depth_img = Some(depth_img);
+
+        if let Some(layer) = self.vk_begin_layer.as_mut() { layer.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref())?; }
+        self.layers3d.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref())?;
+        self.layers2d.recreate_framebuffers(&self.vk_ctx, None)?;
+        if let Some(layer) = self.vk_end_layer.as_mut() { layer.recreate_framebuffers(&self.vk_ctx, self.depth_img.as_ref())?; }
+        Ok(())
+    }
 
-        let draw_cmd_begin_info = vk::CommandBufferBeginInfo
+    /// Moves the cameras that scene nodes are attached to, after update_world_transforms().
+    fn apply_scene_cameras(&mut self)
+    {
+        for (attachment, node) in self.scene.cameras()
         {
-            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
-            p_next: std::ptr::null(),
-            flags: vk::CommandBufferUsageFlags::SIMULTANEOUS_USE,
-            p_inheritance_info: std::ptr::null()
-        };
+            let Some(camera) = self.cameras.get_mut(attachment.slot) else { continue; };
+            camera.view = CamView
+            {
+                pos: node.world_position(),
+                front: node.world_direction(&glm::Vec3::y()),
+                up: node.world_direction(&glm::Vec3::z()),
+            };
+        }
+    }
 
-        unsafe
+    /// Creates the offscreen targets of cameras drawing to textures, and replaces those whose size changed.<br>
+    /// Replaced targets are destroyed once the frames in flight are done with them.
+    fn update_camera_targets(&mut self) -> GkResult<()>
+    {
+        self.camera_targets.resize_with(self.cameras.len(), || None);
+        for (slot, camera) in self.cameras.iter().enumerate()
         {
-            vk_check!(self.vk_ctx.device.begin_command_buffer(*draw_buffer, &draw_cmd_begin_info));
-
-            self.vk_begin_layer.draw_frame(&self.vk_ctx, &draw_buffer, current_img);
-            self.layers3d.draw_frame(&self.vk_ctx, &draw_buffer, current_img);
-            self.layers2d.draw_frame(&self.vk_ctx, &draw_buffer, current_img);
-            self.vk_end_layer.draw_frame(&self.vk_ctx, &draw_buffer, current_img);
-
-            vk_check!(self.vk_ctx.device.end_command_buffer(*draw_buffer));
+            let target = &mut self.camera_targets[slot];
+            let (width, height) = match camera.target
+            {
+                ECameraTarget::Texture { width, height } if width > 0 && height > 0 => (width, height),
+                _ =>
+                {
+                    *target = None;
+                    continue;
+                }
+            };
+            if target.as_ref().is_some_and(|target| target.extent.width == width && target.extent.height == height) { continue; }
+            *target = None;
+            *target = Some(gk_create_vk_render_target(&self.loader.instance, &mut self.vk_ctx, &format!("camera {} target", slot), width, height)?);
         }
+        Ok(())
     }
 
-}
-
-impl renderer_utils::GfxRenderer for VulkanRenderer
-{
-    fn init(&self) 
+    /// ### fn VulkanRenderer::camera_texture( &self, ... ) -> Option\<&GkVkImage\>
+    /// *Color texture the camera in slot draws into, if its target is ECameraTarget::Texture.*<br>
+    /// *It is in SHADER_READ_ONLY_OPTIMAL outside of the targets' drawing, which comes first in the frame,
+    /// so layers can sample this frame's picture. The image is replaced when the camera's texture size changes.*
+    pub fn camera_texture(&self, slot: usize) -> Option<&GkVkImage>
     {
-         
+        self.camera_targets.get(slot)?.as_ref().map(|target| &target.color)
     }
 
-    fn destroy(&mut self) 
+    /// Fits each camera's projection to its viewport and writes its slot of this frame's transform uniform.<br>
+    /// Returns the views the 3d layers draw, skipping cameras whose viewport has no pixels.
+    fn update_cameras(&mut self) -> Vec<GkVkView>
     {
-        gk_destroy_vk_img(&mut self.vk_ctx, self.depth_img.take().unwrap());
-        gk_destroy_vk_buffers(&mut self.vk_ctx, &mut self.transform_uniforms);
+        let extent = self.vk_ctx.swapchain.extent;
+        let stride = camera_uniform_stride(&self.vk_ctx) as usize;
+        let mut uniform_data = vec![0u8; stride * self.cameras.len()];
+        let mut views = Vec::with_capacity(self.cameras.len());
+        for (slot, camera) in self.cameras.iter_mut().enumerate()
+        {
+            let b_offscreen = self.camera_targets.get(slot).is_some_and(|target| target.is_some());
+            let (target_width, target_height) = if b_offscreen { camera.target.extent((0, 0)) } else { (extent.width, extent.height) };
+            let (x, y, width, height) = camera.viewport.to_pixels(target_width, target_height);
+            if width == 0 || height == 0 { continue; }
+            camera.projection.set_viewport_size(width, height);
+            camera.projection.b_reverse_z = self.vk_ctx.b_reverse_z;
+
+            let mut data = GkCameraUniformData::new();
+            data.update(camera);
+            let bytes = unsafe { std::slice::from_raw_parts(&data as *const GkCameraUniformData as *const u8, std::mem::size_of::<GkCameraUniformData>()) };
+            uniform_data[slot * stride..slot * stride + bytes.len()].copy_from_slice(bytes);
+
+            views.push(GkVkView
+            {
+                viewport: vk::Viewport { x: x as f32, y: y as f32, width: width as f32, height: height as f32, min_depth: 0.0, max_depth: 1.0 },
+                scissor: vk::Rect2D { offset: vk::Offset2D { x, y }, extent: vk::Extent2D { width, height } },
+                uniform_offset: (slot * stride) as u32,
+                target: if b_offscreen { Some(slot) } else { None },
+            });
+        }
 
-        self.vk_begin_layer.destroy(&mut self.vk_ctx);
-        self.layers3d.destroy(&mut self.vk_ctx);
-        self.layers2d.destroy(&mut self.vk_ctx);
-        self.vk_end_layer.destroy(&mut self.vk_ctx);
+        let current_frame = self.vk_ctx.frame_sync.get_current_frame_index();
+        map_vk_allocation_data::<u8>(&self.transform_uniforms[current_frame].allocation, &uniform_data, uniform_data.len());
+        views
+    }
 
-        self.vk_ctx.destroy();
-        self.loader.destroy(); 
+    /// Hands assets finished by the loader workers to the layers that requested them.
+    fn receive_streamed_assets(&mut self)
+    {
+        profile_scope!("VulkanRenderer::receive_streamed_assets");
+        for loaded in self.asset_loader.poll()
+        {
+            match loaded.data
+            {
+                Ok(asset) =>
+                {
+                    let claimed = self.layers3d.on_asset_loaded(&mut self.vk_ctx, loaded.handle, &asset)
+                        || self.layers2d.on_asset_loaded(&mut self.vk_ctx, loaded.handle, &asset);
+                    if !claimed { log_warn!(format!("Streamed asset {:?} was not claimed by any layer", loaded.handle)); }
+                },
+                Err(e) => { log_err!(format!("Failed to stream asset {:?}: {}", loaded.handle, e)); }
+            }
+        }
     }
 
-    fn update(&mut self, window: &Window, delta_time: f32) 
+
+    fn draw_frame(&mut self, _window: &Window, draw_buffer: &vk::CommandBuffer, current_img: usize) 
     {
-        let _inner_size = window.inner_size();
+        profile_scope!("VulkanRenderer::draw_frame");
+        // let draw_buffer = *self.vk_ctx.draw_cmds.get_current_buffer();
 
-        // self.model_matrix = glm::rotate(&self.model_matrix, glm::pi::<f32>() * delta_time, &glm::vec3(0.0, 0.0, 1.0));
-        // let m = self.model_matrix.as_slice()[..].try_into().unwrap();
-        let v = self.camera.view.get_matrix().as_slice()[..].try_into().unwrap();
-        let p = self.camera.projection.get_matrix().as_slice()[..].try_into().unwrap();
+        let draw_cmd_begin_info = vk::CommandBufferBeginInfo
+        {
+            s_type: vk::StructureType::COMMAND_BUFFER_BEGIN_INFO,
+            p_next: std::ptr::null(),
+            flags: vk::CommandBufferUsageFlags::SIMULTANEOUS_USE,
+            p_inheritance_info: std::ptr::null()
+        };
 
-        let camera_uniform_data = GkCameraUniformData{ view: v, proj: p };
+        unsafe
+        {
+            vk_check!(self.vk_ctx.device.begin_command_buffer(*draw_buffer, &draw_cmd_begin_info));
+            // Reads back the timings recorded the last time this frame slot was used.
+            self.vk_ctx.profiler.begin_frame(&self.vk_ctx.device, *draw_buffer, self.vk_ctx.frame_sync.get_current_frame_index(), self.vk_ctx.frame_sync.get_frame_number());
 
+            // Offscreen cameras go first, so the swapchain layers can sample their textures.
+            for (slot, target) in self.camera_targets.iter().enumerate()
+            {
+                let Some(target) = target else { continue; };
+                self.vk_ctx.begin_gpu_scope(*draw_buffer, "Camera target", false);
+                target.begin(&self.vk_ctx, draw_buffer);
+                self.layers3d.draw_offscreen(&self.vk_ctx, draw_buffer, slot, target);
+                target.end(&self.vk_ctx, draw_buffer);
+                self.vk_ctx.end_gpu_scope(*draw_buffer);
+            }
 
-        let current_frame = self.vk_ctx.frame_sync.get_current_frame_index();
-        map_vk_allocation_data::<GkCameraUniformData>(&self.transform_uniforms[current_frame].allocation, &[camera_uniform_data], 1);
+            if let Some(layer) = self.vk_begin_layer.as_ref() { layer.draw_frame_labelled(&self.vk_ctx, draw_buffer, current_img); }
+            self.layers3d.draw_frame_labelled(&self.vk_ctx, draw_buffer, current_img);
+            self.layers2d.draw_frame_labelled(&self.vk_ctx, draw_buffer, current_img);
+            if let Some(layer) = self.vk_end_layer.as_ref() { layer.draw_frame_labelled(&self.vk_ctx, draw_buffer, current_img); }
 
-         self.layers3d.update(&self.vk_ctx, &self.transform_uniforms[current_frame], delta_time);
-        self.layers2d.update(&self.vk_ctx);
+            self.vk_ctx.profiler.end_frame(&self.vk_ctx.device, *draw_buffer);
+            vk_check!(self.vk_ctx.device.end_command_buffer(*draw_buffer));
+        }
     }
 
-    fn render(&mut self, window: &Window, delta_time: f32) 
+    /// Records and submits the frame drawing to the acquired swapchain image.<br>
+    /// The in flight fence is only reset right before the submit that signals it again.
+    fn submit_frame(&mut self, window: &Window, delta_time: f32, current_img_idx: u32, in_flight_fence: vk::Fence) -> GkResult<()>
     {
-        unsafe { vk_check!(self.vk_ctx.device.wait_for_fences(&[*self.vk_ctx.frame_sync.get_current_in_flight_fence()], true, std::u64::MAX)).unwrap(); }
-
-        let (current_img_idx, _is_sub_optimal) = unsafe {
-            self.vk_ctx.swapchain.loader.acquire_next_image(self.vk_ctx.swapchain.handle, std::u64::MAX, *self.vk_ctx.frame_sync.get_current_wait_semaphore(), vk::Fence::null()).map_err(
-                |vk_result| 
-                {
-                    match vk_result
-                    {
-                        vk::Result::ERROR_OUT_OF_DATE_KHR => { self.recreate_swapchain(window); log_warn!("Out of date KHR, aquire next image."); }
-                        _ => { log_err!(vk_result); }
-                    }
-                }
-            ).unwrap()
-        };
+        // The swapchain may hand back an image an older frame, in another slot, is still rendering to.
+        let image_fence = self.vk_ctx.frame_sync.images_in_flight[current_img_idx as usize];
+        if image_fence != vk::Fence::null()
+        {
+            let wait_start = std::time::Instant::now();
+            unsafe { self.vk_ctx.device.wait_for_fences(&[image_fence], true, u64::MAX)?; }
+            self.fence_wait_time += wait_start.elapsed();
+        }
 
-        unsafe { vk_check!(self.vk_ctx.device.reset_fences( &[*self.vk_ctx.frame_sync.get_current_in_flight_fence()] )).unwrap(); }
         let draw_buffer = self.vk_ctx.draw_cmds.buffers[self.vk_ctx.frame_sync.get_current_frame_index()];
-        unsafe { vk_check!( self.vk_ctx.device.reset_command_buffer(draw_buffer, vk::CommandBufferResetFlags::empty()) ).unwrap(); }
+        unsafe { self.vk_ctx.device.reset_command_buffer(draw_buffer, vk::CommandBufferResetFlags::empty())?; }
 
         let current_img = current_img_idx as usize;
+        self.update_camera_targets()?;
         self.update(window, delta_time);
-        self.draw_frame(window, &draw_buffer, current_img as usize);
+        self.draw_frame(window, &draw_buffer, current_img);
+
+        // Uploads recorded this frame must reach the graphics queue ahead of the draw that reads them.
+        self.vk_ctx.flush_uploads()?;
 
         let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
 
@@ -262,7 +540,64 @@ impl renderer_utils::GfxRenderer for VulkanRenderer
             p_signal_semaphores: self.vk_ctx.frame_sync.get_current_render_semaphore()
         };
 
-        unsafe { vk_check!(self.vk_ctx.device.queue_submit(self.vk_ctx.queues.graphics.handle, &[submit_info], *self.vk_ctx.frame_sync.get_current_in_flight_fence())); }
+        self.vk_ctx.frame_sync.images_in_flight[current_img] = in_flight_fence;
+        unsafe
+        {
+            self.vk_ctx.device.reset_fences(&[in_flight_fence])?;
+            self.vk_ctx.device.queue_submit(self.vk_ctx.queues.graphics.handle, &[submit_info], in_flight_fence)?;
+        }
+        Ok(())
+    }
+
+    /// Cleans up after submit_frame() failed with an image acquired.<br>
+    /// An empty submit consumes the acquire semaphore, which would otherwise stay signaled with nothing waiting on it,
+    /// and signals the in flight fence again if it was already reset. Otherwise the next frame would wait on it forever.
+    fn abandon_frame(&self, in_flight_fence: vk::Fence)
+    {
+        let b_fence_reset = matches!(unsafe { self.vk_ctx.device.get_fence_status(in_flight_fence) }, Ok(false));
+        let wait_stages = [vk::PipelineStageFlags::COLOR_ATTACHMENT_OUTPUT];
+        let submit_info = vk::SubmitInfo
+        {
+            s_type: vk::StructureType::SUBMIT_INFO,
+            p_next: std::ptr::null(),
+            wait_semaphore_count: 1,
+            p_wait_semaphores: self.vk_ctx.frame_sync.get_current_wait_semaphore(),
+            p_wait_dst_stage_mask: wait_stages.as_ptr(),
+            ..Default::default()
+        };
+        let fence = if b_fence_reset { in_flight_fence } else { vk::Fence::null() };
+        // A lost device fails this too, recreate_device() replaces the semaphores and fences anyway.
+        if let Err(e) = unsafe { self.vk_ctx.device.queue_submit(self.vk_ctx.queues.graphics.handle, &[submit_info], fence) }
+        {
+            log_warn!(format!("Could not release an abandoned frame: {}", e));
+        }
+    }
+
+    /// Waits for the frame slot, records and submits a frame, then presents it.<br>
+    /// Errors are handed back to render(), which decides how to recover.
+    fn render_frame(&mut self, window: &Window, delta_time: f32) -> GkResult<()>
+    {
+        let in_flight_fence = *self.vk_ctx.frame_sync.get_current_in_flight_fence();
+        let wait_start = std::time::Instant::now();
+        {
+            profile_scope!("Wait for frame fence");
+            unsafe { self.vk_ctx.device.wait_for_fences(&[in_flight_fence], true, u64::MAX)?; }
+        }
+        self.fence_wait_time = wait_start.elapsed();
+
+        // The frame that last used this slot is done, resources released since then can go.
+        self.vk_ctx.collect_deferred();
+
+        // An early return here leaves the in flight fence signaled for the next attempt.
+        let (current_img_idx, acquire_sub_optimal) = unsafe {
+            self.vk_ctx.swapchain.loader.acquire_next_image(self.vk_ctx.swapchain.handle, u64::MAX, *self.vk_ctx.frame_sync.get_current_wait_semaphore(), vk::Fence::null())?
+        };
+
+        if let Err(e) = self.submit_frame(window, delta_time, current_img_idx, in_flight_fence)
+        {
+            self.abandon_frame(in_flight_fence);
+            return Err(e);
+        }
 
         let present_info = vk::PresentInfoKHR
         {
@@ -276,29 +611,107 @@ impl renderer_utils::GfxRenderer for VulkanRenderer
             ..Default::default()
         };
 
-        unsafe
+        let present_result = unsafe { self.vk_ctx.swapchain.loader.queue_present(self.vk_ctx.queues.graphics.handle, &present_info) };
+
+        // The frame was submitted, the next one uses the next slot whether presenting worked or not.
+        self.vk_ctx.next_frame();
+
+        let is_out_of_date = match present_result
+        {
+            Ok(present_sub_optimal) => present_sub_optimal || acquire_sub_optimal,
+            Err(vk::Result::ERROR_OUT_OF_DATE_KHR) => true,
+            Err(e) => return Err(e.into())
+        };
+        if is_out_of_date || self.has_resized
+        {
+            self.has_resized = false;
+            self.recreate_swapchain(window)?;
+        }
+        Ok(())
+    }
+}
+
+impl renderer_utils::GfxRenderer for VulkanRenderer
+{
+    fn init(&self) 
+    {
+         
+    }
+
+    fn destroy(&mut self) 
+    {
+        if self.device_objects_alive { unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()); } }
+        self.asset_loader.destroy();
+        self.destroy_device_objects();
+        self.loader.destroy(); 
+    }
+
+    fn update(&mut self, window: &Window, delta_time: f32) 
+    {
+        profile_scope!("VulkanRenderer::update");
+        let _inner_size = window.inner_size();
+
+        self.receive_streamed_assets();
+
+        // self.model_matrix = glm::rotate(&self.model_matrix, glm::pi::<f32>() * delta_time, &glm::vec3(0.0, 0.0, 1.0));
+        // let m = self.model_matrix.as_slice()[..].try_into().unwrap();
+        self.scene.update_world_transforms();
+        self.apply_scene_cameras();
+        let views = self.update_cameras();
+
+        let current_frame = self.vk_ctx.frame_sync.get_current_frame_index();
+        self.layers3d.update(&self.vk_ctx, &self.transform_uniforms[current_frame], &views, &self.scene, delta_time);
+        self.layers2d.update(&self.vk_ctx);
+    }
+
+    fn render(&mut self, window: &Window, delta_time: f32) -> Result<(), ERenderError>
+    {
+        profile_scope!("VulkanRenderer::render");
+        // A minimized window has no extent to build a swapchain with, skip frames until it is restored.
+        let inner_size = window.inner_size();
+        if inner_size.width == 0 || inner_size.height == 0 { return Ok(()); }
+        if !self.device_objects_alive { return Err(ERenderError::DeviceLost); }
+
+        let result = self.render_frame(window, delta_time);
+        let surface_was_lost = self.surface_lost;
+        self.surface_lost = false;
+        match result
         {
-            let is_sub_optimal = self.vk_ctx.swapchain.loader.queue_present(self.vk_ctx.queues.graphics.handle, &present_info).map_err(
-                |vk_result|
+            Ok(()) =>
+            {
+                self.device_lost_count = 0;
+                Ok(())
+            },
+            Err(GkError::Vulkan(vk::Result::ERROR_OUT_OF_DATE_KHR)) =>
+            {
+                log_warn!("VkSwapchain out of date, recreating.");
+                self.recreate_swapchain(window)?;
+                Ok(())
+            },
+            Err(GkError::Vulkan(vk::Result::ERROR_SURFACE_LOST_KHR)) =>
+            {
+                if surface_was_lost
                 {
-                    match vk_result
-                    {
-                        vk::Result::ERROR_OUT_OF_DATE_KHR => { self.recreate_swapchain(window); log_warn!("Out of date KHR, present queue"); }
-                        _ => { log_err!(vk_result); }
-                    }
+                    log_err!("VkSurfaceKHR lost again right after being recreated.");
+                    return Err(ERenderError::SurfaceLost);
                 }
-            ).unwrap();
-            if is_sub_optimal || self.has_resized
+                self.recreate_surface(window)?;
+                self.surface_lost = true;
+                Ok(())
+            },
+            Err(GkError::Vulkan(vk::Result::ERROR_DEVICE_LOST)) =>
             {
-                self.has_resized = false;
-                self.recreate_swapchain(window);
-            }
-
-            vk_check!(self.vk_ctx.device.device_wait_idle());
+                self.device_lost_count += 1;
+                if self.device_lost_count > MAX_DEVICE_RECREATIONS
+                {
+                    log_err!(format!("VkDevice lost {} frames in a row, giving up.", self.device_lost_count));
+                    return Err(ERenderError::DeviceLost);
+                }
+                self.recreate_device(window)?;
+                Ok(())
+            },
+            Err(e) => Err(e.into()),
         }
-
-        // self.vk_ctx.set_next_frame_index();
-        self.vk_ctx.frame_sync.set_next_frame_index();
     }
 
     fn resized(&mut self)
@@ -308,6 +721,60 @@ impl renderer_utils::GfxRenderer for VulkanRenderer
 
     fn wait_idle(&self)
     {
+        if !self.device_objects_alive { return; }
         unsafe { vk_check!(self.vk_ctx.device.device_wait_idle()); }
     }
+
+    fn set_vsync(&mut self, b_vsync: bool)
+    {
+        if self.vk_ctx.b_vsync == b_vsync { return; }
+        self.vk_ctx.b_vsync = b_vsync;
+        // The present mode is picked when the swapchain is built.
+        self.has_resized = true;
+    }
+
+    fn is_present_paced(&self) -> bool
+    {
+        self.vk_ctx.is_present_paced()
+    }
+
+    fn camera_mut(&mut self) -> &mut GkCamera
+    {
+        &mut self.cameras[0]
+    }
+
+    fn cameras(&self) -> &[GkCamera]
+    {
+        &self.cameras
+    }
+
+    fn set_cameras(&mut self, mut cameras: Vec<GkCamera>)
+    {
+        if cameras.is_empty()
+        {
+            log_warn!("Ignoring an empty camera list, the renderer needs a main camera.");
+            return;
+        }
+        if cameras.len() > MAX_CAMERAS
+        {
+            log_warn!(format!("Only the first {} of {} cameras are drawn.", MAX_CAMERAS, cameras.len()));
+            cameras.truncate(MAX_CAMERAS);
+        }
+        self.cameras = cameras;
+    }
+
+    fn scene(&self) -> &GkSceneGraph
+    {
+        &self.scene
+    }
+
+    fn scene_mut(&mut self) -> &mut GkSceneGraph
+    {
+        &mut self.scene
+    }
+
+    fn validation_error_count(&self) -> u64
+    {
+        self.loader.validation_error_count()
+    }
 }
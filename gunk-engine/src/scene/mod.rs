@@ -0,0 +1,3 @@
+pub mod scene_graph;
+
+pub use scene_graph::{ GkSceneGraph, GkSceneNode, GkNodeId, GkMeshAttachment, GkCameraAttachment, GkLightAttachment, ELightKind };
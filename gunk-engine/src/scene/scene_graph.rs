@@ -0,0 +1,490 @@
+use std::path::PathBuf;
+
+use nalgebra_glm as glm;
+
+use crate::math::GkTransform;
+
+/// ### GkNodeId struct
+/// *Handle to a GkSceneGraph node. Handles of removed nodes stay invalid even after their slot is reused.*
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GkNodeId
+{
+    index:      u32,
+    generation: u32,
+}
+
+/// ### GkMeshAttachment struct
+/// *Draws a mesh with the node's world matrix. Layers draw the nodes whose mesh is the file they loaded.*
+#[derive(Clone, Debug, PartialEq)]
+pub struct GkMeshAttachment
+{
+    pub mesh:       PathBuf,
+    pub b_visible:  bool,
+}
+
+impl GkMeshAttachment
+{
+    pub fn new(mesh: PathBuf) -> Self
+    {
+        Self { mesh, b_visible: true }
+    }
+}
+
+/// ### GkCameraAttachment struct
+/// *Moves one of the renderer's cameras with the node. The node looks down its local +Y with +Z up, like a camera controller at yaw 0.*
+/// <pre>
+/// - Members
+///     slot:   usize   <i>// Index into GfxRenderer::cameras(), 0 is the main camera.</i>
+/// </pre>
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GkCameraAttachment
+{
+    pub slot:   usize,
+}
+
+/// ### ELightKind enum
+/// *Shape of a GkLightAttachment. Directional and spot lights shine down the node's local +Y.*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ELightKind
+{
+    Directional,
+    Point { range: f32 },
+    Spot { range: f32, inner_angle: f32, outer_angle: f32 },  // Half angles of the cone in radians
+}
+
+/// ### GkLightAttachment struct
+/// *A light placed by the node. No layer shades with lights yet, they are kept for the lit layers to read with GkSceneGraph::lights().*
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GkLightAttachment
+{
+    pub kind:       ELightKind,
+    pub color:      glm::Vec3,
+    pub intensity:  f32,
+}
+
+/// ### GkSceneNode struct
+/// *A node of a GkSceneGraph. Its transform and family are changed through the graph, so world matrices stay in sync.*
+/// <pre>
+/// - Members
+///     name:       String
+///     mesh:       Option&lt;GkMeshAttachment&gt;
+///     camera:     Option&lt;GkCameraAttachment&gt;
+///     light:      Option&lt;GkLightAttachment&gt;
+/// </pre>
+#[derive(Clone, Debug)]
+pub struct GkSceneNode
+{
+    pub name:       String,
+    pub mesh:       Option<GkMeshAttachment>,
+    pub camera:     Option<GkCameraAttachment>,
+    pub light:      Option<GkLightAttachment>,
+    local:          GkTransform,
+    world:          glm::Mat4,  // As of the last update_world_transforms()
+    parent:         Option<GkNodeId>,
+    children:       Vec<GkNodeId>,
+    b_dirty:        bool,       // local changed, or the node moved to another parent
+}
+
+impl GkSceneNode
+{
+    pub fn local(&self) -> &GkTransform
+    {
+        &self.local
+    }
+
+    pub fn world_matrix(&self) -> &glm::Mat4
+    {
+        &self.world
+    }
+
+    /// World space position of the node's origin.
+    pub fn world_position(&self) -> glm::Vec3
+    {
+        glm::Vec3::new(self.world[(0, 3)], self.world[(1, 3)], self.world[(2, 3)])
+    }
+
+    /// ### fn GkSceneNode::world_direction( &self, ... ) -> glm::Vec3
+    /// *A local direction turned to world space and normalized, such as glm::Vec3::y() for where a camera or light points.*
+    pub fn world_direction(&self, local: &glm::Vec3) -> glm::Vec3
+    {
+        (self.world.fixed_view::<3, 3>(0, 0) * local).normalize()
+    }
+
+    pub fn parent(&self) -> Option<GkNodeId>
+    {
+        self.parent
+    }
+
+    pub fn children(&self) -> &[GkNodeId]
+    {
+        &self.children
+    }
+}
+
+struct GkNodeSlot
+{
+    generation: u32,
+    node:       Option<GkSceneNode>,
+}
+
+/// ### GkSceneGraph struct
+/// *Nodes with local transforms, arranged in a hierarchy. World matrices are cached and only
+/// recomputed by update_world_transforms() for nodes whose transform, or an ancestor's, changed.*
+#[derive(Default)]
+pub struct GkSceneGraph
+{
+    slots:  Vec<GkNodeSlot>,
+    free:   Vec<u32>,
+    roots:  Vec<GkNodeId>,
+}
+
+impl GkSceneGraph
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    /// ### fn GkSceneGraph::add_node( &mut self, ... ) -> GkNodeId
+    /// *Adds a node at the root of the scene.*
+    pub fn add_node(&mut self, name: &str, local: GkTransform) -> GkNodeId
+    {
+        let node = GkSceneNode
+        {
+            name: String::from(name),
+            mesh: None,
+            camera: None,
+            light: None,
+            local,
+            world: local.to_matrix(),
+            parent: None,
+            children: Vec::new(),
+            b_dirty: true,
+        };
+        let id = match self.free.pop()
+        {
+            Some(index) =>
+            {
+                let slot = &mut self.slots[index as usize];
+                slot.node = Some(node);
+                GkNodeId { index, generation: slot.generation }
+            },
+            None =>
+            {
+                self.slots.push(GkNodeSlot { generation: 0, node: Some(node) });
+                GkNodeId { index: self.slots.len() as u32 - 1, generation: 0 }
+            },
+        };
+        self.roots.push(id);
+        id
+    }
+
+    /// ### fn GkSceneGraph::add_child( &mut self, ... ) -> Option\<GkNodeId\>
+    /// *Adds a node under parent. None if parent was removed.*
+    pub fn add_child(&mut self, parent: GkNodeId, name: &str, local: GkTransform) -> Option<GkNodeId>
+    {
+        if !self.contains(parent) { return None; }
+        let id = self.add_node(name, local);
+        self.set_parent(id, Some(parent));
+        Some(id)
+    }
+
+    /// ### fn GkSceneGraph::remove_node( &mut self, ... ) -> bool
+    /// *Removes node and everything under it. Returns false if it was already removed.*
+    pub fn remove_node(&mut self, id: GkNodeId) -> bool
+    {
+        let Some(parent) = self.node(id).map(|node| node.parent) else { return false; };
+        self.detach(id, parent);
+
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop()
+        {
+            let slot = &mut self.slots[id.index as usize];
+            if let Some(node) = slot.node.take() { pending.extend(node.children); }
+            slot.generation = slot.generation.wrapping_add(1);
+            self.free.push(id.index);
+        }
+        true
+    }
+
+    /// ### fn GkSceneGraph::set_parent( &mut self, ... ) -> bool
+    /// *Moves node under parent, or to the root for None. The local transform is kept, so the node follows its new parent.<br>
+    /// Returns false, changing nothing, if either node was removed or parent is node or one of its descendants.*
+    pub fn set_parent(&mut self, id: GkNodeId, parent: Option<GkNodeId>) -> bool
+    {
+        let Some(old_parent) = self.node(id).map(|node| node.parent) else { return false; };
+        if let Some(parent) = parent
+        {
+            let mut ancestor = Some(parent);
+            while let Some(current) = ancestor
+            {
+                if current == id { return false; }
+                let Some(node) = self.node(current) else { return false; };
+                ancestor = node.parent;
+            }
+        }
+        if old_parent == parent { return true; }
+
+        self.detach(id, old_parent);
+        match parent
+        {
+            Some(parent) => self.node_entry(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+        let node = self.node_entry(id);
+        node.parent = parent;
+        node.b_dirty = true;
+        true
+    }
+
+    pub fn contains(&self, id: GkNodeId) -> bool
+    {
+        self.node(id).is_some()
+    }
+
+    pub fn node(&self, id: GkNodeId) -> Option<&GkSceneNode>
+    {
+        self.slots.get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_ref())
+    }
+
+    /// ### fn GkSceneGraph::node_mut( &mut self, ... ) -> Option\<&mut GkSceneNode\>
+    /// *For the name and attachments. Transforms change through set_local().*
+    pub fn node_mut(&mut self, id: GkNodeId) -> Option<&mut GkSceneNode>
+    {
+        self.slots.get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.node.as_mut())
+    }
+
+    /// ### fn GkSceneGraph::set_local( &mut self, ... ) -> bool
+    /// *Replaces node's transform relative to its parent. Returns false if it was removed.*
+    pub fn set_local(&mut self, id: GkNodeId, local: GkTransform) -> bool
+    {
+        let Some(node) = self.node_mut(id) else { return false; };
+        node.local = local;
+        node.b_dirty = true;
+        true
+    }
+
+    /// ### fn GkSceneGraph::update_local( &mut self, ... ) -> bool
+    /// *Changes node's transform in place, such as to spin it a little every frame. Returns false if it was removed.*
+    pub fn update_local(&mut self, id: GkNodeId, update: impl FnOnce(&mut GkTransform)) -> bool
+    {
+        let Some(node) = self.node_mut(id) else { return false; };
+        update(&mut node.local);
+        node.b_dirty = true;
+        true
+    }
+
+    pub fn roots(&self) -> &[GkNodeId]
+    {
+        &self.roots
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.len() == 0
+    }
+
+    /// First node called name, in no particular order if several are.
+    pub fn find(&self, name: &str) -> Option<GkNodeId>
+    {
+        self.iter().find(|(_, node)| node.name == name).map(|(id, _)| id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GkNodeId, &GkSceneNode)>
+    {
+        self.slots.iter().enumerate().filter_map(|(index, slot)|
+        {
+            slot.node.as_ref().map(|node| (GkNodeId { index: index as u32, generation: slot.generation }, node))
+        })
+    }
+
+    /// Visible mesh attachments with their node's world matrix.
+    pub fn meshes(&self) -> impl Iterator<Item = (&GkMeshAttachment, &glm::Mat4)>
+    {
+        self.iter().filter_map(|(_, node)| node.mesh.as_ref().filter(|mesh| mesh.b_visible).map(|mesh| (mesh, &node.world)))
+    }
+
+    pub fn cameras(&self) -> impl Iterator<Item = (&GkCameraAttachment, &GkSceneNode)>
+    {
+        self.iter().filter_map(|(_, node)| node.camera.as_ref().map(|camera| (camera, node)))
+    }
+
+    pub fn lights(&self) -> impl Iterator<Item = (&GkLightAttachment, &GkSceneNode)>
+    {
+        self.iter().filter_map(|(_, node)| node.light.as_ref().map(|light| (light, node)))
+    }
+
+    /// ### fn GkSceneGraph::update_world_transforms( &mut self ) -> usize
+    /// *Recomputes the world matrices of changed nodes and everything under them, parents before children.
+    /// Returns how many were recomputed. The renderer calls it once per frame before drawing.*
+    pub fn update_world_transforms(&mut self) -> usize
+    {
+        let mut updated = 0;
+        let mut pending: Vec<(GkNodeId, glm::Mat4, bool)> = self.roots.iter().rev()
+            .map(|&root| (root, glm::Mat4::identity(), false))
+            .collect();
+        while let Some((id, parent_world, b_parent_changed)) = pending.pop()
+        {
+            let node = self.node_entry(id);
+            let b_changed = b_parent_changed || node.b_dirty;
+            if b_changed
+            {
+                node.world = parent_world * node.local.to_matrix();
+                node.b_dirty = false;
+                updated += 1;
+            }
+            let world = node.world;
+            pending.extend(node.children.iter().rev().map(|&child| (child, world, b_changed)));
+        }
+        updated
+    }
+
+    /// A live node, for ids already checked or taken from the graph itself.
+    fn node_entry(&mut self, id: GkNodeId) -> &mut GkSceneNode
+    {
+        self.node_mut(id).expect("scene graph links to a removed node")
+    }
+
+    /// Unlinks node from parent's children, or from the roots.
+    fn detach(&mut self, id: GkNodeId, parent: Option<GkNodeId>)
+    {
+        let siblings = match parent
+        {
+            Some(parent) => &mut self.node_entry(parent).children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|&sibling| sibling != id);
+    }
+}
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn at(x: f32, y: f32, z: f32) -> GkTransform
+    {
+        GkTransform::from_translation(glm::Vec3::new(x, y, z))
+    }
+
+    fn assert_near(actual: glm::Vec3, expected: glm::Vec3)
+    {
+        assert!((actual - expected).norm() < 1e-4, "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn world_matrices_compose_down_the_hierarchy()
+    {
+        let mut scene = GkSceneGraph::new();
+        let turntable = scene.add_node("turntable", at(10.0, 0.0, 0.0)
+            .with_rotation(glm::quat_angle_axis(std::f32::consts::FRAC_PI_2, &glm::Vec3::z())));
+        let arm = scene.add_child(turntable, "arm", at(2.0, 0.0, 0.0).with_uniform_scale(3.0)).unwrap();
+        let tip = scene.add_child(arm, "tip", at(1.0, 0.0, 0.0)).unwrap();
+
+        assert_eq!(scene.update_world_transforms(), 3);
+        assert_near(scene.node(arm).unwrap().world_position(), glm::Vec3::new(10.0, 2.0, 0.0));
+        assert_near(scene.node(tip).unwrap().world_position(), glm::Vec3::new(10.0, 5.0, 0.0));
+        assert_near(scene.node(tip).unwrap().world_direction(&glm::Vec3::y()), glm::Vec3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn only_changed_subtrees_are_recomputed()
+    {
+        let mut scene = GkSceneGraph::new();
+        let a = scene.add_node("a", at(1.0, 0.0, 0.0));
+        let a_child = scene.add_child(a, "a child", at(0.0, 1.0, 0.0)).unwrap();
+        let b = scene.add_node("b", at(0.0, 0.0, 1.0));
+        scene.add_child(b, "b child", at(0.0, 1.0, 0.0)).unwrap();
+        assert_eq!(scene.update_world_transforms(), 4);
+        assert_eq!(scene.update_world_transforms(), 0);
+
+        scene.update_local(a, |local| local.translation.x = 5.0);
+        assert_eq!(scene.update_world_transforms(), 2);
+        assert_near(scene.node(a_child).unwrap().world_position(), glm::Vec3::new(5.0, 1.0, 0.0));
+
+        scene.set_local(a_child, at(0.0, 2.0, 0.0));
+        assert_eq!(scene.update_world_transforms(), 1);
+        assert_near(scene.node(a_child).unwrap().world_position(), glm::Vec3::new(5.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn reparenting_follows_the_new_parent_and_refuses_cycles()
+    {
+        let mut scene = GkSceneGraph::new();
+        let left = scene.add_node("left", at(-5.0, 0.0, 0.0));
+        let right = scene.add_node("right", at(5.0, 0.0, 0.0));
+        let item = scene.add_child(left, "item", at(0.0, 1.0, 0.0)).unwrap();
+        let grandchild = scene.add_child(item, "grandchild", at(0.0, 1.0, 0.0)).unwrap();
+
+        assert!(scene.set_parent(item, Some(right)));
+        scene.update_world_transforms();
+        assert_near(scene.node(grandchild).unwrap().world_position(), glm::Vec3::new(5.0, 2.0, 0.0));
+        assert!(scene.node(left).unwrap().children().is_empty());
+        assert_eq!(scene.node(right).unwrap().children(), [item]);
+
+        assert!(!scene.set_parent(item, Some(grandchild)));
+        assert!(!scene.set_parent(item, Some(item)));
+        assert_eq!(scene.node(item).unwrap().parent(), Some(right));
+
+        assert!(scene.set_parent(item, None));
+        scene.update_world_transforms();
+        assert_near(scene.node(grandchild).unwrap().world_position(), glm::Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(scene.roots(), [left, right, item]);
+    }
+
+    #[test]
+    fn removing_a_node_removes_its_subtree_and_invalidates_handles()
+    {
+        let mut scene = GkSceneGraph::new();
+        let root = scene.add_node("root", GkTransform::identity());
+        let child = scene.add_child(root, "child", GkTransform::identity()).unwrap();
+        let grandchild = scene.add_child(child, "grandchild", GkTransform::identity()).unwrap();
+        let other = scene.add_node("other", GkTransform::identity());
+
+        assert!(scene.remove_node(child));
+        assert!(!scene.remove_node(child));
+        assert!(!scene.contains(grandchild));
+        assert!(scene.node(root).unwrap().children().is_empty());
+        assert_eq!(scene.len(), 2);
+
+        // The freed slots are reused, but the old handles stay dead.
+        let reused = scene.add_node("reused", GkTransform::identity());
+        assert!(!scene.contains(child) && !scene.contains(grandchild));
+        assert!(!scene.set_local(grandchild, at(1.0, 0.0, 0.0)));
+        assert!(scene.add_child(child, "orphan", GkTransform::identity()).is_none());
+        assert_eq!(scene.find("reused"), Some(reused));
+        assert!(scene.contains(other));
+    }
+
+    #[test]
+    fn attachments_are_listed_with_their_nodes()
+    {
+        let mut scene = GkSceneGraph::new();
+        let room = scene.add_node("room", at(0.0, 0.0, -1.0));
+        let hidden = scene.add_child(room, "hidden", GkTransform::identity()).unwrap();
+        let rig = scene.add_child(room, "camera rig", at(0.0, -3.0, 1.0)).unwrap();
+        scene.node_mut(room).unwrap().mesh = Some(GkMeshAttachment::new(PathBuf::from("room.obj")));
+        scene.node_mut(hidden).unwrap().mesh = Some(GkMeshAttachment { mesh: PathBuf::from("room.obj"), b_visible: false });
+        scene.node_mut(rig).unwrap().camera = Some(GkCameraAttachment { slot: 0 });
+        scene.node_mut(rig).unwrap().light = Some(GkLightAttachment { kind: ELightKind::Point { range: 5.0 }, color: glm::Vec3::new(1.0, 1.0, 1.0), intensity: 2.0 });
+        scene.update_world_transforms();
+
+        let meshes: Vec<_> = scene.meshes().collect();
+        assert_eq!(meshes.len(), 1);
+        assert_near(glm::Vec3::new(meshes[0].1[(0, 3)], meshes[0].1[(1, 3)], meshes[0].1[(2, 3)]), glm::Vec3::new(0.0, 0.0, -1.0));
+        let (camera, node) = scene.cameras().next().unwrap();
+        assert_eq!(camera.slot, 0);
+        assert_near(node.world_position(), glm::Vec3::new(0.0, -3.0, 0.0));
+        assert_eq!(scene.lights().count(), 1);
+    }
+}
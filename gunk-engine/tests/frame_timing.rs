@@ -0,0 +1,60 @@
+// Drives a real window through winit's X11 backend.
+#![cfg(target_os = "linux")]
+
+use std::ffi::CString;
+use std::time::{ Duration, Instant };
+
+use ash::vk;
+use winit::event_loop::EventLoopBuilder;
+use winit::platform::pump_events::EventLoopExtPumpEvents;
+use winit::platform::x11::EventLoopBuilderExtX11;
+use winit::window::Window;
+
+use gunk_engine::renderer::renderer_utils::GfxRenderer;
+use gunk_engine::renderer::vulkan_renderer::vulkan_renderer::VulkanRenderer;
+use gunk_engine::renderer::vulkan_renderer::gk_vulkan::gunk_vk_validation::GkVkValidationConfig;
+
+const FRAME_COUNT: usize = 240;
+
+/// Renders a batch of frames and checks the cpu returns from render() while the gpu
+/// is still working on the frame it just submitted. With a device_wait_idle() at the
+/// end of every frame the submitted fence would always be signaled by then.
+#[test]
+#[ignore = "needs a Vulkan capable gpu and an X11 display"]
+fn cpu_does_not_wait_on_gpu_each_frame()
+{
+    // The test harness does not run tests on the main thread.
+    let mut evloop = EventLoopBuilder::new().with_any_thread(true).build().unwrap();
+    let window = Window::new(&evloop).unwrap();
+    let mut renderer = VulkanRenderer::new(
+        &window, CString::new("frame_timing").unwrap(), vk::make_api_version(0, 0, 1, 0),
+        GkVkValidationConfig::default().with_env(), false, false
+    ).unwrap();
+
+    let num_frames = renderer.vk_ctx.frame_sync.get_num_frames_in_flight();
+    let mut frames_still_in_flight = 0;
+    let mut total_fence_wait = Duration::ZERO;
+    let start = Instant::now();
+
+    for _ in 0..FRAME_COUNT
+    {
+        let _ = evloop.pump_events(Some(Duration::ZERO), |_, _| {});
+        renderer.render(&window, 1.0 / 60.0).unwrap();
+        total_fence_wait += renderer.fence_wait_time;
+
+        let submitted = (renderer.vk_ctx.frame_sync.get_current_frame_index() + num_frames - 1) % num_frames;
+        let fence = renderer.vk_ctx.frame_sync.in_flight_fences[submitted];
+        let signaled = unsafe { renderer.vk_ctx.device.get_fence_status(fence).unwrap() };
+        if !signaled { frames_still_in_flight += 1; }
+    }
+
+    let elapsed = start.elapsed();
+    if let Some(validation) = renderer.loader.validation() { validation.assert_no_errors(); }
+    renderer.destroy();
+
+    println!(
+        "{} frames in {:?}, {:?} spent waiting on fences, {} frames returned before the gpu finished",
+        FRAME_COUNT, elapsed, total_fence_wait, frames_still_in_flight
+    );
+    assert!(frames_still_in_flight > 0, "render() returned only after the gpu finished every frame");
+}